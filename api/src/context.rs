@@ -5,19 +5,24 @@ use anyhow::{anyhow, ensure, format_err, Context as AnyhowContext, Result};
 use aptos_api_types::{AsConverter, BlockInfo, Error, LedgerInfo, TransactionOnChainData, U64};
 use aptos_config::config::{NodeConfig, RoleType};
 use aptos_crypto::HashValue;
-use aptos_mempool::{MempoolClientRequest, MempoolClientSender, SubmissionStatus};
+use aptos_mempool::{
+    MempoolClientRequest, MempoolClientSender, MempoolStats, PendingTransactionInfo,
+    SubmissionStatus,
+};
 use aptos_state_view::StateView;
 use aptos_types::{
     access_path::Path,
     account_address::AccountAddress,
     account_config::CORE_CODE_ADDRESS,
     account_state::AccountState,
+    block_metadata::new_block_event_key,
     chain_id::ChainId,
     contract_event::ContractEvent,
     event::EventKey,
     ledger_info::LedgerInfoWithSignatures,
+    on_chain_config::{OnChainConfig, VMConfig},
     state_store::{state_key::StateKey, state_key_prefix::StateKeyPrefix, state_value::StateValue},
-    transaction::{SignedTransaction, TransactionWithProof, Version},
+    transaction::{SignedTransaction, Transaction, TransactionWithProof, Version},
     write_set::WriteOp,
 };
 use aptos_vm::data_cache::{IntoMoveResolver, RemoteStorageOwned};
@@ -29,6 +34,7 @@ use storage_interface::{
     state_view::{DbStateView, DbStateViewAtVersion, LatestDbStateCheckpointView},
     DbReader, Order,
 };
+use tokio::sync::{watch, OwnedSemaphorePermit, Semaphore};
 use warp::{filters::BoxedFilter, Filter, Reply};
 
 use crate::poem_backend::{AptosErrorCode, InternalError};
@@ -40,6 +46,8 @@ pub struct Context {
     pub db: Arc<dyn DbReader>,
     mp_sender: MempoolClientSender,
     node_config: NodeConfig,
+    committed_versions: watch::Receiver<Version>,
+    stream_permits: Arc<Semaphore>,
 }
 
 impl Context {
@@ -48,12 +56,16 @@ impl Context {
         db: Arc<dyn DbReader>,
         mp_sender: MempoolClientSender,
         node_config: NodeConfig,
+        committed_versions: watch::Receiver<Version>,
     ) -> Self {
+        let stream_permits = Arc::new(Semaphore::new(node_config.api.max_concurrent_streams()));
         Self {
             chain_id,
             db,
             mp_sender,
             node_config,
+            committed_versions,
+            stream_permits,
         }
     }
 
@@ -87,6 +99,30 @@ impl Context {
         self.node_config.api.content_length_limit()
     }
 
+    pub fn view_gas_limit(&self) -> u64 {
+        self.node_config.api.view_gas_limit()
+    }
+
+    pub fn mempool_inspection_enabled(&self) -> bool {
+        self.node_config.api.mempool_inspection_enabled
+    }
+
+    /// Returns a receiver that is notified with the latest version every time new
+    /// transactions are committed
+    pub fn committed_versions(&self) -> watch::Receiver<Version> {
+        self.committed_versions.clone()
+    }
+
+    /// Reserves one of the limited slots for open streams. The slot is released
+    /// when the returned permit is dropped, or None is returned if all are taken.
+    pub fn try_acquire_stream_permit(&self) -> Option<OwnedSemaphorePermit> {
+        self.stream_permits.clone().try_acquire_owned().ok()
+    }
+
+    pub fn max_block_size(&self) -> u64 {
+        self.node_config.consensus.max_block_size
+    }
+
     pub fn filter(self) -> impl Filter<Extract = (Context,), Error = Infallible> + Clone {
         warp::any().map(move || self.clone())
     }
@@ -175,6 +211,24 @@ impl Context {
         self.db.get_block_timestamp(version)
     }
 
+    /// Retrieves the version of the first transaction in the block at the given height, or
+    /// `None` if that block isn't committed as of the ledger version
+    pub fn get_block_start_version_by_height(
+        &self,
+        height: u64,
+        ledger_version: u64,
+    ) -> Result<Option<u64>> {
+        // Every block, genesis included, emits exactly one new block event, so the event's
+        // sequence number is the block height
+        let events = self
+            .db
+            .get_events(&new_block_event_key(), height, Order::Ascending, 1)?;
+        Ok(events
+            .into_iter()
+            .find(|event| event.transaction_version <= ledger_version)
+            .map(|event| event.transaction_version))
+    }
+
     /// Retrieves information about a block
     pub fn get_block_info(&self, version: u64, ledger_version: u64) -> Result<BlockInfo> {
         // We scan the DB to get the block boundaries
@@ -366,6 +420,79 @@ impl Context {
         callback.await.map_err(anyhow::Error::from)
     }
 
+    pub async fn get_pending_gas_prices(&self, count: usize) -> Result<Vec<u64>> {
+        let (req_sender, callback) = oneshot::channel();
+
+        self.mp_sender
+            .clone()
+            .send(MempoolClientRequest::GetGasPrices(count, req_sender))
+            .await
+            .map_err(anyhow::Error::from)?;
+
+        callback.await.map_err(anyhow::Error::from)
+    }
+
+    pub async fn get_mempool_account_transactions(
+        &self,
+        address: AccountAddress,
+    ) -> Result<Vec<PendingTransactionInfo>> {
+        let (req_sender, callback) = oneshot::channel();
+
+        self.mp_sender
+            .clone()
+            .send(MempoolClientRequest::GetAccountTransactions(
+                address, req_sender,
+            ))
+            .await
+            .map_err(anyhow::Error::from)?;
+
+        callback.await.map_err(anyhow::Error::from)
+    }
+
+    pub async fn get_mempool_stats(&self, top_accounts: usize) -> Result<MempoolStats> {
+        let (req_sender, callback) = oneshot::channel();
+
+        self.mp_sender
+            .clone()
+            .send(MempoolClientRequest::GetStats(top_accounts, req_sender))
+            .await
+            .map_err(anyhow::Error::from)?;
+
+        callback.await.map_err(anyhow::Error::from)
+    }
+
+    /// Returns the gas unit prices of the user transactions among the last `limit`
+    /// transactions up to `ledger_version`.
+    pub fn get_recent_gas_prices(&self, limit: u64, ledger_version: u64) -> Result<Vec<u64>> {
+        let start_version = (ledger_version + 1).saturating_sub(limit);
+        let txns = self.db.get_transactions(
+            start_version,
+            ledger_version + 1 - start_version,
+            ledger_version,
+            false,
+        )?;
+        Ok(txns
+            .transactions
+            .iter()
+            .filter_map(|txn| match txn {
+                Transaction::UserTransaction(txn) => Some(txn.gas_unit_price()),
+                _ => None,
+            })
+            .collect())
+    }
+
+    /// Returns the minimum gas unit price accepted by the VM, per the on-chain gas schedule.
+    pub fn get_min_gas_unit_price(&self) -> Result<u64> {
+        let resolver = self.move_resolver()?;
+        let vm_config = VMConfig::fetch_config(&resolver)
+            .ok_or_else(|| format_err!("Failed to read VM config from storage"))?;
+        Ok(vm_config
+            .gas_schedule
+            .gas_constants
+            .min_price_per_gas_unit
+            .get())
+    }
+
     pub fn get_transaction_by_version(
         &self,
         version: u64,
@@ -0,0 +1,179 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use std::sync::Arc;
+
+use super::accept_type::{parse_accept, AcceptType};
+use super::{
+    build_not_found, ApiTags, AptosErrorCode, AptosErrorResponse, BasicErrorWith404, BasicResponse,
+    BasicResponseStatus, BasicResultWith404, InternalError,
+};
+use crate::context::Context;
+use crate::failpoint::fail_point_poem;
+use anyhow::Context as AnyhowContext;
+use aptos_api_types::{AsConverter, Block, LedgerInfo, Transaction, TransactionId, U64};
+use poem::web::Accept;
+use poem_openapi::param::Query;
+use poem_openapi::{param::Path, OpenApi};
+
+pub struct BlocksApi {
+    pub context: Arc<Context>,
+}
+
+#[OpenApi]
+impl BlocksApi {
+    /// Get block by height
+    ///
+    /// This endpoint returns the block at the given height. Set
+    /// `with_transactions` to true to also include the block's transactions.
+    #[oai(
+        path = "/blocks/by_height/:block_height",
+        method = "get",
+        operation_id = "get_block_by_height",
+        tag = "ApiTags::Blocks"
+    )]
+    async fn get_block_by_height(
+        &self,
+        accept: Accept,
+        block_height: Path<U64>,
+        with_transactions: Query<Option<bool>>,
+    ) -> BasicResultWith404<Block> {
+        fail_point_poem("endpoint_get_block_by_height")?;
+        let accept_type = parse_accept(&accept)?;
+        self.get_by_height(
+            &accept_type,
+            block_height.0 .0,
+            with_transactions.0.unwrap_or_default(),
+        )
+    }
+
+    /// Get block by version
+    ///
+    /// This endpoint returns the block containing the transaction with the
+    /// given version. Set `with_transactions` to true to also include the
+    /// block's transactions.
+    #[oai(
+        path = "/blocks/by_version/:version",
+        method = "get",
+        operation_id = "get_block_by_version",
+        tag = "ApiTags::Blocks"
+    )]
+    async fn get_block_by_version(
+        &self,
+        accept: Accept,
+        version: Path<U64>,
+        with_transactions: Query<Option<bool>>,
+    ) -> BasicResultWith404<Block> {
+        fail_point_poem("endpoint_get_block_by_version")?;
+        let accept_type = parse_accept(&accept)?;
+        self.get_by_version(
+            &accept_type,
+            version.0 .0,
+            with_transactions.0.unwrap_or_default(),
+        )
+    }
+}
+
+impl BlocksApi {
+    fn get_by_height(
+        &self,
+        accept_type: &AcceptType,
+        block_height: u64,
+        with_transactions: bool,
+    ) -> BasicResultWith404<Block> {
+        let latest_ledger_info = self.context.get_latest_ledger_info_poem()?;
+        let start_version = self
+            .context
+            .get_block_start_version_by_height(block_height, latest_ledger_info.version())
+            .context("Failed to read block events from storage")
+            .map_err(BasicErrorWith404::internal)?
+            .ok_or_else(|| {
+                build_not_found(
+                    "block",
+                    format!("height({})", block_height),
+                    latest_ledger_info.version(),
+                )
+            })?;
+
+        self.render_block(
+            accept_type,
+            start_version,
+            with_transactions,
+            &latest_ledger_info,
+        )
+    }
+
+    fn get_by_version(
+        &self,
+        accept_type: &AcceptType,
+        version: u64,
+        with_transactions: bool,
+    ) -> BasicResultWith404<Block> {
+        let latest_ledger_info = self.context.get_latest_ledger_info_poem()?;
+        if version > latest_ledger_info.version() {
+            return Err(build_not_found(
+                "block",
+                TransactionId::Version(U64::from(version)),
+                latest_ledger_info.version(),
+            ));
+        }
+
+        self.render_block(accept_type, version, with_transactions, &latest_ledger_info)
+    }
+
+    fn render_block(
+        &self,
+        accept_type: &AcceptType,
+        version: u64,
+        with_transactions: bool,
+        latest_ledger_info: &LedgerInfo,
+    ) -> BasicResultWith404<Block> {
+        let ledger_version = latest_ledger_info.version();
+        let block_info = self
+            .context
+            .get_block_info(version, ledger_version)
+            .context("Failed to read block from storage")
+            .map_err(BasicErrorWith404::internal)?;
+
+        let transactions = if with_transactions {
+            let data = self
+                .context
+                .get_transactions(
+                    block_info.start_version,
+                    block_info.num_transactions,
+                    ledger_version,
+                )
+                .context("Failed to read raw transactions from storage")
+                .map_err(BasicErrorWith404::internal)
+                .map_err(|e| e.error_code(AptosErrorCode::InvalidBcsInStorageError))?;
+
+            let resolver = self.context.move_resolver_poem()?;
+            let converter = resolver.as_converter(self.context.db.clone());
+            let txns: Vec<Transaction> = data
+                .into_iter()
+                .map(|t| converter.try_into_onchain_transaction(block_info.block_timestamp, t))
+                .collect::<Result<_, anyhow::Error>>()
+                .context("Failed to convert transaction data from storage")
+                .map_err(BasicErrorWith404::internal)?;
+            Some(txns)
+        } else {
+            None
+        };
+
+        let block = Block {
+            block_height: block_info.block_height.into(),
+            block_hash: block_info.block_hash,
+            block_timestamp: block_info.block_timestamp.into(),
+            first_version: block_info.start_version.into(),
+            last_version: block_info.end_version.into(),
+            transactions,
+        };
+
+        BasicResponse::try_from_rust_value((
+            block,
+            latest_ledger_info,
+            BasicResponseStatus::Ok,
+            accept_type,
+        ))
+    }
+}
@@ -0,0 +1,144 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use std::sync::Arc;
+
+use super::accept_type::{parse_accept, AcceptType};
+use super::{ApiTags, BasicError, BasicResponse, BasicResponseStatus, BasicResult, InternalError};
+use crate::context::Context;
+use crate::failpoint::fail_point_poem;
+use anyhow::Context as AnyhowContext;
+use aptos_api_types::GasEstimation;
+use poem::web::Accept;
+use poem_openapi::OpenApi;
+
+/// Number of most recent transactions whose gas prices are considered.
+const GAS_ESTIMATION_WINDOW: u64 = 1000;
+
+pub struct GasEstimationApi {
+    pub context: Arc<Context>,
+}
+
+#[OpenApi]
+impl GasEstimationApi {
+    /// Estimate gas price
+    ///
+    /// Estimates gas unit prices from the gas prices of recently committed
+    /// transactions and the transactions currently waiting in mempool. The
+    /// low price is for transactions that can wait, the median price should
+    /// get a transaction committed under the current load, and the
+    /// prioritized price should put a transaction ahead of most pending ones.
+    #[oai(
+        path = "/estimate_gas_price",
+        method = "get",
+        operation_id = "estimate_gas_price",
+        tag = "ApiTags::Transactions"
+    )]
+    async fn estimate_gas_price(&self, accept: Accept) -> BasicResult<GasEstimation> {
+        fail_point_poem("endpoint_estimate_gas_price")?;
+        let accept_type = parse_accept(&accept)?;
+        self.estimate(&accept_type).await
+    }
+}
+
+impl GasEstimationApi {
+    async fn estimate(&self, accept_type: &AcceptType) -> BasicResult<GasEstimation> {
+        let latest_ledger_info = self.context.get_latest_ledger_info_poem()?;
+        let committed = self
+            .context
+            .get_recent_gas_prices(GAS_ESTIMATION_WINDOW, latest_ledger_info.version())
+            .context("Failed to read recent transactions from storage")
+            .map_err(BasicError::internal)?;
+        let block_size = self.context.max_block_size() as usize;
+        let pending = self
+            .context
+            .get_pending_gas_prices(block_size)
+            .await
+            .context("Failed to read gas prices from mempool")
+            .map_err(BasicError::internal)?;
+        let min_gas_unit_price = self
+            .context
+            .get_min_gas_unit_price()
+            .context("Failed to read minimum gas unit price from storage")
+            .map_err(BasicError::internal)?;
+
+        BasicResponse::try_from_rust_value((
+            estimate_gas_price(committed, &pending, block_size, min_gas_unit_price),
+            &latest_ledger_info,
+            BasicResponseStatus::Ok,
+            accept_type,
+        ))
+    }
+}
+
+/// Derives the gas price estimation from the gas prices of recently committed
+/// transactions and those of the ready transactions in mempool, highest first.
+fn estimate_gas_price(
+    mut committed: Vec<u64>,
+    pending: &[u64],
+    block_size: usize,
+    min_gas_unit_price: u64,
+) -> GasEstimation {
+    committed.sort_unstable();
+    let percentile = |pct: usize| {
+        if committed.is_empty() {
+            min_gas_unit_price
+        } else {
+            committed[(committed.len() - 1) * pct / 100].max(min_gas_unit_price)
+        }
+    };
+
+    let low = percentile(10);
+    let mut median = percentile(50);
+    let mut prioritized = percentile(90);
+
+    // If mempool holds more than a full block of ready transactions, anything
+    // priced below the last one that fits in the next block has to wait.
+    if block_size > 0 && pending.len() >= block_size {
+        let clearing_price = pending[block_size - 1];
+        median = median.max(clearing_price);
+        prioritized = prioritized.max(clearing_price.saturating_add(1));
+    }
+
+    GasEstimation {
+        low_gas_unit_price: low.into(),
+        median_gas_unit_price: median.into(),
+        prioritized_gas_unit_price: prioritized.into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::estimate_gas_price;
+
+    #[test]
+    fn test_estimate_gas_price_without_history() {
+        let estimation = estimate_gas_price(vec![], &[], 10, 1);
+        assert_eq!(estimation.low_gas_unit_price.0, 1);
+        assert_eq!(estimation.median_gas_unit_price.0, 1);
+        assert_eq!(estimation.prioritized_gas_unit_price.0, 1);
+    }
+
+    #[test]
+    fn test_estimate_gas_price_from_committed() {
+        let committed = (1..=100).rev().collect();
+        let estimation = estimate_gas_price(committed, &[], 10, 5);
+        assert_eq!(estimation.low_gas_unit_price.0, 10);
+        assert_eq!(estimation.median_gas_unit_price.0, 50);
+        assert_eq!(estimation.prioritized_gas_unit_price.0, 90);
+    }
+
+    #[test]
+    fn test_estimate_gas_price_with_congested_mempool() {
+        let committed = vec![1, 2, 3];
+        let estimation = estimate_gas_price(committed.clone(), &[9, 8, 7], 3, 1);
+        assert_eq!(estimation.low_gas_unit_price.0, 1);
+        assert_eq!(estimation.median_gas_unit_price.0, 7);
+        assert_eq!(estimation.prioritized_gas_unit_price.0, 8);
+
+        // Mempool isn't full enough to fill a block, so it doesn't matter.
+        let estimation = estimate_gas_price(committed, &[9, 8], 3, 1);
+        assert_eq!(estimation.median_gas_unit_price.0, 2);
+        assert_eq!(estimation.prioritized_gas_unit_price.0, 2);
+    }
+}
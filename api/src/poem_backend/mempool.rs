@@ -0,0 +1,179 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use std::sync::Arc;
+use std::time::UNIX_EPOCH;
+
+use super::accept_type::{parse_accept, AcceptType};
+use super::{
+    ApiTags, BasicErrorWith404, BasicResponse, BasicResponseStatus, BasicResultWith404,
+    InternalError, NotFoundError,
+};
+use crate::context::Context;
+use crate::failpoint::fail_point_poem;
+use anyhow::Context as AnyhowContext;
+use aptos_api_types::{
+    Address, AsConverter, MempoolAccountStats, MempoolAccountTransactions, MempoolStats,
+    MempoolTransaction, U64,
+};
+use aptos_mempool::PendingTransactionInfo;
+use poem::web::Accept;
+use poem_openapi::{param::Path, OpenApi};
+
+/// Number of accounts with the most transactions included in the mempool stats.
+const MEMPOOL_STATS_TOP_ACCOUNTS: usize = 25;
+
+pub struct MempoolApi {
+    pub context: Arc<Context>,
+}
+
+#[OpenApi]
+impl MempoolApi {
+    /// Get account mempool transactions
+    ///
+    /// This endpoint returns the transactions of the given account waiting in
+    /// this node's mempool, ordered by sequence number. Parked transactions
+    /// can't be included in the next block, usually because a transaction
+    /// with a lower sequence number is missing. The endpoint returns a 404
+    /// unless mempool inspection is enabled in the node's API config.
+    #[oai(
+        path = "/mempool/accounts/:address",
+        method = "get",
+        operation_id = "get_mempool_account_transactions",
+        tag = "ApiTags::Mempool"
+    )]
+    async fn get_account_transactions(
+        &self,
+        accept: Accept,
+        address: Path<Address>,
+    ) -> BasicResultWith404<MempoolAccountTransactions> {
+        fail_point_poem("endpoint_get_mempool_account_transactions")?;
+        let accept_type = parse_accept(&accept)?;
+        self.check_enabled()?;
+        self.account_transactions(&accept_type, address.0).await
+    }
+
+    /// Get mempool stats
+    ///
+    /// This endpoint returns the number of transactions waiting in this
+    /// node's mempool, and the accounts with the most transactions in it.
+    /// The endpoint returns a 404 unless mempool inspection is enabled in the
+    /// node's API config.
+    #[oai(
+        path = "/mempool/stats",
+        method = "get",
+        operation_id = "get_mempool_stats",
+        tag = "ApiTags::Mempool"
+    )]
+    async fn get_stats(&self, accept: Accept) -> BasicResultWith404<MempoolStats> {
+        fail_point_poem("endpoint_get_mempool_stats")?;
+        let accept_type = parse_accept(&accept)?;
+        self.check_enabled()?;
+        self.stats(&accept_type).await
+    }
+}
+
+impl MempoolApi {
+    /// The mempool is locked while serving these endpoints, so they're off unless enabled.
+    fn check_enabled(&self) -> Result<(), BasicErrorWith404> {
+        if self.context.mempool_inspection_enabled() {
+            Ok(())
+        } else {
+            Err(BasicErrorWith404::not_found_str(
+                "Mempool inspection is disabled on this node",
+            ))
+        }
+    }
+
+    async fn account_transactions(
+        &self,
+        accept_type: &AcceptType,
+        address: Address,
+    ) -> BasicResultWith404<MempoolAccountTransactions> {
+        let latest_ledger_info = self.context.get_latest_ledger_info_poem()?;
+        let txns = self
+            .context
+            .get_mempool_account_transactions(address.into())
+            .await
+            .context("Failed to read account transactions from mempool")
+            .map_err(BasicErrorWith404::internal)?;
+
+        let resolver = self.context.move_resolver_poem()?;
+        let converter = resolver.as_converter(self.context.db.clone());
+        let parked_transactions = txns.iter().filter(|info| info.parked).count();
+        let ready_transactions = txns.len() - parked_transactions;
+        let transactions = txns
+            .into_iter()
+            .map(|info| {
+                let PendingTransactionInfo {
+                    txn,
+                    parked,
+                    timeline_id,
+                    priority_index_position,
+                    insertion_time,
+                } = info;
+                let insertion_timestamp_usecs: Option<U64> = insertion_time
+                    .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+                    .map(|duration| (duration.as_micros() as u64).into());
+                Ok(MempoolTransaction {
+                    transaction: converter.try_into_pending_transaction_poem(txn)?,
+                    parked,
+                    timeline_id: timeline_id.map(Into::into),
+                    priority_index_position: priority_index_position
+                        .map(|position| (position as u64).into()),
+                    insertion_timestamp_usecs,
+                })
+            })
+            .collect::<anyhow::Result<_>>()
+            .context("Failed to convert transactions from mempool")
+            .map_err(BasicErrorWith404::internal)?;
+
+        BasicResponse::try_from_rust_value((
+            MempoolAccountTransactions {
+                address,
+                ready_transactions: (ready_transactions as u64).into(),
+                parked_transactions: (parked_transactions as u64).into(),
+                transactions,
+            },
+            &latest_ledger_info,
+            BasicResponseStatus::Ok,
+            accept_type,
+        ))
+    }
+
+    async fn stats(&self, accept_type: &AcceptType) -> BasicResultWith404<MempoolStats> {
+        let latest_ledger_info = self.context.get_latest_ledger_info_poem()?;
+        let stats = self
+            .context
+            .get_mempool_stats(MEMPOOL_STATS_TOP_ACCOUNTS)
+            .await
+            .context("Failed to read stats from mempool")
+            .map_err(BasicErrorWith404::internal)?;
+
+        let count = |count: usize| -> U64 { (count as u64).into() };
+        let top_accounts = stats
+            .top_accounts
+            .into_iter()
+            .map(|account| MempoolAccountStats {
+                address: account.address.into(),
+                ready_transactions: count(account.ready_txns),
+                parked_transactions: count(account.parked_txns),
+            })
+            .collect();
+
+        BasicResponse::try_from_rust_value((
+            MempoolStats {
+                total_transactions: count(stats.total_txns),
+                ready_transactions: count(stats.ready_txns),
+                parked_transactions: count(stats.parked_txns),
+                num_accounts: count(stats.num_accounts),
+                capacity: count(stats.capacity),
+                capacity_per_account: count(stats.capacity_per_user),
+                top_accounts,
+            },
+            &latest_ledger_info,
+            BasicResponseStatus::Ok,
+            accept_type,
+        ))
+    }
+}
@@ -7,42 +7,62 @@ mod accept_type;
 mod accounts;
 mod basic;
 mod bcs_payload;
+mod blocks;
 mod check_size;
 mod error_converter;
 mod events;
+mod gas_estimation;
 mod index;
 mod log;
+mod mempool;
 mod page;
 mod response;
 mod runtime;
+mod sse_payload;
 mod state;
+mod stream;
 mod transactions;
+mod view_function;
 
 #[derive(Tags)]
 pub enum ApiTags {
     /// Access to account resources and modules
     Accounts,
 
+    /// Access to blocks
+    Blocks,
+
     /// Access to events
     Events,
 
     /// General information
     General,
 
+    /// Inspection of the transactions waiting in mempool
+    Mempool,
+
     /// Access to tables
     Tables,
 
     /// Access to transactions
     Transactions,
+
+    /// Read-only execution of Move functions
+    View,
 }
 
 pub use accept_type::AcceptType;
 pub use accounts::AccountsApi;
 pub use basic::BasicApi;
+pub use blocks::BlocksApi;
 pub use events::EventsApi;
+pub use gas_estimation::GasEstimationApi;
 pub use index::IndexApi;
 pub use log::middleware_log;
+pub use mempool::MempoolApi;
 pub use response::*;
 pub use runtime::attach_poem_to_runtime;
 pub use state::StateApi;
+pub use stream::StreamApi;
 pub use transactions::TransactionsApi;
+pub use view_function::ViewFunctionApi;
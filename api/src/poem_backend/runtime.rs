@@ -3,12 +3,16 @@
 
 use std::{net::SocketAddr, sync::Arc};
 
-use super::{middleware_log, AccountsApi, BasicApi, EventsApi, IndexApi};
+use super::{
+    middleware_log, AccountsApi, BasicApi, BlocksApi, EventsApi, GasEstimationApi, IndexApi,
+    MempoolApi,
+};
 
 use crate::{
     context::Context,
     poem_backend::{
-        check_size::PostSizeLimit, error_converter::convert_error, StateApi, TransactionsApi,
+        check_size::PostSizeLimit, error_converter::convert_error, StateApi, StreamApi,
+        TransactionsApi, ViewFunctionApi,
     },
 };
 use anyhow::Context as AnyhowContext;
@@ -46,16 +50,31 @@ pub fn attach_poem_to_runtime(
         BasicApi {
             context: context.clone(),
         },
+        BlocksApi {
+            context: context.clone(),
+        },
         EventsApi {
             context: context.clone(),
         },
+        GasEstimationApi {
+            context: context.clone(),
+        },
         IndexApi {
             context: context.clone(),
         },
+        MempoolApi {
+            context: context.clone(),
+        },
         StateApi {
             context: context.clone(),
         },
-        TransactionsApi { context },
+        StreamApi {
+            context: context.clone(),
+        },
+        TransactionsApi {
+            context: context.clone(),
+        },
+        ViewFunctionApi { context },
     );
 
     let version = std::env::var("CARGO_PKG_VERSION").unwrap_or_else(|_| "0.1.0".to_string());
@@ -0,0 +1,92 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module defines a Poem payload type for server-sent event streams.
+//! Poem's own EventStream ends silently when the stream does, so a client
+//! can't tell an error on our side from the connection being dropped. This
+//! type instead ends the stream with an `error` event carrying an AptosError.
+
+use futures::stream::{BoxStream, StreamExt};
+use poem::{
+    web::sse::{Event, SSE},
+    IntoResponse, Response,
+};
+use poem_openapi::{
+    payload::Payload,
+    registry::{MetaMediaType, MetaResponse, MetaResponses, MetaSchema, MetaSchemaRef, Registry},
+    types::{ToJSON, Type},
+    ApiResponse,
+};
+
+use super::AptosError;
+
+/// The event type of the message sent when a stream fails.
+pub const ERROR_EVENT_TYPE: &str = "error";
+
+/// A stream of server-sent events, each holding a JSON encoded `T`. The stream
+/// is closed after the first error, which is sent as an `error` event.
+pub struct ServerSentEvents<T> {
+    stream: BoxStream<'static, Result<T, AptosError>>,
+}
+
+impl<T> ServerSentEvents<T> {
+    pub fn new(stream: BoxStream<'static, Result<T, AptosError>>) -> Self {
+        Self { stream }
+    }
+}
+
+impl<T: Type + ToJSON> Payload for ServerSentEvents<T> {
+    const CONTENT_TYPE: &'static str = "text/event-stream";
+
+    fn schema_ref() -> MetaSchemaRef {
+        MetaSchemaRef::Inline(Box::new(MetaSchema {
+            items: Some(Box::new(T::schema_ref())),
+            ..MetaSchema::new_with_format("array", "event-stream")
+        }))
+    }
+
+    fn register(registry: &mut Registry) {
+        T::register(registry);
+        AptosError::register(registry);
+    }
+}
+
+impl<T: Type + ToJSON> IntoResponse for ServerSentEvents<T> {
+    fn into_response(self) -> Response {
+        let events = self.stream.scan(false, |failed, item| {
+            // Stop after sending the error event
+            if *failed {
+                return futures::future::ready(None);
+            }
+            let event = match item {
+                Ok(value) => Event::message(value.to_json_string()),
+                Err(error) => {
+                    *failed = true;
+                    Event::message(error.to_json_string()).event_type(ERROR_EVENT_TYPE)
+                }
+            };
+            futures::future::ready(Some(event))
+        });
+        SSE::new(events).into_response()
+    }
+}
+
+impl<T: Type + ToJSON> ApiResponse for ServerSentEvents<T> {
+    fn meta() -> MetaResponses {
+        MetaResponses {
+            responses: vec![MetaResponse {
+                description: "A stream of server-sent events, closed by an error event on failure",
+                status: Some(200),
+                content: vec![MetaMediaType {
+                    content_type: Self::CONTENT_TYPE,
+                    schema: Self::schema_ref(),
+                }],
+                headers: vec![],
+            }],
+        }
+    }
+
+    fn register(registry: &mut Registry) {
+        <Self as Payload>::register(registry);
+    }
+}
@@ -0,0 +1,256 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use std::cmp::min;
+use std::sync::Arc;
+
+use super::sse_payload::ServerSentEvents;
+use super::{ApiTags, AptosError, AptosErrorCode, ServiceUnavailableError};
+use crate::context::Context;
+use crate::failpoint::fail_point_poem;
+use crate::generate_error_response;
+use anyhow::Context as AnyhowContext;
+use aptos_api_types::{
+    Address, AsConverter, EventKey, Transaction, TransactionOnChainData, VersionedEvent, U64,
+};
+use aptos_logger::warn;
+use futures::stream::{self, Stream, StreamExt};
+use poem_openapi::param::Query;
+use poem_openapi::OpenApi;
+use tokio::sync::{watch, OwnedSemaphorePermit};
+
+/// Maximum number of transactions read from storage at once while streaming.
+const STREAM_BATCH_SIZE: u64 = 100;
+
+generate_error_response!(
+    StreamError,
+    (400, BadRequest),
+    (404, NotFound),
+    (500, Internal),
+    (503, ServiceUnavailable)
+);
+
+pub type StreamResult<T> = Result<ServerSentEvents<T>, StreamError>;
+
+pub struct StreamApi {
+    pub context: Arc<Context>,
+}
+
+#[OpenApi]
+impl StreamApi {
+    /// Stream transactions
+    ///
+    /// Streams committed transactions as server-sent events, starting at
+    /// `start_version`. If no start version is given, only transactions
+    /// committed after the request are streamed. The stream stays open and
+    /// new transactions are sent as soon as they are committed.
+    #[oai(
+        path = "/stream/transactions",
+        method = "get",
+        operation_id = "stream_transactions",
+        tag = "ApiTags::Transactions"
+    )]
+    async fn stream_transactions(
+        &self,
+        start_version: Query<Option<U64>>,
+    ) -> StreamResult<Transaction> {
+        fail_point_poem("endpoint_stream_transactions")?;
+        let permit = self.stream_permit()?;
+        let start_version = self.start_version(start_version.0)?;
+        let stream = committed_transactions(
+            self.context.clone(),
+            permit,
+            start_version,
+            render_transactions,
+        );
+        Ok(ServerSentEvents::new(stream.boxed()))
+    }
+
+    /// Stream events
+    ///
+    /// Streams committed events as server-sent events, starting with the
+    /// events emitted at `start_version`. Events can be filtered by event key,
+    /// or by `address` to only get events from event handles created by
+    /// that account. If no start version is given, only events committed
+    /// after the request are streamed.
+    #[oai(
+        path = "/stream/events",
+        method = "get",
+        operation_id = "stream_events",
+        tag = "ApiTags::Events"
+    )]
+    async fn stream_events(
+        &self,
+        event_key: Query<Option<EventKey>>,
+        address: Query<Option<Address>>,
+        start_version: Query<Option<U64>>,
+    ) -> StreamResult<VersionedEvent> {
+        fail_point_poem("endpoint_stream_events")?;
+        let permit = self.stream_permit()?;
+        let start_version = self.start_version(start_version.0)?;
+        let filter = EventFilter {
+            event_key: event_key.0.map(Into::into),
+            address: address.0.map(Into::into),
+        };
+        let stream = committed_transactions(
+            self.context.clone(),
+            permit,
+            start_version,
+            move |context, txns| render_events(context, txns, &filter),
+        );
+        Ok(ServerSentEvents::new(stream.boxed()))
+    }
+}
+
+impl StreamApi {
+    /// Takes one of the slots for open streams, which is held until the stream is dropped
+    fn stream_permit(&self) -> Result<OwnedSemaphorePermit, StreamError> {
+        self.context.try_acquire_stream_permit().ok_or_else(|| {
+            StreamError::service_unavailable_str("Too many open streams, try again later")
+        })
+    }
+
+    fn start_version(&self, requested_version: Option<U64>) -> Result<u64, StreamError> {
+        match requested_version {
+            Some(version) => Ok(version.0),
+            None => Ok(self.context.get_latest_ledger_info_poem()?.version() + 1),
+        }
+    }
+}
+
+/// Filters the events sent by the event stream. Unset fields match all events.
+struct EventFilter {
+    event_key: Option<aptos_types::event::EventKey>,
+    address: Option<aptos_types::account_address::AccountAddress>,
+}
+
+impl EventFilter {
+    fn matches(&self, event_key: &aptos_types::event::EventKey) -> bool {
+        self.event_key.map_or(true, |key| &key == event_key)
+            && self
+                .address
+                .map_or(true, |address| event_key.get_creator_address() == address)
+    }
+}
+
+/// The state of a stream of committed transactions
+struct StreamState<F> {
+    context: Arc<Context>,
+    committed_versions: watch::Receiver<u64>,
+    /// Held for as long as the stream is open
+    _permit: OwnedSemaphorePermit,
+    next_version: u64,
+    render: F,
+}
+
+/// Returns a stream of the items rendered from committed transactions, starting at
+/// `start_version`. Once the stream has caught up with storage, it waits for the next
+/// commit notification before reading more transactions. If reading from storage or
+/// rendering fails, the error is the last item of the stream.
+fn committed_transactions<T, F>(
+    context: Arc<Context>,
+    permit: OwnedSemaphorePermit,
+    start_version: u64,
+    render: F,
+) -> impl Stream<Item = Result<T, AptosError>>
+where
+    T: Send + 'static,
+    F: Fn(&Context, Vec<TransactionOnChainData>) -> anyhow::Result<Vec<T>> + Send + 'static,
+{
+    let state = StreamState {
+        committed_versions: context.committed_versions(),
+        context,
+        _permit: permit,
+        next_version: start_version,
+        render,
+    };
+    stream::unfold(Some(state), |state| async move {
+        let mut state = state?;
+        loop {
+            let ledger_version = match state.context.get_latest_ledger_info() {
+                Ok(ledger_info) => ledger_info.version(),
+                Err(error) => {
+                    warn!(
+                        "[api] closing stream, failed to read ledger info: {}",
+                        error
+                    );
+                    let error =
+                        AptosError::from(error).error_code(AptosErrorCode::ReadFromStorageError);
+                    return Some((stream::iter(vec![Err(error)]), None));
+                }
+            };
+
+            if state.next_version <= ledger_version {
+                let next_version = state.next_version;
+                let limit = min(ledger_version - next_version + 1, STREAM_BATCH_SIZE);
+                let items = state
+                    .context
+                    .get_transactions(next_version, limit as u16, ledger_version)
+                    .and_then(|txns| (state.render)(&state.context, txns));
+                return match items {
+                    Ok(items) => {
+                        state.next_version += limit;
+                        let items: Vec<_> = items.into_iter().map(Ok).collect();
+                        Some((stream::iter(items), Some(state)))
+                    }
+                    Err(error) => {
+                        warn!(
+                            "[api] closing stream, failed to read transactions at version {}: {}",
+                            next_version, error
+                        );
+                        let error = AptosError::from(error).aptos_ledger_version(ledger_version);
+                        Some((stream::iter(vec![Err(error)]), None))
+                    }
+                };
+            }
+
+            // Caught up with storage, wait for the next commit. The sender only
+            // goes away when the node shuts down.
+            if state.committed_versions.changed().await.is_err() {
+                let error = AptosError::new("The node is shutting down".to_string());
+                return Some((stream::iter(vec![Err(error)]), None));
+            }
+        }
+    })
+    .flatten()
+}
+
+fn render_transactions(
+    context: &Context,
+    txns: Vec<TransactionOnChainData>,
+) -> anyhow::Result<Vec<Transaction>> {
+    let resolver = context.move_resolver()?;
+    let converter = resolver.as_converter(context.db.clone());
+    txns.into_iter()
+        .map(|txn| {
+            let timestamp = context.get_block_timestamp(txn.version)?;
+            converter.try_into_onchain_transaction(timestamp, txn)
+        })
+        .collect::<anyhow::Result<_>>()
+        .context("Failed to convert transaction data from storage")
+}
+
+fn render_events(
+    context: &Context,
+    txns: Vec<TransactionOnChainData>,
+    filter: &EventFilter,
+) -> anyhow::Result<Vec<VersionedEvent>> {
+    let resolver = context.move_resolver()?;
+    let converter = resolver.as_converter(context.db.clone());
+    let mut versioned_events = vec![];
+    for txn in txns {
+        let events: Vec<_> = txn
+            .events
+            .into_iter()
+            .filter(|event| filter.matches(event.key()))
+            .collect();
+        let events = converter
+            .try_into_events(&events)
+            .context("Failed to convert events from storage")?;
+        versioned_events.extend(events.into_iter().map(|event| VersionedEvent {
+            version: txn.version.into(),
+            event,
+        }));
+    }
+    Ok(versioned_events)
+}
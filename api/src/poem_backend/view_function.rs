@@ -0,0 +1,122 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use std::sync::Arc;
+
+use super::accept_type::{parse_accept, AcceptType};
+use super::{
+    build_not_found, ApiTags, BadRequestError, BasicErrorWith404, BasicResponse,
+    BasicResponseStatus, BasicResultWith404, InternalError,
+};
+use crate::context::Context;
+use crate::failpoint::fail_point_poem;
+use anyhow::Context as AnyhowContext;
+use aptos_api_types::{AsConverter, MoveValue, TransactionId, ViewRequest, U64};
+use aptos_vm::{data_cache::AsMoveResolver, AptosVM};
+use poem::web::Accept;
+use poem_openapi::param::Query;
+use poem_openapi::payload::Json;
+use poem_openapi::OpenApi;
+
+pub struct ViewFunctionApi {
+    pub context: Arc<Context>,
+}
+
+#[OpenApi]
+impl ViewFunctionApi {
+    /// Execute view function
+    ///
+    /// Executes a public Move function read-only at a specified ledger version
+    /// (AKA transaction version) and returns its return values. No signer is
+    /// provided to the function and any changes it makes are discarded. If the
+    /// ledger version is not specified in the request, the latest ledger
+    /// version is used.
+    ///
+    /// The Aptos nodes prune account state history, via a configurable time window (link).
+    /// If the requested data has been pruned, the server responds with a 404.
+    #[oai(
+        path = "/view",
+        method = "post",
+        operation_id = "view",
+        tag = "ApiTags::View"
+    )]
+    async fn view_function(
+        &self,
+        accept: Accept,
+        request: Json<ViewRequest>,
+        ledger_version: Query<Option<U64>>,
+    ) -> BasicResultWith404<Vec<MoveValue>> {
+        fail_point_poem("endpoint_view_function")?;
+        let accept_type = parse_accept(&accept)?;
+        self.view(&accept_type, request.0, ledger_version.0)
+    }
+}
+
+impl ViewFunctionApi {
+    fn view(
+        &self,
+        accept_type: &AcceptType,
+        request: ViewRequest,
+        requested_ledger_version: Option<U64>,
+    ) -> BasicResultWith404<Vec<MoveValue>> {
+        let latest_ledger_info = self.context.get_latest_ledger_info_poem()?;
+        let ledger_version: u64 = requested_ledger_version
+            .map(|v| v.0)
+            .unwrap_or_else(|| latest_ledger_info.version());
+
+        if ledger_version > latest_ledger_info.version() {
+            return Err(build_not_found(
+                "ledger",
+                TransactionId::Version(U64::from(ledger_version)),
+                latest_ledger_info.version(),
+            ));
+        }
+
+        let state_view = self
+            .context
+            .state_view_at_version(ledger_version)
+            .context(format!(
+                "Failed to get state view at version {}",
+                ledger_version
+            ))
+            .map_err(BasicErrorWith404::internal)?;
+        let resolver = state_view.as_move_resolver();
+        let converter = resolver.as_converter(self.context.db.clone());
+
+        let view_function = converter
+            .try_into_view_function(request)
+            .context("Invalid view function request")
+            .map_err(BasicErrorWith404::bad_request)?;
+
+        let return_values = AptosVM::execute_view_function(
+            &state_view,
+            view_function.module,
+            view_function.function,
+            view_function.type_arguments,
+            view_function.arguments,
+            self.context.view_gas_limit(),
+        )
+        .map_err(|status| {
+            BasicErrorWith404::bad_request_str(&format!(
+                "Failed to execute view function: {:?}",
+                status
+            ))
+        })?;
+
+        let move_values = view_function
+            .return_types
+            .iter()
+            .zip(return_values.iter())
+            .map(|(typ, bytes)| converter.try_into_move_value(typ, bytes))
+            .collect::<anyhow::Result<Vec<_>>>()
+            .context("Failed to convert view function return values")
+            .map_err(BasicErrorWith404::internal)?;
+
+        BasicResponse::try_from_rust_value((
+            move_values,
+            &latest_ledger_info,
+            BasicResponseStatus::Ok,
+            accept_type,
+        ))
+    }
+}
@@ -4,11 +4,17 @@
 use crate::{context::Context, index, poem_backend::attach_poem_to_runtime};
 use anyhow::Context as AnyhowContext;
 use aptos_config::config::{ApiConfig, NodeConfig};
+use aptos_logger::warn;
 use aptos_mempool::MempoolClientSender;
-use aptos_types::chain_id::ChainId;
+use aptos_types::{chain_id::ChainId, transaction::Version};
+use event_notifications::CommitNotificationListener;
+use futures::StreamExt;
 use std::{convert::Infallible, net::SocketAddr, sync::Arc};
 use storage_interface::DbReader;
-use tokio::runtime::{Builder, Runtime};
+use tokio::{
+    runtime::{Builder, Runtime},
+    sync::watch,
+};
 use warp::{Filter, Reply};
 use warp_reverse_proxy::reverse_proxy_filter;
 
@@ -22,13 +28,21 @@ pub fn bootstrap(
     chain_id: ChainId,
     db: Arc<dyn DbReader>,
     mp_sender: MempoolClientSender,
+    commit_listener: CommitNotificationListener,
 ) -> anyhow::Result<Runtime> {
     let runtime = Builder::new_multi_thread()
         .thread_name("api")
         .enable_all()
         .build()
         .context("[api] failed to create runtime")?;
-    let context = Context::new(chain_id, db, mp_sender, config.clone());
+
+    let latest_version = db.get_latest_version_option()?.unwrap_or_default();
+    let (committed_version_sender, committed_versions) = watch::channel(latest_version);
+    runtime.spawn(publish_committed_versions(
+        commit_listener,
+        committed_version_sender,
+    ));
+    let context = Context::new(chain_id, db, mp_sender, config.clone(), committed_versions);
 
     // Poem will run on a different port.
     let poem_address = attach_poem_to_runtime(runtime.handle(), context.clone(), config)
@@ -43,6 +57,20 @@ pub fn bootstrap(
     Ok(runtime)
 }
 
+/// Publishes the version of every commit notified by state sync, so that the streaming
+/// endpoints know when new transactions are available.
+async fn publish_committed_versions(
+    mut commit_listener: CommitNotificationListener,
+    committed_version_sender: watch::Sender<Version>,
+) {
+    while let Some(notification) = commit_listener.next().await {
+        if committed_version_sender.send(notification.version).is_err() {
+            warn!("[api] no receivers left for commit notifications");
+            break;
+        }
+    }
+}
+
 // TODO: This proxy is temporary while we have both APIs running.
 pub fn get_routes_with_poem(
     poem_address: SocketAddr,
@@ -105,7 +133,11 @@ mod tests {
     use std::time::Duration;
 
     use aptos_config::config::NodeConfig;
+    use aptos_infallible::RwLock;
     use aptos_types::chain_id::ChainId;
+    use event_notifications::EventSubscriptionService;
+    use std::sync::Arc;
+    use storage_interface::DbReaderWriter;
 
     use crate::{
         runtime::bootstrap,
@@ -124,11 +156,16 @@ mod tests {
         let context = runtime.block_on(new_test_context_async(
             "test_bootstrap_jsonprc_and_api_configured_at_different_port".to_string(),
         ));
+        let mut event_subscription_service = EventSubscriptionService::new(
+            &[],
+            Arc::new(RwLock::new(DbReaderWriter::from_arc(context.db.clone()))),
+        );
         let ret = bootstrap(
             &cfg,
             ChainId::test(),
             context.db.clone(),
             context.mempool.ac_client.clone(),
+            event_subscription_service.subscribe_to_commits().unwrap(),
         );
         assert!(ret.is_ok());
 
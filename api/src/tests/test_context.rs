@@ -29,7 +29,7 @@ use aptos_types::{
     block_metadata::BlockMetadata,
     chain_id::ChainId,
     ledger_info::{LedgerInfo, LedgerInfoWithSignatures},
-    transaction::{Transaction, TransactionStatus},
+    transaction::{Transaction, TransactionStatus, Version},
 };
 use aptos_vm::AptosVM;
 use aptosdb::AptosDB;
@@ -46,6 +46,7 @@ use rand::SeedableRng;
 use serde_json::{json, Value};
 use std::{boxed::Box, collections::BTreeMap, iter::once, net::SocketAddr, sync::Arc};
 use storage_interface::state_view::DbStateView;
+use tokio::sync::watch;
 use vm_validator::vm_validator::VMValidator;
 use warp::http::header::CONTENT_TYPE;
 
@@ -98,6 +99,14 @@ impl ApiSpecificConfig {
 }
 
 pub fn new_test_context(test_name: String, api_version: &str) -> TestContext {
+    new_test_context_with_config(test_name, NodeConfig::default(), api_version)
+}
+
+pub fn new_test_context_with_config(
+    test_name: String,
+    node_config: NodeConfig,
+    api_version: &str,
+) -> TestContext {
     let tmp_dir = TempPath::new();
     tmp_dir.create_as_dir().unwrap();
 
@@ -134,13 +143,14 @@ pub fn new_test_context(test_name: String, api_version: &str) -> TestContext {
 
     let mempool = MockSharedMempool::new_in_runtime(&db_rw, VMValidator::new(db.clone()));
 
-    let node_config = NodeConfig::default();
-
+    // Only the genesis transaction has been committed so far
+    let (committed_version_sender, committed_versions) = watch::channel(0);
     let context = Context::new(
         ChainId::test(),
         db.clone(),
         mempool.ac_client.clone(),
         node_config.clone(),
+        committed_versions,
     );
 
     // Configure the testing depending on which API version we're testing.
@@ -164,6 +174,7 @@ pub fn new_test_context(test_name: String, api_version: &str) -> TestContext {
         Box::new(BlockExecutor::<AptosVM>::new(db_rw)),
         mempool,
         db,
+        committed_version_sender,
         test_name,
         api_specific_config,
     )
@@ -175,6 +186,7 @@ pub struct TestContext {
     pub validator_owner: AccountAddress,
     pub mempool: Arc<MockSharedMempool>,
     pub db: Arc<AptosDB>,
+    committed_version_sender: Arc<watch::Sender<Version>>,
     rng: rand::rngs::StdRng,
     root_key: ConfigKey<Ed25519PrivateKey>,
     executor: Arc<dyn BlockExecutorTrait>,
@@ -194,6 +206,7 @@ impl TestContext {
         executor: Box<dyn BlockExecutorTrait>,
         mempool: MockSharedMempool,
         db: Arc<AptosDB>,
+        committed_version_sender: watch::Sender<Version>,
         test_name: String,
         api_specific_config: ApiSpecificConfig,
     ) -> Self {
@@ -206,6 +219,7 @@ impl TestContext {
             mempool: Arc::new(mempool),
             expect_status_code: 200,
             db,
+            committed_version_sender: Arc::new(committed_version_sender),
             test_name,
             golden_output: None,
             fake_time: 0,
@@ -348,6 +362,9 @@ impl TestContext {
                 self.new_ledger_info(&metadata, result.root_hash(), txns.len()),
             )
             .unwrap();
+        self.committed_version_sender
+            .send(self.get_latest_ledger_info().version())
+            .unwrap();
 
         self.mempool
             .mempool_notifier
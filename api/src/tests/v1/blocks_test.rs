@@ -0,0 +1,60 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use super::new_test_context;
+use crate::current_function_name;
+use serde_json::json;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_genesis_block_by_height() {
+    let context = new_test_context(current_function_name!());
+    let resp = context.get("/blocks/by_height/0").await;
+    assert_eq!(resp["block_height"], json!("0"));
+    assert_eq!(resp["first_version"], json!("0"));
+    assert!(resp["transactions"].is_null());
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_block_by_height_and_version() {
+    let mut context = new_test_context(current_function_name!());
+    let account = context.gen_account();
+    let txn = context.create_user_account(&account);
+    context.commit_block(&vec![txn]).await;
+
+    let by_height = context
+        .get("/blocks/by_height/1?with_transactions=true")
+        .await;
+    assert_eq!(by_height["block_height"], json!("1"));
+    assert_eq!(by_height["first_version"], json!("1"));
+    assert_eq!(by_height["last_version"], json!("3"));
+
+    // Block metadata, user transaction and state checkpoint
+    let txns = by_height["transactions"].as_array().unwrap();
+    assert_eq!(txns.len(), 3);
+    assert_eq!(txns[0]["type"], json!("block_metadata_transaction"));
+    assert_eq!(txns[1]["type"], json!("user_transaction"));
+
+    let by_version = context
+        .get("/blocks/by_version/2?with_transactions=true")
+        .await;
+    assert_eq!(by_version, by_height);
+
+    let without_txns = context.get("/blocks/by_version/2").await;
+    assert_eq!(without_txns["block_hash"], by_height["block_hash"]);
+    assert!(without_txns["transactions"].is_null());
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_block_not_found() {
+    let context = new_test_context(current_function_name!());
+    let resp = context
+        .expect_status_code(404)
+        .get("/blocks/by_height/1000")
+        .await;
+    assert!(resp["message"].as_str().unwrap().contains("not found"));
+
+    context
+        .expect_status_code(404)
+        .get("/blocks/by_version/1000000000")
+        .await;
+}
@@ -0,0 +1,18 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use super::new_test_context;
+use crate::current_function_name;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_estimate_gas_price() {
+    let context = new_test_context(current_function_name!());
+    let resp = context.get("/estimate_gas_price").await;
+
+    let price = |field: &str| -> u64 { resp[field].as_str().unwrap().parse().unwrap() };
+    let low = price("low_gas_unit_price");
+    let median = price("median_gas_unit_price");
+    let prioritized = price("prioritized_gas_unit_price");
+    assert!(low <= median);
+    assert!(median <= prioritized);
+}
@@ -0,0 +1,80 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use super::{new_test_context, new_test_context_with_config, TestContext};
+use crate::current_function_name;
+use aptos_config::config::NodeConfig;
+use serde_json::json;
+
+fn new_mempool_inspection_context(test_name: String) -> TestContext {
+    let mut node_config = NodeConfig::default();
+    node_config.api.mempool_inspection_enabled = true;
+    new_test_context_with_config(test_name, node_config)
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_mempool_account_transactions() {
+    let mut context = new_mempool_inspection_context(current_function_name!());
+    let account = context.gen_account();
+    let txn = context.create_user_account(&account);
+    let body = bcs::to_bytes(&txn).unwrap();
+    context
+        .expect_status_code(202)
+        .post_bcs_txn("/transactions", body)
+        .await;
+
+    let sender = context.root_account().address();
+    let resp = context
+        .get(&format!("/mempool/accounts/{}", sender.to_hex_literal()))
+        .await;
+    assert_eq!(resp["ready_transactions"], json!("1"));
+    assert_eq!(resp["parked_transactions"], json!("0"));
+    let txns = resp["transactions"].as_array().unwrap();
+    assert_eq!(txns.len(), 1);
+    assert_eq!(
+        txns[0]["transaction"]["hash"],
+        json!(txn.committed_hash().to_hex_literal())
+    );
+    assert_eq!(txns[0]["parked"], json!(false));
+    assert_eq!(txns[0]["priority_index_position"], json!("0"));
+
+    let resp = context
+        .get(&format!(
+            "/mempool/accounts/{}",
+            account.address().to_hex_literal()
+        ))
+        .await;
+    assert_eq!(resp["transactions"], json!([]));
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_mempool_stats() {
+    let mut context = new_mempool_inspection_context(current_function_name!());
+    let account = context.gen_account();
+    let txn = context.create_user_account(&account);
+    let body = bcs::to_bytes(&txn).unwrap();
+    context
+        .expect_status_code(202)
+        .post_bcs_txn("/transactions", body)
+        .await;
+
+    let resp = context.get("/mempool/stats").await;
+    assert_eq!(resp["total_transactions"], json!("1"));
+    assert_eq!(resp["ready_transactions"], json!("1"));
+    assert_eq!(resp["num_accounts"], json!("1"));
+    assert_eq!(
+        resp["top_accounts"][0]["address"],
+        json!(context.root_account().address().to_hex_literal())
+    );
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_mempool_inspection_disabled_by_default() {
+    let context = new_test_context(current_function_name!());
+    let sender = context.root_account().address();
+    context
+        .expect_status_code(404)
+        .get(&format!("/mempool/accounts/{}", sender.to_hex_literal()))
+        .await;
+    context.expect_status_code(404).get("/mempool/stats").await;
+}
@@ -2,19 +2,29 @@
 // SPDX-License-Identifier: Apache-2.0
 
 mod accounts_test;
+mod blocks_test;
 mod converter_test;
 mod events_test;
+mod gas_estimation_test;
 mod index_test;
 mod invalid_post_request_test;
+mod mempool_test;
 mod state_test;
+mod stream_test;
 mod string_resource_test;
 mod transaction_vector_test;
 mod transactions_test;
+mod view_function_test;
 
 use super::TestContext;
+use aptos_config::config::NodeConfig;
 
 pub const API_VERSION: &str = "v1";
 
 pub fn new_test_context(test_name: String) -> TestContext {
     super::new_test_context(test_name, API_VERSION)
 }
+
+pub fn new_test_context_with_config(test_name: String, node_config: NodeConfig) -> TestContext {
+    super::new_test_context_with_config(test_name, node_config, API_VERSION)
+}
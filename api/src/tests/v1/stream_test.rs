@@ -0,0 +1,121 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use super::new_test_context;
+use crate::{current_function_name, tests::ApiSpecificConfig};
+use serde_json::{json, Value};
+use std::time::Duration;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_stream_transactions() {
+    let mut context = new_test_context(current_function_name!());
+    let account = context.gen_account();
+    let txn = context.create_user_account(&account);
+    context.commit_block(&vec![txn]).await;
+
+    let mut resp = open_stream(
+        &context.api_specific_config,
+        "/stream/transactions?start_version=0",
+    )
+    .await;
+
+    // Genesis, then block metadata, user transaction and state checkpoint
+    let txns = read_messages(&mut resp, 4).await;
+    for (version, txn) in txns.iter().enumerate() {
+        assert_eq!(txn["version"], json!(version.to_string()));
+    }
+    assert_eq!(txns[2]["type"], json!("user_transaction"));
+
+    // Transactions committed while the stream is open are pushed to it
+    let account = context.gen_account();
+    let txn = context.create_user_account(&account);
+    context.commit_block(&vec![txn]).await;
+    let txns = read_messages(&mut resp, 3).await;
+    assert_eq!(txns[0]["version"], json!("4"));
+    assert_eq!(txns[1]["type"], json!("user_transaction"));
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_stream_events_by_event_key() {
+    let mut context = new_test_context(current_function_name!());
+    let mut resp = open_stream(
+        &context.api_specific_config,
+        // The new block event key
+        "/stream/events?event_key=0x02000000000000000000000000000000000000000000000000000000000000000000000000000001",
+    )
+    .await;
+
+    let account = context.gen_account();
+    let txn = context.create_user_account(&account);
+    context.commit_block(&vec![txn]).await;
+
+    let events = read_messages(&mut resp, 1).await;
+    assert_eq!(events[0]["version"], json!("1"));
+    assert_eq!(events[0]["type"], json!("0x1::block::NewBlockEvent"));
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_stream_limit() {
+    let context = new_test_context(current_function_name!());
+
+    // Take every stream slot, so that the next stream is rejected
+    let mut permits = vec![];
+    while let Some(permit) = context.context.try_acquire_stream_permit() {
+        permits.push(permit);
+    }
+    let resp = reqwest::get(stream_url(
+        &context.api_specific_config,
+        "/stream/transactions?start_version=0",
+    ))
+    .await
+    .unwrap();
+    assert_eq!(resp.status(), 503);
+
+    // Closing a stream frees its slot
+    permits.pop();
+    let mut resp = open_stream(
+        &context.api_specific_config,
+        "/stream/transactions?start_version=0",
+    )
+    .await;
+    let txns = read_messages(&mut resp, 1).await;
+    assert_eq!(txns[0]["version"], json!("0"));
+}
+
+fn stream_url(config: &ApiSpecificConfig, path: &str) -> String {
+    match config {
+        ApiSpecificConfig::V1(address) => format!("http://{}{}", address, path),
+        ApiSpecificConfig::V0 => panic!("Streaming is only supported by the v1 API"),
+    }
+}
+
+async fn open_stream(config: &ApiSpecificConfig, path: &str) -> reqwest::Response {
+    let resp = reqwest::get(stream_url(config, path)).await.unwrap();
+    assert_eq!(resp.status(), 200);
+    resp
+}
+
+/// Reads server-sent events from the response until `count` messages were received.
+async fn read_messages(resp: &mut reqwest::Response, count: usize) -> Vec<Value> {
+    let mut messages = vec![];
+    let mut buffer = String::new();
+    while messages.len() < count {
+        let chunk = tokio::time::timeout(Duration::from_secs(10), resp.chunk())
+            .await
+            .expect("timed out waiting for stream messages")
+            .unwrap()
+            .expect("stream ended unexpectedly");
+        buffer.push_str(std::str::from_utf8(&chunk).unwrap());
+
+        // Messages are separated by empty lines
+        while let Some(end) = buffer.find("\n\n") {
+            let message: String = buffer.drain(..end + 2).collect();
+            for line in message.lines() {
+                if let Some(data) = line.strip_prefix("data:") {
+                    messages.push(serde_json::from_str(data.trim()).unwrap());
+                }
+            }
+        }
+    }
+    messages
+}
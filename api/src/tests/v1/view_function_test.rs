@@ -0,0 +1,56 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use super::new_test_context;
+use crate::current_function_name;
+use serde_json::json;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_view_function() {
+    let context = new_test_context(current_function_name!());
+    let resp = context
+        .post(
+            "/view",
+            json!({
+                "function": "0x1::coin::is_coin_initialized",
+                "type_arguments": ["0x1::aptos_coin::AptosCoin"],
+                "arguments": [],
+            }),
+        )
+        .await;
+    assert_eq!(resp, json!([true]));
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_view_function_with_signer_param() {
+    let context = new_test_context(current_function_name!());
+    let resp = context
+        .expect_status_code(400)
+        .post(
+            "/view",
+            json!({
+                "function": "0x1::coin::withdraw",
+                "type_arguments": ["0x1::aptos_coin::AptosCoin"],
+                "arguments": ["1"],
+            }),
+        )
+        .await;
+    assert!(resp["message"].as_str().unwrap().contains("signer"));
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_view_function_with_invalid_ledger_version() {
+    let context = new_test_context(current_function_name!());
+    let resp = context
+        .expect_status_code(404)
+        .post(
+            "/view?ledger_version=1000000000",
+            json!({
+                "function": "0x1::coin::is_coin_initialized",
+                "type_arguments": ["0x1::aptos_coin::AptosCoin"],
+                "arguments": [],
+            }),
+        )
+        .await;
+    assert!(resp["message"].as_str().unwrap().contains("not found"));
+}
@@ -1,7 +1,8 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::HashValue;
+use crate::{HashValue, Transaction, U64};
+use poem_openapi::Object;
 use serde::{Deserialize, Serialize};
 
 // TODO: Consider including this in the API.
@@ -17,3 +18,17 @@ pub struct BlockInfo {
     pub end_version: u64,
     pub num_transactions: u16,
 }
+
+/// A block on the blockchain, along with its transactions if they were requested
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Object)]
+pub struct Block {
+    pub block_height: U64,
+    pub block_hash: HashValue,
+    pub block_timestamp: U64,
+    /// The version of the first transaction in the block
+    pub first_version: U64,
+    /// The version of the last transaction in the block
+    pub last_version: U64,
+    /// The transactions in the block in sequential order
+    pub transactions: Option<Vec<Transaction>>,
+}
@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
+    move_types::MoveFunctionVisibility,
     transaction::{
         DecodedTableData, DeleteModule, DeleteResource, DeleteTableItem, DeletedTableData,
         ModuleBundlePayload, StateCheckpointTransaction, UserTransactionRequestInner, WriteModule,
@@ -10,8 +11,8 @@ use crate::{
     Bytecode, DirectWriteSet, Event, HexEncodedBytes, MoveFunction, MoveModuleBytecode,
     MoveResource, MoveScriptBytecode, MoveValue, PendingTransaction, ScriptFunctionId,
     ScriptFunctionPayload, ScriptPayload, ScriptWriteSet, SubmitTransactionRequest, Transaction,
-    TransactionInfo, TransactionOnChainData, TransactionPayload, UserTransactionRequest, WriteSet,
-    WriteSetChange, WriteSetPayload,
+    TransactionInfo, TransactionOnChainData, TransactionPayload, UserTransactionRequest,
+    ViewFunction, ViewRequest, WriteSet, WriteSetChange, WriteSetPayload,
 };
 use anyhow::{bail, ensure, format_err, Context as AnyhowContext, Result};
 use aptos_crypto::{hash::CryptoHash, HashValue};
@@ -527,6 +528,60 @@ impl<'a, R: MoveResolverExt + ?Sized> MoveConverter<'a, R> {
         Ok(ret)
     }
 
+    pub fn try_into_view_function(&self, request: ViewRequest) -> Result<ViewFunction> {
+        let ViewRequest {
+            function,
+            type_arguments,
+            arguments,
+        } = request;
+
+        let module = function.module.clone();
+        let code = self.inner.get_module(&module.clone().into())? as Rc<dyn Bytecode>;
+        let func = code
+            .find_function(function.name.0.as_ident_str())
+            .ok_or_else(|| format_err!("could not find function by {}", function))?;
+        ensure!(
+            func.visibility == MoveFunctionVisibility::Public,
+            "function {} is not public",
+            function
+        );
+        ensure!(
+            func.generic_type_params.len() == type_arguments.len(),
+            "expect {} type arguments for function {}, but got {}",
+            func.generic_type_params.len(),
+            function,
+            type_arguments.len()
+        );
+        ensure!(
+            !func.params.iter().any(|p| p.is_signer()),
+            "function {} requires a signer, which is not supported for view functions",
+            function
+        );
+
+        let type_arguments = type_arguments
+            .into_iter()
+            .map(|v| v.try_into())
+            .collect::<Result<Vec<TypeTag>>>()?;
+        let return_types = func
+            .return_
+            .iter()
+            .map(|t| t.try_instantiate(&type_arguments))
+            .collect::<Result<_>>()?;
+        let arguments = self
+            .try_into_vm_values(func, arguments)?
+            .iter()
+            .map(bcs::to_bytes)
+            .collect::<Result<_, bcs::Error>>()?;
+
+        Ok(ViewFunction {
+            module: module.into(),
+            function: function.name.into(),
+            type_arguments,
+            arguments,
+            return_types,
+        })
+    }
+
     pub fn try_into_vm_values(
         &self,
         func: MoveFunction,
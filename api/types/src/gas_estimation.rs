@@ -0,0 +1,18 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::U64;
+use poem_openapi::Object;
+use serde::{Deserialize, Serialize};
+
+/// Gas unit prices estimated from recently committed transactions and the
+/// current state of mempool.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Object)]
+pub struct GasEstimation {
+    /// Price for transactions that can wait for a quiet period
+    pub low_gas_unit_price: U64,
+    /// Price that gets a transaction committed under the current load
+    pub median_gas_unit_price: U64,
+    /// Price that puts a transaction ahead of most of the pending ones
+    pub prioritized_gas_unit_price: U64,
+}
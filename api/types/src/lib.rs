@@ -9,26 +9,33 @@ mod convert;
 mod derives;
 mod error;
 mod event_key;
+mod gas_estimation;
 mod hash;
 mod index;
 mod ledger_info;
+mod mempool;
 pub mod mime_types;
 mod move_types;
 mod response;
 mod table;
 mod transaction;
+mod view;
 mod wrappers;
 
 pub use account::AccountData;
 pub use address::Address;
-pub use block::BlockInfo;
+pub use block::{Block, BlockInfo};
 pub use bytecode::Bytecode;
 pub use convert::{new_vm_utf8_string, AsConverter, MoveConverter};
 pub use error::Error;
 pub use event_key::EventKey;
+pub use gas_estimation::GasEstimation;
 pub use hash::HashValue;
 pub use index::IndexResponse;
 pub use ledger_info::LedgerInfo;
+pub use mempool::{
+    MempoolAccountStats, MempoolAccountTransactions, MempoolStats, MempoolTransaction,
+};
 pub use move_types::{
     HexEncodedBytes, MoveFunction, MoveModule, MoveModuleBytecode, MoveModuleId, MoveResource,
     MoveScriptBytecode, MoveStructTag, MoveType, MoveValue, ScriptFunctionId, U128, U64,
@@ -43,7 +50,8 @@ pub use transaction::{
     ScriptPayload, ScriptWriteSet, SubmitTransactionRequest, Transaction, TransactionData,
     TransactionId, TransactionInfo, TransactionOnChainData, TransactionPayload,
     TransactionSigningMessage, UserCreateSigningMessageRequest, UserTransaction,
-    UserTransactionRequest, WriteModule, WriteResource, WriteSet, WriteSetChange, WriteSetPayload,
-    WriteTableItem,
+    UserTransactionRequest, VersionedEvent, WriteModule, WriteResource, WriteSet, WriteSetChange,
+    WriteSetPayload, WriteTableItem,
 };
+pub use view::{ViewFunction, ViewRequest};
 pub use wrappers::{IdentifierWrapper, MoveStructTagWrapper};
@@ -0,0 +1,54 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{Address, PendingTransaction, U64};
+use poem_openapi::Object;
+use serde::{Deserialize, Serialize};
+
+/// A transaction waiting in mempool
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Object)]
+pub struct MempoolTransaction {
+    pub transaction: PendingTransaction,
+    /// Whether the transaction is parked, i.e. it can't be included in the
+    /// next block, usually because a lower sequence number is missing
+    pub parked: bool,
+    /// Position in the log of transactions broadcast to other nodes, if the
+    /// transaction is ready for broadcast
+    pub timeline_id: Option<U64>,
+    /// Position in the queue blocks are built from, if the transaction is ready.
+    /// The queue is ordered by gas unit price, so 0 is the next transaction picked
+    pub priority_index_position: Option<U64>,
+    /// Time the transaction entered mempool in microseconds, if it's still tracked
+    pub insertion_timestamp_usecs: Option<U64>,
+}
+
+/// The transactions of an account waiting in mempool
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Object)]
+pub struct MempoolAccountTransactions {
+    pub address: Address,
+    pub ready_transactions: U64,
+    pub parked_transactions: U64,
+    /// Transactions ordered by sequence number
+    pub transactions: Vec<MempoolTransaction>,
+}
+
+/// Number of transactions of an account waiting in mempool
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Object)]
+pub struct MempoolAccountStats {
+    pub address: Address,
+    pub ready_transactions: U64,
+    pub parked_transactions: U64,
+}
+
+/// Aggregate counts of the transactions waiting in mempool
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Object)]
+pub struct MempoolStats {
+    pub total_transactions: U64,
+    pub ready_transactions: U64,
+    pub parked_transactions: U64,
+    pub num_accounts: U64,
+    pub capacity: U64,
+    pub capacity_per_account: U64,
+    /// Accounts with the most transactions in mempool, most first
+    pub top_accounts: Vec<MempoolAccountStats>,
+}
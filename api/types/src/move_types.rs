@@ -492,6 +492,32 @@ impl MoveType {
             MoveType::Unparsable(string) => string.to_string(),
         }
     }
+
+    // Converts `MoveType` into `TypeTag`, substituting generic type params with the
+    // given type arguments
+    pub fn try_instantiate(&self, type_args: &[TypeTag]) -> anyhow::Result<TypeTag> {
+        let ret = match self {
+            MoveType::GenericTypeParam { index } => type_args
+                .get(*index as usize)
+                .cloned()
+                .ok_or_else(|| format_err!("missing type argument for type param {}", index))?,
+            MoveType::Vector { items } => {
+                TypeTag::Vector(Box::new(items.try_instantiate(type_args)?))
+            }
+            MoveType::Struct(tag) => TypeTag::Struct(StructTag {
+                address: tag.address.into(),
+                module: tag.module.clone().into(),
+                name: tag.name.clone().into(),
+                type_params: tag
+                    .generic_type_params
+                    .iter()
+                    .map(|p| p.try_instantiate(type_args))
+                    .collect::<anyhow::Result<Vec<TypeTag>>>()?,
+            }),
+            _ => self.clone().try_into()?,
+        };
+        Ok(ret)
+    }
 }
 
 impl fmt::Display for MoveType {
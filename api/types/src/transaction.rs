@@ -436,6 +436,15 @@ impl From<(&ContractEvent, serde_json::Value)> for Event {
     }
 }
 
+/// An event along with the version of the transaction that emitted it
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Object)]
+pub struct VersionedEvent {
+    pub version: U64,
+    #[serde(flatten)]
+    #[oai(flatten)]
+    pub event: Event,
+}
+
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Union)]
 #[serde(tag = "type", rename_all = "snake_case")]
 #[oai(one_of, discriminator_name = "type", rename_all = "snake_case")]
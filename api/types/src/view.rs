@@ -0,0 +1,29 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{MoveType, ScriptFunctionId};
+use move_deps::move_core_types::{
+    identifier::Identifier,
+    language_storage::{ModuleId, TypeTag},
+};
+use poem_openapi::Object;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Request to run a public Move function read-only against the ledger.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Object)]
+pub struct ViewRequest {
+    pub function: ScriptFunctionId,
+    pub type_arguments: Vec<MoveType>,
+    pub arguments: Vec<Value>,
+}
+
+/// A `ViewRequest` resolved against the on-chain module ABI, ready to be executed by the VM.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ViewFunction {
+    pub module: ModuleId,
+    pub function: Identifier,
+    pub type_arguments: Vec<TypeTag>,
+    pub arguments: Vec<Vec<u8>>,
+    pub return_types: Vec<TypeTag>,
+}
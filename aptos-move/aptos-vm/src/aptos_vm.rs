@@ -49,7 +49,8 @@ use move_deps::{
         account_address::AccountAddress,
         gas_schedule::{GasAlgebra, GasUnits},
         ident_str,
-        language_storage::ModuleId,
+        identifier::Identifier,
+        language_storage::{ModuleId, TypeTag},
         transaction_argument::convert_txn_args,
         value::{serialize_values, MoveValue},
     },
@@ -923,6 +924,39 @@ impl AptosVM {
         simulation_vm.simulate_signed_transaction(&state_view.as_move_resolver(), txn, &log_context)
     }
 
+    /// Executes a function read-only against the given state view, without a signer, and
+    /// returns the BCS serialized return values. Any changes made by the function are
+    /// discarded. Callers are responsible for checking that the function is public.
+    pub fn execute_view_function(
+        state_view: &impl StateView,
+        module_id: ModuleId,
+        func_name: Identifier,
+        type_args: Vec<TypeTag>,
+        arguments: Vec<Vec<u8>>,
+        gas_budget: u64,
+    ) -> Result<Vec<Vec<u8>>, VMStatus> {
+        let vm = AptosVM::new(state_view);
+        let log_context = AdapterLogSchema::new(state_view.id(), 0);
+        let gas_schedule = vm.0.get_gas_schedule(&log_context)?;
+        let mut gas_status = GasStatus::new(gas_schedule, GasUnits::new(gas_budget));
+        let resolver = state_view.as_move_resolver();
+        let mut session = vm.0.new_session(&resolver, SessionId::void());
+        let return_values = session
+            .execute_function_bypass_visibility(
+                &module_id,
+                &func_name,
+                type_args,
+                arguments,
+                &mut gas_status,
+            )
+            .map_err(|e| e.into_vm_status())?;
+        Ok(return_values
+            .return_values
+            .into_iter()
+            .map(|(bytes, _layout)| bytes)
+            .collect())
+    }
+
     fn run_prologue_with_payload<S: MoveResolverExt>(
         &self,
         session: &mut SessionExt<S>,
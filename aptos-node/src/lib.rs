@@ -8,7 +8,7 @@ use aptos_api::runtime::bootstrap as bootstrap_api;
 use aptos_config::{
     config::{
         AptosDataClientConfig, BaseConfig, DataStreamingServiceConfig, NetworkConfig, NodeConfig,
-        PersistableConfig, StorageServiceConfig,
+        PeerMonitoringServiceConfig, PersistableConfig, StorageServiceConfig,
     },
     network_id::NetworkId,
     utils::get_genesis_txn,
@@ -39,6 +39,14 @@ use hex::FromHex;
 use mempool_notifications::MempoolNotificationSender;
 use network::application::storage::PeerMetadataStorage;
 use network_builder::builder::NetworkBuilder;
+use once_cell::sync::OnceCell;
+use peer_monitoring_service_client::{
+    PeerMonitor, PeerMonitoringServiceClient, PeerMonitoringServiceMultiSender,
+    PeerMonitoringServiceNetworkSender,
+};
+use peer_monitoring_service_server::{
+    network::PeerMonitoringServiceNetworkEvents, PeerMonitoringServiceServer,
+};
 use rand::{rngs::StdRng, SeedableRng};
 use state_sync_multiplexer::{
     state_sync_v1_network_config, StateSyncMultiplexer, StateSyncRuntimes,
@@ -153,6 +161,7 @@ pub struct AptosHandle {
     _consensus_runtime: Option<Runtime>,
     _mempool: Runtime,
     _network_runtimes: Vec<Runtime>,
+    _peer_monitoring_service_runtime: Runtime,
     _state_sync_runtimes: StateSyncRuntimes,
     _telemetry_runtime: Option<Runtime>,
 }
@@ -462,11 +471,51 @@ fn setup_state_sync_storage_service(
     Ok(storage_service_runtime)
 }
 
+fn setup_peer_monitoring_service(
+    config: PeerMonitoringServiceConfig,
+    server_network_handles: Vec<PeerMonitoringServiceNetworkEvents>,
+    client_network_handles: HashMap<NetworkId, PeerMonitoringServiceNetworkSender>,
+    peer_metadata_storage: Arc<PeerMetadataStorage>,
+) -> anyhow::Result<Runtime> {
+    // Create a new peer monitoring service runtime
+    let peer_monitoring_service_runtime = Builder::new_multi_thread()
+        .thread_name("peer-mon-service")
+        .enable_all()
+        .build()
+        .map_err(|err| anyhow!("Failed to start peer monitoring service {}", err))?;
+
+    // Spawn all peer monitoring service servers on the same runtime
+    for network_requests in server_network_handles {
+        let service = PeerMonitoringServiceServer::new(
+            config.clone(),
+            peer_monitoring_service_runtime.handle().clone(),
+            network_requests,
+            peer_metadata_storage.clone(),
+        );
+        peer_monitoring_service_runtime.spawn(service.start());
+    }
+
+    // Spawn the peer monitor that pings and inspects all connected peers
+    let client = PeerMonitoringServiceClient::new(
+        PeerMonitoringServiceMultiSender::new(client_network_handles),
+        peer_metadata_storage,
+    );
+    let peer_monitor = PeerMonitor::new(config, client, TimeService::real());
+    peer_monitoring_service_runtime.spawn(peer_monitor.start());
+
+    Ok(peer_monitoring_service_runtime)
+}
+
 pub fn setup_environment(node_config: NodeConfig) -> anyhow::Result<AptosHandle> {
-    // Start the node inspection service
+    // Start the node inspection service. The DB is handed over once it's open.
     let node_config_clone = node_config.clone();
+    let inspection_service_db = Arc::new(OnceCell::new());
+    let inspection_service_db_clone = Arc::clone(&inspection_service_db);
     thread::spawn(move || {
-        inspection_service::inspection_service::start_inspection_service(node_config_clone)
+        inspection_service::inspection_service::start_inspection_service(
+            node_config_clone,
+            inspection_service_db_clone,
+        )
     });
 
     // Open the database
@@ -482,6 +531,9 @@ pub fn setup_environment(node_config: NodeConfig) -> anyhow::Result<AptosHandle>
         )
         .map_err(|err| anyhow!("DB failed to open {}", err))?,
     );
+    inspection_service_db
+        .set(db_rw.clone())
+        .map_err(|_| anyhow!("DB already handed to the inspection service"))?;
     let backup_service = start_backup_service(
         node_config.storage.backup_service_address,
         Arc::clone(&aptos_db),
@@ -512,6 +564,8 @@ pub fn setup_environment(node_config: NodeConfig) -> anyhow::Result<AptosHandle>
     let mut consensus_network_handles = None;
     let mut storage_service_server_network_handles = vec![];
     let mut storage_service_client_network_handles = HashMap::new();
+    let mut peer_monitoring_service_server_network_handles = vec![];
+    let mut peer_monitoring_service_client_network_handles = HashMap::new();
 
     // Create an event subscription service so that components can be notified of events and reconfigs
     let mut event_subscription_service = EventSubscriptionService::new(
@@ -521,6 +575,9 @@ pub fn setup_environment(node_config: NodeConfig) -> anyhow::Result<AptosHandle>
     let mempool_reconfig_subscription =
         event_subscription_service.subscribe_to_reconfigurations()?;
 
+    // Create an API subscription to commits, so that the API can stream new transactions
+    let api_commit_subscription = event_subscription_service.subscribe_to_commits()?;
+
     // Create a consensus subscription for reconfiguration events (if this node is a validator).
     let consensus_reconfig_subscription = if node_config.base.role.is_validator() {
         Some(event_subscription_service.subscribe_to_reconfigurations()?)
@@ -601,6 +658,18 @@ pub fn setup_environment(node_config: NodeConfig) -> anyhow::Result<AptosHandle>
             network_builder.add_client(&storage_service_client::network_endpoint_config());
         storage_service_client_network_handles.insert(network_id, storage_service_sender);
 
+        // Register the peer monitoring service (both client and server) with Network
+        let peer_monitoring_service_events = network_builder.add_service(
+            &peer_monitoring_service_server::network::network_endpoint_config(
+                node_config.peer_monitoring_service.clone(),
+            ),
+        );
+        peer_monitoring_service_server_network_handles.push(peer_monitoring_service_events);
+        let peer_monitoring_service_sender =
+            network_builder.add_client(&peer_monitoring_service_client::network_endpoint_config());
+        peer_monitoring_service_client_network_handles
+            .insert(network_id, peer_monitoring_service_sender);
+
         // Create the endpoints to connect the Network to mempool.
         let (mempool_sender, mempool_events) = network_builder.add_p2p_service(
             &aptos_mempool::network::network_endpoint_config(MEMPOOL_NETWORK_CHANNEL_BUFFER_SIZE),
@@ -628,6 +697,14 @@ pub fn setup_environment(node_config: NodeConfig) -> anyhow::Result<AptosHandle>
         network_runtimes.push(runtime);
     }
 
+    // Start the peer monitoring service
+    let peer_monitoring_service_runtime = setup_peer_monitoring_service(
+        node_config.peer_monitoring_service.clone(),
+        peer_monitoring_service_server_network_handles,
+        peer_monitoring_service_client_network_handles,
+        peer_metadata_storage.clone(),
+    )?;
+
     // TODO set up on-chain discovery network based on UpstreamConfig.fallback_network
     // and pass network handles to mempool/state sync
 
@@ -655,7 +732,13 @@ pub fn setup_environment(node_config: NodeConfig) -> anyhow::Result<AptosHandle>
 
     let (mp_client_sender, mp_client_events) = channel(AC_SMP_CHANNEL_BUFFER_SIZE);
 
-    let api_runtime = bootstrap_api(&node_config, chain_id, aptos_db, mp_client_sender)?;
+    let api_runtime = bootstrap_api(
+        &node_config,
+        chain_id,
+        aptos_db,
+        mp_client_sender,
+        api_commit_subscription,
+    )?;
 
     let mut consensus_runtime = None;
     let (consensus_to_mempool_sender, consensus_to_mempool_receiver) =
@@ -674,10 +757,6 @@ pub fn setup_environment(node_config: NodeConfig) -> anyhow::Result<AptosHandle>
     );
     debug!("Mempool started in {} ms", instant.elapsed().as_millis());
 
-    assert!(
-        !node_config.consensus.use_quorum_store,
-        "QuorumStore is not yet implemented"
-    );
     assert_ne!(
         node_config.consensus.use_quorum_store,
         node_config.mempool.shared_mempool_validator_broadcast,
@@ -724,6 +803,7 @@ pub fn setup_environment(node_config: NodeConfig) -> anyhow::Result<AptosHandle>
         _consensus_runtime: consensus_runtime,
         _mempool: mempool,
         _network_runtimes: network_runtimes,
+        _peer_monitoring_service_runtime: peer_monitoring_service_runtime,
         _state_sync_runtimes: state_sync_runtimes,
         _telemetry_runtime: telemetry_runtime,
     })
@@ -2,7 +2,9 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::error::Error;
-use aptos_config::config::{self, GitHubConfig, OnDiskStorageConfig, Token, VaultConfig};
+use aptos_config::config::{
+    self, GitHubConfig, OnDiskStorageConfig, SealingSecret, Token, VaultConfig,
+};
 use std::{
     collections::HashMap,
     convert::{TryFrom, TryInto},
@@ -78,6 +80,10 @@ impl TryInto<config::SecureBackend> for SecureBackend {
                     .ok_or_else(|| Error::BackendParsingError("missing path".into()))?;
                 config.path = PathBuf::from(path);
                 config.namespace = self.parameters.remove("namespace");
+                config.sealing_secret = self
+                    .parameters
+                    .remove("sealing_key_file")
+                    .map(|path| SealingSecret::KeyFile(PathBuf::from(path)));
                 config::SecureBackend::OnDiskStorage(config)
             }
             GITHUB => {
@@ -159,6 +165,7 @@ pair: "k0=v0;k1=v1;...".  The current supported formats are:
         an optional namespace: "namespace=NAMESPACE"
     InMemory: "backend=memory"
     OnDisk: "backend=disk;path=LOCAL_PATH"
+        an optional sealing (encryption) key: "sealing_key_file=PATH_TO_KEY"
                 "#)
             )]
             pub $field_name: Option<SecureBackend>,
@@ -212,6 +219,23 @@ mod tests {
         assert!(storage(disk).is_err());
     }
 
+    #[test]
+    fn test_sealed_disk() {
+        let key_path = aptos_temppath::TempPath::new();
+        key_path.create_as_file().unwrap();
+        let mut file = File::create(key_path.path()).unwrap();
+        file.write_all(b"sealing_key").unwrap();
+
+        let path = aptos_temppath::TempPath::new();
+        path.create_as_file().unwrap();
+        let disk = format!(
+            "backend=disk;path={};sealing_key_file={}",
+            path.path().to_str().unwrap(),
+            key_path.path().to_str().unwrap()
+        );
+        storage(&disk).unwrap();
+    }
+
     #[test]
     fn test_github() {
         let path = aptos_temppath::TempPath::new();
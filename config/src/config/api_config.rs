@@ -18,11 +18,23 @@ pub struct ApiConfig {
     // optional for compatible with old configuration
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub content_length_limit: Option<u64>,
+    // optional gas budget for read-only view function calls
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub view_gas_limit: Option<u64>,
+    // optional limit on the number of concurrently open transaction and event streams
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_concurrent_streams: Option<usize>,
+    // enables the mempool inspection endpoints (/mempool/accounts/:address and /mempool/stats),
+    // which are off by default as they're served while holding the mempool lock
+    #[serde(default)]
+    pub mempool_inspection_enabled: bool,
 }
 
 pub const DEFAULT_ADDRESS: &str = "127.0.0.1";
 pub const DEFAULT_PORT: u16 = 8080;
 pub const DEFAULT_REQUEST_CONTENT_LENGTH_LIMIT: u64 = 4 * 1024 * 1024; // 4mb
+pub const DEFAULT_VIEW_GAS_LIMIT: u64 = 1_000_000;
+pub const DEFAULT_MAX_CONCURRENT_STREAMS: usize = 100;
 
 fn default_enabled() -> bool {
     true
@@ -38,6 +50,9 @@ impl Default for ApiConfig {
             tls_cert_path: None,
             tls_key_path: None,
             content_length_limit: None,
+            view_gas_limit: None,
+            max_concurrent_streams: None,
+            mempool_inspection_enabled: false,
         }
     }
 }
@@ -53,4 +68,13 @@ impl ApiConfig {
             None => DEFAULT_REQUEST_CONTENT_LENGTH_LIMIT,
         }
     }
+
+    pub fn view_gas_limit(&self) -> u64 {
+        self.view_gas_limit.unwrap_or(DEFAULT_VIEW_GAS_LIMIT)
+    }
+
+    pub fn max_concurrent_streams(&self) -> usize {
+        self.max_concurrent_streams
+            .unwrap_or(DEFAULT_MAX_CONCURRENT_STREAMS)
+    }
 }
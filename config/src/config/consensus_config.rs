@@ -20,13 +20,22 @@ pub struct ConsensusConfig {
     // validators coordinate on the latest version to apply a manual transaction.
     pub sync_only: bool,
     pub channel_size: usize,
-    // When false, use the Direct Mempool Quorum Store
+    // When false, use the Direct Mempool Quorum Store. When true, validators disseminate batches
+    // of transactions and blocks only carry proofs that a quorum of validators stored them.
     pub use_quorum_store: bool,
     pub quorum_store_pull_timeout_ms: u64,
     // Decides how long the leader waits before proposing empty block if there's no txns in mempool
     // the period = (poll_count - 1) * 30ms
     pub quorum_store_poll_count: u64,
     pub intra_consensus_channel_buffer_size: usize,
+    // Maximum number of transactions pulled from mempool into a single quorum store batch
+    pub quorum_store_max_batch_size: u64,
+    // How often the quorum store pulls transactions from mempool to create a batch (in milliseconds)
+    pub quorum_store_batch_interval_ms: u64,
+    // Number of rounds after which a batch expires and can't be included in blocks anymore
+    pub quorum_store_batch_expiry_rounds: u64,
+    // Timeout for fetching a missing batch from a peer that signed its proof (in milliseconds)
+    pub quorum_store_batch_request_timeout_ms: u64,
 }
 
 impl Default for ConsensusConfig {
@@ -44,6 +53,10 @@ impl Default for ConsensusConfig {
             quorum_store_pull_timeout_ms: 1000,
             quorum_store_poll_count: 20,
             intra_consensus_channel_buffer_size: 10,
+            quorum_store_max_batch_size: 250,
+            quorum_store_batch_interval_ms: 100,
+            quorum_store_batch_expiry_rounds: 100,
+            quorum_store_batch_request_timeout_ms: 1000,
         }
     }
 }
@@ -11,6 +11,9 @@ pub struct InspectionServiceConfig {
     pub port: u16,
     pub expose_configuration: bool,
     pub expose_system_information: bool,
+    /// Exposes the progress of the DB pruners, and allows changing their prune windows and
+    /// pruning to a given version. The latter commands are only accepted from localhost.
+    pub expose_pruner: bool,
 }
 
 impl Default for InspectionServiceConfig {
@@ -20,6 +23,7 @@ impl Default for InspectionServiceConfig {
             port: 9101,
             expose_configuration: false,
             expose_system_information: true,
+            expose_pruner: false,
         }
     }
 }
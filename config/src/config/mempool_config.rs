@@ -1,6 +1,7 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
+use aptos_types::account_address::AccountAddress;
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
@@ -20,6 +21,10 @@ pub struct MempoolConfig {
     pub system_transaction_timeout_secs: u64,
     pub system_transaction_gc_interval_ms: u64,
     pub shared_mempool_validator_broadcast: bool,
+    // minimum increase of the gas unit price, in percent, for a txn to replace a pending txn
+    // with the same sender and sequence number
+    pub replace_by_fee_min_gas_price_bump_pct: u64,
+    pub transaction_filter: TransactionFilterConfig,
 }
 
 impl Default for MempoolConfig {
@@ -38,6 +43,50 @@ impl Default for MempoolConfig {
             system_transaction_timeout_secs: 600,
             system_transaction_gc_interval_ms: 60_000,
             shared_mempool_validator_broadcast: true,
+            replace_by_fee_min_gas_price_bump_pct: 10,
+            transaction_filter: TransactionFilterConfig::default(),
         }
     }
 }
+
+/// Filters the transactions accepted into mempool, both from clients and from peers.
+/// A transaction matching any `deny` rule is rejected. If any `allow` rules are set,
+/// a transaction must also match at least one of them to be accepted.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TransactionFilterConfig {
+    pub allow: Vec<TransactionFilterRule>,
+    pub deny: Vec<TransactionFilterRule>,
+}
+
+impl TransactionFilterConfig {
+    pub fn is_empty(&self) -> bool {
+        self.allow.is_empty() && self.deny.is_empty()
+    }
+}
+
+/// A rule matching transactions. Every field that is set must match for the rule to
+/// match, so a rule with no fields set matches all transactions.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TransactionFilterRule {
+    /// Name used to label the metrics of transactions rejected by this rule
+    pub name: String,
+    pub sender: Option<AccountAddress>,
+    /// Address of the module of the entry function called by the transaction
+    pub module_address: Option<AccountAddress>,
+    /// Name of the module of the entry function called by the transaction
+    pub module_name: Option<String>,
+    /// Name of the entry function called by the transaction
+    pub function: Option<String>,
+    pub payload_type: Option<TransactionPayloadType>,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TransactionPayloadType {
+    EntryFunction,
+    ModuleBundle,
+    Script,
+    WriteSet,
+}
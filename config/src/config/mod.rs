@@ -257,6 +257,8 @@ impl NodeConfig {
 
         let input_dir = RootPath::new(input_path);
         config.execution.load(&input_dir)?;
+        config.state_sync.aptos_data_client.validate()?;
+        config.consensus.safety_rules.validate()?;
 
         let mut config = config.validate_network_configs()?;
         config.set_data_dir(config.data_dir().to_path_buf());
@@ -7,7 +7,7 @@ use crate::{
     network_id::NetworkId,
     utils,
 };
-use aptos_crypto::{x25519, Uniform};
+use aptos_crypto::{ed25519::Ed25519PublicKey, x25519, Uniform};
 use aptos_secure_storage::{CryptoStorage, KVStorage, Storage};
 use aptos_types::{
     account_address::from_identity_public_key, network_address::NetworkAddress,
@@ -45,6 +45,11 @@ pub const MAX_FRAME_SIZE: usize = 16 * 1024 * 1024; /* 16 MiB */
 pub const CONNECTION_BACKOFF_BASE: u64 = 2;
 pub const IP_BYTE_BUCKET_RATE: usize = 102400 /* 100 KiB */;
 pub const IP_BYTE_BUCKET_SIZE: usize = IP_BYTE_BUCKET_RATE;
+pub const PEER_BYTE_RATE: usize = 4 * 1024 * 1024; /* 4 MiB */
+pub const PEER_BYTE_BURST: usize = MAX_FRAME_SIZE;
+pub const PEER_MESSAGE_RATE: usize = 1000;
+pub const PEER_MESSAGE_BURST: usize = 2000;
+pub const TRUSTED_PEER_RATE_LIMIT_MULTIPLIER: usize = 10;
 
 #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 #[serde(default, deny_unknown_fields)]
@@ -97,6 +102,10 @@ pub struct NetworkConfig {
     pub inbound_rate_limit_config: Option<RateLimitConfig>,
     // Outbound rate limiting configuration, if not specified, no rate limiting
     pub outbound_rate_limit_config: Option<RateLimitConfig>,
+    // Inbound rate limiting configuration per peer and protocol, if not specified, no rate limiting
+    pub peer_rate_limit_config: Option<PeerRateLimitConfig>,
+    // Bounds and weights of the outbound message queues of each protocol
+    pub outbound_queue_config: OutboundQueueConfig,
 }
 
 impl Default for NetworkConfig {
@@ -131,6 +140,8 @@ impl NetworkConfig {
             max_inbound_connections: MAX_INBOUND_CONNECTIONS,
             inbound_rate_limit_config: None,
             outbound_rate_limit_config: None,
+            peer_rate_limit_config: None,
+            outbound_queue_config: OutboundQueueConfig::default(),
         };
         config.prepare_identity();
         config
@@ -204,7 +215,7 @@ impl NetworkConfig {
         }
 
         self.prepare_identity();
-        Ok(())
+        self.verify_discovery_methods()
     }
 
     pub fn peer_id(&self) -> PeerId {
@@ -283,6 +294,18 @@ impl NetworkConfig {
         )
     }
 
+    // Verifies the settings of all discovery methods, e.g., that their intervals are positive
+    pub fn verify_discovery_methods(&self) -> Result<(), Error> {
+        for method in std::iter::once(&self.discovery_method).chain(&self.discovery_methods) {
+            match method {
+                DiscoveryMethod::Dns(config) => config.verify()?,
+                DiscoveryMethod::Http(config) => config.verify()?,
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
     // Verifies both the `seed_addrs` and `seeds` before they're merged
     pub fn verify_seeds(&self) -> Result<(), Error> {
         for (peer_id, addrs) in self.seed_addrs.iter() {
@@ -311,6 +334,8 @@ impl NetworkConfig {
 pub struct PeerMonitoringServiceConfig {
     pub max_concurrent_requests: u64, // Max num of concurrent server tasks
     pub max_network_channel_size: u64, // Max num of pending network messages
+    pub peer_monitor_interval_ms: u64, // The interval (ms) between peer monitoring rounds
+    pub peer_request_timeout_ms: u64, // The timeout (ms) of the requests sent to monitor peers
 }
 
 impl Default for PeerMonitoringServiceConfig {
@@ -318,6 +343,8 @@ impl Default for PeerMonitoringServiceConfig {
         Self {
             max_concurrent_requests: 1000,
             max_network_channel_size: 1000,
+            peer_monitor_interval_ms: 10_000,
+            peer_request_timeout_ms: 5_000,
         }
     }
 }
@@ -327,9 +354,57 @@ impl Default for PeerMonitoringServiceConfig {
 pub enum DiscoveryMethod {
     Onchain,
     File(PathBuf, Duration),
+    Dns(DnsDiscoveryConfig),
+    Http(HttpDiscoveryConfig),
     None,
 }
 
+/// Discovers seed peers from DNS records. Each TXT record of `domain_name` holds
+/// the network address of a peer, including its noise public key. Each SRV record
+/// of `_aptos._tcp.<domain_name>` points to the host and port of a peer, and a
+/// TXT record of that host holds the peer's noise public key. DNS records aren't
+/// authenticated, so discovered peers are untrusted (i.e., `PeerRole::Unknown`).
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct DnsDiscoveryConfig {
+    pub domain_name: String,
+    pub interval_secs: u64, // The interval (secs) between DNS lookups
+}
+
+impl DnsDiscoveryConfig {
+    pub fn verify(&self) -> Result<(), Error> {
+        crate::config::invariant(
+            self.interval_secs > 0,
+            format!(
+                "The DNS discovery interval of {} must be positive",
+                self.domain_name
+            ),
+        )
+    }
+}
+
+/// Discovers peers from a signed peer list that is periodically fetched from
+/// `url`. Lists that aren't signed by the key of `public_key` are rejected.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct HttpDiscoveryConfig {
+    pub url: String,
+    pub public_key: Ed25519PublicKey,
+    pub interval_secs: u64, // The interval (secs) between fetches of the peer list
+}
+
+impl HttpDiscoveryConfig {
+    pub fn verify(&self) -> Result<(), Error> {
+        crate::config::invariant(
+            self.interval_secs > 0,
+            format!(
+                "The HTTP discovery interval of {} must be positive",
+                self.url
+            ),
+        )
+    }
+}
+
 #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 #[serde(rename_all = "snake_case", tag = "type")]
 pub enum Identity {
@@ -406,6 +481,136 @@ impl Default for RateLimitConfig {
     }
 }
 
+/// Limits on the messages received from each connected peer. Messages exceeding
+/// a limit are dropped. The peer is told about dropped RPC requests, so they
+/// fail right away instead of timing out. Limits carry over to reconnections.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PeerRateLimitConfig {
+    /// Limits on the traffic of each peer, across all protocols
+    pub peer_limits: TrafficLimits,
+    /// Limits on the traffic of each peer for a single protocol, by protocol
+    /// name (e.g. `StorageServiceRpc`). Other protocols are only limited by `peer_limits`.
+    pub protocol_limits: HashMap<String, TrafficLimits>,
+    /// All limits are multiplied by this for trusted peers, i.e., validators,
+    /// validator fullnodes and upstream peers (including seeds with these roles)
+    pub trusted_peer_multiplier: usize,
+}
+
+impl Default for PeerRateLimitConfig {
+    fn default() -> Self {
+        Self {
+            peer_limits: TrafficLimits::default(),
+            protocol_limits: HashMap::new(),
+            trusted_peer_multiplier: TRUSTED_PEER_RATE_LIMIT_MULTIPLIER,
+        }
+    }
+}
+
+impl PeerRateLimitConfig {
+    /// Verifies that limits are only set for the given protocols, so a typo in
+    /// a protocol name doesn't silently leave the protocol unlimited
+    pub fn verify_protocols(&self, known_protocols: &[&str]) -> Result<(), Error> {
+        verify_protocol_names(
+            "protocol_limits",
+            self.protocol_limits.keys(),
+            known_protocols,
+        )
+    }
+}
+
+#[derive(Copy, Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct TrafficLimits {
+    /// Maximum number of bytes/s
+    pub byte_rate: usize,
+    /// Maximum burst of bytes. Larger messages are always dropped.
+    pub byte_burst: usize,
+    /// Maximum number of messages/s
+    pub message_rate: usize,
+    /// Maximum burst of messages
+    pub message_burst: usize,
+}
+
+impl Default for TrafficLimits {
+    fn default() -> Self {
+        Self {
+            byte_rate: PEER_BYTE_RATE,
+            byte_burst: PEER_BYTE_BURST,
+            message_rate: PEER_MESSAGE_RATE,
+            message_burst: PEER_MESSAGE_BURST,
+        }
+    }
+}
+
+/// Configures the outbound message queues of each peer connection. Consensus
+/// messages are always sent before mempool messages, which are always sent
+/// before state sync messages. Protocols of the same priority share the
+/// connection in proportion to their weights.
+///
+/// Queues are named after their protocol (e.g. `ConsensusRpcBcs`), and error
+/// messages have their own `control` queue.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct OutboundQueueConfig {
+    /// Maximum number of pending messages of a protocol not in `protocol_queue_sizes`
+    pub default_queue_size: usize,
+    /// Maximum number of pending messages of a protocol, by queue name. Once the
+    /// queue is full, new messages are dropped (failing their send) instead of
+    /// waiting for room, so a slow connection doesn't push back on the sender.
+    pub protocol_queue_sizes: HashMap<String, usize>,
+    /// Share of the bandwidth of a protocol relative to the protocols of the
+    /// same priority, by queue name. Protocols not listed have a weight of 1,
+    /// which is also the minimum.
+    pub protocol_weights: HashMap<String, usize>,
+}
+
+impl Default for OutboundQueueConfig {
+    fn default() -> Self {
+        Self {
+            default_queue_size: NETWORK_CHANNEL_SIZE,
+            protocol_queue_sizes: HashMap::new(),
+            protocol_weights: HashMap::new(),
+        }
+    }
+}
+
+impl OutboundQueueConfig {
+    /// Verifies that only the given queues are configured, so a typo in a
+    /// queue name doesn't silently leave its defaults in place
+    pub fn verify_queues(&self, known_queues: &[&str]) -> Result<(), Error> {
+        verify_protocol_names(
+            "protocol_queue_sizes",
+            self.protocol_queue_sizes.keys(),
+            known_queues,
+        )?;
+        verify_protocol_names(
+            "protocol_weights",
+            self.protocol_weights.keys(),
+            known_queues,
+        )
+    }
+}
+
+fn verify_protocol_names<'a>(
+    field: &str,
+    protocol_names: impl Iterator<Item = &'a String>,
+    known_protocols: &[&str],
+) -> Result<(), Error> {
+    for protocol_name in protocol_names {
+        crate::config::invariant(
+            known_protocols.contains(&protocol_name.as_str()),
+            format!(
+                "Unknown protocol '{}' in {}, expected one of: {}",
+                protocol_name,
+                field,
+                known_protocols.join(", "),
+            ),
+        )?;
+    }
+    Ok(())
+}
+
 pub type PeerSet = HashMap<PeerId, Peer>;
 
 // TODO: Combine with RoleType?
@@ -489,3 +694,80 @@ impl Peer {
         Peer::new(addresses, keys, role)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use aptos_crypto::{ed25519::Ed25519PrivateKey, PrivateKey};
+
+    #[test]
+    fn test_verify_peer_rate_limit_protocols() {
+        let known_protocols = ["MempoolDirectSend", "StorageServiceRpc"];
+        let mut config = PeerRateLimitConfig::default();
+        config
+            .protocol_limits
+            .insert("StorageServiceRpc".into(), TrafficLimits::default());
+        config.verify_protocols(&known_protocols).unwrap();
+
+        // A misspelled protocol is rejected
+        config
+            .protocol_limits
+            .insert("StorageServiceRPC".into(), TrafficLimits::default());
+        assert!(matches!(
+            config.verify_protocols(&known_protocols),
+            Err(Error::InvariantViolation(_))
+        ));
+    }
+
+    #[test]
+    fn test_verify_outbound_queues() {
+        let known_queues = ["control", "ConsensusRpcBcs"];
+        let mut config = OutboundQueueConfig::default();
+        config.protocol_queue_sizes.insert("control".into(), 10);
+        config.protocol_weights.insert("ConsensusRpcBcs".into(), 2);
+        config.verify_queues(&known_queues).unwrap();
+
+        // A misspelled queue is rejected, whichever field it's in
+        let mut bad_config = config.clone();
+        bad_config
+            .protocol_queue_sizes
+            .insert("ConsensusRpc".into(), 10);
+        assert!(bad_config.verify_queues(&known_queues).is_err());
+        config.protocol_weights.insert("ConsensusRpc".into(), 2);
+        assert!(config.verify_queues(&known_queues).is_err());
+    }
+
+    #[test]
+    fn test_verify_discovery_intervals() {
+        let dns_config = DnsDiscoveryConfig {
+            domain_name: "seeds.example.com".into(),
+            interval_secs: 60,
+        };
+        let public_key =
+            Ed25519PrivateKey::generate(&mut StdRng::from_seed([0u8; 32])).public_key();
+        let http_config = HttpDiscoveryConfig {
+            url: "https://seeds.example.com/peers.json".into(),
+            public_key,
+            interval_secs: 60,
+        };
+        let mut config = NetworkConfig::network_with_id(NetworkId::Public);
+        config.discovery_methods = vec![
+            DiscoveryMethod::Dns(dns_config.clone()),
+            DiscoveryMethod::Http(http_config.clone()),
+        ];
+        config.verify_discovery_methods().unwrap();
+
+        // A zero interval is rejected, whichever method it's for
+        let mut bad_config = config.clone();
+        bad_config.discovery_method = DiscoveryMethod::Dns(DnsDiscoveryConfig {
+            interval_secs: 0,
+            ..dns_config
+        });
+        assert!(bad_config.verify_discovery_methods().is_err());
+        config.discovery_methods[1] = DiscoveryMethod::Http(HttpDiscoveryConfig {
+            interval_secs: 0,
+            ..http_config
+        });
+        assert!(config.verify_discovery_methods().is_err());
+    }
+}
@@ -2,7 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    config::{IdentityBlob, LoggerConfig, SecureBackend, WaypointConfig},
+    config::{invariant, Error, IdentityBlob, LoggerConfig, SecureBackend, WaypointConfig},
     keys::ConfigKey,
 };
 use aptos_crypto::{bls12381, Uniform};
@@ -44,10 +44,23 @@ impl Default for SafetyRulesConfig {
 
 impl SafetyRulesConfig {
     pub fn set_data_dir(&mut self, data_dir: PathBuf) {
-        if let SecureBackend::OnDiskStorage(backend) = &mut self.backend {
-            backend.set_data_dir(data_dir);
+        match &mut self.backend {
+            SecureBackend::OnDiskStorage(backend) => backend.set_data_dir(data_dir),
+            SecureBackend::RemoteSigner(backend) => backend.set_data_dir(data_dir),
+            _ => {}
         }
     }
+
+    /// Verifies the backend can hold the safety rules data, including the BLS consensus key.
+    /// The remote signer only holds (and signs with) Ed25519 keys, and refuses private keys on
+    /// its plaintext key/value path, so it can't be used here.
+    pub fn validate(&self) -> Result<(), Error> {
+        invariant(
+            !matches!(self.backend, SecureBackend::RemoteSigner(_)),
+            "The remote signer can't hold the BLS consensus key, so it can't be the safety rules backend"
+                .into(),
+        )
+    }
 }
 
 // TODO: Find a cleaner way so WaypointConfig isn't duplicated
@@ -142,3 +155,29 @@ impl SafetyRulesTestConfig {
         self.consensus_key = Some(ConfigKey::<bls12381::PrivateKey>::new(privkey));
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::RemoteSignerConfig;
+
+    #[test]
+    fn test_validate_safety_rules_backend() {
+        SafetyRulesConfig::default().validate().unwrap();
+
+        let remote_signer: RemoteSignerConfig = serde_yaml::from_str(
+            r#"
+                server: "https://signer.example.com:8443"
+                client_certificate: "client.pem"
+                client_key: "client.key"
+                kv_path: "remote_signer_data.json"
+            "#,
+        )
+        .unwrap();
+        let config = SafetyRulesConfig {
+            backend: SecureBackend::RemoteSigner(remote_signer),
+            ..Default::default()
+        };
+        assert!(config.validate().is_err());
+    }
+}
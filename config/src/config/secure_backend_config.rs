@@ -3,9 +3,10 @@
 
 use crate::config::Error;
 use aptos_secure_storage::{
-    GitHubStorage, InMemoryStorage, Namespaced, OnDiskStorage, Storage, VaultStorage,
+    GitHubStorage, InMemoryStorage, Namespaced, OnDiskStorage, RemoteSignerStorage, Storage,
+    VaultStorage,
 };
-use serde::{Deserialize, Serialize};
+use serde::{de::Error as _, Deserialize, Deserializer, Serialize};
 use std::{
     fs::File,
     io::Read,
@@ -19,6 +20,7 @@ pub enum SecureBackend {
     InMemoryStorage,
     Vault(VaultConfig),
     OnDiskStorage(OnDiskStorageConfig),
+    RemoteSigner(RemoteSignerConfig),
 }
 
 impl SecureBackend {
@@ -26,7 +28,8 @@ impl SecureBackend {
         match self {
             SecureBackend::GitHub(GitHubConfig { namespace, .. })
             | SecureBackend::Vault(VaultConfig { namespace, .. })
-            | SecureBackend::OnDiskStorage(OnDiskStorageConfig { namespace, .. }) => {
+            | SecureBackend::OnDiskStorage(OnDiskStorageConfig { namespace, .. })
+            | SecureBackend::RemoteSigner(RemoteSignerConfig { namespace, .. }) => {
                 namespace.as_deref()
             }
             SecureBackend::InMemoryStorage => None,
@@ -37,7 +40,8 @@ impl SecureBackend {
         match self {
             SecureBackend::GitHub(GitHubConfig { namespace, .. })
             | SecureBackend::Vault(VaultConfig { namespace, .. })
-            | SecureBackend::OnDiskStorage(OnDiskStorageConfig { namespace, .. }) => {
+            | SecureBackend::OnDiskStorage(OnDiskStorageConfig { namespace, .. })
+            | SecureBackend::RemoteSigner(RemoteSignerConfig { namespace, .. }) => {
                 *namespace = None;
             }
             SecureBackend::InMemoryStorage => {}
@@ -106,10 +110,103 @@ pub struct OnDiskStorageConfig {
     /// example, a key, S, without a namespace would be available in S, with a namespace, N, it
     /// would be in N/S.
     pub namespace: Option<String>,
+    /// If specified, the storage is sealed (i.e., encrypted at rest) with a key derived from this
+    /// secret. Existing plaintext storage is sealed when first opened.
+    pub sealing_secret: Option<SealingSecret>,
     #[serde(skip)]
     data_dir: PathBuf,
 }
 
+/// The secret an encrypted OnDiskStorage derives its sealing key from.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SealingSecret {
+    Passphrase(String),
+    /// This is an absolute path and not relative to data_dir
+    KeyFile(PathBuf),
+}
+
+impl SealingSecret {
+    pub fn read_secret(&self) -> Result<Vec<u8>, Error> {
+        match self {
+            SealingSecret::Passphrase(passphrase) => Ok(passphrase.as_bytes().to_vec()),
+            SealingSecret::KeyFile(path) => {
+                std::fs::read(path).map_err(|e| Error::IO(path.to_str().unwrap().to_string(), e))
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct RemoteSignerConfig {
+    /// The URL of the remote signing service, e.g., https://signer.example.com:8443. Only https
+    /// URLs are accepted, as the keys used by consensus must not be reachable in plaintext.
+    #[serde(deserialize_with = "deserialize_https_url")]
+    pub server: String,
+    /// Optional SSL Certificate for the signing service, this is expected to be a full path.
+    pub ca_certificate: Option<PathBuf>,
+    /// PEM encoded client certificate (and its PKCS #8 key) to authenticate to the signing
+    /// service, these are expected to be full paths.
+    pub client_certificate: PathBuf,
+    pub client_key: PathBuf,
+    /// Private keys never leave the signing service, so all other (non-secret) data is kept
+    /// on disk at this path. If relative, it is relative to the data dir.
+    pub kv_path: PathBuf,
+    /// A namespace is an optional portion of the name of a key stored by the signing service
+    /// (and of the data stored on disk). For example, a key, S, without a namespace would be
+    /// available in S, with a namespace, N, it would be in N/S.
+    pub namespace: Option<String>,
+    /// Timeout for new socket connections to the signing service, in milliseconds.
+    pub connection_timeout_ms: Option<u64>,
+    /// Timeout for signing service operations (e.g., signing), in milliseconds.
+    pub response_timeout_ms: Option<u64>,
+    #[serde(skip)]
+    data_dir: PathBuf,
+}
+
+impl RemoteSignerConfig {
+    pub fn ca_certificate(&self) -> Result<String, Error> {
+        let path = self
+            .ca_certificate
+            .as_ref()
+            .ok_or(Error::Missing("ca_certificate"))?;
+        read_file(path)
+    }
+
+    /// Returns the client certificate and key
+    pub fn client_identity(&self) -> Result<(String, String), Error> {
+        Ok((
+            read_file(&self.client_certificate)?,
+            read_file(&self.client_key)?,
+        ))
+    }
+
+    pub fn kv_path(&self) -> PathBuf {
+        if self.kv_path.is_relative() {
+            self.data_dir.join(&self.kv_path)
+        } else {
+            self.kv_path.clone()
+        }
+    }
+
+    pub fn set_data_dir(&mut self, data_dir: PathBuf) {
+        self.data_dir = data_dir;
+    }
+}
+
+fn deserialize_https_url<'de, D: Deserializer<'de>>(deserializer: D) -> Result<String, D::Error> {
+    let url = String::deserialize(deserializer)?;
+    if url.starts_with("https://") {
+        Ok(url)
+    } else {
+        Err(D::Error::custom(format!(
+            "Remote signer URL must use https: {}",
+            url
+        )))
+    }
+}
+
 /// Tokens can either be directly within this config or stored somewhere on disk.
 #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 #[serde(rename_all = "snake_case")]
@@ -145,6 +242,7 @@ impl Default for OnDiskStorageConfig {
         Self {
             namespace: None,
             path: PathBuf::from("secure_storage.json"),
+            sealing_secret: None,
             data_dir: PathBuf::from("/opt/aptos/data"),
         }
     }
@@ -195,7 +293,38 @@ impl From<&SecureBackend> for Storage {
             }
             SecureBackend::InMemoryStorage => Storage::from(InMemoryStorage::new()),
             SecureBackend::OnDiskStorage(config) => {
-                let storage = Storage::from(OnDiskStorage::new(config.path()));
+                let storage = match &config.sealing_secret {
+                    Some(sealing_secret) => Storage::from(
+                        OnDiskStorage::new_sealed(
+                            config.path(),
+                            sealing_secret
+                                .read_secret()
+                                .expect("Unable to read sealing secret"),
+                        )
+                        .expect("Unable to unseal storage"),
+                    ),
+                    None => Storage::from(OnDiskStorage::new(config.path())),
+                };
+                if let Some(namespace) = &config.namespace {
+                    Storage::from(Namespaced::new(namespace, Box::new(storage)))
+                } else {
+                    storage
+                }
+            }
+            SecureBackend::RemoteSigner(config) => {
+                let storage = Storage::from(RemoteSignerStorage::new(
+                    config.server.clone(),
+                    config
+                        .ca_certificate
+                        .as_ref()
+                        .map(|_| config.ca_certificate().unwrap()),
+                    config
+                        .client_identity()
+                        .expect("Unable to read client identity"),
+                    config.kv_path(),
+                    config.connection_timeout_ms,
+                    config.response_timeout_ms,
+                ));
                 if let Some(namespace) = &config.namespace {
                     Storage::from(Namespaced::new(namespace, Box::new(storage)))
                 } else {
@@ -320,6 +449,78 @@ vault:
         serde_yaml::to_string(&from_disk).unwrap();
     }
 
+    #[test]
+    fn test_remote_signer_parsing() {
+        let text = r#"
+type: "remote_signer"
+server: "https://127.0.0.1:8443"
+ca_certificate: "/ca.pem"
+client_certificate: "/client.pem"
+client_key: "/client.key"
+kv_path: "remote_signer.json"
+response_timeout_ms: 5000
+        "#;
+
+        let mut backend: SecureBackend = serde_yaml::from_str(text).unwrap();
+        let config = match &mut backend {
+            SecureBackend::RemoteSigner(config) => config,
+            _ => panic!("Expected a remote signer backend"),
+        };
+        assert_eq!(config.server, "https://127.0.0.1:8443");
+        assert_eq!(config.ca_certificate, Some(PathBuf::from("/ca.pem")));
+        assert_eq!(config.connection_timeout_ms, None);
+        assert_eq!(config.response_timeout_ms, Some(5000));
+
+        // The on disk data is relative to the data dir
+        config.set_data_dir(PathBuf::from("/opt/aptos/data"));
+        assert_eq!(
+            config.kv_path(),
+            PathBuf::from("/opt/aptos/data/remote_signer.json")
+        );
+
+        serde_yaml::to_string(&backend).unwrap();
+
+        // Plaintext URLs are rejected
+        let plaintext = text.replace("https://", "http://");
+        let error = serde_yaml::from_str::<SecureBackend>(&plaintext).unwrap_err();
+        assert!(error.to_string().contains("must use https"));
+
+        // Both parts of the client identity are required
+        for field in ["client_certificate", "client_key"] {
+            let text: String = text
+                .lines()
+                .filter(|line| !line.starts_with(field))
+                .map(|line| format!("{}\n", line))
+                .collect();
+            let error = serde_yaml::from_str::<SecureBackend>(&text).unwrap_err();
+            assert!(error.to_string().contains(field));
+        }
+    }
+
+    #[test]
+    fn test_sealing_secret_parsing() {
+        let text = r#"
+type: "on_disk_storage"
+path: "secure_storage.json"
+sealing_secret:
+    key_file: "/sealing.key"
+        "#;
+
+        let backend: SecureBackend = serde_yaml::from_str(text).unwrap();
+        let config = match &backend {
+            SecureBackend::OnDiskStorage(config) => config,
+            _ => panic!("Expected an on disk storage backend"),
+        };
+        assert_eq!(
+            config.sealing_secret,
+            Some(SealingSecret::KeyFile(PathBuf::from("/sealing.key")))
+        );
+        serde_yaml::to_string(&backend).unwrap();
+
+        let config: OnDiskStorageConfig = serde_yaml::from_str("path: storage.json").unwrap();
+        assert_eq!(config.sealing_secret, None);
+    }
+
     #[test]
     fn test_token_reading() {
         let temppath = aptos_temppath::TempPath::new();
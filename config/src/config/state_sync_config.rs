@@ -1,6 +1,8 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
+use crate::config::{invariant, Error};
+use aptos_compression::CompressionAlgorithm;
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
@@ -198,19 +200,91 @@ impl Default for DataStreamingServiceConfig {
 #[derive(Copy, Clone, Debug, Deserialize, PartialEq, Serialize)]
 #[serde(default, deny_unknown_fields)]
 pub struct AptosDataClientConfig {
+    pub compression_algorithm: Option<CompressionAlgorithm>, // The algorithm peers should compress responses with (if any)
+    pub depth_selection_weight: f64, // The weight of a peer's distance from the validators when selecting peers (0 to ignore)
+    pub latency_selection_weight: f64, // The weight of a peer's latency when selecting peers (0 to ignore)
     pub max_num_in_flight_priority_polls: u64, // Max num of in-flight polls for priority peers
-    pub max_num_in_flight_regular_polls: u64,  // Max num of in-flight polls for regular peers
-    pub response_timeout_ms: u64, // Timeout (in milliseconds) when waiting for a response
+    pub max_num_in_flight_regular_polls: u64, // Max num of in-flight polls for regular peers
+    pub response_time_percentile: f64, // The percentile of recent response times used as a peer's latency
+    pub response_timeout_ms: u64,      // Timeout (in milliseconds) when waiting for a response
     pub summary_poll_interval_ms: u64, // Interval (in milliseconds) between data summary polls
 }
 
 impl Default for AptosDataClientConfig {
     fn default() -> Self {
         Self {
+            compression_algorithm: None,
+            depth_selection_weight: 1.0,
+            latency_selection_weight: 1.0,
             max_num_in_flight_priority_polls: 10,
             max_num_in_flight_regular_polls: 10,
+            response_time_percentile: 90.0,
             response_timeout_ms: 5000,
             summary_poll_interval_ms: 200,
         }
     }
 }
+
+impl AptosDataClientConfig {
+    /// Verifies the peer selection settings, which would otherwise produce
+    /// meaningless (e.g., NaN or infinite) peer weights.
+    pub fn validate(&self) -> Result<(), Error> {
+        invariant(
+            self.response_time_percentile > 0.0 && self.response_time_percentile <= 100.0,
+            format!(
+                "The response time percentile must be in (0, 100], got: {}",
+                self.response_time_percentile
+            ),
+        )?;
+        for (name, weight) in [
+            ("depth_selection_weight", self.depth_selection_weight),
+            ("latency_selection_weight", self.latency_selection_weight),
+        ] {
+            invariant(
+                weight.is_finite() && weight >= 0.0,
+                format!("The {} must be non-negative, got: {}", name, weight),
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_aptos_data_client_config() {
+        AptosDataClientConfig::default().validate().unwrap();
+
+        // Ignoring a factor is fine, but the percentile must be meaningful
+        let config = AptosDataClientConfig {
+            depth_selection_weight: 0.0,
+            latency_selection_weight: 0.0,
+            response_time_percentile: 100.0,
+            ..Default::default()
+        };
+        config.validate().unwrap();
+        for response_time_percentile in [0.0, -1.0, 100.1, f64::NAN] {
+            let config = AptosDataClientConfig {
+                response_time_percentile,
+                ..Default::default()
+            };
+            assert!(config.validate().is_err());
+        }
+
+        // The weights must be non-negative and finite
+        for weight in [-1.0, f64::INFINITY, f64::NAN] {
+            let config = AptosDataClientConfig {
+                depth_selection_weight: weight,
+                ..Default::default()
+            };
+            assert!(config.validate().is_err());
+            let config = AptosDataClientConfig {
+                latency_selection_weight: weight,
+                ..Default::default()
+            };
+            assert!(config.validate().is_err());
+        }
+    }
+}
@@ -15,7 +15,7 @@ use aptos_types::{
     block_metadata::BlockMetadata,
     epoch_state::EpochState,
     ledger_info::LedgerInfo,
-    transaction::{Transaction, Version},
+    transaction::{SignedTransaction, Transaction, Version},
     validator_signer::ValidatorSigner,
     validator_verifier::ValidatorVerifier,
 };
@@ -232,18 +232,21 @@ impl Block {
         }
     }
 
-    /// Verifies that the proposal and the QC are correctly signed.
-    /// If this is the genesis block, we skip these checks.
+    /// Verifies that the proposal, the proofs of store in its payload and the QC are correctly
+    /// signed. If this is the genesis block, we skip these checks.
     pub fn validate_signature(&self, validator: &ValidatorVerifier) -> anyhow::Result<()> {
         match self.block_data.block_type() {
             BlockType::Genesis => bail!("We should not accept genesis from others"),
             BlockType::NilBlock { .. } => self.quorum_cert().verify(validator),
-            BlockType::Proposal { author, .. } => {
+            BlockType::Proposal {
+                author, payload, ..
+            } => {
                 let signature = self
                     .signature
                     .as_ref()
                     .ok_or_else(|| format_err!("Missing signature in Proposal"))?;
                 validator.verify(*author, &self.block_data, signature)?;
+                payload.verify(validator)?;
                 self.quorum_cert().verify(validator)
             }
         }
@@ -271,6 +274,18 @@ impl Block {
                 "Reconfiguration suffix should not carry payload"
             );
         }
+        if let Some(Payload::InQuorumStore(proofs)) = self.payload() {
+            for proof in proofs {
+                ensure!(
+                    proof.epoch() == self.epoch(),
+                    "Proof of store must be from the block's epoch"
+                );
+                ensure!(
+                    proof.info().expiration() >= self.round(),
+                    "Proof of store expired before the block's round"
+                );
+            }
+        }
         if let Some(failed_authors) = self.block_data().failed_authors() {
             // when validating for being well formed,
             // allow for missing failed authors,
@@ -330,17 +345,17 @@ impl Block {
         Ok(())
     }
 
-    pub fn transactions_to_execute(&self, validators: &[AccountAddress]) -> Vec<Transaction> {
+    /// Returns the transactions to execute for this block, given the user transactions of its
+    /// payload (for proofs of store, the transactions of the referenced batches).
+    pub fn transactions_to_execute(
+        &self,
+        validators: &[AccountAddress],
+        txns: Vec<SignedTransaction>,
+    ) -> Vec<Transaction> {
         std::iter::once(Transaction::BlockMetadata(
             self.new_block_metadata(validators),
         ))
-        .chain(
-            self.payload()
-                .unwrap_or(&Payload::new_empty())
-                .clone()
-                .into_iter()
-                .map(Transaction::UserTransaction),
-        )
+        .chain(txns.into_iter().map(Transaction::UserTransaction))
         .chain(once(Transaction::StateCheckpoint(self.id)))
         .collect()
     }
@@ -1,9 +1,14 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
-use aptos_types::{account_address::AccountAddress, transaction::SignedTransaction};
+use crate::proof_of_store::ProofOfStore;
+use aptos_crypto::HashValue;
+use aptos_types::{
+    account_address::AccountAddress, transaction::SignedTransaction,
+    validator_verifier::ValidatorVerifier,
+};
 use serde::{Deserialize, Serialize};
-use std::fmt;
+use std::{collections::HashSet, fmt};
 
 /// The round of a block is a consensus-internal counter, which starts with 0 and increases
 /// monotonically. It is used for the protocol safety and liveness (please see the detailed
@@ -36,30 +41,35 @@ impl Payload {
         Payload::DirectMempool(Vec::new())
     }
 
+    /// Number of transactions in the payload. For proofs of store, this counts the transactions
+    /// of the batches they refer to.
     pub fn len(&self) -> usize {
         match self {
             Payload::DirectMempool(txns) => txns.len(),
-            Payload::InQuorumStore(_poavs) => todo!(),
+            Payload::InQuorumStore(proofs) => proofs
+                .iter()
+                .map(|proof| proof.info().num_txns() as usize)
+                .sum(),
         }
     }
 
     pub fn is_empty(&self) -> bool {
         match self {
             Payload::DirectMempool(txns) => txns.is_empty(),
-            Payload::InQuorumStore(_poavs) => todo!(),
+            Payload::InQuorumStore(proofs) => proofs.is_empty(),
         }
     }
-}
-
-// TODO: What I really want is an iterator that isn't necessarily a vector (e.g., read lazily from RocksDB). This doesn't seem like the way.
-impl IntoIterator for Payload {
-    type Item = SignedTransaction;
-    type IntoIter = std::vec::IntoIter<Self::Item>;
 
-    fn into_iter(self) -> Self::IntoIter {
+    /// Verifies the signatures of the proofs of store, if any.
+    pub fn verify(&self, validator: &ValidatorVerifier) -> anyhow::Result<()> {
         match self {
-            Payload::DirectMempool(txns) => txns.into_iter(),
-            Payload::InQuorumStore(_poavs) => todo!(),
+            Payload::DirectMempool(_) => Ok(()),
+            Payload::InQuorumStore(proofs) => {
+                for proof in proofs {
+                    proof.verify(validator)?;
+                }
+                Ok(())
+            }
         }
     }
 }
@@ -70,7 +80,14 @@ impl fmt::Display for Payload {
             Payload::DirectMempool(txns) => {
                 write!(f, "InMemory txns: {}", txns.len())
             }
-            Payload::InQuorumStore(_poavs) => todo!(),
+            Payload::InQuorumStore(proofs) => {
+                write!(
+                    f,
+                    "InQuorumStore proofs: {}, txns: {}",
+                    proofs.len(),
+                    self.len()
+                )
+            }
         }
     }
 }
@@ -79,30 +96,40 @@ impl fmt::Display for Payload {
 #[derive(Deserialize, Serialize, Clone, Debug, PartialEq, Eq)]
 pub enum PayloadFilter {
     DirectMempool(Vec<TransactionSummary>),
-    InQuorumStore(Vec<ProofOfStore>),
+    /// Digests of the batches already included in pending blocks.
+    InQuorumStore(HashSet<HashValue>),
 }
 
 impl From<&Vec<&Payload>> for PayloadFilter {
     fn from(exclude_payloads: &Vec<&Payload>) -> Self {
-        if exclude_payloads.is_empty() {
-            return PayloadFilter::DirectMempool(vec![]);
-        }
-        match exclude_payloads.first().unwrap() {
-            Payload::DirectMempool(_) => {
-                let mut exclude_txns = vec![];
-                for payload in exclude_payloads {
-                    if let Payload::DirectMempool(txns) = payload {
-                        for txn in txns {
-                            exclude_txns.push(TransactionSummary {
-                                sender: txn.sender(),
-                                sequence_number: txn.sequence_number(),
-                            });
-                        }
+        // Empty payloads are always created as DirectMempool, so any proof of store means the
+        // payloads come from the quorum store.
+        if exclude_payloads
+            .iter()
+            .any(|payload| matches!(payload, Payload::InQuorumStore(_)))
+        {
+            let mut exclude_digests = HashSet::new();
+            for payload in exclude_payloads {
+                if let Payload::InQuorumStore(proofs) = payload {
+                    for proof in proofs {
+                        exclude_digests.insert(proof.digest());
                     }
                 }
-                PayloadFilter::DirectMempool(exclude_txns)
             }
-            Payload::InQuorumStore(_) => todo!(),
+            PayloadFilter::InQuorumStore(exclude_digests)
+        } else {
+            let mut exclude_txns = vec![];
+            for payload in exclude_payloads {
+                if let Payload::DirectMempool(txns) = payload {
+                    for txn in txns {
+                        exclude_txns.push(TransactionSummary {
+                            sender: txn.sender(),
+                            sequence_number: txn.sequence_number(),
+                        });
+                    }
+                }
+            }
+            PayloadFilter::DirectMempool(exclude_txns)
         }
     }
 }
@@ -117,13 +144,13 @@ impl fmt::Display for PayloadFilter {
                 }
                 write!(f, "{}", txns_str)
             }
-            PayloadFilter::InQuorumStore(_poavs) => todo!(),
+            PayloadFilter::InQuorumStore(excluded_digests) => {
+                let mut digests_str = "".to_string();
+                for digest in excluded_digests.iter() {
+                    digests_str += &format!("{} ", digest);
+                }
+                write!(f, "{}", digests_str)
+            }
         }
     }
 }
-
-#[derive(Deserialize, Serialize, Clone, Debug, PartialEq, Eq, Hash)]
-pub struct ProofOfStore {
-    // TODO: This is currently just a placeholder for a real ProofOfStore implementation.
-    placeholder: u64,
-}
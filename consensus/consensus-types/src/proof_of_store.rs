@@ -0,0 +1,303 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::common::{Author, Round};
+use anyhow::{ensure, Context};
+use aptos_crypto::{bls12381, hash::CryptoHash, HashValue};
+use aptos_crypto_derive::{BCSCryptoHash, CryptoHasher};
+use aptos_types::{
+    transaction::SignedTransaction, validator_signer::ValidatorSigner,
+    validator_verifier::ValidatorVerifier,
+};
+use serde::{Deserialize, Serialize};
+use short_hex_str::AsShortHexStr;
+use std::{
+    collections::BTreeMap,
+    fmt::{Display, Formatter},
+};
+
+#[cfg(test)]
+#[path = "proof_of_store_test.rs"]
+pub mod proof_of_store_test;
+
+/// Describes a batch of transactions disseminated by the quorum store. This is what
+/// validators sign to attest that they store the batch.
+#[derive(
+    Deserialize, Serialize, Clone, Debug, PartialEq, Eq, Hash, CryptoHasher, BCSCryptoHash,
+)]
+pub struct BatchInfo {
+    epoch: u64,
+    author: Author,
+    batch_id: u64,
+    digest: HashValue,
+    num_txns: u64,
+    num_bytes: u64,
+    /// The batch is stored until a block with a higher round is committed, so it can only be
+    /// included in blocks up to this round.
+    expiration: Round,
+}
+
+impl BatchInfo {
+    pub fn epoch(&self) -> u64 {
+        self.epoch
+    }
+
+    pub fn author(&self) -> Author {
+        self.author
+    }
+
+    pub fn batch_id(&self) -> u64 {
+        self.batch_id
+    }
+
+    pub fn digest(&self) -> HashValue {
+        self.digest
+    }
+
+    pub fn num_txns(&self) -> u64 {
+        self.num_txns
+    }
+
+    pub fn num_bytes(&self) -> u64 {
+        self.num_bytes
+    }
+
+    pub fn expiration(&self) -> Round {
+        self.expiration
+    }
+}
+
+impl Display for BatchInfo {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "BatchInfo: [author: {}, epoch: {}, batch_id: {}, digest: {}, num_txns: {}, expiration: {}]",
+            self.author.short_str(),
+            self.epoch,
+            self.batch_id,
+            self.digest,
+            self.num_txns,
+            self.expiration,
+        )
+    }
+}
+
+/// The transactions of a batch, hashed to compute the batch digest.
+#[derive(Deserialize, Serialize, CryptoHasher, BCSCryptoHash)]
+struct BatchPayload {
+    txns: Vec<SignedTransaction>,
+}
+
+fn compute_digest(txns: &[SignedTransaction]) -> HashValue {
+    BatchPayload {
+        txns: txns.to_vec(),
+    }
+    .hash()
+}
+
+/// A batch of transactions, broadcast by its author to all validators.
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct Batch {
+    info: BatchInfo,
+    txns: Vec<SignedTransaction>,
+}
+
+impl Batch {
+    pub fn new(
+        epoch: u64,
+        author: Author,
+        batch_id: u64,
+        expiration: Round,
+        txns: Vec<SignedTransaction>,
+    ) -> Self {
+        let info = BatchInfo {
+            epoch,
+            author,
+            batch_id,
+            digest: compute_digest(&txns),
+            num_txns: txns.len() as u64,
+            num_bytes: txns.iter().map(|txn| txn.raw_txn_bytes_len() as u64).sum(),
+            expiration,
+        };
+        Self { info, txns }
+    }
+
+    pub fn info(&self) -> &BatchInfo {
+        &self.info
+    }
+
+    pub fn epoch(&self) -> u64 {
+        self.info.epoch
+    }
+
+    pub fn txns(&self) -> &[SignedTransaction] {
+        &self.txns
+    }
+
+    pub fn into_transactions(self) -> Vec<SignedTransaction> {
+        self.txns
+    }
+
+    /// Verifies that the batch info matches the transactions.
+    pub fn verify(&self) -> anyhow::Result<()> {
+        ensure!(
+            self.info.digest == compute_digest(&self.txns),
+            "Batch digest doesn't match its transactions"
+        );
+        ensure!(
+            self.info.num_txns == self.txns.len() as u64,
+            "Batch has {} transactions, but its info says {}",
+            self.txns.len(),
+            self.info.num_txns
+        );
+        let num_bytes: u64 = self
+            .txns
+            .iter()
+            .map(|txn| txn.raw_txn_bytes_len() as u64)
+            .sum();
+        ensure!(
+            self.info.num_bytes == num_bytes,
+            "Batch has {} bytes, but its info says {}",
+            num_bytes,
+            self.info.num_bytes
+        );
+        Ok(())
+    }
+}
+
+impl Display for Batch {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(f, "Batch: [{}]", self.info)
+    }
+}
+
+/// A validator's signature on the info of a batch it stored, sent back to the batch author.
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct SignedDigest {
+    info: BatchInfo,
+    signer: Author,
+    signature: bls12381::Signature,
+}
+
+impl SignedDigest {
+    pub fn new(info: BatchInfo, validator_signer: &ValidatorSigner) -> Self {
+        let signature = validator_signer.sign(&info);
+        Self {
+            info,
+            signer: validator_signer.author(),
+            signature,
+        }
+    }
+
+    pub fn info(&self) -> &BatchInfo {
+        &self.info
+    }
+
+    pub fn epoch(&self) -> u64 {
+        self.info.epoch
+    }
+
+    pub fn signer(&self) -> Author {
+        self.signer
+    }
+
+    pub fn signature(&self) -> &bls12381::Signature {
+        &self.signature
+    }
+
+    pub fn verify(&self, validator: &ValidatorVerifier) -> anyhow::Result<()> {
+        validator
+            .verify(self.signer, &self.info, &self.signature)
+            .context("Failed to verify SignedDigest")
+    }
+}
+
+impl Display for SignedDigest {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "SignedDigest: [signer: {}, {}]",
+            self.signer.short_str(),
+            self.info
+        )
+    }
+}
+
+/// Proof that a quorum of validators stored a batch, so that its transactions are available
+/// to execute blocks that only include the proof.
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ProofOfStore {
+    info: BatchInfo,
+    signatures: BTreeMap<Author, bls12381::Signature>,
+}
+
+impl ProofOfStore {
+    pub fn new(info: BatchInfo, signatures: BTreeMap<Author, bls12381::Signature>) -> Self {
+        Self { info, signatures }
+    }
+
+    pub fn info(&self) -> &BatchInfo {
+        &self.info
+    }
+
+    pub fn epoch(&self) -> u64 {
+        self.info.epoch
+    }
+
+    pub fn digest(&self) -> HashValue {
+        self.info.digest
+    }
+
+    /// Validators that signed the batch, i.e. that can serve its transactions.
+    pub fn signers(&self) -> impl Iterator<Item = &Author> {
+        self.signatures.keys()
+    }
+
+    pub fn verify(&self, validator: &ValidatorVerifier) -> anyhow::Result<()> {
+        validator
+            .verify_aggregated_struct_signature(&self.info, &self.signatures)
+            .context("Failed to verify ProofOfStore")
+    }
+}
+
+impl Display for ProofOfStore {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "ProofOfStore: [{}, signers: {}]",
+            self.info,
+            self.signatures.len()
+        )
+    }
+}
+
+/// RPC to get a batch that isn't stored locally, from one of the validators that signed it.
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct BatchRequest {
+    epoch: u64,
+    digest: HashValue,
+}
+
+impl BatchRequest {
+    pub fn new(epoch: u64, digest: HashValue) -> Self {
+        Self { epoch, digest }
+    }
+
+    pub fn epoch(&self) -> u64 {
+        self.epoch
+    }
+
+    pub fn digest(&self) -> HashValue {
+        self.digest
+    }
+}
+
+impl Display for BatchRequest {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "BatchRequest: [epoch: {}, digest: {}]",
+            self.epoch, self.digest
+        )
+    }
+}
@@ -0,0 +1,81 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::proof_of_store::{Batch, ProofOfStore, SignedDigest};
+use aptos_crypto::{ed25519::Ed25519PrivateKey, PrivateKey, Uniform};
+use aptos_types::{
+    account_address::AccountAddress, test_helpers::transaction_test_helpers::get_test_signed_txn,
+    transaction::SignedTransaction, validator_verifier::random_validator_verifier,
+};
+use std::collections::BTreeMap;
+
+fn create_txns(count: u64) -> Vec<SignedTransaction> {
+    let private_key = Ed25519PrivateKey::generate_for_testing();
+    let public_key = private_key.public_key();
+    (0..count)
+        .map(|i| {
+            get_test_signed_txn(
+                AccountAddress::random(),
+                i,
+                &private_key,
+                public_key.clone(),
+                None,
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn test_batch_verify() {
+    let (signers, _) = random_validator_verifier(1, None, false);
+    let batch = Batch::new(1, signers[0].author(), 0, 10, create_txns(3));
+    assert_eq!(batch.info().num_txns(), 3);
+    assert!(batch.verify().is_ok());
+
+    // The same info with other transactions doesn't verify.
+    let other = Batch::new(1, signers[0].author(), 0, 10, create_txns(3));
+    let tampered: Batch =
+        bcs::from_bytes(&bcs::to_bytes(&(batch.info(), other.txns())).unwrap()).unwrap();
+    assert!(tampered.verify().is_err());
+
+    // The transaction count and size must match the transactions.
+    let info = batch.info();
+    for (num_txns, num_bytes) in [
+        (info.num_txns() + 1, info.num_bytes()),
+        (info.num_txns(), info.num_bytes() + 1),
+    ] {
+        let tampered_info = (
+            info.epoch(),
+            info.author(),
+            info.batch_id(),
+            info.digest(),
+            num_txns,
+            num_bytes,
+            info.expiration(),
+        );
+        let tampered: Batch =
+            bcs::from_bytes(&bcs::to_bytes(&(tampered_info, batch.txns())).unwrap()).unwrap();
+        assert!(tampered.verify().is_err());
+    }
+}
+
+#[test]
+fn test_proof_of_store_verify() {
+    let (signers, verifier) = random_validator_verifier(4, None, false);
+    let batch = Batch::new(1, signers[0].author(), 0, 10, create_txns(2));
+
+    let mut signatures = BTreeMap::new();
+    for signer in &signers {
+        let signed_digest = SignedDigest::new(batch.info().clone(), signer);
+        assert!(signed_digest.verify(&verifier).is_ok());
+        signatures.insert(signed_digest.signer(), signed_digest.signature().clone());
+        let proof = ProofOfStore::new(batch.info().clone(), signatures.clone());
+        // 4 validators with equal voting power need 3 signatures.
+        assert_eq!(proof.verify(&verifier).is_ok(), signatures.len() >= 3);
+    }
+
+    // Signatures on another batch don't verify.
+    let other = Batch::new(1, signers[0].author(), 1, 10, create_txns(2));
+    let proof = ProofOfStore::new(other.info().clone(), signatures);
+    assert!(proof.verify(&verifier).is_err());
+}
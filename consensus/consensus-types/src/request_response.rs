@@ -3,6 +3,7 @@
 
 use crate::common::{Payload, PayloadFilter, Round};
 use anyhow::Result;
+use aptos_crypto::HashValue;
 use futures::channel::oneshot;
 use std::{fmt, fmt::Formatter};
 
@@ -23,6 +24,8 @@ pub enum ConsensusRequest {
         u64,
         // round
         Round,
+        // digests of the committed batches
+        Vec<HashValue>,
         // callback to respond to
         oneshot::Sender<Result<ConsensusResponse>>,
     ),
@@ -38,8 +41,14 @@ impl fmt::Display for ConsensusRequest {
                     block_size, excluded
                 )
             }
-            ConsensusRequest::CleanRequest(epoch, round, _) => {
-                write!(f, "CleanRequest [epoch: {}, round: {}]", epoch, round)
+            ConsensusRequest::CleanRequest(epoch, round, digests, _) => {
+                write!(
+                    f,
+                    "CleanRequest [epoch: {}, round: {}, batches: {}]",
+                    epoch,
+                    round,
+                    digests.len()
+                )
             }
         }
     }
@@ -19,9 +19,12 @@ mod t_safety_rules;
 mod thread;
 
 pub use crate::{
-    consensus_state::ConsensusState, error::Error,
-    persistent_safety_storage::PersistentSafetyStorage, process::Process,
-    safety_rules::SafetyRules, safety_rules_manager::SafetyRulesManager,
+    consensus_state::ConsensusState,
+    error::Error,
+    persistent_safety_storage::PersistentSafetyStorage,
+    process::Process,
+    safety_rules::SafetyRules,
+    safety_rules_manager::{storage, SafetyRulesManager},
     t_safety_rules::TSafetyRules,
 };
 
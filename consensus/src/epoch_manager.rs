@@ -25,11 +25,17 @@ use crate::{
     },
     logging::{LogEvent, LogSchema},
     metrics_safety_rules::MetricsSafetyRules,
-    network::{IncomingBlockRetrievalRequest, NetworkReceivers, NetworkSender},
+    network::{
+        IncomingBatchRetrievalRequest, IncomingBlockRetrievalRequest, NetworkReceivers,
+        NetworkSender,
+    },
     network_interface::{ConsensusMsg, ConsensusNetworkSender},
-    payload_manager::QuorumStoreClient,
+    payload_manager::{PayloadReader, QuorumStoreClient},
     persistent_liveness_storage::{LedgerRecoveryData, PersistentLivenessStorage, RecoveryData},
-    quorum_store::direct_mempool_quorum_store::DirectMempoolQuorumStore,
+    quorum_store::{
+        batch_quorum_store::QuorumStore, batch_store::BatchStore,
+        direct_mempool_quorum_store::DirectMempoolQuorumStore,
+    },
     round_manager::{RoundManager, UnverifiedEvent, VerifiedEvent},
     state_replication::StateComputer,
     util::time_service::TimeService,
@@ -48,6 +54,7 @@ use aptos_types::{
         LeaderReputationType, OnChainConfigPayload, OnChainConsensusConfig, ProposerElectionType,
         ValidatorSet,
     },
+    validator_signer::ValidatorSigner,
     validator_verifier::ValidatorVerifier,
 };
 use channel::{aptos_channel, message_queues::QueueStyle};
@@ -66,7 +73,7 @@ use futures::{
     SinkExt, StreamExt,
 };
 use network::protocols::network::{ApplicationNetworkSender, Event};
-use safety_rules::SafetyRulesManager;
+use safety_rules::{PersistentSafetyStorage, SafetyRulesManager};
 use std::{
     cmp::Ordering,
     mem::{discriminant, Discriminant},
@@ -109,17 +116,22 @@ pub struct EpochManager {
     commit_state_computer: Arc<dyn StateComputer>,
     storage: Arc<dyn PersistentLivenessStorage>,
     safety_rules_manager: SafetyRulesManager,
+    // storage of the consensus key, used to sign batches in quorum store mode
+    key_storage: Option<PersistentSafetyStorage>,
     reconfig_events: ReconfigNotificationListener,
     commit_notifier: Arc<dyn CommitNotifier>,
     // channels to buffer manager
     buffer_manager_msg_tx: Option<aptos_channel::Sender<AccountAddress, VerifiedEvent>>,
     buffer_manager_reset_tx: Option<UnboundedSender<ResetRequest>>,
+    // channel to quorum store, only in quorum store mode
+    quorum_store_msg_tx: Option<aptos_channel::Sender<AccountAddress, (Author, VerifiedEvent)>>,
     // channels to round manager
     round_manager_tx: Option<
         aptos_channel::Sender<(Author, Discriminant<VerifiedEvent>), (Author, VerifiedEvent)>,
     >,
     epoch_state: Option<EpochState>,
     block_store: Option<Arc<BlockStore>>,
+    batch_store: Option<Arc<BatchStore>>,
 }
 
 impl EpochManager {
@@ -139,6 +151,11 @@ impl EpochManager {
         let config = node_config.consensus.clone();
         let sr_config = &node_config.consensus.safety_rules;
         let safety_rules_manager = SafetyRulesManager::new(sr_config);
+        let key_storage = if config.use_quorum_store {
+            Some(safety_rules::storage(sr_config))
+        } else {
+            None
+        };
         Self {
             author,
             config,
@@ -150,13 +167,16 @@ impl EpochManager {
             commit_state_computer,
             storage,
             safety_rules_manager,
+            key_storage,
             reconfig_events,
             commit_notifier,
             buffer_manager_msg_tx: None,
             buffer_manager_reset_tx: None,
+            quorum_store_msg_tx: None,
             round_manager_tx: None,
             epoch_state: None,
             block_store: None,
+            batch_store: None,
         }
     }
 
@@ -364,16 +384,77 @@ impl EpochManager {
         Ok(())
     }
 
+    /// Spawns the quorum store serving the payloads of the proposals, and returns the reader
+    /// that resolves the transactions of these payloads for execution.
     fn spawn_quorum_store(
         &mut self,
+        epoch_state: &EpochState,
         consensus_to_quorum_store_receiver: Receiver<ConsensusRequest>,
-    ) {
-        let quorum_store = DirectMempoolQuorumStore::new(
+    ) -> Arc<PayloadReader> {
+        if !self.config.use_quorum_store {
+            let quorum_store = DirectMempoolQuorumStore::new(
+                consensus_to_quorum_store_receiver,
+                self.quorum_store_to_mempool_sender.clone(),
+                self.config.mempool_txn_pull_timeout_ms,
+            );
+            tokio::spawn(quorum_store.start());
+            return Arc::new(PayloadReader::DirectMempool);
+        }
+
+        let network_sender = NetworkSender::new(
+            self.author,
+            self.network_sender.clone(),
+            self.self_sender.clone(),
+            epoch_state.verifier.clone(),
+        );
+        let batch_store = Arc::new(BatchStore::new(
+            epoch_state.epoch,
+            self.author,
+            network_sender.clone(),
+            Duration::from_millis(self.config.quorum_store_batch_request_timeout_ms),
+            self.config.quorum_store_max_batch_size,
+            self.config.quorum_store_batch_expiry_rounds,
+        ));
+        let (quorum_store_msg_tx, quorum_store_msg_rx) =
+            aptos_channel::new::<AccountAddress, (Author, VerifiedEvent)>(
+                QueueStyle::FIFO,
+                self.config.channel_size,
+                Some(&counters::QUORUM_STORE_MSGS),
+            );
+        self.quorum_store_msg_tx = Some(quorum_store_msg_tx);
+        self.batch_store = Some(batch_store.clone());
+
+        let quorum_store = QuorumStore::new(
+            epoch_state.epoch,
+            self.author,
+            self.create_validator_signer(&epoch_state.verifier)
+                .map(Arc::new),
+            epoch_state.verifier.clone(),
+            network_sender,
+            batch_store.clone(),
             consensus_to_quorum_store_receiver,
+            quorum_store_msg_rx,
             self.quorum_store_to_mempool_sender.clone(),
             self.config.mempool_txn_pull_timeout_ms,
+            self.config.quorum_store_max_batch_size,
+            Duration::from_millis(self.config.quorum_store_batch_interval_ms),
+            self.config.quorum_store_batch_expiry_rounds,
         );
         tokio::spawn(quorum_store.start());
+        Arc::new(PayloadReader::InQuorumStore(batch_store))
+    }
+
+    /// Creates the signer of the quorum store batches from the consensus key, returns None if
+    /// this node isn't a validator in the epoch.
+    fn create_validator_signer(&self, verifier: &ValidatorVerifier) -> Option<ValidatorSigner> {
+        let public_key = verifier.get_public_key(&self.author)?;
+        let private_key = self
+            .key_storage
+            .as_ref()
+            .expect("Key storage is required in quorum store mode")
+            .consensus_key_for_version(public_key)
+            .expect("Unable to retrieve the consensus key for the quorum store");
+        Some(ValidatorSigner::new(self.author, private_key))
     }
 
     /// this function spawns the phases and a buffer manager
@@ -435,6 +516,10 @@ impl EpochManager {
         }
         self.round_manager_tx = None;
 
+        // The quorum store stops once the consensus requests sender is dropped
+        self.quorum_store_msg_tx = None;
+        self.batch_store = None;
+
         // Shutdown the previous buffer manager, to release the SafetyRule client
         self.buffer_manager_msg_tx = None;
         if let Some(mut tx) = self.buffer_manager_reset_tx.take() {
@@ -497,7 +582,8 @@ impl EpochManager {
 
         let (consensus_to_quorum_store_sender, consensus_to_quorum_store_receiver) =
             mpsc::channel(self.config.intra_consensus_channel_buffer_size);
-        self.spawn_quorum_store(consensus_to_quorum_store_receiver);
+        let payload_reader =
+            self.spawn_quorum_store(&epoch_state, consensus_to_quorum_store_receiver);
         let payload_manager = QuorumStoreClient::new(
             consensus_to_quorum_store_sender.clone(),
             self.config.quorum_store_poll_count,
@@ -506,7 +592,8 @@ impl EpochManager {
         self.commit_notifier
             .new_epoch(consensus_to_quorum_store_sender);
 
-        self.commit_state_computer.new_epoch(&epoch_state);
+        self.commit_state_computer
+            .new_epoch(&epoch_state, payload_reader);
         let state_computer = if onchain_config.decoupled_execution() {
             Arc::new(self.spawn_decoupled_execution(
                 safety_rules_container.clone(),
@@ -630,7 +717,10 @@ impl EpochManager {
             | ConsensusMsg::SyncInfo(_)
             | ConsensusMsg::VoteMsg(_)
             | ConsensusMsg::CommitVoteMsg(_)
-            | ConsensusMsg::CommitDecisionMsg(_) => {
+            | ConsensusMsg::CommitDecisionMsg(_)
+            | ConsensusMsg::BatchMsg(_)
+            | ConsensusMsg::SignedDigestMsg(_)
+            | ConsensusMsg::ProofOfStoreMsg(_) => {
                 let event: UnverifiedEvent = msg.into();
                 if event.epoch() == self.epoch() {
                     return Ok(Some(event));
@@ -690,6 +780,15 @@ impl EpochManager {
                     bail!("Commit Phase not started but received Commit Message (CommitVote/CommitDecision)");
                 }
             }
+            quorum_store_event @ (VerifiedEvent::Batch(_)
+            | VerifiedEvent::SignedDigest(_)
+            | VerifiedEvent::ProofOfStore(_)) => {
+                if let Some(sender) = &mut self.quorum_store_msg_tx {
+                    sender.push(peer_id, (peer_id, quorum_store_event))?;
+                } else {
+                    bail!("Quorum store not enabled but received Quorum Store Message (Batch/SignedDigest/ProofOfStore)");
+                }
+            }
             round_manager_event => {
                 self.forward_to_round_manager(peer_id, round_manager_event);
             }
@@ -718,6 +817,17 @@ impl EpochManager {
         }
     }
 
+    fn process_batch_retrieval(
+        &self,
+        request: IncomingBatchRetrievalRequest,
+    ) -> anyhow::Result<()> {
+        if let Some(batch_store) = &self.batch_store {
+            batch_store.process_batch_retrieval(request)
+        } else {
+            Err(anyhow::anyhow!("Quorum store not started"))
+        }
+    }
+
     fn process_local_timeout(&mut self, round: u64) {
         self.forward_to_round_manager(self.author, VerifiedEvent::LocalTimeout(round));
     }
@@ -746,11 +856,21 @@ impl EpochManager {
                         error!(epoch = self.epoch(), error = ?e, kind = error_kind(&e));
                     }
                 }
+                Some((peer, msg)) = network_receivers.quorum_store_messages.next() => {
+                    if let Err(e) = self.process_message(peer, msg).await {
+                        error!(epoch = self.epoch(), error = ?e, kind = error_kind(&e));
+                    }
+                }
                 Some(request) = network_receivers.block_retrieval.next() => {
                     if let Err(e) = self.process_block_retrieval(request).await {
                         error!(epoch = self.epoch(), error = ?e, kind = error_kind(&e));
                     }
                 }
+                Some(request) = network_receivers.batch_retrieval.next() => {
+                    if let Err(e) = self.process_batch_retrieval(request) {
+                        error!(epoch = self.epoch(), error = ?e, kind = error_kind(&e));
+                    }
+                }
                 Some(round) = round_timeout_sender_rx.next() => {
                     self.process_local_timeout(round);
                 }
@@ -7,6 +7,7 @@ use crate::{
         buffer_manager::{OrderedBlocks, ResetAck, ResetRequest},
         errors::Error,
     },
+    payload_manager::PayloadReader,
     state_replication::{StateComputer, StateComputerCommitCallBackType},
 };
 use anyhow::Result;
@@ -117,5 +118,5 @@ impl StateComputer for OrderingStateComputer {
         Ok(())
     }
 
-    fn new_epoch(&self, _: &EpochState) {}
+    fn new_epoch(&self, _: &EpochState, _: Arc<PayloadReader>) {}
 }
@@ -169,6 +169,16 @@ impl ProposalGenerator {
                 )
                 .await
                 .context("Fail to retrieve payload")?;
+            // Proofs of store can only be included in blocks up to their expiration round
+            let payload = match payload {
+                Payload::InQuorumStore(proofs) => Payload::InQuorumStore(
+                    proofs
+                        .into_iter()
+                        .filter(|proof| proof.info().expiration() >= round)
+                        .collect(),
+                ),
+                payload => payload,
+            };
 
             (payload, timestamp.as_micros() as u64)
         };
@@ -21,6 +21,7 @@ pub enum LogEvent {
     NewEpoch,
     NewRound,
     Propose,
+    ReceiveBatchRetrieval,
     ReceiveBlockRetrieval,
     ReceiveEpochChangeProof,
     ReceiveEpochRetrieval,
@@ -19,6 +19,7 @@ use consensus_types::{
     block_retrieval::{BlockRetrievalRequest, BlockRetrievalResponse, MAX_BLOCKS_PER_REQUEST},
     common::Author,
     experimental::{commit_decision::CommitDecision, commit_vote::CommitVote},
+    proof_of_store::{Batch, BatchRequest, ProofOfStore, SignedDigest},
     proposal_msg::ProposalMsg,
     sync_info::SyncInfo,
     vote_msg::VoteMsg,
@@ -37,6 +38,11 @@ use std::{
     time::Duration,
 };
 
+/// Per peer buffer size of the quorum store messages (batches, signed digests and proofs).
+const QUORUM_STORE_CHANNEL_SIZE: usize = 100;
+/// Per peer buffer size of the batch retrieval requests.
+const BATCH_RETRIEVAL_CHANNEL_SIZE: usize = 10;
+
 /// The block retrieval request is used internally for implementing RPC: the callback is executed
 /// for carrying the response
 #[derive(Debug)]
@@ -46,6 +52,15 @@ pub struct IncomingBlockRetrievalRequest {
     pub response_sender: oneshot::Sender<Result<Bytes, RpcError>>,
 }
 
+/// The batch retrieval request is used internally for implementing the quorum store RPC: the
+/// callback is executed for carrying the response
+#[derive(Debug)]
+pub struct IncomingBatchRetrievalRequest {
+    pub req: BatchRequest,
+    pub protocol: ProtocolId,
+    pub response_sender: oneshot::Sender<Result<Bytes, RpcError>>,
+}
+
 /// Just a convenience struct to keep all the network proxy receiving queues in one place.
 /// Will be returned by the NetworkTask upon startup.
 pub struct NetworkReceivers {
@@ -54,7 +69,12 @@ pub struct NetworkReceivers {
         (AccountAddress, Discriminant<ConsensusMsg>),
         (AccountAddress, ConsensusMsg),
     >,
+    /// Provide a FIFO buffer for each Author, as quorum store messages can't be dropped in
+    /// favor of the latest one
+    pub quorum_store_messages:
+        aptos_channel::Receiver<AccountAddress, (AccountAddress, ConsensusMsg)>,
     pub block_retrieval: aptos_channel::Receiver<AccountAddress, IncomingBlockRetrievalRequest>,
+    pub batch_retrieval: aptos_channel::Receiver<AccountAddress, IncomingBatchRetrievalRequest>,
 }
 
 /// Implements the actual networking support for all consensus messaging.
@@ -120,6 +140,46 @@ impl NetworkSender {
         Ok(response)
     }
 
+    /// Tries to retrieve the batch with the given digest from the given peer: the function
+    /// returns a future that is fulfilled with the verified batch.
+    pub async fn request_batch(
+        &self,
+        request: BatchRequest,
+        from: Author,
+        timeout: Duration,
+    ) -> anyhow::Result<Batch> {
+        fail_point!("consensus::send::batch_retrieval", |_| {
+            Err(anyhow::anyhow!("Injected error in request_batch"))
+        });
+
+        ensure!(from != self.author, "Retrieve batch from self");
+        let msg = ConsensusMsg::BatchRequestMsg(Box::new(request.clone()));
+        let response_msg = monitor!(
+            "batch_retrieval",
+            self.network_sender.send_rpc(from, msg, timeout).await
+        )?;
+        let batch = match response_msg {
+            ConsensusMsg::BatchMsg(batch) => *batch,
+            _ => return Err(anyhow!("Invalid response to request")),
+        };
+        ensure!(
+            batch.epoch() == request.epoch() && batch.info().digest() == request.digest(),
+            "Retrieved batch {} doesn't match {}",
+            batch,
+            request
+        );
+        batch.verify().map_err(|e| {
+            error!(
+                SecurityEvent::InvalidRetrievedBatch,
+                remote_peer = from,
+                error = ?e,
+            );
+            e
+        })?;
+
+        Ok(batch)
+    }
+
     /// Tries to send the given msg to all the participants.
     ///
     /// The future is fulfilled as soon as the message put into the mpsc channel to network
@@ -225,6 +285,24 @@ impl NetworkSender {
         self.send(msg, vec![self.author]).await
     }
 
+    pub async fn broadcast_batch(&mut self, batch: Batch) {
+        fail_point!("consensus::send::broadcast_batch", |_| ());
+        let msg = ConsensusMsg::BatchMsg(Box::new(batch));
+        self.broadcast(msg).await
+    }
+
+    pub async fn send_signed_digest(&self, signed_digest: SignedDigest, recipient: Author) {
+        fail_point!("consensus::send::signed_digest", |_| ());
+        let msg = ConsensusMsg::SignedDigestMsg(Box::new(signed_digest));
+        self.send(msg, vec![recipient]).await
+    }
+
+    pub async fn broadcast_proof_of_store(&mut self, proof: ProofOfStore) {
+        fail_point!("consensus::send::broadcast_proof_of_store", |_| ());
+        let msg = ConsensusMsg::ProofOfStoreMsg(Box::new(proof));
+        self.broadcast(msg).await
+    }
+
     /// Sends the ledger info to self buffer manager
     pub async fn send_commit_proof(&self, ledger_info: LedgerInfoWithSignatures) {
         fail_point!("consensus::send::commit_proof", |_| ());
@@ -240,7 +318,9 @@ pub struct NetworkTask {
         (AccountAddress, Discriminant<ConsensusMsg>),
         (AccountAddress, ConsensusMsg),
     >,
+    quorum_store_messages_tx: aptos_channel::Sender<AccountAddress, (AccountAddress, ConsensusMsg)>,
     block_retrieval_tx: aptos_channel::Sender<AccountAddress, IncomingBlockRetrievalRequest>,
+    batch_retrieval_tx: aptos_channel::Sender<AccountAddress, IncomingBatchRetrievalRequest>,
     all_events: Box<dyn Stream<Item = Event<ConsensusMsg>> + Send + Unpin>,
 }
 
@@ -257,16 +337,30 @@ impl NetworkTask {
             1,
             Some(&counters::BLOCK_RETRIEVAL_CHANNEL_MSGS),
         );
+        let (quorum_store_messages_tx, quorum_store_messages) = aptos_channel::new(
+            QueueStyle::FIFO,
+            QUORUM_STORE_CHANNEL_SIZE,
+            Some(&counters::QUORUM_STORE_CHANNEL_MSGS),
+        );
+        let (batch_retrieval_tx, batch_retrieval) = aptos_channel::new(
+            QueueStyle::FIFO,
+            BATCH_RETRIEVAL_CHANNEL_SIZE,
+            Some(&counters::BATCH_RETRIEVAL_CHANNEL_MSGS),
+        );
         let all_events = Box::new(select(network_events, self_receiver));
         (
             NetworkTask {
                 consensus_messages_tx,
+                quorum_store_messages_tx,
                 block_retrieval_tx,
+                batch_retrieval_tx,
                 all_events,
             },
             NetworkReceivers {
                 consensus_messages,
+                quorum_store_messages,
                 block_retrieval,
+                batch_retrieval,
             },
         )
     }
@@ -274,6 +368,16 @@ impl NetworkTask {
     pub async fn start(mut self) {
         while let Some(message) = self.all_events.next().await {
             match message {
+                Event::Message(peer_id, msg @ ConsensusMsg::BatchMsg(_))
+                | Event::Message(peer_id, msg @ ConsensusMsg::SignedDigestMsg(_))
+                | Event::Message(peer_id, msg @ ConsensusMsg::ProofOfStoreMsg(_)) => {
+                    if let Err(e) = self.quorum_store_messages_tx.push(peer_id, (peer_id, msg)) {
+                        warn!(
+                            remote_peer = peer_id,
+                            error = ?e, "Error pushing quorum store msg",
+                        );
+                    }
+                }
                 Event::Message(peer_id, msg) => {
                     if let Err(e) = self
                         .consensus_messages_tx
@@ -310,6 +414,22 @@ impl NetworkTask {
                             warn!(error = ?e, "aptos channel closed");
                         }
                     }
+                    ConsensusMsg::BatchRequestMsg(request) => {
+                        debug!(
+                            remote_peer = peer_id,
+                            event = LogEvent::ReceiveBatchRetrieval,
+                            "{}",
+                            request
+                        );
+                        let req_with_callback = IncomingBatchRetrievalRequest {
+                            req: *request,
+                            protocol,
+                            response_sender: callback,
+                        };
+                        if let Err(e) = self.batch_retrieval_tx.push(peer_id, req_with_callback) {
+                            warn!(error = ?e, "aptos channel closed");
+                        }
+                    }
                     _ => {
                         warn!(remote_peer = peer_id, "Unexpected msg: {:?}", msg);
                         continue;
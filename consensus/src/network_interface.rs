@@ -14,6 +14,7 @@ use consensus_types::{
     block_retrieval::{BlockRetrievalRequest, BlockRetrievalResponse},
     epoch_retrieval::EpochRetrievalRequest,
     experimental::{commit_decision::CommitDecision, commit_vote::CommitVote},
+    proof_of_store::{Batch, BatchRequest, ProofOfStore, SignedDigest},
     proposal_msg::ProposalMsg,
     sync_info::SyncInfo,
     vote_msg::VoteMsg,
@@ -62,6 +63,16 @@ pub enum ConsensusMsg {
     /// than 2f + 1 signatures on the commit proposal. This part is not on the critical path, but
     /// it can save slow machines to quickly confirm the execution result.
     CommitDecisionMsg(Box<CommitDecision>),
+    /// Batch of transactions broadcast by its author to be stored by all validators, in
+    /// quorum store mode. It is also the response to BatchRequestMsg.
+    BatchMsg(Box<Batch>),
+    /// SignedDigest is sent back to the batch author by the validators that stored the batch.
+    SignedDigestMsg(Box<SignedDigest>),
+    /// ProofOfStore is broadcast by the batch author after collecting no fewer than 2f + 1
+    /// signatures on the batch, so that the batch can be included in blocks.
+    ProofOfStoreMsg(Box<ProofOfStore>),
+    /// RPC to get a batch that isn't stored locally from a validator that signed its proof.
+    BatchRequestMsg(Box<BatchRequest>),
 }
 
 /// The interface from Network to Consensus layer.
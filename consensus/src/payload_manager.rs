@@ -1,11 +1,16 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{error::QuorumStoreError, state_replication::PayloadManager};
+use crate::{
+    error::QuorumStoreError, quorum_store::batch_store::BatchStore,
+    state_replication::PayloadManager,
+};
 use anyhow::Result;
 use aptos_logger::prelude::*;
 use aptos_metrics_core::monitor;
+use aptos_types::transaction::SignedTransaction;
 use consensus_types::{
+    block::Block,
     common::{Payload, PayloadFilter},
     request_response::{ConsensusRequest, ConsensusResponse},
 };
@@ -14,7 +19,7 @@ use futures::{
     channel::{mpsc, oneshot},
     future::BoxFuture,
 };
-use std::time::Duration;
+use std::{sync::Arc, time::Duration};
 use tokio::time::{sleep, timeout};
 
 const NO_TXN_DELAY: u64 = 30;
@@ -111,3 +116,33 @@ impl PayloadManager for QuorumStoreClient {
         Ok(payload)
     }
 }
+
+/// Resolves the transactions of block payloads for execution.
+pub enum PayloadReader {
+    /// Payloads carry the transactions themselves.
+    DirectMempool,
+    /// Payloads carry proofs of store, the transactions are read from the batch store.
+    InQuorumStore(Arc<BatchStore>),
+}
+
+impl PayloadReader {
+    pub async fn get_transactions(&self, block: &Block) -> Result<Vec<SignedTransaction>> {
+        match block.payload() {
+            None => Ok(vec![]),
+            Some(Payload::DirectMempool(txns)) => Ok(txns.clone()),
+            Some(Payload::InQuorumStore(proofs)) => match self {
+                PayloadReader::InQuorumStore(batch_store) => {
+                    let mut txns = vec![];
+                    for proof in proofs {
+                        txns.extend(batch_store.get_transactions(proof).await?);
+                    }
+                    Ok(txns)
+                }
+                PayloadReader::DirectMempool => Err(anyhow::anyhow!(
+                    "Block {} carries proofs of store, but quorum store is disabled",
+                    block.id()
+                )),
+            },
+        }
+    }
+}
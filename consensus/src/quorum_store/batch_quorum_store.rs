@@ -0,0 +1,371 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    network::NetworkSender,
+    quorum_store::{batch_store::BatchStore, counters, utils::pull_txns_from_mempool},
+    round_manager::VerifiedEvent,
+};
+use anyhow::Result;
+use aptos_crypto::{bls12381, HashValue};
+use aptos_logger::prelude::*;
+use aptos_mempool::QuorumStoreRequest;
+use aptos_types::{
+    account_address::AccountAddress, validator_signer::ValidatorSigner,
+    validator_verifier::ValidatorVerifier,
+};
+use channel::aptos_channel;
+use consensus_types::{
+    common::{Author, Payload, PayloadFilter, Round, TransactionSummary},
+    proof_of_store::{Batch, BatchInfo, ProofOfStore, SignedDigest},
+    request_response::{ConsensusRequest, ConsensusResponse},
+};
+use futures::{
+    channel::{
+        mpsc::{Receiver, Sender},
+        oneshot,
+    },
+    StreamExt,
+};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+/// A batch created by this validator, waiting for signatures or to be committed.
+struct OwnBatch {
+    info: BatchInfo,
+    txns: Vec<TransactionSummary>,
+    signatures: BTreeMap<Author, bls12381::Signature>,
+    certified: bool,
+}
+
+/// Disseminates batches of transactions pulled from mempool to all validators, collects
+/// their signatures into proofs of store and serves these proofs to consensus, so that blocks
+/// only carry batch digests instead of transactions.
+pub struct QuorumStore {
+    epoch: u64,
+    author: Author,
+    /// None if this node isn't a validator in the current epoch, in which case it only stores
+    /// the batches of the validators.
+    validator_signer: Option<Arc<ValidatorSigner>>,
+    validator_verifier: ValidatorVerifier,
+    network_sender: NetworkSender,
+    batch_store: Arc<BatchStore>,
+    consensus_receiver: Receiver<ConsensusRequest>,
+    network_msg_rx: aptos_channel::Receiver<AccountAddress, (Author, VerifiedEvent)>,
+    mempool_sender: Sender<QuorumStoreRequest>,
+    mempool_txn_pull_timeout_ms: u64,
+    max_batch_size: u64,
+    batch_interval: Duration,
+    batch_expiry_rounds: Round,
+    next_batch_id: u64,
+    committed_round: Round,
+    own_batches: HashMap<HashValue, OwnBatch>,
+    /// Proofs of store that weren't committed yet, in the order they were received.
+    proofs: Vec<ProofOfStore>,
+}
+
+impl QuorumStore {
+    pub fn new(
+        epoch: u64,
+        author: Author,
+        validator_signer: Option<Arc<ValidatorSigner>>,
+        validator_verifier: ValidatorVerifier,
+        network_sender: NetworkSender,
+        batch_store: Arc<BatchStore>,
+        consensus_receiver: Receiver<ConsensusRequest>,
+        network_msg_rx: aptos_channel::Receiver<AccountAddress, (Author, VerifiedEvent)>,
+        mempool_sender: Sender<QuorumStoreRequest>,
+        mempool_txn_pull_timeout_ms: u64,
+        max_batch_size: u64,
+        batch_interval: Duration,
+        batch_expiry_rounds: Round,
+    ) -> Self {
+        Self {
+            epoch,
+            author,
+            validator_signer,
+            validator_verifier,
+            network_sender,
+            batch_store,
+            consensus_receiver,
+            network_msg_rx,
+            mempool_sender,
+            mempool_txn_pull_timeout_ms,
+            max_batch_size,
+            batch_interval,
+            batch_expiry_rounds,
+            next_batch_id: 0,
+            committed_round: 0,
+            own_batches: HashMap::new(),
+            proofs: vec![],
+        }
+    }
+
+    /// Pulls transactions from mempool that aren't in one of our pending batches yet, and
+    /// broadcasts them as a new batch.
+    async fn create_batch(&mut self) {
+        if self.validator_signer.is_none() {
+            return;
+        }
+        let exclude_txns = self
+            .own_batches
+            .values()
+            .flat_map(|batch| batch.txns.iter().cloned())
+            .collect();
+        let txns = match pull_txns_from_mempool(
+            &self.mempool_sender,
+            self.max_batch_size,
+            exclude_txns,
+            self.mempool_txn_pull_timeout_ms,
+        )
+        .await
+        {
+            Ok(txns) => txns,
+            Err(e) => {
+                error!(error = ?e, "GetBatch failed");
+                return;
+            }
+        };
+        if txns.is_empty() {
+            return;
+        }
+
+        let summaries = txns
+            .iter()
+            .map(|txn| TransactionSummary {
+                sender: txn.sender(),
+                sequence_number: txn.sequence_number(),
+            })
+            .collect();
+        let batch = Batch::new(
+            self.epoch,
+            self.author,
+            self.next_batch_id,
+            self.committed_round + self.batch_expiry_rounds,
+            txns,
+        );
+        self.next_batch_id += 1;
+        counters::NUM_BATCHES_CREATED.inc();
+        debug!("[quorum_store] created {}", batch);
+
+        self.own_batches.insert(
+            batch.info().digest(),
+            OwnBatch {
+                info: batch.info().clone(),
+                txns: summaries,
+                signatures: BTreeMap::new(),
+                certified: false,
+            },
+        );
+        // The batch is sent to ourselves too, to be stored and signed like any other batch
+        self.network_sender.broadcast_batch(batch).await;
+    }
+
+    async fn handle_batch(&mut self, peer_id: Author, batch: Batch) {
+        if batch.info().author() != peer_id {
+            warn!(
+                remote_peer = peer_id,
+                "[quorum_store] ignoring {} not sent by its author", batch
+            );
+            return;
+        }
+        let info = batch.info().clone();
+        if let Err(e) = self.batch_store.insert(batch) {
+            warn!(
+                remote_peer = peer_id,
+                error = ?e,
+                "[quorum_store] ignoring batch"
+            );
+            return;
+        }
+        if let Some(validator_signer) = &self.validator_signer {
+            let signed_digest = SignedDigest::new(info, validator_signer);
+            self.network_sender
+                .send_signed_digest(signed_digest, peer_id)
+                .await;
+        }
+    }
+
+    async fn handle_signed_digest(&mut self, peer_id: Author, signed_digest: SignedDigest) {
+        if signed_digest.signer() != peer_id {
+            warn!(
+                remote_peer = peer_id,
+                "[quorum_store] ignoring {} not sent by its signer", signed_digest
+            );
+            return;
+        }
+        let digest = signed_digest.info().digest();
+        let own_batch = match self.own_batches.get_mut(&digest) {
+            Some(own_batch) if !own_batch.certified && &own_batch.info == signed_digest.info() => {
+                own_batch
+            }
+            // Unknown, expired or already certified batch
+            _ => return,
+        };
+        own_batch
+            .signatures
+            .insert(signed_digest.signer(), signed_digest.signature().clone());
+        if self
+            .validator_verifier
+            .check_voting_power(own_batch.signatures.keys())
+            .is_err()
+        {
+            return;
+        }
+
+        // The batch is kept to exclude its transactions from new batches until it's committed
+        own_batch.certified = true;
+        let proof = ProofOfStore::new(
+            own_batch.info.clone(),
+            std::mem::take(&mut own_batch.signatures),
+        );
+        counters::NUM_PROOFS_CREATED.inc();
+        debug!("[quorum_store] created {}", proof);
+        self.network_sender.broadcast_proof_of_store(proof).await;
+    }
+
+    fn handle_proof_of_store(&mut self, peer_id: Author, proof: ProofOfStore) {
+        if proof.info().author() != peer_id {
+            warn!(
+                remote_peer = peer_id,
+                "[quorum_store] ignoring {} not sent by its author", proof
+            );
+            return;
+        }
+        if proof.info().expiration() < self.committed_round
+            || self.proofs.iter().any(|p| p.digest() == proof.digest())
+        {
+            return;
+        }
+        self.proofs.push(proof);
+        counters::NUM_PROOFS_PENDING.set(self.proofs.len() as i64);
+    }
+
+    async fn handle_network_event(&mut self, peer_id: Author, event: VerifiedEvent) {
+        match event {
+            VerifiedEvent::Batch(batch) => self.handle_batch(peer_id, *batch).await,
+            VerifiedEvent::SignedDigest(signed_digest) => {
+                self.handle_signed_digest(peer_id, *signed_digest).await
+            }
+            VerifiedEvent::ProofOfStore(proof) => self.handle_proof_of_store(peer_id, *proof),
+            unexpected_event => {
+                error!("[quorum_store] unexpected event: {:?}", unexpected_event)
+            }
+        }
+    }
+
+    fn handle_block_request(
+        &self,
+        max_size: u64,
+        payload_filter: PayloadFilter,
+        callback: oneshot::Sender<Result<ConsensusResponse>>,
+    ) {
+        let get_block_start_time = Instant::now();
+        let exclude_digests = match payload_filter {
+            PayloadFilter::InQuorumStore(exclude_digests) => exclude_digests,
+            // No pending blocks, or pending blocks created without quorum store
+            PayloadFilter::DirectMempool(_) => HashSet::new(),
+        };
+
+        let mut num_txns = 0;
+        let mut proofs = vec![];
+        for proof in &self.proofs {
+            if exclude_digests.contains(&proof.digest()) {
+                continue;
+            }
+            if num_txns + proof.info().num_txns() > max_size {
+                break;
+            }
+            num_txns += proof.info().num_txns();
+            proofs.push(proof.clone());
+        }
+
+        let result = match callback.send(Ok(ConsensusResponse::GetBlockResponse(
+            Payload::InQuorumStore(proofs),
+        ))) {
+            Err(_) => {
+                error!("Callback failed");
+                counters::CALLBACK_FAIL_LABEL
+            }
+            Ok(_) => counters::CALLBACK_SUCCESS_LABEL,
+        };
+        counters::quorum_store_service_latency(
+            counters::GET_BLOCK_RESPONSE_LABEL,
+            result,
+            get_block_start_time.elapsed(),
+        );
+    }
+
+    fn handle_clean_request(
+        &mut self,
+        epoch: u64,
+        round: Round,
+        digests: Vec<HashValue>,
+        callback: oneshot::Sender<Result<ConsensusResponse>>,
+    ) {
+        let clean_start_time = Instant::now();
+        if epoch == self.epoch && round > self.committed_round {
+            self.committed_round = round;
+        }
+        let committed_round = self.committed_round;
+        let committed: HashSet<_> = digests.into_iter().collect();
+
+        self.batch_store.clean(committed_round);
+        self.proofs.retain(|proof| {
+            !committed.contains(&proof.digest()) && proof.info().expiration() >= committed_round
+        });
+        self.own_batches.retain(|digest, batch| {
+            !committed.contains(digest) && batch.info.expiration() >= committed_round
+        });
+        counters::NUM_PROOFS_PENDING.set(self.proofs.len() as i64);
+
+        let result = match callback.send(Ok(ConsensusResponse::CleanResponse())) {
+            Err(_) => {
+                error!("Callback failed");
+                counters::CALLBACK_FAIL_LABEL
+            }
+            Ok(_) => counters::CALLBACK_SUCCESS_LABEL,
+        };
+        counters::quorum_store_service_latency(
+            counters::CLEAN_LABEL,
+            result,
+            clean_start_time.elapsed(),
+        );
+    }
+
+    fn handle_consensus_request(&mut self, req: ConsensusRequest) {
+        match req {
+            ConsensusRequest::GetBlockRequest(max_size, payload_filter, callback) => {
+                self.handle_block_request(max_size, payload_filter, callback);
+            }
+            ConsensusRequest::CleanRequest(epoch, round, digests, callback) => {
+                self.handle_clean_request(epoch, round, digests, callback);
+            }
+        }
+    }
+
+    pub async fn start(mut self) {
+        info!(epoch = self.epoch, "QuorumStore started");
+        let mut batch_interval = tokio::time::interval(self.batch_interval);
+        loop {
+            let _timer = counters::MAIN_LOOP.start_timer();
+            tokio::select! {
+                msg = self.consensus_receiver.next() => match msg {
+                    Some(msg) => self.handle_consensus_request(msg),
+                    // Consensus moved to a new epoch
+                    None => break,
+                },
+                Some((peer_id, event)) = self.network_msg_rx.next() => {
+                    self.handle_network_event(peer_id, event).await;
+                },
+                _ = batch_interval.tick() => {
+                    self.create_batch().await;
+                },
+            }
+        }
+        info!(epoch = self.epoch, "QuorumStore stopped");
+    }
+}
@@ -0,0 +1,175 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    network::{IncomingBatchRetrievalRequest, NetworkSender},
+    network_interface::ConsensusMsg,
+    quorum_store::counters,
+};
+use anyhow::{anyhow, bail, ensure, Result};
+use aptos_crypto::HashValue;
+use aptos_infallible::Mutex;
+use aptos_logger::prelude::*;
+use aptos_types::transaction::SignedTransaction;
+use consensus_types::{
+    common::{Author, Round},
+    proof_of_store::{Batch, BatchRequest, ProofOfStore},
+};
+use network::protocols::rpc::error::RpcError;
+use std::{collections::HashMap, time::Duration};
+
+/// Stores the batches received in the current epoch, so that blocks carrying proofs of store
+/// can be executed and peers missing a batch can retrieve it.
+pub struct BatchStore {
+    epoch: u64,
+    author: Author,
+    batches: Mutex<HashMap<HashValue, Batch>>,
+    /// Round of the last committed block, batches expiring before it are dropped.
+    committed_round: Mutex<Round>,
+    network_sender: NetworkSender,
+    request_timeout: Duration,
+    /// Maximum number of transactions in a batch.
+    max_batch_size: u64,
+    /// Batches can expire at most this many rounds after the committed round.
+    max_expiry_rounds: Round,
+}
+
+impl BatchStore {
+    pub fn new(
+        epoch: u64,
+        author: Author,
+        network_sender: NetworkSender,
+        request_timeout: Duration,
+        max_batch_size: u64,
+        max_expiry_rounds: Round,
+    ) -> Self {
+        Self {
+            epoch,
+            author,
+            batches: Mutex::new(HashMap::new()),
+            committed_round: Mutex::new(0),
+            network_sender,
+            request_timeout,
+            max_batch_size,
+            max_expiry_rounds,
+        }
+    }
+
+    /// Stores the batch. Fails if it's from another epoch, has too many transactions, is
+    /// already expired or expires too far in the future, as it would be stored until then.
+    pub fn insert(&self, batch: Batch) -> Result<()> {
+        let info = batch.info();
+        ensure!(
+            batch.epoch() == self.epoch,
+            "{} is not from the current epoch {}",
+            info,
+            self.epoch
+        );
+        ensure!(
+            info.num_txns() <= self.max_batch_size,
+            "{} has more than {} transactions",
+            info,
+            self.max_batch_size
+        );
+        let committed_round = *self.committed_round.lock();
+        ensure!(
+            info.expiration() >= committed_round,
+            "{} expired before the committed round {}",
+            info,
+            committed_round
+        );
+        ensure!(
+            info.expiration() <= committed_round + self.max_expiry_rounds,
+            "{} expires more than {} rounds after the committed round {}",
+            info,
+            self.max_expiry_rounds,
+            committed_round
+        );
+
+        let mut batches = self.batches.lock();
+        batches.insert(info.digest(), batch);
+        counters::NUM_BATCHES_STORED.set(batches.len() as i64);
+        Ok(())
+    }
+
+    pub fn get(&self, digest: &HashValue) -> Option<Batch> {
+        self.batches.lock().get(digest).cloned()
+    }
+
+    /// Returns the transactions of the batch the proof refers to. If the batch isn't stored
+    /// locally, it is retrieved from the validators that signed the proof.
+    pub async fn get_transactions(&self, proof: &ProofOfStore) -> Result<Vec<SignedTransaction>> {
+        if let Some(batch) = self.get(&proof.digest()) {
+            return Ok(batch.into_transactions());
+        }
+
+        let request = BatchRequest::new(proof.epoch(), proof.digest());
+        for signer in proof.signers().filter(|signer| **signer != self.author) {
+            match self
+                .network_sender
+                .request_batch(request.clone(), *signer, self.request_timeout)
+                .await
+            {
+                Ok(batch) if batch.info() == proof.info() => {
+                    counters::BATCH_RETRIEVAL_COUNT
+                        .with_label_values(&[counters::REQUEST_SUCCESS_LABEL])
+                        .inc();
+                    let txns = batch.txns().to_vec();
+                    if let Err(e) = self.insert(batch) {
+                        debug!(error = ?e, "Not storing the retrieved batch");
+                    }
+                    return Ok(txns);
+                }
+                Ok(batch) => {
+                    warn!(
+                        remote_peer = *signer,
+                        "Retrieved {} doesn't match {}", batch, proof
+                    );
+                }
+                Err(e) => {
+                    warn!(
+                        remote_peer = *signer,
+                        error = ?e,
+                        "Failed to retrieve batch {}", proof.digest()
+                    );
+                }
+            }
+            counters::BATCH_RETRIEVAL_COUNT
+                .with_label_values(&[counters::REQUEST_FAIL_LABEL])
+                .inc();
+        }
+        bail!("Unable to retrieve batch for {}", proof)
+    }
+
+    /// Responds to a peer with the requested batch, or with an error if it isn't stored.
+    pub fn process_batch_retrieval(&self, request: IncomingBatchRetrievalRequest) -> Result<()> {
+        let response = match self.get(&request.req.digest()) {
+            Some(batch) if batch.epoch() == request.req.epoch() => {
+                let response_bytes = request
+                    .protocol
+                    .to_bytes(&ConsensusMsg::BatchMsg(Box::new(batch)))?;
+                Ok(response_bytes.into())
+            }
+            _ => Err(RpcError::ApplicationError(anyhow!(
+                "Batch not found: {}",
+                request.req
+            ))),
+        };
+        request
+            .response_sender
+            .send(response)
+            .map_err(|e| anyhow!("{:?}", e))
+    }
+
+    /// Drops the batches that expired before the committed round. Committed batches are kept
+    /// until they expire, so that validators that are behind can still retrieve them.
+    pub fn clean(&self, committed_round: Round) {
+        let mut current_round = self.committed_round.lock();
+        if committed_round > *current_round {
+            *current_round = committed_round;
+        }
+        let mut batches = self.batches.lock();
+        batches.retain(|_, batch| batch.info().expiration() >= *current_round);
+        counters::NUM_BATCHES_STORED.set(batches.len() as i64);
+    }
+}
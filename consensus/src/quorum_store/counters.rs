@@ -1,13 +1,16 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 use aptos_metrics_core::{
-    op_counters::DurationHistogram, register_histogram, register_histogram_vec, HistogramVec,
+    op_counters::DurationHistogram, register_histogram, register_histogram_vec,
+    register_int_counter, register_int_counter_vec, register_int_gauge, HistogramVec, IntCounter,
+    IntCounterVec, IntGauge,
 };
 use once_cell::sync::Lazy;
 use std::time::Duration;
 
 pub const GET_BATCH_LABEL: &str = "get_batch";
 pub const GET_BLOCK_RESPONSE_LABEL: &str = "get_block_response";
+pub const CLEAN_LABEL: &str = "clean";
 
 pub const REQUEST_FAIL_LABEL: &str = "fail";
 pub const REQUEST_SUCCESS_LABEL: &str = "success";
@@ -42,3 +45,49 @@ pub static MAIN_LOOP: Lazy<DurationHistogram> = Lazy::new(|| {
         .unwrap(),
     )
 });
+
+/// Number of batches created by this validator.
+pub static NUM_BATCHES_CREATED: Lazy<IntCounter> = Lazy::new(|| {
+    register_int_counter!(
+        "quorum_store_num_batches_created",
+        "Number of batches created by this validator"
+    )
+    .unwrap()
+});
+
+/// Number of proofs of store created for the batches of this validator.
+pub static NUM_PROOFS_CREATED: Lazy<IntCounter> = Lazy::new(|| {
+    register_int_counter!(
+        "quorum_store_num_proofs_created",
+        "Number of proofs of store created for the batches of this validator"
+    )
+    .unwrap()
+});
+
+/// Number of batches currently stored.
+pub static NUM_BATCHES_STORED: Lazy<IntGauge> = Lazy::new(|| {
+    register_int_gauge!(
+        "quorum_store_num_batches_stored",
+        "Number of batches currently stored"
+    )
+    .unwrap()
+});
+
+/// Number of proofs of store waiting to be included in a block.
+pub static NUM_PROOFS_PENDING: Lazy<IntGauge> = Lazy::new(|| {
+    register_int_gauge!(
+        "quorum_store_num_proofs_pending",
+        "Number of proofs of store waiting to be included in a block"
+    )
+    .unwrap()
+});
+
+/// Count of the batch retrievals from peers, by result.
+pub static BATCH_RETRIEVAL_COUNT: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "quorum_store_batch_retrieval_count",
+        "Count of the batch retrievals from peers, by result",
+        &["result"]
+    )
+    .unwrap()
+});
@@ -1,14 +1,12 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::quorum_store::counters;
+use crate::quorum_store::{counters, utils::pull_txns_from_mempool};
 use anyhow::Result;
 use aptos_logger::prelude::*;
-use aptos_mempool::{QuorumStoreRequest, QuorumStoreResponse};
-use aptos_metrics_core::monitor;
-use aptos_types::transaction::SignedTransaction;
+use aptos_mempool::QuorumStoreRequest;
 use consensus_types::{
-    common::{Payload, PayloadFilter, TransactionSummary},
+    common::{Payload, PayloadFilter},
     request_response::{ConsensusRequest, ConsensusResponse},
 };
 use futures::{
@@ -18,8 +16,7 @@ use futures::{
     },
     StreamExt,
 };
-use std::time::{Duration, Instant};
-use tokio::time::timeout;
+use std::time::Instant;
 
 pub struct DirectMempoolQuorumStore {
     consensus_receiver: Receiver<ConsensusRequest>,
@@ -40,38 +37,6 @@ impl DirectMempoolQuorumStore {
         }
     }
 
-    async fn pull_internal(
-        &self,
-        max_size: u64,
-        exclude_txns: Vec<TransactionSummary>,
-    ) -> Result<Vec<SignedTransaction>, anyhow::Error> {
-        let (callback, callback_rcv) = oneshot::channel();
-        let msg = QuorumStoreRequest::GetBatchRequest(max_size, exclude_txns, callback);
-        self.mempool_sender
-            .clone()
-            .try_send(msg)
-            .map_err(anyhow::Error::from)?;
-        // wait for response
-        match monitor!(
-            "pull_txn",
-            timeout(
-                Duration::from_millis(self.mempool_txn_pull_timeout_ms),
-                callback_rcv
-            )
-            .await
-        ) {
-            Err(_) => Err(anyhow::anyhow!(
-                "[direct_mempool_quorum_store] did not receive GetBatchResponse on time"
-            )),
-            Ok(resp) => match resp.map_err(anyhow::Error::from)?? {
-                QuorumStoreResponse::GetBatchResponse(txns) => Ok(txns),
-                _ => Err(anyhow::anyhow!(
-                    "[direct_mempool_quorum_store] did not receive expected GetBatchResponse"
-                )),
-            },
-        }
-    }
-
     async fn handle_block_request(
         &self,
         max_size: u64,
@@ -81,7 +46,14 @@ impl DirectMempoolQuorumStore {
         let get_batch_start_time = Instant::now();
         let (txns, result) = match payload_filter {
             PayloadFilter::DirectMempool(exclude_txns) => {
-                match self.pull_internal(max_size, exclude_txns).await {
+                match pull_txns_from_mempool(
+                    &self.mempool_sender,
+                    max_size,
+                    exclude_txns,
+                    self.mempool_txn_pull_timeout_ms,
+                )
+                .await
+                {
                     Err(_) => {
                         error!("GetBatch failed");
                         (vec![], counters::REQUEST_FAIL_LABEL)
@@ -131,7 +103,7 @@ impl DirectMempoolQuorumStore {
                 self.handle_block_request(max_size, payload_filter, callback)
                     .await;
             }
-            ConsensusRequest::CleanRequest(_, _, callback) => {
+            ConsensusRequest::CleanRequest(_, _, _, callback) => {
                 self.handle_clean_request(callback).await;
             }
         }
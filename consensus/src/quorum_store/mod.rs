@@ -1,9 +1,14 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
+/// Disseminates batches of transactions and certifies their availability with proofs of store.
+pub mod batch_quorum_store;
+/// Stores the batches of the current epoch and retrieves missing ones from peers.
+pub mod batch_store;
 /// Equivalent to directly fetching blocks from mempool without a quorum store.
 pub mod direct_mempool_quorum_store;
 
 mod counters;
 #[cfg(test)]
 mod tests;
+mod utils;
@@ -0,0 +1,191 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    network_interface::ConsensusMsg,
+    quorum_store::{
+        batch_quorum_store::QuorumStore,
+        batch_store::BatchStore,
+        tests::utils::{create_network_sender, create_txns},
+    },
+    round_manager::VerifiedEvent,
+};
+use aptos_mempool::{QuorumStoreRequest, QuorumStoreResponse};
+use aptos_types::{account_address::AccountAddress, validator_verifier::random_validator_verifier};
+use channel::{aptos_channel, message_queues::QueueStyle};
+use consensus_types::{
+    common::{Author, Payload, PayloadFilter},
+    proof_of_store::{ProofOfStore, SignedDigest},
+    request_response::{ConsensusRequest, ConsensusResponse},
+};
+use futures::{
+    channel::{mpsc, oneshot},
+    StreamExt,
+};
+use network::protocols::network::Event;
+use std::{collections::HashSet, sync::Arc, time::Duration};
+use tokio::time::timeout;
+
+const EPOCH: u64 = 1;
+const BATCH_EXPIRY_ROUNDS: u64 = 10;
+
+async fn next_self_msg(self_receiver: &mut channel::Receiver<Event<ConsensusMsg>>) -> ConsensusMsg {
+    match timeout(Duration::from_millis(1_000), self_receiver.next())
+        .await
+        .unwrap()
+        .unwrap()
+    {
+        Event::Message(_, msg) => msg,
+        event => panic!("Unexpected event {:?}", event),
+    }
+}
+
+async fn get_block_proofs(
+    consensus_sender: &mut mpsc::Sender<ConsensusRequest>,
+) -> Vec<ProofOfStore> {
+    let (callback, callback_rcv) = oneshot::channel();
+    consensus_sender
+        .try_send(ConsensusRequest::GetBlockRequest(
+            100,
+            PayloadFilter::InQuorumStore(HashSet::new()),
+            callback,
+        ))
+        .unwrap();
+    match timeout(Duration::from_millis(1_000), callback_rcv)
+        .await
+        .unwrap()
+        .unwrap()
+        .unwrap()
+    {
+        ConsensusResponse::GetBlockResponse(Payload::InQuorumStore(proofs)) => proofs,
+        _ => panic!("Unexpected response"),
+    }
+}
+
+fn push_network_event(
+    network_msg_tx: &aptos_channel::Sender<AccountAddress, (Author, VerifiedEvent)>,
+    peer_id: Author,
+    event: VerifiedEvent,
+) {
+    network_msg_tx.push(peer_id, (peer_id, event)).unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_quorum_formation_and_expiry() {
+    let (signers, verifier) = random_validator_verifier(4, None, false);
+    let author = signers[0].author();
+    let (network_sender, mut self_receiver) = create_network_sender(author, verifier.clone());
+    let batch_store = Arc::new(BatchStore::new(
+        EPOCH,
+        author,
+        network_sender.clone(),
+        Duration::from_millis(100),
+        100,
+        BATCH_EXPIRY_ROUNDS,
+    ));
+    let (mut consensus_sender, consensus_receiver) = mpsc::channel(1_024);
+    let (network_msg_tx, network_msg_rx) = aptos_channel::new(QueueStyle::FIFO, 1_024, None);
+    let (mempool_sender, mut mempool_receiver) = mpsc::channel(1_024);
+    let quorum_store = QuorumStore::new(
+        EPOCH,
+        author,
+        Some(Arc::new(signers[0].clone())),
+        verifier.clone(),
+        network_sender,
+        batch_store.clone(),
+        consensus_receiver,
+        network_msg_rx,
+        mempool_sender,
+        1_000,
+        100,
+        // Only the first batch is created during the test
+        Duration::from_secs(3_600),
+        BATCH_EXPIRY_ROUNDS,
+    );
+    tokio::spawn(quorum_store.start());
+
+    // The quorum store pulls transactions from mempool and broadcasts them as a batch
+    match timeout(Duration::from_millis(1_000), mempool_receiver.next())
+        .await
+        .unwrap()
+        .unwrap()
+    {
+        QuorumStoreRequest::GetBatchRequest(_, _, callback) => callback
+            .send(Ok(QuorumStoreResponse::GetBatchResponse(create_txns(3))))
+            .unwrap(),
+        _ => panic!("Unexpected request"),
+    }
+    let batch = match next_self_msg(&mut self_receiver).await {
+        ConsensusMsg::BatchMsg(batch) => batch,
+        msg => panic!("Unexpected msg {:?}", msg),
+    };
+    assert_eq!(batch.info().author(), author);
+    assert_eq!(batch.info().num_txns(), 3);
+    assert_eq!(batch.info().expiration(), BATCH_EXPIRY_ROUNDS);
+    let info = batch.info().clone();
+
+    // The batch is stored and signed when it's received
+    push_network_event(&network_msg_tx, author, VerifiedEvent::Batch(batch));
+    let own_signed_digest = match next_self_msg(&mut self_receiver).await {
+        ConsensusMsg::SignedDigestMsg(signed_digest) => signed_digest,
+        msg => panic!("Unexpected msg {:?}", msg),
+    };
+    assert_eq!(own_signed_digest.info(), &info);
+    assert!(batch_store.get(&info.digest()).is_some());
+
+    // 4 validators with equal voting power need 3 signatures to form a proof of store
+    push_network_event(
+        &network_msg_tx,
+        author,
+        VerifiedEvent::SignedDigest(own_signed_digest),
+    );
+    for signer in &signers[1..3] {
+        let signed_digest = SignedDigest::new(info.clone(), signer);
+        push_network_event(
+            &network_msg_tx,
+            signer.author(),
+            VerifiedEvent::SignedDigest(Box::new(signed_digest)),
+        );
+    }
+    let proof = match next_self_msg(&mut self_receiver).await {
+        ConsensusMsg::ProofOfStoreMsg(proof) => proof,
+        msg => panic!("Unexpected msg {:?}", msg),
+    };
+    assert_eq!(proof.info(), &info);
+    assert_eq!(proof.signers().count(), 3);
+    proof.verify(&verifier).unwrap();
+
+    // Received proofs are served to consensus
+    push_network_event(
+        &network_msg_tx,
+        author,
+        VerifiedEvent::ProofOfStore(proof.clone()),
+    );
+    let mut proofs = vec![];
+    for _ in 0..100 {
+        proofs = get_block_proofs(&mut consensus_sender).await;
+        if !proofs.is_empty() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    assert_eq!(proofs, vec![*proof]);
+
+    // Once a block after the expiration is committed, the batch and its proof are dropped
+    let (callback, callback_rcv) = oneshot::channel();
+    consensus_sender
+        .try_send(ConsensusRequest::CleanRequest(
+            EPOCH,
+            BATCH_EXPIRY_ROUNDS + 1,
+            vec![],
+            callback,
+        ))
+        .unwrap();
+    timeout(Duration::from_millis(1_000), callback_rcv)
+        .await
+        .unwrap()
+        .unwrap()
+        .unwrap();
+    assert!(get_block_proofs(&mut consensus_sender).await.is_empty());
+    assert!(batch_store.get(&info.digest()).is_none());
+}
@@ -0,0 +1,84 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::quorum_store::{
+    batch_store::BatchStore,
+    tests::utils::{create_network_sender, create_txns},
+};
+use aptos_types::validator_verifier::random_validator_verifier;
+use consensus_types::{common::Author, proof_of_store::Batch};
+use std::time::Duration;
+
+const EPOCH: u64 = 1;
+const MAX_BATCH_SIZE: u64 = 5;
+const MAX_EXPIRY_ROUNDS: u64 = 20;
+
+fn create_batch_store() -> (BatchStore, Author) {
+    let (signers, verifier) = random_validator_verifier(1, None, false);
+    let author = signers[0].author();
+    let (network_sender, _) = create_network_sender(author, verifier);
+    let batch_store = BatchStore::new(
+        EPOCH,
+        author,
+        network_sender,
+        Duration::from_millis(100),
+        MAX_BATCH_SIZE,
+        MAX_EXPIRY_ROUNDS,
+    );
+    (batch_store, author)
+}
+
+#[test]
+fn test_insert_and_get() {
+    let (batch_store, author) = create_batch_store();
+    let batch = Batch::new(EPOCH, author, 0, 10, create_txns(MAX_BATCH_SIZE));
+    let digest = batch.info().digest();
+
+    assert!(batch_store.get(&digest).is_none());
+    batch_store.insert(batch.clone()).unwrap();
+    assert_eq!(batch_store.get(&digest), Some(batch));
+}
+
+#[test]
+fn test_insert_rejects_invalid_batches() {
+    let (batch_store, author) = create_batch_store();
+    let invalid_batches = vec![
+        // From another epoch
+        Batch::new(EPOCH + 1, author, 0, 10, create_txns(1)),
+        // Too many transactions
+        Batch::new(EPOCH, author, 1, 10, create_txns(MAX_BATCH_SIZE + 1)),
+        // Expires too far in the future
+        Batch::new(EPOCH, author, 2, MAX_EXPIRY_ROUNDS + 1, create_txns(1)),
+    ];
+    for batch in invalid_batches {
+        let digest = batch.info().digest();
+        assert!(batch_store.insert(batch).is_err());
+        assert!(batch_store.get(&digest).is_none());
+    }
+}
+
+#[test]
+fn test_expiry() {
+    let (batch_store, author) = create_batch_store();
+    let expiring = Batch::new(EPOCH, author, 0, 5, create_txns(1));
+    let remaining = Batch::new(EPOCH, author, 1, 15, create_txns(1));
+    batch_store.insert(expiring.clone()).unwrap();
+    batch_store.insert(remaining.clone()).unwrap();
+
+    // Only the batches that expired before the committed round are dropped
+    batch_store.clean(10);
+    assert!(batch_store.get(&expiring.info().digest()).is_none());
+    assert!(batch_store.get(&remaining.info().digest()).is_some());
+
+    // An older committed round doesn't move the expiry window back
+    batch_store.clean(0);
+    assert!(batch_store.get(&remaining.info().digest()).is_some());
+
+    // The accepted expirations moved with the committed round
+    let expired = Batch::new(EPOCH, author, 2, 9, create_txns(1));
+    assert!(batch_store.insert(expired).is_err());
+    let too_late = Batch::new(EPOCH, author, 3, 10 + MAX_EXPIRY_ROUNDS + 1, create_txns(1));
+    assert!(batch_store.insert(too_late).is_err());
+    let latest = Batch::new(EPOCH, author, 4, 10 + MAX_EXPIRY_ROUNDS, create_txns(1));
+    assert!(batch_store.insert(latest).is_ok());
+}
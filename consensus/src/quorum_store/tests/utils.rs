@@ -0,0 +1,53 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    network::NetworkSender,
+    network_interface::{ConsensusMsg, ConsensusNetworkSender},
+};
+use aptos_crypto::{ed25519::Ed25519PrivateKey, PrivateKey, Uniform};
+use aptos_types::{
+    account_address::AccountAddress, test_helpers::transaction_test_helpers::get_test_signed_txn,
+    transaction::SignedTransaction, validator_verifier::ValidatorVerifier,
+};
+use channel::{aptos_channel, message_queues::QueueStyle};
+use consensus_types::common::Author;
+use network::{
+    peer_manager::{ConnectionRequestSender, PeerManagerRequestSender},
+    protocols::network::{Event, NewNetworkSender},
+};
+
+pub fn create_txns(count: u64) -> Vec<SignedTransaction> {
+    let private_key = Ed25519PrivateKey::generate_for_testing();
+    let public_key = private_key.public_key();
+    (0..count)
+        .map(|i| {
+            get_test_signed_txn(
+                AccountAddress::random(),
+                i,
+                &private_key,
+                public_key.clone(),
+                None,
+            )
+        })
+        .collect()
+}
+
+/// Creates a network sender for `author`. Messages sent to other validators are dropped, the
+/// messages the author sends to itself are delivered to the returned receiver.
+pub fn create_network_sender(
+    author: Author,
+    validators: ValidatorVerifier,
+) -> (NetworkSender, channel::Receiver<Event<ConsensusMsg>>) {
+    let (network_reqs_tx, _) = aptos_channel::new(QueueStyle::FIFO, 8, None);
+    let (connection_reqs_tx, _) = aptos_channel::new(QueueStyle::FIFO, 8, None);
+    let network_sender = ConsensusNetworkSender::new(
+        PeerManagerRequestSender::new(network_reqs_tx),
+        ConnectionRequestSender::new(connection_reqs_tx),
+    );
+    let (self_sender, self_receiver) = channel::new_test(1000);
+    (
+        NetworkSender::new(author, network_sender, self_sender, validators),
+        self_receiver,
+    )
+}
@@ -0,0 +1,45 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::Result;
+use aptos_mempool::{QuorumStoreRequest, QuorumStoreResponse};
+use aptos_metrics_core::monitor;
+use aptos_types::transaction::SignedTransaction;
+use consensus_types::common::TransactionSummary;
+use futures::channel::{mpsc::Sender, oneshot};
+use std::time::Duration;
+use tokio::time::timeout;
+
+/// Pulls up to `max_size` transactions from mempool, excluding the given ones.
+pub(crate) async fn pull_txns_from_mempool(
+    mempool_sender: &Sender<QuorumStoreRequest>,
+    max_size: u64,
+    exclude_txns: Vec<TransactionSummary>,
+    mempool_txn_pull_timeout_ms: u64,
+) -> Result<Vec<SignedTransaction>> {
+    let (callback, callback_rcv) = oneshot::channel();
+    let msg = QuorumStoreRequest::GetBatchRequest(max_size, exclude_txns, callback);
+    mempool_sender
+        .clone()
+        .try_send(msg)
+        .map_err(anyhow::Error::from)?;
+    // wait for response
+    match monitor!(
+        "pull_txn",
+        timeout(
+            Duration::from_millis(mempool_txn_pull_timeout_ms),
+            callback_rcv
+        )
+        .await
+    ) {
+        Err(_) => Err(anyhow::anyhow!(
+            "[quorum_store] did not receive GetBatchResponse on time"
+        )),
+        Ok(resp) => match resp.map_err(anyhow::Error::from)?? {
+            QuorumStoreResponse::GetBatchResponse(txns) => Ok(txns),
+            _ => Err(anyhow::anyhow!(
+                "[quorum_store] did not receive expected GetBatchResponse"
+            )),
+        },
+    }
+}
@@ -34,6 +34,7 @@ use consensus_types::{
     block::Block,
     common::{Author, Round},
     experimental::{commit_decision::CommitDecision, commit_vote::CommitVote},
+    proof_of_store::{Batch, ProofOfStore, SignedDigest},
     proposal_msg::ProposalMsg,
     quorum_cert::QuorumCert,
     sync_info::SyncInfo,
@@ -57,6 +58,9 @@ pub enum UnverifiedEvent {
     SyncInfo(Box<SyncInfo>),
     CommitVote(Box<CommitVote>),
     CommitDecision(Box<CommitDecision>),
+    Batch(Box<Batch>),
+    SignedDigest(Box<SignedDigest>),
+    ProofOfStore(Box<ProofOfStore>),
 }
 
 impl UnverifiedEvent {
@@ -80,6 +84,18 @@ impl UnverifiedEvent {
                 cd.verify(validator)?;
                 VerifiedEvent::CommitDecision(cd)
             }
+            UnverifiedEvent::Batch(b) => {
+                b.verify()?;
+                VerifiedEvent::Batch(b)
+            }
+            UnverifiedEvent::SignedDigest(sd) => {
+                sd.verify(validator)?;
+                VerifiedEvent::SignedDigest(sd)
+            }
+            UnverifiedEvent::ProofOfStore(p) => {
+                p.verify(validator)?;
+                VerifiedEvent::ProofOfStore(p)
+            }
         })
     }
 
@@ -90,6 +106,9 @@ impl UnverifiedEvent {
             UnverifiedEvent::SyncInfo(s) => s.epoch(),
             UnverifiedEvent::CommitVote(cv) => cv.epoch(),
             UnverifiedEvent::CommitDecision(cd) => cd.epoch(),
+            UnverifiedEvent::Batch(b) => b.epoch(),
+            UnverifiedEvent::SignedDigest(sd) => sd.epoch(),
+            UnverifiedEvent::ProofOfStore(p) => p.epoch(),
         }
     }
 }
@@ -102,6 +121,9 @@ impl From<ConsensusMsg> for UnverifiedEvent {
             ConsensusMsg::SyncInfo(m) => UnverifiedEvent::SyncInfo(m),
             ConsensusMsg::CommitVoteMsg(m) => UnverifiedEvent::CommitVote(m),
             ConsensusMsg::CommitDecisionMsg(m) => UnverifiedEvent::CommitDecision(m),
+            ConsensusMsg::BatchMsg(m) => UnverifiedEvent::Batch(m),
+            ConsensusMsg::SignedDigestMsg(m) => UnverifiedEvent::SignedDigest(m),
+            ConsensusMsg::ProofOfStoreMsg(m) => UnverifiedEvent::ProofOfStore(m),
             _ => unreachable!("Unexpected conversion"),
         }
     }
@@ -115,6 +137,9 @@ pub enum VerifiedEvent {
     UnverifiedSyncInfo(Box<SyncInfo>),
     CommitVote(Box<CommitVote>),
     CommitDecision(Box<CommitDecision>),
+    Batch(Box<Batch>),
+    SignedDigest(Box<SignedDigest>),
+    ProofOfStore(Box<ProofOfStore>),
     // local messages
     LocalTimeout(Round),
     Shutdown(oneshot::Sender<()>),
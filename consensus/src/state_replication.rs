@@ -1,7 +1,10 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::error::{QuorumStoreError, StateSyncError};
+use crate::{
+    error::{QuorumStoreError, StateSyncError},
+    payload_manager::PayloadReader,
+};
 use anyhow::Result;
 use aptos_crypto::HashValue;
 use aptos_types::{epoch_state::EpochState, ledger_info::LedgerInfoWithSignatures};
@@ -60,6 +63,7 @@ pub trait StateComputer: Send + Sync {
     /// can assume there were no modifications to the storage made.
     async fn sync_to(&self, target: LedgerInfoWithSignatures) -> Result<(), StateSyncError>;
 
-    // Reconfigure to execute transactions for a new epoch.
-    fn new_epoch(&self, epoch_state: &EpochState);
+    // Reconfigure to execute transactions for a new epoch, reading block payloads with the
+    // given reader.
+    fn new_epoch(&self, epoch_state: &EpochState, payload_reader: Arc<PayloadReader>);
 }
@@ -3,6 +3,7 @@
 
 use crate::{
     error::StateSyncError,
+    payload_manager::PayloadReader,
     state_replication::{StateComputer, StateComputerCommitCallBackType},
     test_utils::mock_storage::MockStorage,
 };
@@ -68,14 +69,14 @@ impl StateComputer for MockStateComputer {
         // mock sending commit notif to state sync
         let mut txns = vec![];
         for block in blocks {
-            let mut payload = self
+            let payload = self
                 .block_cache
                 .lock()
                 .remove(&block.id())
-                .ok_or_else(|| format_err!("Cannot find block"))?
-                .into_iter()
-                .collect();
-            txns.append(&mut payload);
+                .ok_or_else(|| format_err!("Cannot find block"))?;
+            if let Payload::DirectMempool(mut payload_txns) = payload {
+                txns.append(&mut payload_txns);
+            }
         }
         // they may fail during shutdown
         let _ = self.state_sync_client.unbounded_send(txns);
@@ -102,7 +103,7 @@ impl StateComputer for MockStateComputer {
         Ok(())
     }
 
-    fn new_epoch(&self, _: &EpochState) {}
+    fn new_epoch(&self, _: &EpochState, _: Arc<PayloadReader>) {}
 }
 
 pub struct EmptyStateComputer;
@@ -130,7 +131,7 @@ impl StateComputer for EmptyStateComputer {
         Ok(())
     }
 
-    fn new_epoch(&self, _: &EpochState) {}
+    fn new_epoch(&self, _: &EpochState, _: Arc<PayloadReader>) {}
 }
 
 /// Random Compute Result State Computer
@@ -181,5 +182,5 @@ impl StateComputer for RandomComputeResultStateComputer {
         Ok(())
     }
 
-    fn new_epoch(&self, _: &EpochState) {}
+    fn new_epoch(&self, _: &EpochState, _: Arc<PayloadReader>) {}
 }
@@ -5,8 +5,8 @@ use crate::error::MempoolError;
 use anyhow::{format_err, Result};
 use aptos_mempool::QuorumStoreRequest;
 use aptos_metrics_core::monitor;
-use aptos_types::transaction::TransactionStatus;
-use consensus_types::{block::Block, common::TransactionSummary};
+use aptos_types::transaction::{SignedTransaction, TransactionStatus};
+use consensus_types::common::TransactionSummary;
 use executor_types::StateComputeResult;
 use futures::channel::{mpsc, oneshot};
 use itertools::Itertools;
@@ -16,11 +16,11 @@ use tokio::time::timeout;
 /// Notification of failed transactions.
 #[async_trait::async_trait]
 pub trait TxnNotifier: Send + Sync {
-    /// Notification of txns which failed execution. (Committed txns is notified by
-    /// state sync.)
+    /// Notification of txns which failed execution, given the user txns of the executed block.
+    /// (Committed txns is notified by state sync.)
     async fn notify_failed_txn(
         &self,
-        block: &Block,
+        txns: &[SignedTransaction],
         compute_results: &StateComputeResult,
     ) -> Result<(), MempoolError>;
 }
@@ -49,18 +49,10 @@ impl MempoolNotifier {
 impl TxnNotifier for MempoolNotifier {
     async fn notify_failed_txn(
         &self,
-        block: &Block,
+        txns: &[SignedTransaction],
         compute_results: &StateComputeResult,
     ) -> Result<(), MempoolError> {
         let mut rejected_txns = vec![];
-        let txns: Vec<_> = match block.payload() {
-            Some(payload) => payload,
-            None => return Ok(()),
-        }
-        .clone()
-        .into_iter()
-        .collect();
-
         if txns.is_empty() {
             return Ok(());
         }
@@ -7,13 +7,18 @@ use crate::metrics::{
 };
 use aptos_logger::prelude::*;
 use lz4::block::CompressionMode;
+use serde::{Deserialize, Serialize};
+use std::convert::TryInto;
 use thiserror::Error;
 
 /// This crate provides a simple library interface for data compression.
 /// It is useful for compressing large data chunks that are
 /// sent across the network (e.g., by state sync and consensus).
-/// Internally, it uses LZ4 in fast mode to compress the data.
-/// See https://github.com/10xGenomics/lz4-rs for more information.
+/// Internally, it supports LZ4 (in fast mode) and zstd, see
+/// https://github.com/10xGenomics/lz4-rs and https://github.com/gyscos/zstd-rs
+/// for more information. The algorithm used to compress the data is
+/// encoded in the first byte of the compressed data, so decompression
+/// doesn't need to know which algorithm was used.
 ///
 /// Note: the crate also exposes some basic compression metrics
 /// that can be used to track the cumulative compression ratio
@@ -22,9 +27,21 @@ mod metrics;
 #[cfg(test)]
 mod tests;
 
-/// The acceleration parameter to use for FAST compression mode.
+/// The acceleration parameter to use for LZ4 FAST compression mode.
 /// This was determined anecdotally.
-const ACCELERATION_PARAMETER: i32 = 1;
+const DEFAULT_LZ4_ACCELERATION: i32 = 1;
+
+/// The highest zstd level used when a peer picks the algorithm. Higher levels
+/// cost much more CPU for little extra compression, so peers are not allowed to
+/// request them.
+pub const MAX_PEER_ZSTD_LEVEL: i32 = 9;
+
+/// Header bytes identifying the algorithm used to compress the data
+const LZ4_HEADER: u8 = 0;
+const ZSTD_HEADER: u8 = 1;
+
+/// The length of the size prefix of LZ4 compressed data
+const LZ4_SIZE_PREFIX_LENGTH: usize = 4;
 
 /// A useful wrapper for representing compressed data
 pub type CompressedData = Vec<u8>;
@@ -34,17 +51,159 @@ pub type CompressedData = Vec<u8>;
 #[error("Encountered a compression error! Error: {0}")]
 pub struct CompressionError(String);
 
-/// Compresses the raw data stream
-pub fn compress(raw_data: Vec<u8>) -> Result<CompressedData, CompressionError> {
+/// The compression algorithms (and their parameters) supported by this crate.
+/// LZ4 is cheap on CPU, while zstd trades more CPU for better compression
+/// ratios (i.e., less bandwidth), especially at higher levels.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CompressionAlgorithm {
+    /// LZ4 in fast mode. Higher acceleration values are faster, but compress less.
+    Lz4 { acceleration: i32 },
+    /// Zstd at the given level (1-22). Higher levels are slower, but compress more.
+    Zstd { level: i32 },
+}
+
+impl CompressionAlgorithm {
+    /// Returns a summary label for the algorithm
+    pub fn get_label(&self) -> &'static str {
+        match self {
+            CompressionAlgorithm::Lz4 { .. } => "lz4",
+            CompressionAlgorithm::Zstd { .. } => "zstd",
+        }
+    }
+
+    /// Returns the algorithm with its parameters limited to those we're willing
+    /// to spend CPU on when a peer picks the algorithm.
+    pub fn limit_for_peers(self) -> Self {
+        match self {
+            CompressionAlgorithm::Zstd { level } => CompressionAlgorithm::Zstd {
+                level: level.min(MAX_PEER_ZSTD_LEVEL),
+            },
+            // Lower accelerations are no slower than the default
+            algorithm @ CompressionAlgorithm::Lz4 { .. } => algorithm,
+        }
+    }
+
+    /// Returns the header byte identifying the algorithm
+    fn header(&self) -> u8 {
+        match self {
+            CompressionAlgorithm::Lz4 { .. } => LZ4_HEADER,
+            CompressionAlgorithm::Zstd { .. } => ZSTD_HEADER,
+        }
+    }
+
+    /// Returns the algorithm identified by the header byte. The parameters
+    /// are irrelevant for decompression, so the defaults are returned.
+    fn from_header(header: u8) -> Result<Self, CompressionError> {
+        match header {
+            LZ4_HEADER => Ok(Self::default()),
+            ZSTD_HEADER => Ok(CompressionAlgorithm::Zstd {
+                level: zstd::DEFAULT_COMPRESSION_LEVEL,
+            }),
+            header => Err(CompressionError(format!(
+                "Unknown compression algorithm header: {:?}",
+                header
+            ))),
+        }
+    }
+
+    /// Compresses the raw data (without the header)
+    fn compress(&self, raw_data: &[u8]) -> std::io::Result<Vec<u8>> {
+        match self {
+            CompressionAlgorithm::Lz4 { acceleration } => {
+                let compression_mode = CompressionMode::FAST(*acceleration);
+                lz4::block::compress(raw_data, Some(compression_mode), true)
+            }
+            CompressionAlgorithm::Zstd { level } => zstd::bulk::compress(raw_data, *level),
+        }
+    }
+
+    /// Decompresses the compressed data (without the header). Fails if the
+    /// data decompresses to more than `max_size` bytes.
+    fn decompress(&self, compressed_data: &[u8], max_size: usize) -> std::io::Result<Vec<u8>> {
+        match self {
+            CompressionAlgorithm::Lz4 { .. } => {
+                // The size prefix is checked before it's used to allocate the output
+                if compressed_data.len() < LZ4_SIZE_PREFIX_LENGTH {
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::InvalidData,
+                        "Missing the size prefix",
+                    ));
+                }
+                let (size_prefix, data) = compressed_data.split_at(LZ4_SIZE_PREFIX_LENGTH);
+                let size = i32::from_le_bytes(size_prefix.try_into().unwrap());
+                if size < 0 || size as usize > max_size {
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::InvalidData,
+                        format!(
+                            "Invalid decompressed size: {}, the maximum is: {}",
+                            size, max_size
+                        ),
+                    ));
+                }
+                lz4::block::decompress(data, Some(size))
+            }
+            CompressionAlgorithm::Zstd { .. } => {
+                // As with LZ4, the size in the frame header is checked before it's used to
+                // allocate the output (the data is compressed in bulk, so the size is known)
+                let size = match zstd::zstd_safe::get_frame_content_size(compressed_data) {
+                    zstd::zstd_safe::CONTENTSIZE_ERROR => {
+                        return Err(std::io::Error::new(
+                            std::io::ErrorKind::InvalidData,
+                            "Invalid frame header",
+                        ))
+                    }
+                    zstd::zstd_safe::CONTENTSIZE_UNKNOWN => {
+                        return Err(std::io::Error::new(
+                            std::io::ErrorKind::InvalidData,
+                            "Missing the decompressed size",
+                        ))
+                    }
+                    size => size,
+                };
+                if size > max_size as u64 {
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::InvalidData,
+                        format!(
+                            "Invalid decompressed size: {}, the maximum is: {}",
+                            size, max_size
+                        ),
+                    ));
+                }
+
+                // Decompression fails if the data doesn't fit in the capacity
+                let mut raw_data = Vec::with_capacity(size as usize);
+                zstd::bulk::Decompressor::new()?
+                    .decompress_to_buffer(compressed_data, &mut raw_data)?;
+                Ok(raw_data)
+            }
+        }
+    }
+}
+
+impl Default for CompressionAlgorithm {
+    fn default() -> Self {
+        CompressionAlgorithm::Lz4 {
+            acceleration: DEFAULT_LZ4_ACCELERATION,
+        }
+    }
+}
+
+/// Compresses the raw data stream using the given algorithm
+pub fn compress(
+    raw_data: Vec<u8>,
+    algorithm: CompressionAlgorithm,
+) -> Result<CompressedData, CompressionError> {
     // Start the compression timer
-    let timer = start_compression_operation_timer(COMPRESS);
+    let label = algorithm.get_label();
+    let timer = start_compression_operation_timer(COMPRESS, label);
 
-    // Compress the data
-    let compression_mode = CompressionMode::FAST(ACCELERATION_PARAMETER);
-    let compressed_data = match lz4::block::compress(&raw_data, Some(compression_mode), true) {
-        Ok(compressed_data) => compressed_data,
+    // Compress the data and prepend the algorithm header
+    let mut compressed_data = vec![algorithm.header()];
+    match algorithm.compress(&raw_data) {
+        Ok(data) => compressed_data.extend(data),
         Err(error) => {
-            increment_compression_error(COMPRESS);
+            increment_compression_error(COMPRESS, label);
             return Err(CompressionError(format!(
                 "Failed to compress the data: {:?}",
                 error.to_string()
@@ -54,32 +213,45 @@ pub fn compress(raw_data: Vec<u8>) -> Result<CompressedData, CompressionError> {
 
     // Stop the timer and update the metrics
     let compression_duration = timer.stop_and_record();
-    increment_compression_byte_count(RAW_BYTES, raw_data.len() as u64);
-    increment_compression_byte_count(COMPRESSED_BYTES, compressed_data.len() as u64);
+    increment_compression_byte_count(RAW_BYTES, label, raw_data.len() as u64);
+    increment_compression_byte_count(COMPRESSED_BYTES, label, compressed_data.len() as u64);
 
     // Log the relative data compression statistics
     let relative_data_size = calculate_relative_size(&raw_data, &compressed_data);
     trace!(
-        "Compressed {:?} bytes to {:?} bytes ({:?} %) in {:?} seconds.",
+        "Compressed {:?} bytes to {:?} bytes ({:?} %) using {:?} in {:?} seconds.",
         raw_data.len(),
         compressed_data.len(),
         relative_data_size,
+        label,
         compression_duration
     );
 
     Ok(compressed_data)
 }
 
-/// Decompresses the compressed data stream
-pub fn decompress(compressed_data: &CompressedData) -> Result<Vec<u8>, CompressionError> {
+/// Decompresses the compressed data stream. The algorithm is read from
+/// the header of the compressed data. Fails if the data decompresses to
+/// more than `max_size` bytes.
+pub fn decompress(
+    compressed_data: &CompressedData,
+    max_size: usize,
+) -> Result<Vec<u8>, CompressionError> {
+    // Identify the compression algorithm
+    let (header, data) = compressed_data
+        .split_first()
+        .ok_or_else(|| CompressionError("Failed to decompress the data: missing header!".into()))?;
+    let algorithm = CompressionAlgorithm::from_header(*header)?;
+
     // Start the decompression timer
-    let timer = start_compression_operation_timer(DECOMPRESS);
+    let label = algorithm.get_label();
+    let timer = start_compression_operation_timer(DECOMPRESS, label);
 
     // Decompress the data
-    let raw_data = match lz4::block::decompress(compressed_data, None) {
+    let raw_data = match algorithm.decompress(data, max_size) {
         Ok(raw_data) => raw_data,
         Err(error) => {
-            increment_compression_error(DECOMPRESS);
+            increment_compression_error(DECOMPRESS, label);
             return Err(CompressionError(format!(
                 "Failed to decompress the data: {:?}",
                 error.to_string()
@@ -91,10 +263,11 @@ pub fn decompress(compressed_data: &CompressedData) -> Result<Vec<u8>, Compressi
     let decompression_duration = timer.stop_and_record();
     let relative_data_size = calculate_relative_size(compressed_data, &raw_data);
     trace!(
-        "Decompressed {:?} bytes to {:?} bytes ({:?} %) in {:?} seconds.",
+        "Decompressed {:?} bytes to {:?} bytes ({:?} %) using {:?} in {:?} seconds.",
         compressed_data.len(),
         raw_data.len(),
         relative_data_size,
+        label,
         decompression_duration
     );
 
@@ -103,6 +276,6 @@ pub fn decompress(compressed_data: &CompressedData) -> Result<Vec<u8>, Compressi
 
 /// Calculates the relative size (%) between the input and output after a
 /// compression/decompression operation, i.e., (output / input) * 100.
-fn calculate_relative_size(input: &Vec<u8>, output: &Vec<u8>) -> f64 {
+fn calculate_relative_size(input: &[u8], output: &[u8]) -> f64 {
     (output.len() as f64 / input.len() as f64) * 100.0
 }
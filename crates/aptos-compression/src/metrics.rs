@@ -17,7 +17,7 @@ pub static BYTE_COUNTS: Lazy<IntCounterVec> = Lazy::new(|| {
     register_int_counter_vec!(
         "aptos_compression_byte_count",
         "Counters for tracking the data compression ratio",
-        &["data_type"]
+        &["data_type", "algorithm"]
     )
     .unwrap()
 });
@@ -27,7 +27,7 @@ pub static ERROR_COUNTS: Lazy<IntCounterVec> = Lazy::new(|| {
     register_int_counter_vec!(
         "aptos_compression_error_count",
         "Counters for tracking the data compression errors",
-        &["operation"]
+        &["operation", "algorithm"]
     )
     .unwrap()
 });
@@ -37,26 +37,28 @@ pub static OPERATION_LATENCY: Lazy<HistogramVec> = Lazy::new(|| {
     register_histogram_vec!(
         "aptos_compression_operation_latency",
         "Time it takes to perform a compression/decompression operation",
-        &["operation"]
+        &["operation", "algorithm"]
     )
     .unwrap()
 });
 
-/// Increments the compression byte count based on the given data type
-pub fn increment_compression_byte_count(data_type: &str, byte_count: u64) {
+/// Increments the compression byte count based on the given data type and algorithm
+pub fn increment_compression_byte_count(data_type: &str, algorithm: &str, byte_count: u64) {
     BYTE_COUNTS
-        .with_label_values(&[data_type])
+        .with_label_values(&[data_type, algorithm])
         .inc_by(byte_count)
 }
 
-/// Increments the compression error count based on the given operation
-pub fn increment_compression_error(operation: &str) {
-    ERROR_COUNTS.with_label_values(&[operation]).inc()
+/// Increments the compression error count based on the given operation and algorithm
+pub fn increment_compression_error(operation: &str, algorithm: &str) {
+    ERROR_COUNTS
+        .with_label_values(&[operation, algorithm])
+        .inc()
 }
 
-/// Starts the timer for the compression operation using the label
-pub fn start_compression_operation_timer(operation: &str) -> HistogramTimer {
+/// Starts the timer for the compression operation using the operation and algorithm labels
+pub fn start_compression_operation_timer(operation: &str, algorithm: &str) -> HistogramTimer {
     OPERATION_LATENCY
-        .with_label_values(&[operation])
+        .with_label_values(&[operation, algorithm])
         .start_timer()
 }
@@ -1,6 +1,7 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
+use crate::CompressionAlgorithm;
 use aptos_crypto::ed25519::Ed25519PrivateKey;
 use aptos_crypto::hash::HashValue;
 use aptos_crypto::{PrivateKey, SigningKey, Uniform};
@@ -19,27 +20,108 @@ use serde::Serialize;
 use std::collections::BTreeMap;
 use std::fmt::Debug;
 
+/// The maximum decompressed size used by the tests
+const MAX_DECOMPRESSED_SIZE: usize = 64 * 1024 * 1024;
+
 #[test]
 fn test_basic_compression() {
-    // Test epoch ending ledger infos
-    let epoch_ending_ledger_infos = create_epoch_ending_ledger_infos(0, 999);
-    test_compress_and_decompress(epoch_ending_ledger_infos);
+    for algorithm in [
+        CompressionAlgorithm::default(),
+        CompressionAlgorithm::Lz4 { acceleration: 10 },
+        CompressionAlgorithm::Zstd { level: 1 },
+        CompressionAlgorithm::Zstd { level: 19 },
+    ] {
+        // Test epoch ending ledger infos
+        let epoch_ending_ledger_infos = create_epoch_ending_ledger_infos(0, 999);
+        test_compress_and_decompress(epoch_ending_ledger_infos, algorithm);
+
+        // Test transaction outputs with proof
+        let outputs_with_proof = create_output_list_with_proof(13434, 17000, 19000);
+        test_compress_and_decompress(outputs_with_proof, algorithm);
+
+        // Test transactions with proof
+        let transactions_with_proof = create_transaction_list_with_proof(1000, 1999, 1999, true);
+        test_compress_and_decompress(transactions_with_proof, algorithm);
+    }
+}
 
-    // Test transaction outputs with proof
-    let outputs_with_proof = create_output_list_with_proof(13434, 17000, 19000);
-    test_compress_and_decompress(outputs_with_proof);
+#[test]
+fn test_invalid_header() {
+    // Empty data has no header
+    assert!(crate::decompress(&vec![], MAX_DECOMPRESSED_SIZE).is_err());
+
+    // Unknown algorithm header
+    let mut compressed_bytes =
+        crate::compress(vec![0; 100], CompressionAlgorithm::default()).unwrap();
+    compressed_bytes[0] = u8::MAX;
+    assert!(crate::decompress(&compressed_bytes, MAX_DECOMPRESSED_SIZE).is_err());
+
+    // Data compressed with zstd, but labeled as LZ4
+    let mut compressed_bytes =
+        crate::compress(vec![0; 100], CompressionAlgorithm::Zstd { level: 3 }).unwrap();
+    compressed_bytes[0] = CompressionAlgorithm::default().header();
+    assert!(crate::decompress(&compressed_bytes, MAX_DECOMPRESSED_SIZE).is_err());
+}
 
-    // Test transactions with proof
-    let transactions_with_proof = create_transaction_list_with_proof(1000, 1999, 1999, true);
-    test_compress_and_decompress(transactions_with_proof);
+#[test]
+fn test_decompression_size_limit() {
+    for algorithm in [
+        CompressionAlgorithm::default(),
+        CompressionAlgorithm::Zstd { level: 3 },
+    ] {
+        let compressed_bytes = crate::compress(vec![0; 1000], algorithm).unwrap();
+        assert!(crate::decompress(&compressed_bytes, 999).is_err());
+        let decompressed_bytes = crate::decompress(&compressed_bytes, 1000).unwrap();
+        assert_eq!(decompressed_bytes, vec![0; 1000]);
+    }
+
+    // A forged LZ4 size prefix is rejected before allocating the output
+    let mut compressed_bytes =
+        crate::compress(vec![0; 1000], CompressionAlgorithm::default()).unwrap();
+    compressed_bytes[1..5].copy_from_slice(&i32::MAX.to_le_bytes());
+    assert!(crate::decompress(&compressed_bytes, MAX_DECOMPRESSED_SIZE).is_err());
+    compressed_bytes[1..5].copy_from_slice(&(-1i32).to_le_bytes());
+    assert!(crate::decompress(&compressed_bytes, MAX_DECOMPRESSED_SIZE).is_err());
+
+    // LZ4 data that is too short to hold the size prefix
+    let compressed_bytes = vec![CompressionAlgorithm::default().header(), 0, 0];
+    assert!(crate::decompress(&compressed_bytes, MAX_DECOMPRESSED_SIZE).is_err());
+
+    // zstd frames must have a valid header with the decompressed size
+    let zstd_header = CompressionAlgorithm::Zstd { level: 3 }.header();
+    let compressed_bytes = vec![zstd_header, 1, 2, 3, 4, 5];
+    assert!(crate::decompress(&compressed_bytes, MAX_DECOMPRESSED_SIZE).is_err());
+    let mut compressed_bytes = vec![zstd_header];
+    compressed_bytes.extend(zstd::stream::encode_all(&[0u8; 1000][..], 3).unwrap());
+    assert!(crate::decompress(&compressed_bytes, MAX_DECOMPRESSED_SIZE).is_err());
+}
+
+#[test]
+fn test_limit_for_peers() {
+    let algorithm = CompressionAlgorithm::Zstd { level: 22 };
+    assert_eq!(
+        algorithm.limit_for_peers(),
+        CompressionAlgorithm::Zstd {
+            level: crate::MAX_PEER_ZSTD_LEVEL
+        }
+    );
+    for algorithm in [
+        CompressionAlgorithm::Zstd { level: 1 },
+        CompressionAlgorithm::Lz4 { acceleration: 10 },
+    ] {
+        assert_eq!(algorithm.limit_for_peers(), algorithm);
+    }
 }
 
 /// Ensures that the given object can be compressed and decompressed successfully
 /// when BCS encoded.
-fn test_compress_and_decompress<T: Debug + DeserializeOwned + PartialEq + Serialize>(object: T) {
+fn test_compress_and_decompress<T: Debug + DeserializeOwned + PartialEq + Serialize>(
+    object: T,
+    algorithm: CompressionAlgorithm,
+) {
     let bcs_encoded_bytes = bcs::to_bytes(&object).unwrap();
-    let compressed_bytes = crate::compress(bcs_encoded_bytes).unwrap();
-    let decompressed_bytes = crate::decompress(&compressed_bytes).unwrap();
+    let compressed_bytes = crate::compress(bcs_encoded_bytes, algorithm).unwrap();
+    let decompressed_bytes = crate::decompress(&compressed_bytes, MAX_DECOMPRESSED_SIZE).unwrap();
     let decoded_object = bcs::from_bytes::<T>(&decompressed_bytes).unwrap();
 
     assert_eq!(object, decoded_object);
@@ -52,6 +52,9 @@ pub enum SecurityEvent {
     /// A received block is invalid
     InvalidRetrievedBlock,
 
+    /// A batch of transactions retrieved from the quorum store of a peer is invalid
+    InvalidRetrievedBatch,
+
     /// A block being committed or executed is invalid
     InvalidBlock,
 
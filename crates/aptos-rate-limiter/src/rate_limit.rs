@@ -375,6 +375,16 @@ impl Bucket {
         self.allowed_in_period = self.allowed_in_period.saturating_sub(new_tokens);
         self.add_tokens(new_tokens);
     }
+
+    /// Tells us if the bucket has refilled all of its tokens, i.e., it can be
+    /// replaced by a new bucket without changing what it allows through
+    pub fn is_full(&mut self) -> bool {
+        if !self.enabled {
+            return true;
+        }
+        self.refill();
+        self.tokens >= self.size
+    }
 }
 
 #[cfg(test)]
@@ -474,6 +484,24 @@ mod tests {
         bucket.acquire_tokens(1).unwrap();
     }
 
+    #[test]
+    fn test_is_full() {
+        let bucket_size = 5;
+        let bucket_rate = 5;
+        let key = "Key";
+        let rate_limiter = TokenBucketRateLimiter::test(bucket_size, bucket_rate);
+
+        let bucket_arc = rate_limiter.bucket(key);
+        let mut bucket = bucket_arc.lock();
+        assert!(bucket.is_full());
+
+        // Any used token keeps the bucket from being full, until the next refill
+        bucket.acquire_tokens(1).unwrap();
+        assert!(!bucket.is_full());
+        sleep(bucket.time_of_next_refill().duration_since(Instant::now()));
+        assert!(bucket.is_full());
+    }
+
     #[test]
     fn test_time_checks() {
         let bucket_size = 5;
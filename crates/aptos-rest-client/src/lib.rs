@@ -5,7 +5,9 @@ use anyhow::{anyhow, Result};
 pub use aptos_api_types::{
     self, IndexResponse, MoveModuleBytecode, PendingTransaction, Transaction,
 };
-use aptos_api_types::{mime_types::BCS_SIGNED_TRANSACTION as BCS_CONTENT_TYPE, BlockInfo};
+use aptos_api_types::{
+    mime_types::BCS_SIGNED_TRANSACTION as BCS_CONTENT_TYPE, BlockInfo, GasEstimation, ViewRequest,
+};
 use aptos_crypto::HashValue;
 use aptos_types::{
     account_address::AccountAddress, account_config::CORE_CODE_ADDRESS,
@@ -367,6 +369,29 @@ impl Client {
         self.json(response).await
     }
 
+    /// Executes a public Move function read-only, returning its return values as JSON.
+    /// View functions are only served by the v1 API.
+    pub async fn view(
+        &self,
+        request: &ViewRequest,
+        version: Option<u64>,
+    ) -> Result<Response<Vec<Value>>> {
+        let url = self.base_url.join("v1/view")?;
+        let mut request_builder = self.inner.post(url).json(request);
+        if let Some(version) = version {
+            request_builder = request_builder.query(&[("ledger_version", version.to_string())]);
+        }
+
+        let response = request_builder.send().await?;
+        self.json(response).await
+    }
+
+    pub async fn estimate_gas_price(&self) -> Result<Response<GasEstimation>> {
+        let url = self.base_url.join("v1/estimate_gas_price")?;
+        let response = self.inner.get(url).send().await?;
+        self.json(response).await
+    }
+
     pub async fn get_account(&self, address: AccountAddress) -> Result<Response<Account>> {
         let url = self.base_url.join(&format!("accounts/{}", address))?;
         let response = self.inner.get(url).send().await?;
@@ -168,6 +168,9 @@ impl ServerArgs for OfflineArgs {
             tls_cert_path: self.tls_cert_path.clone(),
             tls_key_path: self.tls_key_path.clone(),
             content_length_limit: self.content_length_limit,
+            view_gas_limit: None,
+            max_concurrent_streams: None,
+            mempool_inspection_enabled: false,
         }
     }
 
@@ -905,16 +905,15 @@ impl FaucetOptions {
 }
 
 pub const DEFAULT_MAX_GAS: u64 = 1000;
-pub const DEFAULT_GAS_UNIT_PRICE: u64 = 1;
 
 /// Gas price options for manipulating how to prioritize transactions
 #[derive(Debug, Eq, Parser, PartialEq)]
 pub struct GasOptions {
     /// Amount to increase gas bid by for a transaction
     ///
-    /// Defaults to 1 coin per gas unit
-    #[clap(long, default_value_t = DEFAULT_GAS_UNIT_PRICE)]
-    pub gas_unit_price: u64,
+    /// Defaults to the median gas unit price estimated by the node
+    #[clap(long)]
+    pub gas_unit_price: Option<u64>,
     /// Maximum gas to be used to send a transaction
     ///
     /// Defaults to 1000 gas units
@@ -925,7 +924,7 @@ pub struct GasOptions {
 impl Default for GasOptions {
     fn default() -> Self {
         GasOptions {
-            gas_unit_price: DEFAULT_GAS_UNIT_PRICE,
+            gas_unit_price: None,
             max_gas: DEFAULT_MAX_GAS,
         }
     }
@@ -993,9 +992,22 @@ impl TransactionOptions {
         // Get sequence number for account
         let sequence_number = get_sequence_number(&client, sender_address).await?;
 
+        // Use the node's gas price estimation unless a price was given
+        let gas_unit_price = if let Some(gas_unit_price) = self.gas_options.gas_unit_price {
+            gas_unit_price
+        } else {
+            client
+                .estimate_gas_price()
+                .await
+                .map_err(|err| CliError::ApiError(err.to_string()))?
+                .into_inner()
+                .median_gas_unit_price
+                .0
+        };
+
         // Sign and submit transaction
         let transaction_factory = TransactionFactory::new(chain_id(&client).await?)
-            .with_gas_unit_price(self.gas_options.gas_unit_price)
+            .with_gas_unit_price(gas_unit_price)
             .with_max_gas_amount(self.gas_options.max_gas);
         let sender_account = &mut LocalAccount::new(sender_address, sender_key, sequence_number);
         let transaction =
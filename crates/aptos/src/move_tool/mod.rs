@@ -10,14 +10,14 @@ use crate::{
     common::{
         types::{
             load_account_arg, AccountAddressWrapper, CliError, CliTypedResult, MovePackageDir,
-            PromptOptions, TransactionOptions, TransactionSummary,
+            ProfileOptions, PromptOptions, RestOptions, TransactionOptions, TransactionSummary,
         },
         utils::check_if_file_exists,
     },
     CliCommand, CliResult,
 };
 use aptos_module_verifier::module_init::verify_module_init_function;
-use aptos_rest_client::aptos_api_types::MoveType;
+use aptos_rest_client::aptos_api_types::{MoveType, ScriptFunctionId, ViewRequest};
 use aptos_types::transaction::{ModuleBundle, ScriptFunction, TransactionPayload};
 use aptos_vm;
 use aptos_vm::move_vm_ext::UpgradePolicy;
@@ -37,6 +37,7 @@ use move_deps::{
     move_prover,
     move_unit_test::UnitTestingConfig,
 };
+use serde_json::json;
 use std::{
     collections::BTreeMap,
     convert::TryFrom,
@@ -56,6 +57,7 @@ pub enum MoveTool {
     Run(RunFunction),
     Test(TestPackage),
     Prove(ProvePackage),
+    View(ViewFunction),
 }
 
 impl MoveTool {
@@ -67,6 +69,7 @@ impl MoveTool {
             MoveTool::Run(tool) => tool.execute_serialized().await,
             MoveTool::Test(tool) => tool.execute_serialized().await,
             MoveTool::Prove(tool) => tool.execute_serialized().await,
+            MoveTool::View(tool) => tool.execute_serialized().await,
         }
     }
 }
@@ -395,6 +398,69 @@ impl CliCommand<TransactionSummary> for RunFunction {
     }
 }
 
+/// Run a view function
+///
+/// Executes a public Move function read-only against the ledger, without a signer, and
+/// returns its return values.  Nothing is submitted to the chain.
+#[derive(Parser)]
+pub struct ViewFunction {
+    #[clap(flatten)]
+    rest_options: RestOptions,
+    #[clap(flatten)]
+    profile_options: ProfileOptions,
+    /// Function name as `<ADDRESS>::<MODULE_ID>::<FUNCTION_NAME>`
+    ///
+    /// Example: `0x1::coin::balance`
+    #[clap(long, parse(try_from_str = parse_function_name))]
+    function_id: FunctionId,
+    /// Arguments combined with their type separated by spaces.
+    ///
+    /// Example: `address:0x1 u64:5`
+    #[clap(long, multiple_values = true)]
+    args: Vec<ArgWithType>,
+    /// TypeTag arguments separated by spaces.
+    ///
+    /// Example: `0x1::aptos_coin::AptosCoin`
+    #[clap(long, multiple_values = true)]
+    type_args: Vec<MoveType>,
+    /// Ledger version to run the function against
+    ///
+    /// Defaults to the latest ledger version
+    #[clap(long)]
+    ledger_version: Option<u64>,
+}
+
+#[async_trait]
+impl CliCommand<Vec<serde_json::Value>> for ViewFunction {
+    fn command_name(&self) -> &'static str {
+        "ViewFunction"
+    }
+
+    async fn execute(self) -> CliTypedResult<Vec<serde_json::Value>> {
+        let arguments = self
+            .args
+            .iter()
+            .map(|arg_with_type| arg_with_type.to_json())
+            .collect::<CliTypedResult<Vec<_>>>()?;
+
+        let request = ViewRequest {
+            function: ScriptFunctionId {
+                module: self.function_id.module_id.clone().into(),
+                name: self.function_id.function_id.clone().into(),
+            },
+            type_arguments: self.type_args.clone(),
+            arguments,
+        };
+
+        let client = self.rest_options.client(&self.profile_options.profile)?;
+        Ok(client
+            .view(&request, self.ledger_version)
+            .await
+            .map_err(|err| CliError::ApiError(err.to_string()))?
+            .into_inner())
+    }
+}
+
 #[derive(Clone, Debug)]
 enum FunctionArgType {
     Address,
@@ -435,6 +501,33 @@ impl FunctionArgType {
         }
         .map_err(|err| CliError::BCS("arg", err))
     }
+
+    /// Parses the argument into the JSON representation used by the REST API
+    fn parse_json_arg(&self, arg: &str) -> CliTypedResult<serde_json::Value> {
+        Ok(match self {
+            FunctionArgType::Address => json!(load_account_arg(arg)
+                .map_err(|err| CliError::UnableToParse("address", err.to_string()))?
+                .to_hex_literal()),
+            FunctionArgType::Bool => json!(bool::from_str(arg)
+                .map_err(|err| CliError::UnableToParse("bool", err.to_string()))?),
+            FunctionArgType::Hex => {
+                let bytes = hex::decode(arg)
+                    .map_err(|err| CliError::UnableToParse("hex", err.to_string()))?;
+                json!(format!("0x{}", hex::encode(bytes)))
+            }
+            FunctionArgType::String => json!(arg),
+            FunctionArgType::U8 => {
+                json!(u8::from_str(arg)
+                    .map_err(|err| CliError::UnableToParse("u8", err.to_string()))?)
+            }
+            FunctionArgType::U64 => json!(u64::from_str(arg)
+                .map_err(|err| CliError::UnableToParse("u64", err.to_string()))?
+                .to_string()),
+            FunctionArgType::U128 => json!(u128::from_str(arg)
+                .map_err(|err| CliError::UnableToParse("u128", err.to_string()))?
+                .to_string()),
+        })
+    }
 }
 
 impl FromStr for FunctionArgType {
@@ -455,10 +548,18 @@ impl FromStr for FunctionArgType {
 
 /// A parseable arg with a type separated by a colon
 pub struct ArgWithType {
-    _ty: FunctionArgType,
+    ty: FunctionArgType,
+    raw: String,
     arg: Vec<u8>,
 }
 
+impl ArgWithType {
+    /// Converts the argument into the JSON representation used by the REST API
+    fn to_json(&self) -> CliTypedResult<serde_json::Value> {
+        self.ty.parse_json_arg(&self.raw)
+    }
+}
+
 impl FromStr for ArgWithType {
     type Err = CliError;
 
@@ -471,10 +572,10 @@ impl FromStr for ArgWithType {
         }
 
         let ty = FunctionArgType::from_str(parts.first().unwrap())?;
-        let arg = parts.last().unwrap();
-        let arg = ty.parse_arg(arg)?;
+        let raw = parts.last().unwrap().to_string();
+        let arg = ty.parse_arg(&raw)?;
 
-        Ok(ArgWithType { _ty: ty, arg })
+        Ok(ArgWithType { ty, raw, arg })
     }
 }
 
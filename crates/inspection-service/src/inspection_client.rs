@@ -1,9 +1,10 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
-use anyhow::Result;
+use anyhow::{ensure, Result};
 use reqwest::Url;
 use std::collections::HashMap;
+use storage_interface::{PrunerIndex, PrunerProgress};
 
 pub struct InspectionClient {
     client: reqwest::Client,
@@ -78,4 +79,51 @@ impl InspectionClient {
             })
             .collect()
     }
+
+    pub async fn get_pruner_progress(&self) -> Result<Vec<PrunerProgress>> {
+        let mut url = self.url.clone();
+        url.set_path("pruner");
+        let response = self.client.get(url).send().await?;
+        ensure!(
+            response.status().is_success(),
+            "Failed to get pruner progress: {}",
+            response.text().await?
+        );
+
+        Ok(response.json().await?)
+    }
+
+    pub async fn set_prune_window(&self, pruner_index: PrunerIndex, window: u64) -> Result<()> {
+        self.send_pruner_command(pruner_index, "prune_window", window)
+            .await
+    }
+
+    pub async fn prune_to_version(&self, pruner_index: PrunerIndex, version: u64) -> Result<()> {
+        self.send_pruner_command(pruner_index, "prune_to_version", version)
+            .await
+    }
+
+    async fn send_pruner_command(
+        &self,
+        pruner_index: PrunerIndex,
+        command: &str,
+        version: u64,
+    ) -> Result<()> {
+        let mut url = self.url.clone();
+        url.set_path(&format!(
+            "pruner/{}/{}/{}",
+            pruner_index.name(),
+            command,
+            version
+        ));
+        let response = self.client.post(url).send().await?;
+        ensure!(
+            response.status().is_success(),
+            "Pruner command {} failed: {}",
+            command,
+            response.text().await?
+        );
+
+        Ok(())
+    }
 }
@@ -4,9 +4,11 @@
 use crate::{gather_metrics, json_encoder::JsonEncoder, NUM_METRICS};
 use aptos_config::config::NodeConfig;
 use hyper::{
+    server::conn::AddrStream,
     service::{make_service_fn, service_fn},
     Body, Method, Request, Response, Server, StatusCode,
 };
+use once_cell::sync::OnceCell;
 use prometheus::{
     proto::{MetricFamily, MetricType},
     Encoder, TextEncoder,
@@ -14,9 +16,12 @@ use prometheus::{
 use std::{
     collections::HashMap,
     convert::Infallible,
-    net::{SocketAddr, ToSocketAddrs},
+    net::{IpAddr, SocketAddr, ToSocketAddrs},
+    str::FromStr,
+    sync::Arc,
     thread,
 };
+use storage_interface::{DbReaderWriter, PrunerIndex};
 use tokio::runtime;
 
 // The message displayed when the endpoint is disabled.
@@ -81,9 +86,58 @@ pub fn get_all_metrics() -> HashMap<String, String> {
     get_metrics(all_metric_families)
 }
 
-async fn serve_requests(
+/// Returns true if the address is a loopback address, including the IPv4 loopback addresses
+/// mapped to IPv6 (as seen by a dual stack listener).
+fn is_localhost(addr: &SocketAddr) -> bool {
+    match addr.ip() {
+        IpAddr::V4(ip) => ip.is_loopback(),
+        IpAddr::V6(ip) => {
+            ip.is_loopback()
+                || matches!(
+                    ip.octets(),
+                    [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0xff, 0xff, 127, ..]
+                )
+        }
+    }
+}
+
+/// Serves the pruner endpoints:
+/// - GET /pruner: the progress of all the pruners.
+/// - POST /pruner/<pruner_name>/prune_window/<window>: changes the prune window of a pruner.
+/// - POST /pruner/<pruner_name>/prune_to_version/<version>: prunes everything before the version.
+///
+/// The POST endpoints are admin commands: they are only served to clients on localhost.
+fn serve_pruner_request(
+    method: &Method,
+    path: &str,
+    db: &DbReaderWriter,
+) -> anyhow::Result<String> {
+    let segments: Vec<_> = path.trim_matches('/').split('/').collect();
+    match (method, segments.as_slice()) {
+        (&Method::GET, ["pruner"]) => {
+            let progress = db.reader.get_pruner_progress()?;
+            Ok(serde_json::to_string(&progress)?)
+        }
+        (&Method::POST, ["pruner", pruner_name, command, version]) => {
+            let pruner_index = PrunerIndex::from_str(pruner_name)?;
+            let version = u64::from_str(version)?;
+            match *command {
+                "prune_window" => db.writer.set_prune_window(pruner_index, version)?,
+                "prune_to_version" => db.writer.prune_to_version(pruner_index, version)?,
+                _ => anyhow::bail!("Unknown pruner command: {}", command),
+            }
+            // Pruning happens in the background, its progress is found at GET /pruner.
+            Ok("OK".to_string())
+        }
+        _ => anyhow::bail!("Unknown pruner request: {} {}", method, path),
+    }
+}
+
+pub(crate) async fn serve_requests(
     req: Request<Body>,
+    remote_addr: SocketAddr,
     node_config: NodeConfig,
+    db: Arc<OnceCell<DbReaderWriter>>,
 ) -> Result<Response<Body>, hyper::Error> {
     let mut resp = Response::new(Body::empty());
     match (req.method(), req.uri().path()) {
@@ -128,6 +182,26 @@ async fn serve_requests(
                 *resp.body_mut() = Body::from(DISABLED_ENDPOINT_MESSAGE);
             }
         }
+        // Exposes the DB pruners' progress and admin commands
+        (_, path) if path == "/pruner" || path.starts_with("/pruner/") => {
+            if !node_config.inspection_service.expose_pruner {
+                *resp.body_mut() = Body::from(DISABLED_ENDPOINT_MESSAGE);
+            } else if req.method() != Method::GET && !is_localhost(&remote_addr) {
+                *resp.status_mut() = StatusCode::FORBIDDEN;
+                *resp.body_mut() = Body::from("Pruner commands are only accepted from localhost.");
+            } else if let Some(db) = db.get() {
+                match serve_pruner_request(req.method(), path, db) {
+                    Ok(body) => *resp.body_mut() = Body::from(body),
+                    Err(e) => {
+                        *resp.status_mut() = StatusCode::BAD_REQUEST;
+                        *resp.body_mut() = Body::from(format!("{:#}", e));
+                    }
+                }
+            } else {
+                *resp.status_mut() = StatusCode::SERVICE_UNAVAILABLE;
+                *resp.body_mut() = Body::from("The DB isn't open yet.");
+            }
+        }
         _ => {
             *resp.status_mut() = StatusCode::NOT_FOUND;
         }
@@ -136,7 +210,9 @@ async fn serve_requests(
     Ok(resp)
 }
 
-pub fn start_inspection_service(node_config: NodeConfig) {
+/// Starts the inspection service. `db` is expected to be set once the DB is open, the endpoints
+/// depending on it are unavailable till then.
+pub fn start_inspection_service(node_config: NodeConfig, db: Arc<OnceCell<DbReaderWriter>>) {
     // Fetch the service port and address
     let service_port = node_config.inspection_service.port;
     let service_address = node_config.inspection_service.address.clone();
@@ -155,11 +231,13 @@ pub fn start_inspection_service(node_config: NodeConfig) {
 
     // Spawn the server
     thread::spawn(move || {
-        let make_service = make_service_fn(move |_conn| {
+        let make_service = make_service_fn(move |conn: &AddrStream| {
+            let remote_addr = conn.remote_addr();
             let node_config = node_config.clone();
+            let db = db.clone();
             async move {
                 Ok::<_, Infallible>(service_fn(move |request| {
-                    serve_requests(request, node_config.clone())
+                    serve_requests(request, remote_addr, node_config.clone(), db.clone())
                 }))
             }
         });
@@ -0,0 +1,61 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::inspection_service::serve_requests;
+use aptos_config::config::NodeConfig;
+use hyper::{Body, Method, Request, Response, StatusCode};
+use once_cell::sync::OnceCell;
+use std::sync::Arc;
+
+const PRUNE_WINDOW_PATH: &str = "/pruner/ledger_pruner/prune_window/1000000";
+
+async fn send_request(method: Method, path: &str, remote_addr: &str) -> Response<Body> {
+    let mut node_config = NodeConfig::default();
+    node_config.inspection_service.expose_pruner = true;
+    let request = Request::builder()
+        .method(method)
+        .uri(path)
+        .body(Body::empty())
+        .unwrap();
+    // The DB is never opened, so the requests let through find it unavailable
+    serve_requests(
+        request,
+        remote_addr.parse().unwrap(),
+        node_config,
+        Arc::new(OnceCell::new()),
+    )
+    .await
+    .unwrap()
+}
+
+#[tokio::test]
+async fn test_pruner_commands_only_from_localhost() {
+    for remote_addr in ["127.0.0.1:1234", "[::1]:1234", "[::ffff:127.0.0.1]:1234"] {
+        assert_eq!(
+            send_request(Method::POST, PRUNE_WINDOW_PATH, remote_addr)
+                .await
+                .status(),
+            StatusCode::SERVICE_UNAVAILABLE
+        );
+    }
+    for remote_addr in [
+        "10.0.0.1:1234",
+        "[::ffff:10.0.0.1]:1234",
+        "[2001:db8::1]:1234",
+    ] {
+        assert_eq!(
+            send_request(Method::POST, PRUNE_WINDOW_PATH, remote_addr)
+                .await
+                .status(),
+            StatusCode::FORBIDDEN
+        );
+    }
+
+    // The progress can still be read remotely
+    assert_eq!(
+        send_request(Method::GET, "/pruner", "10.0.0.1:1234")
+            .await
+            .status(),
+        StatusCode::SERVICE_UNAVAILABLE
+    );
+}
@@ -1,4 +1,5 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
+mod inspection_service_test;
 mod lib_test;
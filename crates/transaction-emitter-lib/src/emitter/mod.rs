@@ -86,7 +86,7 @@ pub struct EmitJobRequest {
     accounts_per_client: usize,
     workers_per_endpoint: Option<usize>,
     thread_params: EmitThreadParams,
-    gas_price: u64,
+    gas_price: Option<u64>,
     invalid_transaction_ratio: usize,
     pub duration: Duration,
     vasp: bool,
@@ -100,7 +100,7 @@ impl Default for EmitJobRequest {
             accounts_per_client: 15,
             workers_per_endpoint: None,
             thread_params: EmitThreadParams::default(),
-            gas_price: 0,
+            gas_price: None,
             invalid_transaction_ratio: 0,
             duration: Duration::from_secs(300),
             vasp: false,
@@ -135,7 +135,7 @@ impl EmitJobRequest {
     }
 
     pub fn gas_price(mut self, gas_price: u64) -> Self {
-        self.gas_price = gas_price;
+        self.gas_price = Some(gas_price);
         self
     }
 
@@ -245,6 +245,17 @@ impl<'t> TxnEmitter<'t> {
     }
 
     pub async fn start_job(&mut self, req: EmitJobRequest) -> Result<EmitJob> {
+        let gas_price = match req.gas_price {
+            Some(gas_price) => gas_price,
+            None => {
+                let estimation = req.rest_clients[0].estimate_gas_price().await?.into_inner();
+                info!(
+                    "Using estimated gas price {}",
+                    estimation.median_gas_unit_price
+                );
+                estimation.median_gas_unit_price.0
+            }
+        };
         let workers_per_endpoint = match req.workers_per_endpoint {
             Some(x) => x,
             None => {
@@ -321,7 +332,7 @@ impl<'t> TxnEmitter<'t> {
                     req.invalid_transaction_ratio,
                     self.from_rng(),
                 );
-                let join_handle = tokio_handle.spawn(worker.run(req.gas_price).boxed());
+                let join_handle = tokio_handle.spawn(worker.run(gas_price).boxed());
                 workers.push(Worker { join_handle });
             }
         }
@@ -54,8 +54,7 @@ pub async fn emit_transactions_with_cluster(
             .thread_params(thread_params)
             .invalid_transaction_ratio(args.invalid_tx)
             .transaction_type(args.transaction_type)
-            .duration(duration)
-            .gas_price(1);
+            .duration(duration);
     if let Some(workers_per_endpoint) = args.workers_per_ac {
         emit_job_request = emit_job_request.workers_per_endpoint(workers_per_endpoint);
     }
@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+drop table if exists coin_activities;
+drop table if exists coin_balances;
+drop table if exists coin_infos;
@@ -0,0 +1,50 @@
+-- Your SQL goes here
+
+-- Metadata of each coin type, from `0x1::coin::CoinInfo`
+CREATE TABLE coin_infos
+(
+    coin_type VARCHAR NOT NULL,
+    creator_address VARCHAR NOT NULL,
+    name VARCHAR NOT NULL,
+    symbol VARCHAR NOT NULL,
+    decimals NUMERIC NOT NULL,
+    supply NUMERIC,
+    transaction_version_created BIGINT NOT NULL,
+    last_transaction_version BIGINT NOT NULL,
+
+    inserted_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (coin_type)
+);
+
+-- Balance snapshots of `0x1::coin::CoinStore`, one per account and coin type changed in a version
+CREATE TABLE coin_balances
+(
+    transaction_version BIGINT NOT NULL,
+    owner_address VARCHAR NOT NULL,
+    coin_type VARCHAR NOT NULL,
+    amount NUMERIC NOT NULL,
+    transaction_timestamp TIMESTAMP NOT NULL,
+
+    inserted_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (transaction_version, owner_address, coin_type)
+);
+CREATE INDEX cb_owner_coin_type_index ON coin_balances (owner_address, coin_type);
+
+-- Deposits and withdrawals, from `0x1::coin::DepositEvent` and `0x1::coin::WithdrawEvent`
+CREATE TABLE coin_activities
+(
+    event_key VARCHAR NOT NULL,
+    sequence_number BIGINT NOT NULL,
+    transaction_version BIGINT NOT NULL,
+    owner_address VARCHAR NOT NULL,
+    coin_type VARCHAR NOT NULL,
+    amount NUMERIC NOT NULL,
+    activity_type VARCHAR NOT NULL,
+    counterparty_address VARCHAR,
+    transaction_timestamp TIMESTAMP NOT NULL,
+
+    inserted_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (event_key, sequence_number)
+);
+CREATE INDEX ca_owner_coin_type_index ON coin_activities (owner_address, coin_type);
+CREATE INDEX ca_transaction_version_index ON coin_activities (transaction_version);
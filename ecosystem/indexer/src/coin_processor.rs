@@ -0,0 +1,362 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    database::{execute_with_better_error, PgDbPool, PgPoolConnection},
+    indexer::{
+        errors::TransactionProcessingError, processing_result::ProcessingResult,
+        transaction_processor::TransactionProcessor,
+    },
+    models::{
+        coin::{CoinEvent, CoinResource, DEPOSIT_EVENT_TYPE},
+        coin_activity::CoinActivity,
+        coin_balance::CoinBalance,
+        coin_info::CoinInfo,
+        transactions::TransactionModel,
+    },
+    schema,
+};
+use aptos_rest_client::Transaction;
+use async_trait::async_trait;
+use diesel::{Connection, ExpressionMethods, QueryDsl, RunQueryDsl};
+use futures::future::Either;
+use std::{collections::HashMap, fmt::Debug, sync::Arc};
+
+/// Postgres allows at most 65535 bind parameters per statement, and the widest table has 10 columns
+const MAX_ROWS_PER_INSERT: usize = 5_000;
+
+pub struct CoinTransactionProcessor {
+    connection_pool: PgDbPool,
+}
+
+impl CoinTransactionProcessor {
+    pub fn new(connection_pool: PgDbPool) -> Self {
+        Self { connection_pool }
+    }
+}
+
+impl Debug for CoinTransactionProcessor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let state = &self.connection_pool.state();
+        write!(
+            f,
+            "CoinTransactionProcessor {{ connections: {:?}  idle_connections: {:?} }}",
+            state.connections, state.idle_connections
+        )
+    }
+}
+
+/// Extracts the coin infos, balances and activities from the transaction. Deposit and
+/// withdraw events don't carry the coin type, so they are matched to the `CoinStore`s
+/// written by the same transaction through their event handles. Resources and events
+/// that can't be parsed are skipped, so they don't stop the processor.
+fn get_coin_data(
+    transaction: &Transaction,
+) -> (Vec<CoinInfo>, Vec<CoinBalance>, Vec<CoinActivity>) {
+    let mut coin_infos = vec![];
+    let mut coin_balances = vec![];
+    let mut coin_activities = vec![];
+
+    let transaction_info = match transaction.transaction_info() {
+        Ok(transaction_info) => transaction_info,
+        Err(_) => return (coin_infos, coin_balances, coin_activities),
+    };
+    let version = transaction.version().unwrap_or(0) as i64;
+    // time is in microseconds, but chronos wants seconds
+    let timestamp =
+        chrono::NaiveDateTime::from_timestamp(transaction.timestamp() as i64 / 1000000, 0);
+    let (_, maybe_details_model, maybe_events, _) = TransactionModel::from_transaction(transaction);
+
+    // Event key -> (owner address, coin type) of the coin stores written by the transaction
+    let mut coin_store_event_keys = HashMap::new();
+    for write_set_change in &transaction_info.changes {
+        let coin_resource =
+            CoinResource::from_write_set_change(write_set_change).unwrap_or_else(|error| {
+                aptos_logger::warn!("Skipping coin resource at version {}: {:#}", version, error);
+                None
+            });
+        match coin_resource {
+            Some(CoinResource::CoinStore {
+                owner_address,
+                coin_type,
+                resource,
+            }) => {
+                for event_handle in [&resource.deposit_events, &resource.withdraw_events] {
+                    coin_store_event_keys.insert(
+                        event_handle.guid.to_event_key(),
+                        (owner_address.clone(), coin_type.clone()),
+                    );
+                }
+                coin_balances.push(CoinBalance::new(
+                    version,
+                    owner_address,
+                    coin_type,
+                    resource.coin.value,
+                    timestamp,
+                ));
+            }
+            Some(CoinResource::CoinInfo {
+                creator_address,
+                coin_type,
+                resource,
+            }) => {
+                coin_infos.push(CoinInfo::from_resource(
+                    coin_type,
+                    creator_address,
+                    resource,
+                    version,
+                ));
+            }
+            None => {}
+        }
+    }
+
+    for event in maybe_events.unwrap_or_default() {
+        let coin_event = match CoinEvent::from_event(&event) {
+            Ok(Some(coin_event)) => coin_event,
+            Ok(None) => continue,
+            Err(error) => {
+                aptos_logger::warn!("Skipping coin event at version {}: {:#}", version, error);
+                continue;
+            }
+        };
+        if let Some((owner_address, coin_type)) = coin_store_event_keys.get(&event.key) {
+            coin_activities.push(CoinActivity {
+                event_key: event.key.clone(),
+                sequence_number: event.sequence_number,
+                transaction_version: version,
+                owner_address: owner_address.clone(),
+                coin_type: coin_type.clone(),
+                amount: coin_event.amount().clone(),
+                activity_type: coin_event.type_str().to_string(),
+                counterparty_address: None,
+                transaction_timestamp: timestamp,
+                inserted_at: chrono::Utc::now().naive_utc(),
+            });
+        }
+    }
+
+    let sender = match maybe_details_model {
+        Some(Either::Left(user_txn)) => Some(user_txn.sender),
+        _ => None,
+    };
+    set_counterparties(&mut coin_activities, sender.as_deref());
+
+    (coin_infos, coin_balances, coin_activities)
+}
+
+/// A transfer shows up as a withdrawal and a deposit of the same amount of the same coin
+/// between two accounts, so these are paired up as each other's counterparties. Deposits
+/// that can't be paired are attributed to the sender of the transaction (if it isn't the owner).
+fn set_counterparties(coin_activities: &mut [CoinActivity], sender: Option<&str>) {
+    let mut remaining = &mut coin_activities[..];
+    while let Some((activity, rest)) = remaining.split_first_mut() {
+        if activity.counterparty_address.is_none() {
+            if let Some(other) = rest.iter_mut().find(|other| {
+                other.counterparty_address.is_none()
+                    && other.activity_type != activity.activity_type
+                    && other.coin_type == activity.coin_type
+                    && other.amount == activity.amount
+                    && other.owner_address != activity.owner_address
+            }) {
+                activity.counterparty_address = Some(other.owner_address.clone());
+                other.counterparty_address = Some(activity.owner_address.clone());
+            }
+        }
+        remaining = rest;
+    }
+
+    for activity in coin_activities.iter_mut() {
+        if activity.counterparty_address.is_none() && activity.activity_type == DEPOSIT_EVENT_TYPE {
+            activity.counterparty_address = sender
+                .filter(|sender| *sender != activity.owner_address)
+                .map(|sender| sender.to_string());
+        }
+    }
+}
+
+fn insert_coin_info(conn: &PgPoolConnection, coin_info: &CoinInfo) {
+    use schema::coin_infos::dsl::*;
+
+    execute_with_better_error(
+        conn,
+        diesel::insert_into(schema::coin_infos::table)
+            .values(coin_info)
+            .on_conflict_do_nothing(),
+    )
+    .expect("Error inserting row into coin_infos");
+
+    // The metadata can't change after creation, but the supply changes with every mint and burn.
+    // Versions may be reprocessed (e.g., by a repair), so an older version mustn't overwrite it.
+    // Diesel 1.4 upserts can't have a WHERE clause, hence the separate update.
+    let query = diesel::update(
+        coin_infos
+            .find(&coin_info.coin_type)
+            .filter(last_transaction_version.le(coin_info.last_transaction_version)),
+    )
+    .set((
+        supply.eq(&coin_info.supply),
+        last_transaction_version.eq(coin_info.last_transaction_version),
+    ));
+    query
+        .execute(conn)
+        .expect("Error updating row in coin_infos");
+}
+
+fn insert_coin_balances(conn: &PgPoolConnection, coin_balances: &[CoinBalance]) {
+    execute_with_better_error(
+        conn,
+        diesel::insert_into(schema::coin_balances::table)
+            .values(coin_balances)
+            .on_conflict_do_nothing(),
+    )
+    .expect("Error inserting row into coin_balances");
+}
+
+fn insert_coin_activities(conn: &PgPoolConnection, coin_activities: &[CoinActivity]) {
+    execute_with_better_error(
+        conn,
+        diesel::insert_into(schema::coin_activities::table)
+            .values(coin_activities)
+            .on_conflict_do_nothing(),
+    )
+    .expect("Error inserting row into coin_activities");
+}
+
+#[async_trait]
+impl TransactionProcessor for CoinTransactionProcessor {
+    fn name(&self) -> &'static str {
+        "coin_processor"
+    }
+
+    async fn process_transactions(
+        &self,
+        transactions: Vec<Arc<Transaction>>,
+        start_version: u64,
+        end_version: u64,
+    ) -> Result<ProcessingResult, TransactionProcessingError> {
+        let mut coin_infos = vec![];
+        let mut coin_balances = vec![];
+        let mut coin_activities = vec![];
+        for transaction in &transactions {
+            let (txn_coin_infos, txn_coin_balances, txn_coin_activities) =
+                get_coin_data(transaction);
+            coin_infos.extend(txn_coin_infos);
+            coin_balances.extend(txn_coin_balances);
+            coin_activities.extend(txn_coin_activities);
+        }
+
+        let conn = self.get_conn();
+        let tx_result = conn.transaction::<(), diesel::result::Error, _>(|| {
+            // The same coin info may be written several times in the batch, and a single
+            // upsert can't touch the same row twice, so they're inserted one by one
+            for coin_info in &coin_infos {
+                insert_coin_info(&conn, coin_info);
+            }
+            for chunk in coin_balances.chunks(MAX_ROWS_PER_INSERT) {
+                insert_coin_balances(&conn, chunk);
+            }
+            for chunk in coin_activities.chunks(MAX_ROWS_PER_INSERT) {
+                insert_coin_activities(&conn, chunk);
+            }
+            Ok(())
+        });
+
+        match tx_result {
+            Ok(_) => Ok(ProcessingResult::new(
+                self.name(),
+                start_version,
+                end_version,
+            )),
+            Err(err) => Err(TransactionProcessingError::TransactionCommitError((
+                anyhow::Error::from(err),
+                start_version,
+                end_version,
+                self.name(),
+            ))),
+        }
+    }
+
+    fn connection_pool(&self) -> &PgDbPool {
+        &self.connection_pool
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indexer::tailer::test::setup_indexer;
+    use bigdecimal::BigDecimal;
+
+    fn create_activity(owner_address: &str, activity_type: &str, amount: u64) -> CoinActivity {
+        CoinActivity {
+            event_key: "0x0".to_string(),
+            sequence_number: 0,
+            transaction_version: 0,
+            owner_address: owner_address.to_string(),
+            coin_type: "0x1::aptos_coin::AptosCoin".to_string(),
+            amount: BigDecimal::from(amount),
+            activity_type: activity_type.to_string(),
+            counterparty_address: None,
+            transaction_timestamp: chrono::NaiveDateTime::from_timestamp(0, 0),
+            inserted_at: chrono::NaiveDateTime::from_timestamp(0, 0),
+        }
+    }
+
+    #[test]
+    fn test_set_counterparties() {
+        let withdraw_type = crate::models::coin::WITHDRAW_EVENT_TYPE;
+        let mut activities = vec![
+            create_activity("0xa", withdraw_type, 100),
+            create_activity("0xb", DEPOSIT_EVENT_TYPE, 100),
+            create_activity("0xc", DEPOSIT_EVENT_TYPE, 5),
+            create_activity("0xa", DEPOSIT_EVENT_TYPE, 7),
+        ];
+        set_counterparties(&mut activities, Some("0xa"));
+
+        // The transfer between 0xa and 0xb is paired up
+        assert_eq!(activities[0].counterparty_address.as_deref(), Some("0xb"));
+        assert_eq!(activities[1].counterparty_address.as_deref(), Some("0xa"));
+        // Unpaired deposits are attributed to the sender, unless it's the owner
+        assert_eq!(activities[2].counterparty_address.as_deref(), Some("0xa"));
+        assert_eq!(activities[3].counterparty_address, None);
+    }
+
+    #[test]
+    fn test_reprocess_coin_info() {
+        if crate::should_skip_pg_tests() {
+            return;
+        }
+        let (conn_pool, _tailer) = setup_indexer().unwrap();
+        let conn = conn_pool.get().unwrap();
+        let coin_type = "0x1::aptos_coin::AptosCoin";
+        let create_coin_info = |supply: u64, version: i64| CoinInfo {
+            coin_type: coin_type.to_string(),
+            creator_address: "0x1".to_string(),
+            name: "Aptos Coin".to_string(),
+            symbol: "APT".to_string(),
+            decimals: BigDecimal::from(8),
+            supply: Some(BigDecimal::from(supply)),
+            transaction_version_created: version,
+            last_transaction_version: version,
+            inserted_at: chrono::NaiveDateTime::from_timestamp(0, 0),
+        };
+        let get_coin_info = || {
+            schema::coin_infos::table
+                .find(coin_type)
+                .first::<CoinInfo>(&conn)
+                .unwrap()
+        };
+
+        insert_coin_info(&conn, &create_coin_info(100, 10));
+        insert_coin_info(&conn, &create_coin_info(200, 20));
+        assert_eq!(get_coin_info().supply, Some(BigDecimal::from(200)));
+
+        // Reprocessing an older version doesn't roll the supply back
+        insert_coin_info(&conn, &create_coin_info(100, 10));
+        let coin_info = get_coin_info();
+        assert_eq!(coin_info.supply, Some(BigDecimal::from(200)));
+        assert_eq!(coin_info.last_transaction_version, 20);
+        assert_eq!(coin_info.transaction_version_created, 10);
+    }
+}
@@ -122,60 +122,68 @@ fn insert_block_metadata_transaction(
     .expect("Error inserting row into database");
 }
 
+fn insert_to_db(conn: &PgPoolConnection, transaction: &Transaction) {
+    let version = transaction.version().unwrap_or(0);
+
+    let (transaction_model, maybe_details_model, maybe_events, maybe_write_set_changes) =
+        TransactionModel::from_transaction(transaction);
+
+    insert_transaction(conn, version, &transaction_model);
+    if let Some(tx_details_model) = maybe_details_model {
+        match tx_details_model {
+            Either::Left(user_transaction_model) => {
+                insert_user_transaction(conn, version, &transaction_model, &user_transaction_model);
+            }
+            Either::Right(block_metadata_transaction_model) => {
+                insert_block_metadata_transaction(
+                    conn,
+                    version,
+                    &transaction_model,
+                    &block_metadata_transaction_model,
+                );
+            }
+        };
+    };
+
+    if let Some(events) = maybe_events {
+        insert_events(conn, &events);
+    };
+    if let Some(write_set_changes) = maybe_write_set_changes {
+        insert_write_set_changes(conn, &write_set_changes);
+    };
+}
+
 #[async_trait]
 impl TransactionProcessor for DefaultTransactionProcessor {
     fn name(&self) -> &'static str {
         "default_processor"
     }
 
-    async fn process_transaction(
+    async fn process_transactions(
         &self,
-        transaction: Arc<Transaction>,
+        transactions: Vec<Arc<Transaction>>,
+        start_version: u64,
+        end_version: u64,
     ) -> Result<ProcessingResult, TransactionProcessingError> {
-        let version = transaction.version().unwrap_or(0);
-
-        let (transaction_model, maybe_details_model, maybe_events, maybe_write_set_changes) =
-            TransactionModel::from_transaction(&transaction);
-
         let conn = self.get_conn();
 
         let tx_result = conn.transaction::<(), diesel::result::Error, _>(|| {
-            insert_transaction(&conn, version, &transaction_model);
-            if let Some(tx_details_model) = maybe_details_model {
-                match tx_details_model {
-                    Either::Left(user_transaction_model) => {
-                        insert_user_transaction(
-                            &conn,
-                            version,
-                            &transaction_model,
-                            &user_transaction_model,
-                        );
-                    }
-                    Either::Right(block_metadata_transaction_model) => {
-                        insert_block_metadata_transaction(
-                            &conn,
-                            version,
-                            &transaction_model,
-                            &block_metadata_transaction_model,
-                        );
-                    }
-                };
-            };
-
-            if let Some(events) = maybe_events {
-                insert_events(&conn, &events);
-            };
-            if let Some(write_set_changes) = maybe_write_set_changes {
-                insert_write_set_changes(&conn, &write_set_changes);
-            };
+            for transaction in &transactions {
+                insert_to_db(&conn, transaction);
+            }
             Ok(())
         });
 
         match tx_result {
-            Ok(_) => Ok(ProcessingResult::new(self.name(), version)),
+            Ok(_) => Ok(ProcessingResult::new(
+                self.name(),
+                start_version,
+                end_version,
+            )),
             Err(err) => Err(TransactionProcessingError::TransactionCommitError((
                 anyhow::Error::from(err),
-                version,
+                start_version,
+                end_version,
                 self.name(),
             ))),
         }
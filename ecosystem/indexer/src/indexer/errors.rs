@@ -3,19 +3,20 @@
 
 use anyhow::Error;
 
-type ErrorWithVersionAndName = (Error, u64, &'static str);
+/// The error, the range of versions (inclusive) it applies to, and the name of the processor
+type ErrorWithVersionRangeAndName = (Error, u64, u64, &'static str);
 
 #[derive(Debug)]
 #[allow(clippy::large_enum_variant)]
 pub enum TransactionProcessingError {
     /// Could not get a connection
-    ConnectionPoolError(ErrorWithVersionAndName),
+    ConnectionPoolError(ErrorWithVersionRangeAndName),
     /// Could not commit the transaction
-    TransactionCommitError(ErrorWithVersionAndName),
+    TransactionCommitError(ErrorWithVersionRangeAndName),
 }
 
 impl TransactionProcessingError {
-    pub fn inner(&self) -> &ErrorWithVersionAndName {
+    pub fn inner(&self) -> &ErrorWithVersionRangeAndName {
         match self {
             TransactionProcessingError::ConnectionPoolError(ewv) => ewv,
             TransactionProcessingError::TransactionCommitError(ewv) => ewv,
@@ -3,86 +3,100 @@
 
 use crate::counters::{FETCHED_TRANSACTION, UNABLE_TO_FETCH_TRANSACTION};
 use aptos_rest_client::{Client as RestClient, Transaction};
+use futures::StreamExt;
 use std::time::Duration;
-use tokio::sync::Mutex;
+use tokio::{sync::mpsc, task::JoinHandle};
 use url::Url;
 
 // TODO: make this configurable
 const RETRY_TIME_MILLIS: u64 = 5000;
-const TRANSACTION_FETCH_BATCH_SIZE: u16 = 500;
+
+#[derive(Clone, Copy, Debug)]
+pub struct TransactionFetcherOptions {
+    /// How many versions to fetch in a single request (and to hand out per batch)
+    pub batch_size: u16,
+    /// How many requests (i.e., version ranges) to keep in flight at once
+    pub concurrent_fetches: usize,
+}
+
+impl Default for TransactionFetcherOptions {
+    fn default() -> Self {
+        Self {
+            batch_size: 500,
+            concurrent_fetches: 10,
+        }
+    }
+}
 
 #[derive(Debug)]
 pub struct TransactionFetcher {
     client: RestClient,
     version: u64,
-    transactions_buffer: Mutex<Vec<Transaction>>,
+    options: TransactionFetcherOptions,
+    /// Receives the fetched batches, in order, from the background fetching task
+    transaction_receiver: Option<mpsc::Receiver<Vec<Transaction>>>,
+    fetcher_task: Option<JoinHandle<()>>,
 }
 
 impl TransactionFetcher {
-    pub fn new(node_url: Url, starting_version: Option<u64>) -> Self {
+    pub fn new(
+        node_url: Url,
+        starting_version: Option<u64>,
+        options: TransactionFetcherOptions,
+    ) -> Self {
         let client = RestClient::new(node_url);
 
         Self {
             client,
             version: starting_version.unwrap_or(0),
-            transactions_buffer: Default::default(),
+            // Neither can be zero, or we'd never fetch anything
+            options: TransactionFetcherOptions {
+                batch_size: options.batch_size.max(1),
+                concurrent_fetches: options.concurrent_fetches.max(1),
+            },
+            transaction_receiver: None,
+            fetcher_task: None,
         }
     }
 
+    /// Sets the version to fetch from next. Any batches fetched ahead of time are dropped.
     pub fn set_version(&mut self, version: u64) {
+        self.stop_fetcher_task();
         self.version = version;
     }
 
-    /// Fetches the next version based on its internal version counter
-    /// Under the hood, it fetches TRANSACTION_FETCH_BATCH_SIZE versions in bulk (when needed), and uses that buffer to feed out
+    /// Fetches the next batch of consecutive versions based on its internal version counter.
+    /// Under the hood, a background task keeps `concurrent_fetches` ranges of `batch_size`
+    /// versions in flight, and hands them out in order, so processing and fetching overlap.
     /// In the event it can't fetch, it will keep retrying every RETRY_TIME_MILLIS ms
-    pub async fn fetch_next(&mut self) -> Transaction {
-        let mut transactions_buffer = self.transactions_buffer.lock().await;
-        if transactions_buffer.is_empty() {
-            // Fill it up!
-            loop {
-                let res = self
-                    .client
-                    .get_transactions(Some(self.version), Some(TRANSACTION_FETCH_BATCH_SIZE))
-                    .await;
-                match res {
-                    Ok(response) => {
-                        FETCHED_TRANSACTION.inc();
-                        let mut transactions = response.into_inner();
-                        transactions.reverse();
-                        *transactions_buffer = transactions;
-                        break;
-                    }
-                    Err(err) => {
-                        let err_str = err.to_string();
-                        // If it's a 404, then we're all caught up; no need to increment the `UNABLE_TO_FETCH_TRANSACTION` counter
-                        if err_str.contains("404") {
-                            aptos_logger::debug!(
-                            "Could not fetch {} transactions starting at {}: all caught up. Will check again in {}ms.",
-                            TRANSACTION_FETCH_BATCH_SIZE,
-                            self.version,
-                            RETRY_TIME_MILLIS,
-                        );
-                            tokio::time::sleep(Duration::from_millis(RETRY_TIME_MILLIS)).await;
-                            continue;
-                        }
-                        UNABLE_TO_FETCH_TRANSACTION.inc();
-                        aptos_logger::error!(
-                            "Could not fetch {} transactions starting at {}, will retry in {}ms. Err: {:?}",
-                            TRANSACTION_FETCH_BATCH_SIZE,
-                            self.version,
-                            RETRY_TIME_MILLIS,
-                            err
-                        );
-                        tokio::time::sleep(Duration::from_millis(RETRY_TIME_MILLIS)).await;
-                    }
-                };
+    pub async fn fetch_next_batch(&mut self) -> Vec<Transaction> {
+        loop {
+            if self.transaction_receiver.is_none() {
+                self.start_fetcher_task();
+            }
+            let transaction_receiver = self.transaction_receiver.as_mut().unwrap();
+            match transaction_receiver.recv().await {
+                Some(transactions) => {
+                    self.version += transactions.len() as u64;
+                    return transactions;
+                }
+                None => {
+                    // The task died (which it only does on panics), so start it again
+                    aptos_logger::error!(
+                        "Transaction fetcher task stopped unexpectedly, restarting it at version {}",
+                        self.version
+                    );
+                    self.stop_fetcher_task();
+                }
             }
         }
-        // At this point we're guaranteed to have something in the buffer
-        let transaction = transactions_buffer.pop().unwrap();
-        self.version += 1;
-        transaction
+    }
+
+    /// Fetches up to `batch_size` consecutive versions starting at `start_version`, independently
+    /// of the internal version counter; this is used for verification
+    /// Returns an empty batch if the start version doesn't exist yet
+    pub async fn fetch_batch(&self, start_version: u64) -> Vec<Transaction> {
+        fetch_range(&self.client, start_version, self.options.batch_size).await
     }
 
     /// fetches one version; this used for error checking/repair/etc
@@ -108,4 +122,183 @@ impl TransactionFetcher {
             };
         }
     }
+
+    fn start_fetcher_task(&mut self) {
+        let (transaction_sender, transaction_receiver) =
+            mpsc::channel(self.options.concurrent_fetches);
+        self.fetcher_task = Some(tokio::spawn(run_fetcher(
+            self.client.clone(),
+            self.version,
+            self.options,
+            transaction_sender,
+        )));
+        self.transaction_receiver = Some(transaction_receiver);
+    }
+
+    fn stop_fetcher_task(&mut self) {
+        if let Some(fetcher_task) = self.fetcher_task.take() {
+            fetcher_task.abort();
+        }
+        self.transaction_receiver = None;
+    }
+}
+
+impl Drop for TransactionFetcher {
+    fn drop(&mut self) {
+        self.stop_fetcher_task();
+    }
+}
+
+/// Fetches consecutive ranges of versions starting at `version`, keeping up to
+/// `concurrent_fetches` requests in flight, and sends the batches out in order.
+/// Once a range comes back short (i.e., we've caught up with the chain), the ranges
+/// requested after it are dropped, and fetching restarts right after the last version.
+async fn run_fetcher(
+    client: RestClient,
+    mut version: u64,
+    options: TransactionFetcherOptions,
+    transaction_sender: mpsc::Sender<Vec<Transaction>>,
+) {
+    loop {
+        let mut batches = futures::stream::iter((version..).step_by(options.batch_size as usize))
+            .map(|start_version| fetch_range(&client, start_version, options.batch_size))
+            .buffered(options.concurrent_fetches);
+        while let Some(transactions) = batches.next().await {
+            let num_transactions = transactions.len();
+            if num_transactions == 0 {
+                // We're all caught up, so wait a bit before checking for new versions
+                aptos_logger::debug!(
+                    "All caught up at version {}. Will check again in {}ms.",
+                    version,
+                    RETRY_TIME_MILLIS,
+                );
+                tokio::time::sleep(Duration::from_millis(RETRY_TIME_MILLIS)).await;
+                break;
+            }
+            version += num_transactions as u64;
+            if transaction_sender.send(transactions).await.is_err() {
+                // The fetcher was dropped or reset
+                return;
+            }
+            if num_transactions < options.batch_size as usize {
+                break;
+            }
+        }
+    }
+}
+
+/// Fetches up to `batch_size` versions starting at `start_version`. Returns an empty batch
+/// if the start version doesn't exist yet, and otherwise keeps retrying every
+/// RETRY_TIME_MILLIS ms until it succeeds.
+async fn fetch_range(client: &RestClient, start_version: u64, batch_size: u16) -> Vec<Transaction> {
+    loop {
+        let res = client
+            .get_transactions(Some(start_version), Some(batch_size))
+            .await;
+        match res {
+            Ok(response) => {
+                let transactions = response.into_inner();
+                // The versions must be consecutive, or processor statuses would have gaps
+                let is_consecutive = transactions
+                    .iter()
+                    .zip(start_version..)
+                    .all(|(transaction, version)| transaction.version() == Some(version));
+                if is_consecutive {
+                    FETCHED_TRANSACTION.inc();
+                    return transactions;
+                }
+                UNABLE_TO_FETCH_TRANSACTION.inc();
+                aptos_logger::error!(
+                    "Fetched {} transactions starting at {} with non-consecutive versions, will retry in {}ms.",
+                    transactions.len(),
+                    start_version,
+                    RETRY_TIME_MILLIS,
+                );
+            }
+            Err(err) => {
+                // If it's a 404, then we're all caught up; no need to increment the `UNABLE_TO_FETCH_TRANSACTION` counter
+                if err.to_string().contains("404") {
+                    return vec![];
+                }
+                UNABLE_TO_FETCH_TRANSACTION.inc();
+                aptos_logger::error!(
+                    "Could not fetch {} transactions starting at {}, will retry in {}ms. Err: {:?}",
+                    batch_size,
+                    start_version,
+                    RETRY_TIME_MILLIS,
+                    err
+                );
+            }
+        };
+        tokio::time::sleep(Duration::from_millis(RETRY_TIME_MILLIS)).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indexer::mock_node::MockNode;
+
+    fn versions(transactions: &[Transaction]) -> Vec<u64> {
+        transactions
+            .iter()
+            .map(|transaction| transaction.version().unwrap())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_fetch_range() {
+        let node = MockNode::start(10);
+        let client = RestClient::new(node.url());
+
+        let transactions = fetch_range(&client, 3, 4).await;
+        assert_eq!(versions(&transactions), vec![3, 4, 5, 6]);
+
+        // The range is cut short at the end of the ledger
+        let transactions = fetch_range(&client, 8, 4).await;
+        assert_eq!(versions(&transactions), vec![8, 9]);
+
+        // Versions that don't exist yet return an empty batch
+        assert!(fetch_range(&client, 10, 4).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_run_fetcher() {
+        let node = MockNode::start(23);
+        let options = TransactionFetcherOptions {
+            batch_size: 5,
+            concurrent_fetches: 3,
+        };
+        let mut fetcher = TransactionFetcher::new(node.url(), Some(2), options);
+
+        // The batches are handed out in order and without gaps
+        let mut fetched_versions = vec![];
+        while fetched_versions.len() < 21 {
+            let batch = versions(&fetcher.fetch_next_batch().await);
+            assert!(!batch.is_empty() && batch.len() <= 5);
+            fetched_versions.extend(batch);
+        }
+        assert_eq!(fetched_versions, (2..23).collect::<Vec<_>>());
+
+        // Once caught up, fetching resumes right after the last version
+        node.extend(7);
+        let mut fetched_versions = vec![];
+        while fetched_versions.len() < 7 {
+            let batch = tokio::time::timeout(
+                Duration::from_millis(2 * RETRY_TIME_MILLIS),
+                fetcher.fetch_next_batch(),
+            )
+            .await
+            .unwrap();
+            fetched_versions.extend(versions(&batch));
+        }
+        assert_eq!(fetched_versions, (23..30).collect::<Vec<_>>());
+
+        // Resetting the version drops the batches fetched ahead of time
+        fetcher.set_version(4);
+        assert_eq!(
+            versions(&fetcher.fetch_next_batch().await),
+            vec![4, 5, 6, 7, 8]
+        );
+    }
 }
@@ -0,0 +1,113 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+//! A fake node serving the transaction endpoints of the REST API from an in-memory ledger,
+//! so that fetching can be tested without a running node.
+
+use aptos_rest_client::{
+    aptos_api_types::{
+        HashValue, StateCheckpointTransaction, TransactionInfo, X_APTOS_CHAIN_ID, X_APTOS_EPOCH,
+        X_APTOS_LEDGER_TIMESTAMP, X_APTOS_LEDGER_VERSION,
+    },
+    Transaction,
+};
+use std::{
+    collections::HashMap,
+    str::FromStr,
+    sync::{Arc, Mutex},
+};
+use url::Url;
+use warp::{http::StatusCode, Filter, Reply};
+
+const DEFAULT_LIMIT: u64 = 25;
+
+pub struct MockNode {
+    ledger: Arc<Mutex<Vec<Transaction>>>,
+    url: Url,
+}
+
+impl MockNode {
+    /// Starts serving a ledger with `num_versions` versions. Must be called within a tokio runtime.
+    pub fn start(num_versions: u64) -> Self {
+        let ledger = Arc::new(Mutex::new(vec![]));
+        let node_ledger = ledger.clone();
+        let transaction_by_version = warp::path!("transactions" / u64).map(move |version: u64| {
+            let ledger = node_ledger.lock().unwrap();
+            match ledger.get(version as usize) {
+                Some(transaction) => ok(&ledger, transaction),
+                None => not_found(version),
+            }
+        });
+        let node_ledger = ledger.clone();
+        let transactions = warp::path("transactions")
+            .and(warp::path::end())
+            .and(warp::query::<HashMap<String, u64>>())
+            .map(move |params: HashMap<String, u64>| {
+                let ledger = node_ledger.lock().unwrap();
+                let start = params.get("start").copied().unwrap_or(0);
+                let limit = params.get("limit").copied().unwrap_or(DEFAULT_LIMIT);
+                if start >= ledger.len() as u64 {
+                    return not_found(start);
+                }
+                let end = (start + limit).min(ledger.len() as u64);
+                ok(&ledger, &ledger[start as usize..end as usize].to_vec())
+            });
+
+        let (address, server) = warp::serve(transaction_by_version.or(transactions))
+            .bind_ephemeral(([127, 0, 0, 1], 0));
+        tokio::spawn(server);
+
+        let node = Self {
+            ledger,
+            url: Url::parse(&format!("http://{}", address)).unwrap(),
+        };
+        node.extend(num_versions);
+        node
+    }
+
+    pub fn url(&self) -> Url {
+        self.url.clone()
+    }
+
+    /// Commits `num_versions` new versions
+    pub fn extend(&self, num_versions: u64) {
+        let mut ledger = self.ledger.lock().unwrap();
+        let next_version = ledger.len() as u64;
+        ledger.extend((next_version..next_version + num_versions).map(create_transaction));
+    }
+}
+
+/// Creates a transaction with the given version, and a hash derived from the version
+pub fn create_transaction(version: u64) -> Transaction {
+    let hash = |value: u64| HashValue::from_str(&format!("{:064x}", value)).unwrap();
+    Transaction::StateCheckpointTransaction(StateCheckpointTransaction {
+        info: TransactionInfo {
+            version: version.into(),
+            hash: hash(version),
+            state_root_hash: hash(0),
+            event_root_hash: hash(0),
+            gas_used: 0.into(),
+            success: true,
+            vm_status: "Executed successfully".to_string(),
+            accumulator_root_hash: hash(0),
+            changes: vec![],
+        },
+        timestamp: version.into(),
+    })
+}
+
+fn ok<T: serde::Serialize>(ledger: &[Transaction], body: &T) -> warp::reply::Response {
+    let reply = warp::reply::json(body);
+    let reply = warp::reply::with_header(reply, X_APTOS_CHAIN_ID, "4");
+    let reply = warp::reply::with_header(reply, X_APTOS_EPOCH, "1");
+    let reply = warp::reply::with_header(reply, X_APTOS_LEDGER_VERSION, ledger.len() - 1);
+    warp::reply::with_header(reply, X_APTOS_LEDGER_TIMESTAMP, ledger.len() - 1).into_response()
+}
+
+fn not_found(version: u64) -> warp::reply::Response {
+    let error = serde_json::json!({
+        "code": 404,
+        "message": format!("Ledger version {} not found", version),
+    });
+    warp::reply::with_status(warp::reply::json(&error), StatusCode::NOT_FOUND).into_response()
+}
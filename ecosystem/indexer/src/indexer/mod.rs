@@ -4,6 +4,9 @@
 pub mod errors;
 pub mod fetcher;
 pub mod metadata_fetcher;
+#[cfg(test)]
+pub mod mock_node;
 pub mod processing_result;
+pub mod repair;
 pub mod tailer;
 pub mod transaction_processor;
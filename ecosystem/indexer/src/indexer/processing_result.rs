@@ -1,14 +1,20 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
+/// The range of versions (inclusive) a processor has successfully processed
 #[derive(Debug)]
 pub struct ProcessingResult {
     pub name: &'static str,
-    pub version: u64,
+    pub start_version: u64,
+    pub end_version: u64,
 }
 
 impl ProcessingResult {
-    pub fn new(name: &'static str, version: u64) -> Self {
-        Self { name, version }
+    pub fn new(name: &'static str, start_version: u64, end_version: u64) -> Self {
+        Self {
+            name,
+            start_version,
+            end_version,
+        }
     }
 }
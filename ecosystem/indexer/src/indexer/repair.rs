@@ -0,0 +1,126 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::indexer::{tailer::Tailer, transaction_processor::TransactionProcessor};
+use aptos_logger::info;
+use std::{collections::BTreeMap, fmt};
+
+/// Runs the `repair` subcommand: repairs the versions from `start_version` to `end_version` (inclusive),
+/// and if `verify` is set, cross-checks the transactions stored by `default_processor` against the node.
+/// If no end version is given, verification goes up to the highest version `default_processor` has processed.
+pub async fn run_repair(
+    tailer: &Tailer,
+    default_processor: &dyn TransactionProcessor,
+    start_version: u64,
+    end_version: Option<u64>,
+    verify: bool,
+) -> RepairSummary {
+    let mut summary = tailer.repair(start_version, end_version).await;
+    if verify {
+        let end_version = end_version
+            .or_else(|| default_processor.get_max_version())
+            .unwrap_or_default();
+        info!(
+            "Verifying versions from {} to {}...",
+            start_version, end_version
+        );
+        summary.verification = Some(tailer.verify(start_version, end_version).await);
+    }
+    summary
+}
+
+/// What `Tailer::repair` found and fixed for a single processor
+#[derive(Debug, Default)]
+pub struct ProcessorRepairSummary {
+    /// Versions which were processed, but failed
+    pub errored_versions: usize,
+    /// Versions which were never processed at all
+    pub missing_versions: usize,
+    /// Versions which were successfully reprocessed
+    pub repaired_versions: usize,
+    /// Versions which failed again when reprocessed
+    pub failed_versions: Vec<u64>,
+}
+
+/// What `Tailer::verify` found when cross-checking the stored transactions against the node
+#[derive(Debug, Default)]
+pub struct VerificationSummary {
+    /// Versions whose stored hash matches the node
+    pub verified_versions: usize,
+    /// Versions which aren't stored at all
+    pub missing_versions: Vec<u64>,
+    /// Versions whose stored hash doesn't match the node
+    pub mismatched_versions: Vec<u64>,
+}
+
+impl VerificationSummary {
+    pub fn is_ok(&self) -> bool {
+        self.missing_versions.is_empty() && self.mismatched_versions.is_empty()
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct RepairSummary {
+    pub processors: BTreeMap<&'static str, ProcessorRepairSummary>,
+    pub verification: Option<VerificationSummary>,
+}
+
+impl RepairSummary {
+    /// Returns true if every version was repaired and (if verified) matches the node
+    pub fn is_ok(&self) -> bool {
+        self.processors
+            .values()
+            .all(|processor| processor.failed_versions.is_empty())
+            && self
+                .verification
+                .as_ref()
+                .map_or(true, VerificationSummary::is_ok)
+    }
+}
+
+impl fmt::Display for RepairSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (name, summary) in &self.processors {
+            writeln!(
+                f,
+                "[{}] errored: {}, missing: {}, repaired: {}, still failing: {}",
+                name,
+                summary.errored_versions,
+                summary.missing_versions,
+                summary.repaired_versions,
+                summary.failed_versions.len(),
+            )?;
+            if !summary.failed_versions.is_empty() {
+                writeln!(
+                    f,
+                    "[{}] versions still failing: {:?}",
+                    name, summary.failed_versions
+                )?;
+            }
+        }
+        if let Some(verification) = &self.verification {
+            writeln!(
+                f,
+                "[verify] matching: {}, missing: {}, mismatched: {}",
+                verification.verified_versions,
+                verification.missing_versions.len(),
+                verification.mismatched_versions.len(),
+            )?;
+            if !verification.missing_versions.is_empty() {
+                writeln!(
+                    f,
+                    "[verify] missing versions: {:?}",
+                    verification.missing_versions
+                )?;
+            }
+            if !verification.mismatched_versions.is_empty() {
+                writeln!(
+                    f,
+                    "[verify] mismatched versions: {:?}",
+                    verification.mismatched_versions
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -3,14 +3,22 @@
 use crate::{
     database::PgDbPool,
     indexer::{
-        errors::TransactionProcessingError, fetcher::TransactionFetcher,
-        processing_result::ProcessingResult, transaction_processor::TransactionProcessor,
+        errors::TransactionProcessingError,
+        fetcher::{TransactionFetcher, TransactionFetcherOptions},
+        processing_result::ProcessingResult,
+        repair::{ProcessorRepairSummary, RepairSummary, VerificationSummary},
+        transaction_processor::{TransactionProcessor, MISSING_VERSIONS_CHUNK_SIZE},
     },
+    models::transactions::TransactionModel,
 };
 use aptos_logger::info;
 use aptos_rest_client::Transaction;
 use serde_json::Value;
-use std::{fmt::Debug, sync::Arc};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    fmt::Debug,
+    sync::Arc,
+};
 use tokio::{sync::Mutex, task::JoinHandle};
 use url::{ParseError, Url};
 
@@ -57,9 +65,13 @@ pub struct Tailer {
 }
 
 impl Tailer {
-    pub fn new(node_url: &str, connection_pool: PgDbPool) -> Result<Tailer, ParseError> {
+    pub fn new(
+        node_url: &str,
+        connection_pool: PgDbPool,
+        fetcher_options: TransactionFetcherOptions,
+    ) -> Result<Tailer, ParseError> {
         let url = Url::parse(node_url)?;
-        let transaction_fetcher = TransactionFetcher::new(url, None);
+        let transaction_fetcher = TransactionFetcher::new(url, None, fetcher_options);
         Ok(Self {
             transaction_fetcher: Arc::new(Mutex::new(transaction_fetcher)),
             processors: vec![],
@@ -108,7 +120,7 @@ impl Tailer {
                 for version in errored_versions {
                     let txn = self2.get_txn(version).await;
                     if processor2
-                        .process_transaction_with_status(txn)
+                        .process_transactions_with_status(vec![txn])
                         .await
                         .is_ok()
                     {
@@ -128,6 +140,155 @@ impl Tailer {
         info!("Fixing previously errored versions complete!");
     }
 
+    /// Reprocesses the versions in the (inclusive) range which each processor either failed to process,
+    /// or never processed at all (e.g., because the indexer was stopped midway through a batch).
+    /// If no end version is given, each processor is repaired up to the highest version it has processed.
+    /// Each version is fetched once, and reprocessing is idempotent, so it is safe to run this repeatedly.
+    /// The range is repaired in chunks of `MISSING_VERSIONS_CHUNK_SIZE` versions, so memory stays bounded.
+    pub async fn repair(&self, start_version: u64, end_version: Option<u64>) -> RepairSummary {
+        let mut summary = RepairSummary::default();
+        // The processors to repair, with the last version to repair and their errored versions
+        let mut processors_to_repair = vec![];
+        for processor in &self.processors {
+            let processor_summary = summary.processors.entry(processor.name()).or_default();
+            let end_version = match end_version.or_else(|| processor.get_max_version()) {
+                Some(end_version) if end_version >= start_version => end_version,
+                _ => {
+                    info!("Nothing to repair for {}", processor.name());
+                    continue;
+                }
+            };
+
+            let error_versions: BTreeSet<u64> = processor
+                .get_error_versions()
+                .into_iter()
+                .filter(|version| (start_version..=end_version).contains(version))
+                .collect();
+            processor_summary.errored_versions = error_versions.len();
+            processors_to_repair.push((processor.clone(), end_version, error_versions));
+        }
+
+        let last_version = match processors_to_repair
+            .iter()
+            .map(|(_, end_version, _)| *end_version)
+            .max()
+        {
+            Some(last_version) => last_version,
+            None => return summary,
+        };
+        let mut chunk_start_version = start_version;
+        loop {
+            let chunk_end_version = last_version
+                .min(chunk_start_version.saturating_add(MISSING_VERSIONS_CHUNK_SIZE - 1));
+            // Version -> the processors which need to reprocess it
+            let mut versions_to_repair: BTreeMap<u64, Vec<Arc<dyn TransactionProcessor>>> =
+                BTreeMap::new();
+            for (processor, end_version, error_versions) in &processors_to_repair {
+                if *end_version < chunk_start_version {
+                    continue;
+                }
+                let processor_end_version = chunk_end_version.min(*end_version);
+                let missing_versions =
+                    processor.get_missing_versions(chunk_start_version, processor_end_version);
+                summary
+                    .processors
+                    .get_mut(processor.name())
+                    .unwrap()
+                    .missing_versions += missing_versions.len();
+                let error_versions = error_versions
+                    .range(chunk_start_version..=processor_end_version)
+                    .copied();
+                for version in error_versions.chain(missing_versions) {
+                    versions_to_repair
+                        .entry(version)
+                        .or_default()
+                        .push(processor.clone());
+                }
+            }
+
+            for (version, processors) in versions_to_repair {
+                let txn = remove_null_bytes_from_txn(self.get_txn(version).await);
+                for processor in processors {
+                    let processor_summary: &mut ProcessorRepairSummary =
+                        summary.processors.get_mut(processor.name()).unwrap();
+                    match processor
+                        .process_transactions_with_status(vec![txn.clone()])
+                        .await
+                    {
+                        Ok(_) => processor_summary.repaired_versions += 1,
+                        Err(_) => processor_summary.failed_versions.push(version),
+                    }
+                }
+            }
+
+            if chunk_end_version == last_version {
+                break;
+            }
+            chunk_start_version = chunk_end_version + 1;
+        }
+
+        for (processor, end_version, _) in &processors_to_repair {
+            let processor_summary = &summary.processors[processor.name()];
+            info!(
+                "Found {} errored and {} missing versions from {} to {} for {}",
+                processor_summary.errored_versions,
+                processor_summary.missing_versions,
+                start_version,
+                end_version,
+                processor.name(),
+            );
+        }
+        summary
+    }
+
+    /// Cross-checks the hashes of the transactions stored by the `DefaultTransactionProcessor` in the
+    /// (inclusive) range against the node, to find the versions which are missing or don't match.
+    pub async fn verify(&self, start_version: u64, end_version: u64) -> VerificationSummary {
+        let mut summary = VerificationSummary::default();
+        let conn = self
+            .connection_pool
+            .get()
+            .expect("Could not get connection for verification");
+        let mut version = start_version;
+        while version <= end_version {
+            let txns = self
+                .transaction_fetcher
+                .lock()
+                .await
+                .fetch_batch(version)
+                .await;
+            if txns.is_empty() {
+                aptos_logger::warn!(
+                    "Versions from {} to {} don't exist on the node, skipping their verification",
+                    version,
+                    end_version
+                );
+                break;
+            }
+            let batch_end_version = end_version.min(version + txns.len() as u64 - 1);
+
+            let stored_hashes: HashMap<u64, String> =
+                TransactionModel::get_hashes_in_range(version, batch_end_version, &conn)
+                    .expect("Error loading the stored transaction hashes")
+                    .into_iter()
+                    .map(|(version, hash)| (version as u64, hash))
+                    .collect();
+            for txn in txns.iter().take((batch_end_version - version + 1) as usize) {
+                let txn_version = txn.version().unwrap();
+                let txn_hash = txn.transaction_info().unwrap().hash.to_string();
+                match stored_hashes.get(&txn_version) {
+                    None => summary.missing_versions.push(txn_version),
+                    Some(stored_hash) if *stored_hash != txn_hash => {
+                        summary.mismatched_versions.push(txn_version)
+                    }
+                    Some(_) => summary.verified_versions += 1,
+                }
+            }
+            version = batch_end_version + 1;
+        }
+        summary
+    }
+
     /// Sets the version of the fetcher to the lowest version among all processors
     pub async fn set_fetcher_to_lowest_processor_version(&self) -> u64 {
         let mut lowest = u64::MAX;
@@ -153,13 +314,6 @@ impl Tailer {
         version
     }
 
-    pub async fn process_next(
-        &mut self,
-    ) -> anyhow::Result<Vec<Result<ProcessingResult, TransactionProcessingError>>> {
-        let txn = self.get_next_txn().await;
-        self.process_transaction(txn).await
-    }
-
     pub async fn process_version(
         &mut self,
         version: u64,
@@ -168,33 +322,26 @@ impl Tailer {
         self.process_transaction(txn).await
     }
 
-    pub async fn process_next_batch(
-        &mut self,
-        batch_size: u8,
-    ) -> Vec<anyhow::Result<Vec<Result<ProcessingResult, TransactionProcessingError>>>> {
-        let mut tasks = vec![];
-        for _ in 0..batch_size {
-            let mut self2 = self.clone();
-            let task = tokio::task::spawn(async move { self2.process_next().await });
-            tasks.push(task);
-        }
-        let results = await_tasks(tasks).await;
-        results
-    }
-
     pub async fn process_transaction(
         &self,
         txn: Arc<Transaction>,
+    ) -> anyhow::Result<Vec<Result<ProcessingResult, TransactionProcessingError>>> {
+        self.process_transactions(vec![txn]).await
+    }
+
+    /// Has every processor process the (consecutive) transactions in parallel
+    pub async fn process_transactions(
+        &self,
+        txns: Vec<Arc<Transaction>>,
     ) -> anyhow::Result<Vec<Result<ProcessingResult, TransactionProcessingError>>> {
         let mut tasks = vec![];
-        let txn = remove_null_bytes_from_txn(txn.clone());
+        let txns: Vec<Arc<Transaction>> =
+            txns.into_iter().map(remove_null_bytes_from_txn).collect();
         for processor in &self.processors {
             let processor2 = processor.clone();
-            let txn2 = txn.clone();
+            let txns2 = txns.clone();
             let task = tokio::task::spawn(async move {
-                processor2
-                    .process_transaction_with_status(txn2.clone())
-                    .await
+                processor2.process_transactions_with_status(txns2).await
             });
             tasks.push(task);
         }
@@ -202,8 +349,17 @@ impl Tailer {
         Ok(results)
     }
 
-    pub async fn get_next_txn(&mut self) -> Arc<Transaction> {
-        Arc::new(self.transaction_fetcher.lock().await.fetch_next().await)
+    /// Gets the next batch of consecutive versions. Following batches are fetched in the
+    /// background in the meantime, so they're usually ready by the time they're needed.
+    pub async fn get_next_batch(&mut self) -> Vec<Arc<Transaction>> {
+        self.transaction_fetcher
+            .lock()
+            .await
+            .fetch_next_batch()
+            .await
+            .into_iter()
+            .map(Arc::new)
+            .collect()
     }
 
     pub async fn get_txn(&self, version: u64) -> Arc<Transaction> {
@@ -230,8 +386,12 @@ pub async fn await_tasks<T: Debug>(tasks: Vec<JoinHandle<T>>) -> Vec<T> {
 }
 
 #[cfg(test)]
-mod test {
+pub(crate) mod test {
     use super::*;
+    use crate::indexer::{
+        mock_node::{create_transaction, MockNode},
+        repair::run_repair,
+    };
     use crate::{
         database::{new_db_pool, PgPoolConnection},
         default_processor::DefaultTransactionProcessor,
@@ -243,6 +403,9 @@ mod test {
 
     pub fn wipe_database(conn: &PgPoolConnection) {
         for table in [
+            "coin_activities",
+            "coin_balances",
+            "coin_infos",
             "metadatas",
             "ownerships",
             "token_activities",
@@ -262,12 +425,20 @@ mod test {
     }
 
     pub fn setup_indexer() -> anyhow::Result<(PgDbPool, Tailer)> {
+        setup_indexer_with_node("http://fake-url.aptos.dev")
+    }
+
+    pub fn setup_indexer_with_node(node_url: &str) -> anyhow::Result<(PgDbPool, Tailer)> {
         let database_url = std::env::var("INDEXER_DATABASE_URL")
             .expect("must set 'INDEXER_DATABASE_URL' to run tests!");
         let conn_pool = new_db_pool(database_url.as_str())?;
         wipe_database(&conn_pool.get()?);
 
-        let mut tailer = Tailer::new("http://fake-url.aptos.dev", conn_pool.clone())?;
+        let mut tailer = Tailer::new(
+            node_url,
+            conn_pool.clone(),
+            TransactionFetcherOptions::default(),
+        )?;
         tailer.run_migrations();
 
         let pg_transaction_processor = DefaultTransactionProcessor::new(conn_pool.clone());
@@ -768,4 +939,114 @@ mod test {
             .await
             .unwrap();
     }
+
+    /// Starts a mock node with `num_versions` versions, and has the indexer process all of them
+    async fn setup_processed_indexer(
+        num_versions: u64,
+    ) -> (PgDbPool, Tailer, Arc<dyn TransactionProcessor>, MockNode) {
+        let node = MockNode::start(num_versions);
+        let (conn_pool, tailer) = setup_indexer_with_node(node.url().as_str()).unwrap();
+        let txns = (0..num_versions)
+            .map(|version| Arc::new(create_transaction(version)))
+            .collect();
+        for result in tailer.process_transactions(txns).await.unwrap() {
+            result.unwrap();
+        }
+        let default_processor = tailer.processors[0].clone();
+        assert_eq!(default_processor.name(), "default_processor");
+        (conn_pool, tailer, default_processor, node)
+    }
+
+    #[tokio::test]
+    async fn test_repair() {
+        if crate::should_skip_pg_tests() {
+            return;
+        }
+        let (conn_pool, tailer, default_processor, _node) = setup_processed_indexer(20).await;
+        let conn = conn_pool.get().unwrap();
+
+        // Leave a gap in the processed versions, and fail another one
+        conn.execute(
+            "DELETE FROM processor_statuses WHERE name = 'default_processor' AND version BETWEEN 5 AND 7",
+        )
+        .unwrap();
+        conn.execute(
+            "UPDATE processor_statuses SET success = false WHERE name = 'default_processor' AND version = 12",
+        )
+        .unwrap();
+        assert_eq!(default_processor.get_missing_versions(0, 19), vec![5, 6, 7]);
+        assert_eq!(default_processor.get_error_versions(), vec![12]);
+
+        let summary = tailer.repair(0, None).await;
+        assert!(summary.is_ok());
+        let processor_summary = &summary.processors["default_processor"];
+        assert_eq!(processor_summary.errored_versions, 1);
+        assert_eq!(processor_summary.missing_versions, 3);
+        assert_eq!(processor_summary.repaired_versions, 4);
+        assert!(default_processor.get_missing_versions(0, 19).is_empty());
+        assert!(default_processor.get_error_versions().is_empty());
+
+        // Nothing is left to repair
+        let summary = tailer.repair(0, None).await;
+        let processor_summary = &summary.processors["default_processor"];
+        assert_eq!(processor_summary.errored_versions, 0);
+        assert_eq!(processor_summary.missing_versions, 0);
+        assert_eq!(processor_summary.repaired_versions, 0);
+    }
+
+    #[tokio::test]
+    async fn test_verify() {
+        if crate::should_skip_pg_tests() {
+            return;
+        }
+        let (conn_pool, tailer, _, _node) = setup_processed_indexer(10).await;
+        let conn = conn_pool.get().unwrap();
+        let summary = tailer.verify(0, 9).await;
+        assert!(summary.is_ok());
+        assert_eq!(summary.verified_versions, 10);
+
+        // Tamper with one stored transaction, and drop another one
+        conn.execute("UPDATE transactions SET hash = '0xbad' WHERE version = 3")
+            .unwrap();
+        conn.execute("DELETE FROM transactions WHERE version = 6")
+            .unwrap();
+        let summary = tailer.verify(0, 9).await;
+        assert!(!summary.is_ok());
+        assert_eq!(summary.verified_versions, 8);
+        assert_eq!(summary.mismatched_versions, vec![3]);
+        assert_eq!(summary.missing_versions, vec![6]);
+
+        // Versions past the end of the node's ledger are skipped
+        let summary = tailer.verify(8, 20).await;
+        assert_eq!(summary.verified_versions, 2);
+    }
+
+    #[tokio::test]
+    async fn test_repair_command() {
+        if crate::should_skip_pg_tests() {
+            return;
+        }
+        let (conn_pool, tailer, default_processor, _node) = setup_processed_indexer(10).await;
+        let conn = conn_pool.get().unwrap();
+
+        // A version which was dropped, together with its status, is refetched and verified
+        conn.execute("DELETE FROM transactions WHERE version = 6")
+            .unwrap();
+        conn.execute("DELETE FROM processor_statuses WHERE version = 6")
+            .unwrap();
+        let summary = run_repair(&tailer, default_processor.as_ref(), 0, None, true).await;
+        assert!(summary.is_ok());
+        assert_eq!(summary.processors["default_processor"].repaired_versions, 1);
+        assert_eq!(summary.verification.as_ref().unwrap().verified_versions, 10);
+
+        // A tampered transaction is still processed, so it's only caught by verification
+        conn.execute("UPDATE transactions SET hash = '0xbad' WHERE version = 3")
+            .unwrap();
+        let summary = run_repair(&tailer, default_processor.as_ref(), 0, Some(9), false).await;
+        assert!(summary.is_ok());
+        assert!(summary.verification.is_none());
+        let summary = run_repair(&tailer, default_processor.as_ref(), 0, Some(9), true).await;
+        assert!(!summary.is_ok());
+        assert_eq!(summary.verification.unwrap().mismatched_versions, vec![3]);
+    }
 }
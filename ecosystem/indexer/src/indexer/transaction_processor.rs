@@ -13,9 +13,15 @@ use crate::{
 };
 use aptos_rest_client::Transaction;
 use async_trait::async_trait;
-use diesel::{prelude::*, RunQueryDsl};
+use diesel::{pg::upsert::excluded, prelude::*, RunQueryDsl};
 use schema::processor_statuses::{self, dsl};
-use std::{fmt::Debug, sync::Arc};
+use std::{collections::HashSet, fmt::Debug, sync::Arc};
+
+/// Postgres allows at most 65535 bind parameters per statement, and each status has 5 columns
+const MAX_PROCESSOR_STATUSES_PER_INSERT: usize = 10_000;
+
+/// How many versions are checked for gaps per query, so that memory stays bounded on large ranges
+pub const MISSING_VERSIONS_CHUNK_SIZE: u64 = 10_000;
 
 /// The `TransactionProcessor` is used by an instance of a `Tailer` to process transactions
 #[async_trait]
@@ -24,12 +30,16 @@ pub trait TransactionProcessor: Send + Sync + Debug {
     /// This will get stored in the database for each (`TransactionProcessor`, transaction_version) pair
     fn name(&self) -> &'static str;
 
-    /// Accepts a transaction, and processes it. This method will be called from `process_transaction_with_status`
-    /// In case a transaction cannot be processed, returns an error: the `Tailer` will mark it as failed in the database,
-    /// and it will be retried next time the indexer is started.
-    async fn process_transaction(
+    /// Accepts a batch of consecutive transactions, from `start_version` to `end_version` (inclusive),
+    /// and processes them. This method will be called from `process_transactions_with_status`
+    /// The whole batch should be committed in a single DB transaction, so it either succeeds or fails as a whole.
+    /// In case the batch cannot be processed, returns an error: the `Tailer` will mark all of its versions as failed
+    /// in the database, and they will be retried next time the indexer is started.
+    async fn process_transactions(
         &self,
-        transaction: Arc<Transaction>,
+        transactions: Vec<Arc<Transaction>>,
+        start_version: u64,
+        end_version: u64,
     ) -> Result<ProcessingResult, TransactionProcessingError>;
 
     /// Gets a reference to the connection pool
@@ -61,16 +71,25 @@ pub trait TransactionProcessor: Send + Sync + Debug {
     }
 
     /// This is a helper method, tying together the other helper methods to allow tracking status in the DB
-    async fn process_transaction_with_status(
+    /// The transactions must be consecutive, so that every version gets a status and there are no gaps.
+    async fn process_transactions_with_status(
         &self,
-        transaction: Arc<Transaction>,
+        transactions: Vec<Arc<Transaction>>,
     ) -> Result<ProcessingResult, TransactionProcessingError> {
         PROCESSOR_INVOCATIONS
             .with_label_values(&[self.name()])
             .inc();
 
-        self.mark_version_started(transaction.version().unwrap());
-        let res = self.process_transaction(transaction).await;
+        let start_version = transactions
+            .first()
+            .expect("Cannot process an empty batch of transactions")
+            .version()
+            .unwrap();
+        let end_version = transactions.last().unwrap().version().unwrap();
+        self.mark_versions_started(start_version, end_version);
+        let res = self
+            .process_transactions(transactions, start_version, end_version)
+            .await;
         // Handle version success/failure
         match res.as_ref() {
             Ok(processing_result) => self.update_status_success(processing_result),
@@ -79,52 +98,63 @@ pub trait TransactionProcessor: Send + Sync + Debug {
         res
     }
 
-    /// Writes that a version has been started for this `TransactionProcessor` to the DB
-    fn mark_version_started(&self, version: u64) {
+    /// Writes that a range of versions has been started for this `TransactionProcessor` to the DB
+    fn mark_versions_started(&self, start_version: u64, end_version: u64) {
         aptos_logger::debug!(
-            "[{}] Marking processing version started: {}",
+            "[{}] Marking processing versions started: {} to {}",
             self.name(),
-            version
+            start_version,
+            end_version
         );
-        let psm = ProcessorStatusModel::for_mark_started(self.name(), version as i64);
-        self.apply_processor_status(&psm);
+        let psms = ProcessorStatusModel::for_mark_started(self.name(), start_version, end_version);
+        self.apply_processor_statuses(&psms);
     }
 
-    /// Writes that a version has been completed successfully for this `TransactionProcessor` to the DB
+    /// Writes that a range of versions has been completed successfully for this `TransactionProcessor` to the DB
     fn update_status_success(&self, processing_result: &ProcessingResult) {
         aptos_logger::debug!(
-            "[{}] Marking processing version OK: {}",
+            "[{}] Marking processing versions OK: {} to {}",
             self.name(),
-            processing_result.version
+            processing_result.start_version,
+            processing_result.end_version
         );
         PROCESSOR_SUCCESSES.with_label_values(&[self.name()]).inc();
-        let psm = ProcessorStatusModel::from_processing_result_ok(processing_result);
-        self.apply_processor_status(&psm);
+        let psms = ProcessorStatusModel::from_processing_result_ok(processing_result);
+        self.apply_processor_statuses(&psms);
     }
 
-    /// Writes that a version has errored for this `TransactionProcessor` to the DB
+    /// Writes that a range of versions has errored for this `TransactionProcessor` to the DB
     fn update_status_err(&self, tpe: &TransactionProcessingError) {
         aptos_logger::debug!(
-            "[{}] Marking processing version Err: {:?}",
+            "[{}] Marking processing versions Err: {:?}",
             self.name(),
             tpe
         );
         PROCESSOR_ERRORS.with_label_values(&[self.name()]).inc();
-        let psm = ProcessorStatusModel::from_transaction_processing_err(tpe);
-        self.apply_processor_status(&psm);
+        let psms = ProcessorStatusModel::from_transaction_processing_err(tpe);
+        self.apply_processor_statuses(&psms);
     }
 
-    /// Actually performs the write for a `ProcessorStatusModel` changeset
-    fn apply_processor_status(&self, psm: &ProcessorStatusModel) {
+    /// Actually performs the writes for `ProcessorStatusModel` changesets, in a single DB transaction
+    fn apply_processor_statuses(&self, psms: &[ProcessorStatusModel]) {
         let conn = self.get_conn();
-        execute_with_better_error(
-            &conn,
-            diesel::insert_into(processor_statuses::table)
-                .values(psm)
-                .on_conflict((dsl::name, dsl::version))
-                .do_update()
-                .set(psm),
-        )
+        conn.transaction::<(), diesel::result::Error, _>(|| {
+            for chunk in psms.chunks(MAX_PROCESSOR_STATUSES_PER_INSERT) {
+                execute_with_better_error(
+                    &conn,
+                    diesel::insert_into(processor_statuses::table)
+                        .values(chunk)
+                        .on_conflict((dsl::name, dsl::version))
+                        .do_update()
+                        .set((
+                            dsl::success.eq(excluded(dsl::success)),
+                            dsl::details.eq(excluded(dsl::details)),
+                            dsl::last_updated.eq(excluded(dsl::last_updated)),
+                        )),
+                )?;
+            }
+            Ok(())
+        })
         .expect("Error updating Processor Status!");
     }
 
@@ -147,6 +177,41 @@ pub trait TransactionProcessor: Send + Sync + Debug {
             .collect()
     }
 
+    /// Gets all versions in the (inclusive) range which have no status for this `TransactionProcessor` in the DB,
+    /// i.e., which were never processed at all
+    /// This is so the `Tailer` can find gaps when repairing
+    /// The range is walked in chunks of `MISSING_VERSIONS_CHUNK_SIZE` versions, so only one chunk of
+    /// processed versions is loaded at a time
+    fn get_missing_versions(&self, start_version: u64, end_version: u64) -> Vec<u64> {
+        let conn = self.get_conn();
+
+        let mut missing_versions = vec![];
+        let mut chunk_start_version = start_version;
+        while chunk_start_version <= end_version {
+            let chunk_end_version = end_version
+                .min(chunk_start_version.saturating_add(MISSING_VERSIONS_CHUNK_SIZE - 1));
+            let processed_versions: HashSet<u64> = dsl::processor_statuses
+                .select(dsl::version)
+                .filter(dsl::name.eq(self.name().to_string()).and(
+                    dsl::version.between(chunk_start_version as i64, chunk_end_version as i64),
+                ))
+                .load::<i64>(&conn)
+                .expect("Error loading the processed versions query")
+                .iter()
+                .map(|v| *v as u64)
+                .collect();
+            missing_versions.extend(
+                (chunk_start_version..=chunk_end_version)
+                    .filter(|version| !processed_versions.contains(version)),
+            );
+            match chunk_end_version.checked_add(1) {
+                Some(next_version) => chunk_start_version = next_version,
+                None => break,
+            }
+        }
+        missing_versions
+    }
+
     /// Gets the highest version for this `TransactionProcessor` from the DB
     /// This is so we know where to resume from on restarts
     fn get_max_version(&self) -> Option<u64> {
@@ -160,3 +225,38 @@ pub trait TransactionProcessor: Send + Sync + Debug {
             .map(|v| v as u64)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The number of columns of the processor_statuses table
+    const NUM_PROCESSOR_STATUS_COLUMNS: usize = 5;
+
+    #[test]
+    fn test_processor_status_chunks() {
+        assert!(MAX_PROCESSOR_STATUSES_PER_INSERT * NUM_PROCESSOR_STATUS_COLUMNS <= 65535);
+
+        let start_version = 7;
+        let end_version = start_version + 2 * MAX_PROCESSOR_STATUSES_PER_INSERT as u64 + 10;
+        let psms =
+            ProcessorStatusModel::for_version_range("test", start_version, end_version, true, None);
+        let chunk_sizes: Vec<_> = psms
+            .chunks(MAX_PROCESSOR_STATUSES_PER_INSERT)
+            .map(|chunk| chunk.len())
+            .collect();
+        assert_eq!(
+            chunk_sizes,
+            vec![
+                MAX_PROCESSOR_STATUSES_PER_INSERT,
+                MAX_PROCESSOR_STATUSES_PER_INSERT,
+                11
+            ]
+        );
+
+        // Every version of the range gets a status, in order
+        let versions: Vec<_> = psms.iter().map(|psm| psm.version as u64).collect();
+        assert_eq!(versions, (start_version..=end_version).collect::<Vec<_>>());
+        assert!(psms.iter().all(|psm| psm.success && psm.name == "test"));
+    }
+}
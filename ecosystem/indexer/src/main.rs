@@ -8,11 +8,14 @@
 #![forbid(unsafe_code)]
 
 use aptos_logger::info;
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use std::sync::Arc;
 
 use aptos_indexer::{
-    database::new_db_pool, default_processor::DefaultTransactionProcessor, indexer::tailer::Tailer,
+    coin_processor::CoinTransactionProcessor,
+    database::new_db_pool,
+    default_processor::DefaultTransactionProcessor,
+    indexer::{fetcher::TransactionFetcherOptions, repair::run_repair, tailer::Tailer},
     token_processor::TokenTransactionProcessor,
 };
 
@@ -44,9 +47,13 @@ struct IndexerArgs {
     #[clap(long)]
     start_from_version: Option<u64>,
 
-    /// How many versions to fetch and process from a node in parallel
+    /// How many versions to fetch from a node in a single request, and to commit in a single DB transaction
+    #[clap(long, default_value_t = 500)]
+    batch_size: u16,
+
+    /// How many batches of versions to fetch from a node in parallel, ahead of processing
     #[clap(long, default_value_t = 10)]
-    batch_size: u8,
+    concurrent_fetches: usize,
 
     /// How many versions to process before logging a "processed X versions" message.
     /// This will only be checked every `--batch-size` number of versions.
@@ -58,6 +65,37 @@ struct IndexerArgs {
     /// in the postgres DB tables.
     #[clap(long)]
     index_token_data: bool,
+
+    /// Turn on the indexer to collect coin infos, balances and deposit/withdraw activities and
+    /// store them in the postgres DB tables.
+    #[clap(long)]
+    index_coin_data: bool,
+
+    #[clap(subcommand)]
+    command: Option<IndexerCommand>,
+}
+
+#[derive(Debug, Subcommand)]
+enum IndexerCommand {
+    /// Instead of indexing new versions, reprocess the versions in a range which failed or were
+    /// never processed, and print a summary
+    Repair(RepairArgs),
+}
+
+#[derive(Debug, Parser)]
+struct RepairArgs {
+    /// The first version to repair
+    #[clap(long, default_value_t = 0)]
+    start_version: u64,
+
+    /// The last version to repair (inclusive). Defaults to the highest version each processor has
+    /// processed. Versions past the latest version of the node will be waited for.
+    #[clap(long)]
+    end_version: Option<u64>,
+
+    /// If set, also cross-check the hashes of the stored transactions against the node
+    #[clap(long)]
+    verify: bool,
 }
 
 #[tokio::main]
@@ -70,19 +108,45 @@ async fn main() -> std::io::Result<()> {
     let conn_pool = new_db_pool(&args.pg_uri).unwrap();
     info!("Created the connection pool... ");
 
-    let mut tailer = Tailer::new(&args.node_url, conn_pool.clone()).unwrap();
+    let fetcher_options = TransactionFetcherOptions {
+        batch_size: args.batch_size,
+        concurrent_fetches: args.concurrent_fetches,
+    };
+    let mut tailer = Tailer::new(&args.node_url, conn_pool.clone(), fetcher_options).unwrap();
 
     if !args.skip_migrations {
         tailer.run_migrations();
     }
 
-    let pg_transaction_processor = DefaultTransactionProcessor::new(conn_pool.clone());
-    tailer.add_processor(Arc::new(pg_transaction_processor));
+    let pg_transaction_processor = Arc::new(DefaultTransactionProcessor::new(conn_pool.clone()));
+    tailer.add_processor(pg_transaction_processor.clone());
     if args.index_token_data {
         let token_transaction_processor = TokenTransactionProcessor::new(conn_pool.clone());
         tailer.add_processor(Arc::new(token_transaction_processor));
     }
 
+    if args.index_coin_data {
+        let coin_transaction_processor = CoinTransactionProcessor::new(conn_pool.clone());
+        tailer.add_processor(Arc::new(coin_transaction_processor));
+    }
+
+    if let Some(IndexerCommand::Repair(repair_args)) = args.command {
+        info!("Repairing versions...");
+        let summary = run_repair(
+            &tailer,
+            pg_transaction_processor.as_ref(),
+            repair_args.start_version,
+            repair_args.end_version,
+            repair_args.verify,
+        )
+        .await;
+        info!("Repair complete!\n{}", summary);
+        if !summary.is_ok() {
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
     let starting_version = match args.start_from_version {
         None => tailer.set_fetcher_to_lowest_processor_version().await,
         Some(version) => tailer.set_fetcher_version(version).await,
@@ -101,8 +165,9 @@ async fn main() -> std::io::Result<()> {
     let mut processed: usize = starting_version as usize;
     let mut base: usize = 0;
     loop {
-        let res = tailer.process_next_batch(args.batch_size).await;
-        processed += res.len();
+        let txns = tailer.get_next_batch().await;
+        processed += txns.len();
+        tailer.process_transactions(txns).await.unwrap();
         if args.emit_every != 0 {
             let new_base: usize = processed / args.emit_every;
             if base != new_base {
@@ -0,0 +1,264 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::models::{
+    events::Event,
+    token::{deserialize_option_from_string, MoveOption},
+};
+use anyhow::Context;
+use aptos_rest_client::{aptos_api_types::WriteSetChange as APIWriteSetChange, types};
+use bigdecimal::BigDecimal;
+use serde::{Deserialize, Serialize};
+
+const COIN_STORE_TYPE_PREFIX: &str = "0x1::coin::CoinStore<";
+const COIN_INFO_TYPE_PREFIX: &str = "0x1::coin::CoinInfo<";
+pub const DEPOSIT_EVENT_TYPE: &str = "0x1::coin::DepositEvent";
+pub const WITHDRAW_EVENT_TYPE: &str = "0x1::coin::WithdrawEvent";
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Coin {
+    #[serde(deserialize_with = "types::deserialize_from_string")]
+    pub value: BigDecimal,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct GuidId {
+    pub addr: String,
+    #[serde(deserialize_with = "types::deserialize_from_string")]
+    pub creation_num: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Guid {
+    pub id: GuidId,
+}
+
+impl Guid {
+    /// Returns the event key of the event handle with this GUID, formatted the same
+    /// way as the `key` of the events returned by the API, i.e., the hex encoded
+    /// BCS bytes of the creation number followed by the account address.
+    pub fn to_event_key(&self) -> String {
+        let creation_num = self
+            .id
+            .creation_num
+            .to_le_bytes()
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect::<String>();
+        let addr = self.id.addr.trim_start_matches("0x");
+        format!("0x{}{:0>64}", creation_num, addr)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct EventHandle {
+    #[serde(deserialize_with = "types::deserialize_from_string")]
+    pub counter: u64,
+    pub guid: Guid,
+}
+
+/// `0x1::coin::CoinStore<CoinType>`, held by each account for each coin type
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CoinStoreResource {
+    pub coin: Coin,
+    pub deposit_events: EventHandle,
+    pub withdraw_events: EventHandle,
+}
+
+/// `0x1::coin::CoinInfo<CoinType>`, held by the account that created the coin type
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CoinInfoResource {
+    pub name: String,
+    pub symbol: String,
+    /// A u64 in Move, so it's kept as a decimal like the amounts
+    #[serde(deserialize_with = "types::deserialize_from_string")]
+    pub decimals: BigDecimal,
+    #[serde(deserialize_with = "deserialize_option_from_string")]
+    pub supply: MoveOption<BigDecimal>,
+}
+
+#[derive(Debug, Clone)]
+pub enum CoinResource {
+    CoinStore {
+        owner_address: String,
+        coin_type: String,
+        resource: CoinStoreResource,
+    },
+    CoinInfo {
+        creator_address: String,
+        coin_type: String,
+        resource: CoinInfoResource,
+    },
+}
+
+impl CoinResource {
+    /// Returns the coin resource written by the change, if any. Fails if the resource
+    /// doesn't have the expected fields.
+    pub fn from_write_set_change(
+        write_set_change: &APIWriteSetChange,
+    ) -> anyhow::Result<Option<CoinResource>> {
+        let write_resource = match write_set_change {
+            APIWriteSetChange::WriteResource(write_resource) => write_resource,
+            _ => return Ok(None),
+        };
+        let type_str = write_resource.data.typ.to_string();
+        let coin_type = match write_resource.data.typ.generic_type_params.first() {
+            Some(coin_type) => coin_type.to_string(),
+            None => return Ok(None),
+        };
+        let address = write_resource.address.to_string();
+
+        if type_str.starts_with(COIN_STORE_TYPE_PREFIX) {
+            let resource = parse_resource::<CoinStoreResource>(write_resource, &type_str)?;
+            Ok(Some(CoinResource::CoinStore {
+                owner_address: address,
+                coin_type,
+                resource,
+            }))
+        } else if type_str.starts_with(COIN_INFO_TYPE_PREFIX) {
+            let resource = parse_resource::<CoinInfoResource>(write_resource, &type_str)?;
+            Ok(Some(CoinResource::CoinInfo {
+                creator_address: address,
+                coin_type,
+                resource,
+            }))
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+fn parse_resource<T: serde::de::DeserializeOwned>(
+    write_resource: &aptos_rest_client::aptos_api_types::WriteResource,
+    type_str: &str,
+) -> anyhow::Result<T> {
+    serde_json::to_value(&write_resource.data.data)
+        .and_then(serde_json::from_value)
+        .with_context(|| {
+            format!(
+                "Failed to parse {} at address {}",
+                type_str, write_resource.address
+            )
+        })
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CoinEventType {
+    #[serde(deserialize_with = "types::deserialize_from_string")]
+    pub amount: BigDecimal,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub enum CoinEvent {
+    DepositEvent(CoinEventType),
+    WithdrawEvent(CoinEventType),
+}
+
+impl CoinEvent {
+    /// Returns the coin event, if the event is one. Fails if the event doesn't have
+    /// the expected fields.
+    pub fn from_event(event: &Event) -> anyhow::Result<Option<CoinEvent>> {
+        let parse = || {
+            serde_json::from_value::<CoinEventType>(event.data.clone()).with_context(|| {
+                format!(
+                    "Failed to parse {} {} at sequence number {}",
+                    event.type_, event.key, event.sequence_number
+                )
+            })
+        };
+        match event.type_.as_str() {
+            DEPOSIT_EVENT_TYPE => Ok(Some(CoinEvent::DepositEvent(parse()?))),
+            WITHDRAW_EVENT_TYPE => Ok(Some(CoinEvent::WithdrawEvent(parse()?))),
+            _ => Ok(None),
+        }
+    }
+
+    pub fn amount(&self) -> &BigDecimal {
+        match self {
+            CoinEvent::DepositEvent(event) | CoinEvent::WithdrawEvent(event) => &event.amount,
+        }
+    }
+
+    pub fn type_str(&self) -> &'static str {
+        match self {
+            CoinEvent::DepositEvent(_) => DEPOSIT_EVENT_TYPE,
+            CoinEvent::WithdrawEvent(_) => WITHDRAW_EVENT_TYPE,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_guid_to_event_key() {
+        let guid = Guid {
+            id: GuidId {
+                addr: "0x1".to_string(),
+                creation_num: 2,
+            },
+        };
+        assert_eq!(
+            guid.to_event_key(),
+            "0x02000000000000000000000000000000000000000000000000000000000000000000000000000001"
+        );
+    }
+
+    #[test]
+    fn test_coin_info_resource() {
+        let data = serde_json::json!({
+            "name": "Aptos Coin",
+            "symbol": "APT",
+            "decimals": "8",
+            "supply": {"vec": ["18446744073709551616"]},
+        });
+        let resource = serde_json::from_value::<CoinInfoResource>(data).unwrap();
+        assert_eq!(resource.decimals, BigDecimal::from(8));
+        assert_eq!(
+            resource.supply.value.unwrap().to_string(),
+            "18446744073709551616"
+        );
+
+        let data = serde_json::json!({
+            "name": "Aptos Coin",
+            "symbol": "APT",
+            "decimals": "8",
+            "supply": {"vec": []},
+        });
+        let resource = serde_json::from_value::<CoinInfoResource>(data).unwrap();
+        assert!(resource.supply.value.is_none());
+
+        // Decimals are a u64 in Move, so they may not fit an i32 or i64
+        let data = serde_json::json!({
+            "name": "Odd Coin",
+            "symbol": "ODD",
+            "decimals": "18446744073709551615",
+            "supply": {"vec": []},
+        });
+        let resource = serde_json::from_value::<CoinInfoResource>(data).unwrap();
+        assert_eq!(resource.decimals.to_string(), "18446744073709551615");
+    }
+
+    #[test]
+    fn test_coin_event() {
+        let mut event = Event {
+            transaction_hash: "0x0".to_string(),
+            key: "0x0".to_string(),
+            sequence_number: 0,
+            type_: DEPOSIT_EVENT_TYPE.to_string(),
+            data: serde_json::json!({"amount": "100"}),
+            inserted_at: chrono::NaiveDateTime::from_timestamp(0, 0),
+        };
+        let coin_event = CoinEvent::from_event(&event).unwrap().unwrap();
+        assert_eq!(coin_event.amount(), &BigDecimal::from(100));
+
+        // Malformed events are reported instead of panicking
+        event.data = serde_json::json!({"amount": "not a number"});
+        assert!(CoinEvent::from_event(&event).is_err());
+
+        // Other events are ignored
+        event.type_ = "0x1::block::NewBlockEvent".to_string();
+        assert!(CoinEvent::from_event(&event).unwrap().is_none());
+    }
+}
@@ -0,0 +1,24 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::schema::coin_activities;
+use bigdecimal::BigDecimal;
+use serde::Serialize;
+
+/// A deposit into or withdrawal from a `CoinStore`
+#[derive(Debug, Identifiable, Insertable, Queryable, Serialize, Clone)]
+#[diesel(table_name = "coin_activities")]
+#[primary_key(event_key, sequence_number)]
+pub struct CoinActivity {
+    pub event_key: String,
+    pub sequence_number: i64,
+    pub transaction_version: i64,
+    pub owner_address: String,
+    pub coin_type: String,
+    pub amount: BigDecimal,
+    pub activity_type: String,
+    /// The account on the other side of the transfer, if it can be identified
+    pub counterparty_address: Option<String>,
+    pub transaction_timestamp: chrono::NaiveDateTime,
+    pub inserted_at: chrono::NaiveDateTime,
+}
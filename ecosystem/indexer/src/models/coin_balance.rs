@@ -0,0 +1,38 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::schema::coin_balances;
+use bigdecimal::BigDecimal;
+use serde::Serialize;
+
+/// The balance of a `CoinStore` after the transaction at `transaction_version`
+#[derive(Debug, Identifiable, Insertable, Queryable, Serialize, Clone)]
+#[diesel(table_name = "coin_balances")]
+#[primary_key(transaction_version, owner_address, coin_type)]
+pub struct CoinBalance {
+    pub transaction_version: i64,
+    pub owner_address: String,
+    pub coin_type: String,
+    pub amount: BigDecimal,
+    pub transaction_timestamp: chrono::NaiveDateTime,
+    pub inserted_at: chrono::NaiveDateTime,
+}
+
+impl CoinBalance {
+    pub fn new(
+        transaction_version: i64,
+        owner_address: String,
+        coin_type: String,
+        amount: BigDecimal,
+        transaction_timestamp: chrono::NaiveDateTime,
+    ) -> Self {
+        Self {
+            transaction_version,
+            owner_address,
+            coin_type,
+            amount,
+            transaction_timestamp,
+            inserted_at: chrono::Utc::now().naive_utc(),
+        }
+    }
+}
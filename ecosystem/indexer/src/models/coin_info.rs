@@ -0,0 +1,42 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{models::coin::CoinInfoResource, schema::coin_infos};
+use bigdecimal::BigDecimal;
+use serde::Serialize;
+
+#[derive(AsChangeset, Debug, Identifiable, Insertable, Queryable, Serialize, Clone)]
+#[diesel(table_name = "coin_infos")]
+#[primary_key(coin_type)]
+pub struct CoinInfo {
+    pub coin_type: String,
+    pub creator_address: String,
+    pub name: String,
+    pub symbol: String,
+    pub decimals: BigDecimal,
+    pub supply: Option<BigDecimal>,
+    pub transaction_version_created: i64,
+    pub last_transaction_version: i64,
+    pub inserted_at: chrono::NaiveDateTime,
+}
+
+impl CoinInfo {
+    pub fn from_resource(
+        coin_type: String,
+        creator_address: String,
+        resource: CoinInfoResource,
+        transaction_version: i64,
+    ) -> Self {
+        Self {
+            coin_type,
+            creator_address,
+            name: resource.name,
+            symbol: resource.symbol,
+            decimals: resource.decimals,
+            supply: resource.supply.value,
+            transaction_version_created: transaction_version,
+            last_transaction_version: transaction_version,
+            inserted_at: chrono::Utc::now().naive_utc(),
+        }
+    }
+}
@@ -1,6 +1,10 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
+pub mod coin;
+pub mod coin_activity;
+pub mod coin_balance;
+pub mod coin_info;
 pub mod collection;
 pub mod events;
 pub mod metadata;
@@ -27,23 +27,43 @@ impl ProcessorStatus {
         }
     }
 
-    pub fn from_processing_result_ok(processing_result: &ProcessingResult) -> Self {
-        Self::new(
+    /// Returns a status for each version in the (inclusive) range, so that there are no gaps
+    pub fn for_version_range(
+        name: &'static str,
+        start_version: u64,
+        end_version: u64,
+        success: bool,
+        details: Option<String>,
+    ) -> Vec<Self> {
+        (start_version..=end_version)
+            .map(|version| Self::new(name, version as i64, success, details.clone()))
+            .collect()
+    }
+
+    pub fn from_processing_result_ok(processing_result: &ProcessingResult) -> Vec<Self> {
+        Self::for_version_range(
             processing_result.name,
-            processing_result.version as i64,
+            processing_result.start_version,
+            processing_result.end_version,
             true,
             None,
         )
     }
 
-    pub fn from_transaction_processing_err(tpe: &TransactionProcessingError) -> Self {
-        let (error, version, name) = tpe.inner();
+    pub fn from_transaction_processing_err(tpe: &TransactionProcessingError) -> Vec<Self> {
+        let (error, start_version, end_version, name) = tpe.inner();
 
-        Self::new(name, *version as i64, false, Some(error.to_string()))
+        Self::for_version_range(
+            name,
+            *start_version,
+            *end_version,
+            false,
+            Some(error.to_string()),
+        )
     }
 
-    pub fn for_mark_started(name: &'static str, version: i64) -> Self {
-        Self::new(name, version, false, None)
+    pub fn for_mark_started(name: &'static str, start_version: u64, end_version: u64) -> Vec<Self> {
+        Self::for_version_range(name, start_version, end_version, false, None)
     }
 }
 
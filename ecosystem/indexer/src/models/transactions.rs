@@ -91,6 +91,19 @@ impl Transaction {
         Ok(result)
     }
 
+    /// Returns the (version, hash) of the stored transactions in the (inclusive) range
+    pub fn get_hashes_in_range(
+        start_version: u64,
+        end_version: u64,
+        connection: &PgPoolConnection,
+    ) -> diesel::QueryResult<Vec<(i64, String)>> {
+        transactions::table
+            .select((transactions::version, transactions::hash))
+            .filter(transactions::version.between(start_version as i64, end_version as i64))
+            .order(transactions::version.asc())
+            .load::<(i64, String)>(connection)
+    }
+
     pub fn get_by_version(
         version: u64,
         connection: &PgPoolConnection,
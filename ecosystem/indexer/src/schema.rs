@@ -16,6 +16,46 @@ table! {
     }
 }
 
+table! {
+    coin_activities (event_key, sequence_number) {
+        event_key -> Varchar,
+        sequence_number -> Int8,
+        transaction_version -> Int8,
+        owner_address -> Varchar,
+        coin_type -> Varchar,
+        amount -> Numeric,
+        activity_type -> Varchar,
+        counterparty_address -> Nullable<Varchar>,
+        transaction_timestamp -> Timestamp,
+        inserted_at -> Timestamp,
+    }
+}
+
+table! {
+    coin_balances (transaction_version, owner_address, coin_type) {
+        transaction_version -> Int8,
+        owner_address -> Varchar,
+        coin_type -> Varchar,
+        amount -> Numeric,
+        transaction_timestamp -> Timestamp,
+        inserted_at -> Timestamp,
+    }
+}
+
+table! {
+    coin_infos (coin_type) {
+        coin_type -> Varchar,
+        creator_address -> Varchar,
+        name -> Varchar,
+        symbol -> Varchar,
+        decimals -> Numeric,
+        supply -> Nullable<Numeric>,
+        transaction_version_created -> Int8,
+        last_transaction_version -> Int8,
+        inserted_at -> Timestamp,
+    }
+}
+
 table! {
     collections (collection_id) {
         collection_id -> Varchar,
@@ -156,6 +196,9 @@ table! {
 
 allow_tables_to_appear_in_same_query!(
     block_metadata_transactions,
+    coin_activities,
+    coin_balances,
+    coin_infos,
     collections,
     events,
     metadatas,
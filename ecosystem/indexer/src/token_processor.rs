@@ -185,22 +185,23 @@ impl TransactionProcessor for TokenTransactionProcessor {
         "token_processor"
     }
 
-    async fn process_transaction(
+    async fn process_transactions(
         &self,
-        transaction: Arc<Transaction>,
+        transactions: Vec<Arc<Transaction>>,
+        start_version: u64,
+        end_version: u64,
     ) -> Result<ProcessingResult, TransactionProcessingError> {
-        let version = transaction.version().unwrap_or(0);
-
-        let (_, maybe_details_model, maybe_events, _) =
-            TransactionModel::from_transaction(&transaction);
-
         let conn = self.get_conn();
         let mut token_uris: Vec<(String, String)> = vec![];
 
         let tx_result = conn.transaction::<(), diesel::result::Error, _>(|| {
-            if let Some(Either::Left(user_txn)) = maybe_details_model {
-                if let Some(events) = maybe_events {
-                    process_token_on_chain_data(&conn, &events, &user_txn, &mut token_uris);
+            for transaction in &transactions {
+                let (_, maybe_details_model, maybe_events, _) =
+                    TransactionModel::from_transaction(transaction);
+                if let Some(Either::Left(user_txn)) = maybe_details_model {
+                    if let Some(events) = maybe_events {
+                        process_token_on_chain_data(&conn, &events, &user_txn, &mut token_uris);
+                    }
                 }
             }
             Ok(())
@@ -209,7 +210,8 @@ impl TransactionProcessor for TokenTransactionProcessor {
         if let Err(err) = tx_result {
             return Err(TransactionProcessingError::TransactionCommitError((
                 anyhow::Error::from(err),
-                version,
+                start_version,
+                end_version,
                 self.name(),
             )));
         };
@@ -229,10 +231,15 @@ impl TransactionProcessor for TokenTransactionProcessor {
             Ok(())
         });
         match tx_result {
-            Ok(_) => Ok(ProcessingResult::new(self.name(), version)),
+            Ok(_) => Ok(ProcessingResult::new(
+                self.name(),
+                start_version,
+                end_version,
+            )),
             Err(err) => Err(TransactionProcessingError::TransactionCommitError((
                 anyhow::Error::from(err),
-                version,
+                start_version,
+                end_version,
                 self.name(),
             ))),
         }
@@ -334,6 +334,14 @@ impl ParkingLotIndex {
             .map_or(false, |(_account, txns)| txns.contains(seq_num))
     }
 
+    /// Returns the number of "non-ready" transactions of the account.
+    pub(crate) fn account_size(&self, account: &AccountAddress) -> usize {
+        self.account_indices
+            .get(account)
+            .and_then(|idx| self.data.get(*idx))
+            .map_or(0, |(_account, txns)| txns.len())
+    }
+
     /// Returns a random "non-ready" transaction (with highest sequence number for that account).
     pub(crate) fn get_poppable(&self) -> Option<TxnPointer> {
         let mut rng = rand::thread_rng();
@@ -6,7 +6,8 @@
 use crate::{
     core_mempool::{
         index::TxnPointer,
-        transaction::{MempoolTransaction, TimelineState},
+        transaction::{MempoolStats, MempoolTransaction, PendingTransactionInfo, TimelineState},
+        transaction_filter::TransactionFilter,
         transaction_store::TransactionStore,
         ttl_cache::TtlCache,
     },
@@ -31,6 +32,8 @@ use std::{
 pub struct Mempool {
     // Stores the metadata of all transactions in mempool (of all states).
     transactions: TransactionStore,
+    // Rejects transactions according to the node's filter configuration.
+    filter: TransactionFilter,
 
     sequence_number_cache: TtlCache<AccountAddress, u64>,
     // For each transaction, an entry with a timestamp is added when the transaction enters mempool.
@@ -44,6 +47,7 @@ impl Mempool {
     pub fn new(config: &NodeConfig) -> Self {
         Mempool {
             transactions: TransactionStore::new(&config.mempool),
+            filter: TransactionFilter::new(config.mempool.transaction_filter.clone()),
             sequence_number_cache: TtlCache::new(config.mempool.capacity, Duration::from_secs(100)),
             metrics_cache: TtlCache::new(config.mempool.capacity, Duration::from_secs(100)),
             system_transaction_timeout: Duration::from_secs(
@@ -124,7 +128,7 @@ impl Mempool {
     }
 
     /// Used to add a transaction to the Mempool.
-    /// Performs basic validation: checks account's sequence number.
+    /// Performs basic validation: checks the transaction filter and account's sequence number.
     pub(crate) fn add_txn(
         &mut self,
         txn: SignedTransaction,
@@ -138,6 +142,10 @@ impl Mempool {
                 .txns(TxnsLog::new_txn(txn.sender(), txn.sequence_number())),
             committed_seq_number = db_sequence_number
         );
+        if let Err(status) = self.filter.check(&txn) {
+            return status;
+        }
+
         let cached_value = self.sequence_number_cache.get(&txn.sender());
         let sequence_number = match crsn_or_seqno {
             AccountSequenceInfo::CRSN { .. } => crsn_or_seqno,
@@ -294,6 +302,30 @@ impl Mempool {
         self.transactions.timeline_range(start_id, end_id)
     }
 
+    /// Returns the gas unit prices of up to `count` ready transactions, highest priority first.
+    pub(crate) fn get_gas_prices(&self, count: usize) -> Vec<u64> {
+        self.transactions
+            .iter_queue()
+            .take(count)
+            .map(|key| key.gas_ranking_score)
+            .collect()
+    }
+
+    /// Returns the transactions of `address` in mempool, ordered by sequence number.
+    pub(crate) fn get_account_transactions(
+        &self,
+        address: &AccountAddress,
+    ) -> Vec<PendingTransactionInfo> {
+        self.transactions
+            .get_account_txns(address, &self.metrics_cache)
+    }
+
+    /// Returns aggregate counts of the transactions in mempool, including the
+    /// counts of every account (see `MempoolStats::retain_top_accounts`).
+    pub(crate) fn get_stats(&self) -> MempoolStats {
+        self.transactions.get_stats()
+    }
+
     pub fn gen_snapshot(&self) -> TxnsLog {
         self.transactions.gen_snapshot(&self.metrics_cache)
     }
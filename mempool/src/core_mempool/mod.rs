@@ -4,9 +4,15 @@
 mod index;
 mod mempool;
 mod transaction;
+mod transaction_filter;
 mod transaction_store;
 mod ttl_cache;
 
 #[cfg(test)]
 pub use self::ttl_cache::TtlCache;
-pub use self::{index::TxnPointer, mempool::Mempool as CoreMempool, transaction::TimelineState};
+pub use self::{
+    index::TxnPointer,
+    mempool::Mempool as CoreMempool,
+    transaction::{AccountMempoolStats, MempoolStats, PendingTransactionInfo, TimelineState},
+    transaction_filter::TransactionFilter,
+};
@@ -7,7 +7,10 @@ use aptos_types::{
     transaction::SignedTransaction,
 };
 use serde::{Deserialize, Serialize};
-use std::time::Duration;
+use std::{
+    cmp::Reverse,
+    time::{Duration, SystemTime},
+};
 
 #[derive(Clone, Debug)]
 pub struct MempoolTransaction {
@@ -61,6 +64,60 @@ pub enum TimelineState {
     NonQualified,
 }
 
+/// A transaction waiting in mempool, as reported to clients inspecting mempool.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PendingTransactionInfo {
+    pub txn: SignedTransaction,
+    // Whether the transaction is in the ParkingLotIndex, i.e. can't be included in the next block.
+    pub parked: bool,
+    // Position in the TimelineIndex, if the transaction is ready for broadcast.
+    pub timeline_id: Option<u64>,
+    // Position in the PriorityIndex (0 is picked first by consensus), if the transaction is ready
+    // and near enough to the top of the index.
+    pub priority_index_position: Option<usize>,
+    // Time the transaction entered mempool, if it's still tracked in the metrics cache.
+    pub insertion_time: Option<SystemTime>,
+}
+
+/// Number of transactions of an account in mempool.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AccountMempoolStats {
+    pub address: AccountAddress,
+    pub ready_txns: usize,
+    pub parked_txns: usize,
+}
+
+/// Aggregate counts of the transactions in mempool.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MempoolStats {
+    pub total_txns: usize,
+    pub ready_txns: usize,
+    pub parked_txns: usize,
+    pub num_accounts: usize,
+    pub capacity: usize,
+    pub capacity_per_user: usize,
+    // Accounts with the most transactions in mempool, most first.
+    pub top_accounts: Vec<AccountMempoolStats>,
+}
+
+impl MempoolStats {
+    /// Keeps only the `count` accounts with the most transactions, most first (ties are broken by
+    /// address). This doesn't need the mempool lock, so it's done once the lock is released.
+    pub(crate) fn retain_top_accounts(&mut self, count: usize) {
+        let key = |account: &AccountMempoolStats| {
+            (
+                Reverse(account.ready_txns + account.parked_txns),
+                account.address,
+            )
+        };
+        if self.top_accounts.len() > count {
+            self.top_accounts.select_nth_unstable_by_key(count, key);
+            self.top_accounts.truncate(count);
+        }
+        self.top_accounts.sort_by_key(key);
+    }
+}
+
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
 pub struct SequenceInfo {
     pub transaction_sequence_number: u64,
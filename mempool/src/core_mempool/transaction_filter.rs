@@ -0,0 +1,125 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+//! Filters the transactions accepted into mempool according to the node's
+//! `TransactionFilterConfig`.
+
+use crate::counters;
+use aptos_config::config::{
+    TransactionFilterConfig, TransactionFilterRule, TransactionPayloadType,
+};
+use aptos_types::{
+    mempool_status::{MempoolStatus, MempoolStatusCode},
+    transaction::{SignedTransaction, TransactionPayload},
+};
+
+/// Rule label used when a transaction doesn't match any of the allow rules.
+const NOT_ALLOWED_LABEL: &str = "not_allowed";
+
+pub struct TransactionFilter {
+    config: TransactionFilterConfig,
+}
+
+impl TransactionFilter {
+    pub fn new(config: TransactionFilterConfig) -> Self {
+        Self { config }
+    }
+
+    /// Returns an error status if the transaction is rejected by the filter, and records
+    /// the rejecting rule in the metrics.
+    pub fn check(&self, txn: &SignedTransaction) -> Result<(), MempoolStatus> {
+        if self.config.is_empty() {
+            return Ok(());
+        }
+
+        match self.rejecting_rule(txn) {
+            None => Ok(()),
+            Some(rule) => {
+                counters::TRANSACTION_FILTER_REJECTED_COUNT
+                    .with_label_values(&[&rule])
+                    .inc();
+                Err(MempoolStatus::new(MempoolStatusCode::RejectedByFilter)
+                    .with_message(format!("transaction is rejected by filter rule {}", rule)))
+            }
+        }
+    }
+
+    /// Returns the label of the rule rejecting the transaction, if any.
+    fn rejecting_rule(&self, txn: &SignedTransaction) -> Option<String> {
+        if let Some((idx, rule)) = self
+            .config
+            .deny
+            .iter()
+            .enumerate()
+            .find(|(_, rule)| rule_matches(rule, txn))
+        {
+            return Some(rule_label(rule, "deny", idx));
+        }
+
+        if !self.config.allow.is_empty()
+            && !self.config.allow.iter().any(|rule| rule_matches(rule, txn))
+        {
+            return Some(NOT_ALLOWED_LABEL.to_string());
+        }
+
+        None
+    }
+}
+
+fn rule_label(rule: &TransactionFilterRule, list: &str, idx: usize) -> String {
+    if rule.name.is_empty() {
+        format!("{}_{}", list, idx)
+    } else {
+        rule.name.clone()
+    }
+}
+
+fn rule_matches(rule: &TransactionFilterRule, txn: &SignedTransaction) -> bool {
+    if let Some(sender) = rule.sender {
+        if txn.sender() != sender {
+            return false;
+        }
+    }
+
+    let payload = txn.payload();
+    if let Some(payload_type) = rule.payload_type {
+        if payload_type != get_payload_type(payload) {
+            return false;
+        }
+    }
+
+    // Module and function filters only match entry function calls
+    if rule.module_address.is_some() || rule.module_name.is_some() || rule.function.is_some() {
+        let script_function = match payload {
+            TransactionPayload::ScriptFunction(script_function) => script_function,
+            _ => return false,
+        };
+        let module = script_function.module();
+        if let Some(module_address) = rule.module_address {
+            if *module.address() != module_address {
+                return false;
+            }
+        }
+        if let Some(module_name) = &rule.module_name {
+            if module.name().as_str() != module_name {
+                return false;
+            }
+        }
+        if let Some(function) = &rule.function {
+            if script_function.function().as_str() != function {
+                return false;
+            }
+        }
+    }
+
+    true
+}
+
+fn get_payload_type(payload: &TransactionPayload) -> TransactionPayloadType {
+    match payload {
+        TransactionPayload::ScriptFunction(_) => TransactionPayloadType::EntryFunction,
+        TransactionPayload::ModuleBundle(_) => TransactionPayloadType::ModuleBundle,
+        TransactionPayload::Script(_) => TransactionPayloadType::Script,
+        TransactionPayload::WriteSet(_) => TransactionPayloadType::WriteSet,
+    }
+}
@@ -7,7 +7,10 @@ use crate::{
             AccountTransactions, ParkingLotIndex, PriorityIndex, PriorityQueueIter, TTLIndex,
             TimelineIndex,
         },
-        transaction::{MempoolTransaction, TimelineState},
+        transaction::{
+            AccountMempoolStats, MempoolStats, MempoolTransaction, PendingTransactionInfo,
+            TimelineState,
+        },
         ttl_cache::TtlCache,
     },
     counters,
@@ -28,6 +31,10 @@ use std::{
     time::{Duration, SystemTime},
 };
 
+/// Transactions further down the priority index aren't given a position when inspecting mempool,
+/// so an inspection request can't walk the whole index.
+const MAX_PRIORITY_INDEX_POSITION: usize = 10_000;
+
 /// TransactionStore is in-memory storage for all transactions in mempool.
 pub struct TransactionStore {
     // main DS
@@ -55,6 +62,7 @@ pub struct TransactionStore {
     // configuration
     capacity: usize,
     capacity_per_user: usize,
+    replace_by_fee_min_gas_price_bump_pct: u64,
 }
 
 impl TransactionStore {
@@ -76,6 +84,7 @@ impl TransactionStore {
             // configuration
             capacity: config.capacity,
             capacity_per_user: config.capacity_per_user,
+            replace_by_fee_min_gas_price_bump_pct: config.replace_by_fee_min_gas_price_bump_pct,
         }
     }
 
@@ -119,44 +128,52 @@ impl TransactionStore {
         let address = txn.get_sender();
         let sequence_number = txn.sequence_info;
 
-        // If the transaction is already in Mempool, we only allow the user to
-        // increase the gas unit price to speed up a transaction, but not the max gas.
+        // If the transaction is already in Mempool, it can only be replaced by a transaction
+        // paying a high enough gas unit price (replace-by-fee). The replacement gets a new
+        // position in the timeline, so it is broadcast again to peers.
         //
         // Transactions with all the same inputs (but possibly signed differently) are idempotent
         // since the raw transaction is the same
+        let min_gas_price_bump_pct = self.replace_by_fee_min_gas_price_bump_pct;
         if let Some(txns) = self.transactions.get_mut(&address) {
-            if let Some(current_version) =
-                txns.get_mut(&sequence_number.transaction_sequence_number)
-            {
-                if current_version.txn.payload() != txn.txn.payload() {
-                    return MempoolStatus::new(MempoolStatusCode::InvalidUpdate).with_message(
-                        "Transaction already in mempool with different payload".to_string(),
-                    );
-                } else if current_version.txn.expiration_timestamp_secs()
-                    != txn.txn.expiration_timestamp_secs()
+            if let Some(current_version) = txns.get(&sequence_number.transaction_sequence_number) {
+                let current_gas_price = current_version.get_gas_price();
+                if current_version.txn.payload() == txn.txn.payload()
+                    && current_version.txn.expiration_timestamp_secs()
+                        == txn.txn.expiration_timestamp_secs()
+                    && current_version.txn.max_gas_amount() == txn.txn.max_gas_amount()
+                    && current_gas_price == txn.get_gas_price()
                 {
-                    return MempoolStatus::new(MempoolStatusCode::InvalidUpdate).with_message(
-                        "Transaction already in mempool with different expiration timestamp"
-                            .to_string(),
-                    );
-                } else if current_version.txn.max_gas_amount() != txn.txn.max_gas_amount() {
-                    return MempoolStatus::new(MempoolStatusCode::InvalidUpdate).with_message(
-                        "Transaction already in mempool with different max gas amount".to_string(),
-                    );
-                } else if current_version.txn.gas_unit_price() < txn.get_gas_price() {
-                    // Update txn if gas unit price is a larger value than before
-                    if let Some(txn) = txns.remove(&sequence_number.transaction_sequence_number) {
-                        self.index_remove(&txn);
-                    };
-                } else if current_version.get_gas_price() > txn.get_gas_price() {
-                    return MempoolStatus::new(MempoolStatusCode::InvalidUpdate).with_message(
-                        "Transaction already in mempool with higher gas price".to_string(),
-                    );
-                } else {
                     // If the transaction is the same, it's an idempotent call
                     // Updating signers is not supported, the previous submission must fail
                     return MempoolStatus::new(MempoolStatusCode::Accepted);
                 }
+
+                let min_gas_price =
+                    min_replacement_gas_price(current_gas_price, min_gas_price_bump_pct);
+                if txn.get_gas_price() < min_gas_price {
+                    return MempoolStatus::new(MempoolStatusCode::InvalidUpdate).with_message(
+                        format!(
+                            "Transaction already in mempool with gas unit price {}, replacing it requires a gas unit price of at least {}",
+                            current_gas_price, min_gas_price,
+                        ),
+                    );
+                }
+
+                if let Some(current_version) =
+                    txns.remove(&sequence_number.transaction_sequence_number)
+                {
+                    debug!(
+                        LogSchema::new(LogEntry::ReplaceTxn).txns(TxnsLog::new_txn(
+                            address,
+                            sequence_number.transaction_sequence_number
+                        )),
+                        old_gas_price = current_gas_price,
+                        new_gas_price = txn.get_gas_price(),
+                    );
+                    counters::CORE_MEMPOOL_REPLACED_TXNS.inc();
+                    self.index_remove(&current_version);
+                }
             }
         }
 
@@ -579,8 +596,88 @@ impl TransactionStore {
         txns_log
     }
 
+    /// Returns the transactions of `address` in mempool, ordered by sequence number.
+    pub(crate) fn get_account_txns(
+        &self,
+        address: &AccountAddress,
+        metrics_cache: &TtlCache<(AccountAddress, u64), SystemTime>,
+    ) -> Vec<PendingTransactionInfo> {
+        let txns = match self.transactions.get(address) {
+            Some(txns) => txns,
+            None => return vec![],
+        };
+
+        // Finding positions means walking the priority index, so only the top of it is looked at
+        // and the walk stops as soon as all the account's ready transactions have been seen.
+        let num_ready_txns = txns.len() - self.parking_lot_index.account_size(address);
+        let mut priority_index_positions = HashMap::new();
+        for (position, key) in self
+            .priority_index
+            .iter()
+            .take(MAX_PRIORITY_INDEX_POSITION)
+            .enumerate()
+        {
+            if priority_index_positions.len() == num_ready_txns {
+                break;
+            }
+            if &key.address == address {
+                priority_index_positions
+                    .insert(key.sequence_number.transaction_sequence_number, position);
+            }
+        }
+
+        txns.iter()
+            .map(|(seq_num, txn)| PendingTransactionInfo {
+                txn: txn.txn.clone(),
+                parked: self.parking_lot_index.contains(address, seq_num),
+                timeline_id: match txn.timeline_state {
+                    TimelineState::Ready(timeline_id) => Some(timeline_id),
+                    _ => None,
+                },
+                priority_index_position: priority_index_positions.remove(seq_num),
+                insertion_time: metrics_cache.get(&(*address, *seq_num)).cloned(),
+            })
+            .collect()
+    }
+
+    /// Returns aggregate counts of the transactions in mempool, with the counts of every account
+    /// in `top_accounts`. Only constant time work is done per account, picking the top accounts
+    /// is left to `MempoolStats::retain_top_accounts` once the mempool lock is released.
+    pub(crate) fn get_stats(&self) -> MempoolStats {
+        let accounts: Vec<_> = self
+            .transactions
+            .iter()
+            .filter(|(_, txns)| !txns.is_empty())
+            .map(|(address, txns)| {
+                let parked_txns = self.parking_lot_index.account_size(address);
+                AccountMempoolStats {
+                    address: *address,
+                    ready_txns: txns.len().saturating_sub(parked_txns),
+                    parked_txns,
+                }
+            })
+            .collect();
+
+        MempoolStats {
+            total_txns: self.system_ttl_index.size(),
+            ready_txns: self.priority_index.size(),
+            parked_txns: self.parking_lot_index.size(),
+            num_accounts: accounts.len(),
+            capacity: self.capacity,
+            capacity_per_user: self.capacity_per_user,
+            top_accounts: accounts,
+        }
+    }
+
     #[cfg(test)]
     pub(crate) fn get_parking_lot_size(&self) -> usize {
         self.parking_lot_index.size()
     }
 }
+
+/// Minimum gas unit price for a transaction to replace a pending transaction paying
+/// `current_gas_price`. The replacement always has to pay strictly more.
+fn min_replacement_gas_price(current_gas_price: u64, min_gas_price_bump_pct: u64) -> u64 {
+    let bump = current_gas_price as u128 * min_gas_price_bump_pct as u128 / 100;
+    (current_gas_price as u128 + bump.max(1)).min(u64::MAX as u128) as u64
+}
@@ -52,6 +52,9 @@ pub const SUCCESS_LABEL: &str = "success";
 // Bounded executor task labels
 pub const CLIENT_EVENT_LABEL: &str = "client_event";
 pub const CLIENT_EVENT_GET_TXN_LABEL: &str = "client_event_get_txn";
+pub const CLIENT_EVENT_GET_GAS_PRICES_LABEL: &str = "client_event_get_gas_prices";
+pub const CLIENT_EVENT_GET_ACCOUNT_TXNS_LABEL: &str = "client_event_get_account_txns";
+pub const CLIENT_EVENT_GET_STATS_LABEL: &str = "client_event_get_stats";
 pub const RECONFIG_EVENT_LABEL: &str = "reconfig";
 pub const PEER_BROADCAST_EVENT_LABEL: &str = "peer_broadcast";
 
@@ -101,6 +104,25 @@ pub static CORE_MEMPOOL_REMOVED_TXNS: Lazy<IntCounter> = Lazy::new(|| {
     .unwrap()
 });
 
+/// Counter tracking number of txns replaced in core mempool by a txn with a higher gas price
+pub static CORE_MEMPOOL_REPLACED_TXNS: Lazy<IntCounter> = Lazy::new(|| {
+    register_int_counter!(
+        "aptos_core_mempool_replaced_txns_count",
+        "Number of txns replaced in core mempool by a txn with a higher gas price"
+    )
+    .unwrap()
+});
+
+/// Counter tracking number of txns rejected by the transaction filter, by rule
+pub static TRANSACTION_FILTER_REJECTED_COUNT: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "aptos_mempool_transaction_filter_rejected_count",
+        "Number of txns rejected by the transaction filter",
+        &["rule"]
+    )
+    .unwrap()
+});
+
 /// Counter tracking latency of txns reaching various stages in committing
 /// (e.g. time from txn entering core mempool to being pulled in consensus block)
 pub static CORE_MEMPOOL_TXN_COMMIT_LATENCY: Lazy<HistogramVec> = Lazy::new(|| {
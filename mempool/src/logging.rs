@@ -130,6 +130,9 @@ pub enum LogEntry {
     ReconfigUpdate,
     JsonRpc,
     GetTransaction,
+    GetGasPrices,
+    GetAccountTransactions,
+    GetStats,
     GetBlock,
     QuorumStore,
     StateSyncCommit,
@@ -139,6 +142,7 @@ pub enum LogEntry {
     InvariantViolated,
     AddTxn,
     RemoveTxn,
+    ReplaceTxn,
     MempoolFullEvictedTxn,
     GCRemoveTxns,
     CleanCommittedTxn,
@@ -148,6 +148,69 @@ async fn handle_client_request<V>(
                 ))
                 .await;
         }
+        MempoolClientRequest::GetGasPrices(count, callback) => {
+            // This timer measures how long it took for the bounded executor to *schedule* the
+            // task.
+            let _timer = counters::task_spawn_latency_timer(
+                counters::CLIENT_EVENT_GET_GAS_PRICES_LABEL,
+                counters::SPAWN_LABEL,
+            );
+            // This timer measures how long it took for the task to go from scheduled to started.
+            let task_start_timer = counters::task_spawn_latency_timer(
+                counters::CLIENT_EVENT_GET_GAS_PRICES_LABEL,
+                counters::START_LABEL,
+            );
+            bounded_executor
+                .spawn(tasks::process_client_get_gas_prices(
+                    smp.clone(),
+                    count,
+                    callback,
+                    task_start_timer,
+                ))
+                .await;
+        }
+        MempoolClientRequest::GetAccountTransactions(address, callback) => {
+            // This timer measures how long it took for the bounded executor to *schedule* the
+            // task.
+            let _timer = counters::task_spawn_latency_timer(
+                counters::CLIENT_EVENT_GET_ACCOUNT_TXNS_LABEL,
+                counters::SPAWN_LABEL,
+            );
+            // This timer measures how long it took for the task to go from scheduled to started.
+            let task_start_timer = counters::task_spawn_latency_timer(
+                counters::CLIENT_EVENT_GET_ACCOUNT_TXNS_LABEL,
+                counters::START_LABEL,
+            );
+            bounded_executor
+                .spawn(tasks::process_client_get_account_transactions(
+                    smp.clone(),
+                    address,
+                    callback,
+                    task_start_timer,
+                ))
+                .await;
+        }
+        MempoolClientRequest::GetStats(top_accounts, callback) => {
+            // This timer measures how long it took for the bounded executor to *schedule* the
+            // task.
+            let _timer = counters::task_spawn_latency_timer(
+                counters::CLIENT_EVENT_GET_STATS_LABEL,
+                counters::SPAWN_LABEL,
+            );
+            // This timer measures how long it took for the task to go from scheduled to started.
+            let task_start_timer = counters::task_spawn_latency_timer(
+                counters::CLIENT_EVENT_GET_STATS_LABEL,
+                counters::START_LABEL,
+            );
+            bounded_executor
+                .spawn(tasks::process_client_get_stats(
+                    smp.clone(),
+                    top_accounts,
+                    callback,
+                    task_start_timer,
+                ))
+                .await;
+        }
     }
 }
 
@@ -3,7 +3,7 @@
 
 //! Tasks that are executed by coordinators (short-lived compared to coordinators)
 use crate::{
-    core_mempool::{CoreMempool, TimelineState, TxnPointer},
+    core_mempool::{CoreMempool, MempoolStats, PendingTransactionInfo, TimelineState, TxnPointer},
     counters,
     logging::{LogEntry, LogEvent, LogSchema},
     network::{BroadcastError, MempoolSyncMsg},
@@ -20,6 +20,7 @@ use aptos_infallible::{Mutex, RwLock};
 use aptos_logger::prelude::*;
 use aptos_metrics_core::HistogramTimer;
 use aptos_types::{
+    account_address::AccountAddress,
     mempool_status::{MempoolStatus, MempoolStatusCode},
     on_chain_config::OnChainConfigPayload,
     transaction::SignedTransaction,
@@ -142,6 +143,70 @@ pub(crate) async fn process_client_get_transaction<V>(
     }
 }
 
+/// Processes get gas prices request by client.
+pub(crate) async fn process_client_get_gas_prices<V>(
+    smp: SharedMempool<V>,
+    count: usize,
+    callback: oneshot::Sender<Vec<u64>>,
+    timer: HistogramTimer,
+) where
+    V: TransactionValidation,
+{
+    timer.stop_and_record();
+    let gas_prices = smp.mempool.lock().get_gas_prices(count);
+
+    if callback.send(gas_prices).is_err() {
+        error!(LogSchema::event_log(
+            LogEntry::GetGasPrices,
+            LogEvent::CallbackFail
+        ));
+        counters::CLIENT_CALLBACK_FAIL.inc();
+    }
+}
+
+/// Processes get account transactions request by client.
+pub(crate) async fn process_client_get_account_transactions<V>(
+    smp: SharedMempool<V>,
+    address: AccountAddress,
+    callback: oneshot::Sender<Vec<PendingTransactionInfo>>,
+    timer: HistogramTimer,
+) where
+    V: TransactionValidation,
+{
+    timer.stop_and_record();
+    let txns = smp.mempool.lock().get_account_transactions(&address);
+
+    if callback.send(txns).is_err() {
+        error!(LogSchema::event_log(
+            LogEntry::GetAccountTransactions,
+            LogEvent::CallbackFail
+        ));
+        counters::CLIENT_CALLBACK_FAIL.inc();
+    }
+}
+
+/// Processes get mempool stats request by client.
+pub(crate) async fn process_client_get_stats<V>(
+    smp: SharedMempool<V>,
+    top_accounts: usize,
+    callback: oneshot::Sender<MempoolStats>,
+    timer: HistogramTimer,
+) where
+    V: TransactionValidation,
+{
+    timer.stop_and_record();
+    let mut stats = smp.mempool.lock().get_stats();
+    stats.retain_top_accounts(top_accounts);
+
+    if callback.send(stats).is_err() {
+        error!(LogSchema::event_log(
+            LogEntry::GetStats,
+            LogEvent::CallbackFail
+        ));
+        counters::CLIENT_CALLBACK_FAIL.inc();
+    }
+}
+
 /// Processes transactions from other nodes.
 pub(crate) async fn process_transaction_broadcast<V>(
     smp: SharedMempool<V>,
@@ -233,6 +298,21 @@ where
 {
     let mut statuses = vec![];
 
+    // Reject filtered transactions before reading storage or running validation
+    let transactions: Vec<_> = transactions
+        .into_iter()
+        .filter(|t| match smp.transaction_filter.check(t) {
+            Ok(()) => true,
+            Err(mempool_status) => {
+                statuses.push((t.clone(), (mempool_status, None)));
+                false
+            }
+        })
+        .collect();
+    if transactions.is_empty() {
+        return statuses;
+    }
+
     let start_storage_read = Instant::now();
     // Track latency: fetching seq number
     let seq_numbers = transactions
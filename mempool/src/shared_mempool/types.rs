@@ -3,7 +3,8 @@
 
 //! Objects used by/related to shared mempool
 use crate::{
-    core_mempool::CoreMempool, network::MempoolNetworkInterface,
+    core_mempool::{CoreMempool, MempoolStats, PendingTransactionInfo, TransactionFilter},
+    network::MempoolNetworkInterface,
     shared_mempool::network::MempoolNetworkSender,
 };
 use anyhow::Result;
@@ -14,7 +15,8 @@ use aptos_config::{
 use aptos_crypto::HashValue;
 use aptos_infallible::{Mutex, RwLock};
 use aptos_types::{
-    mempool_status::MempoolStatus, transaction::SignedTransaction, vm_status::DiscardedVMStatus,
+    account_address::AccountAddress, mempool_status::MempoolStatus, transaction::SignedTransaction,
+    vm_status::DiscardedVMStatus,
 };
 use consensus_types::common::TransactionSummary;
 use futures::{
@@ -47,6 +49,7 @@ where
     pub(crate) network_interface: MempoolNetworkInterface,
     pub db: Arc<dyn DbReader>,
     pub validator: Arc<RwLock<V>>,
+    pub transaction_filter: Arc<TransactionFilter>,
     pub subscribers: Vec<UnboundedSender<SharedMempoolNotification>>,
 }
 
@@ -67,12 +70,15 @@ impl<V: TransactionValidation + 'static> SharedMempool<V> {
             role,
             config.clone(),
         );
+        let transaction_filter =
+            Arc::new(TransactionFilter::new(config.transaction_filter.clone()));
         SharedMempool {
             mempool,
             config,
             network_interface,
             db,
             validator,
+            transaction_filter,
             subscribers,
         }
     }
@@ -208,6 +214,13 @@ pub type SubmissionStatusBundle = (SignedTransaction, SubmissionStatus);
 pub enum MempoolClientRequest {
     SubmitTransaction(SignedTransaction, oneshot::Sender<Result<SubmissionStatus>>),
     GetTransactionByHash(HashValue, oneshot::Sender<Option<SignedTransaction>>),
+    /// Gas unit prices of up to the given number of ready transactions, highest priority first
+    GetGasPrices(usize, oneshot::Sender<Vec<u64>>),
+    /// Transactions of the given account, ordered by sequence number
+    GetAccountTransactions(AccountAddress, oneshot::Sender<Vec<PendingTransactionInfo>>),
+    /// Aggregate counts of the transactions in mempool, including the given number of
+    /// accounts with the most transactions
+    GetStats(usize, oneshot::Sender<MempoolStats>),
 }
 
 pub type MempoolClientSender = mpsc::Sender<MempoolClientRequest>;
@@ -2,15 +2,22 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    core_mempool::{CoreMempool, TimelineState, TtlCache},
+    core_mempool::{
+        AccountMempoolStats, CoreMempool, PendingTransactionInfo, TimelineState, TtlCache,
+    },
     tests::common::{
         add_signed_txn, add_txn, add_txns_to_mempool, exist_in_metrics_cache, setup_mempool,
         TestTransaction,
     },
 };
-use aptos_config::config::NodeConfig;
+use aptos_config::config::{
+    NodeConfig, TransactionFilterConfig, TransactionFilterRule, TransactionPayloadType,
+};
 use aptos_crypto::HashValue;
-use aptos_types::{account_config::AccountSequenceInfo, transaction::SignedTransaction};
+use aptos_types::{
+    account_config::AccountSequenceInfo, mempool_status::MempoolStatusCode,
+    transaction::SignedTransaction,
+};
 use std::{
     collections::HashSet,
     time::{Duration, SystemTime},
@@ -265,19 +272,19 @@ fn test_fail_for_same_gas_amount_and_not_same_expiration_time_crsn() {
 }
 
 #[test]
-fn test_update_invalid_transaction_in_mempool() {
+fn test_replace_transaction_with_lower_gas_rejected() {
     let (mut mempool, mut consensus) = setup_mempool();
     let txns = add_txns_to_mempool(
         &mut mempool,
         vec![TestTransaction::new(0, 0, 1), TestTransaction::new(1, 0, 2)],
     );
     let updated_txn = TestTransaction::make_signed_transaction_with_max_gas_amount(
-        &TestTransaction::new(0, 0, 5),
+        &TestTransaction::new(0, 0, 1),
         200,
     );
-    let _added_tnx = add_signed_txn(&mut mempool, updated_txn);
+    assert!(add_signed_txn(&mut mempool, updated_txn).is_err());
 
-    // Since both gas price and mas gas amount were updated, the ordering should not have changed.
+    // Since the gas price wasn't bumped, the transaction should not have been replaced.
     // The second transaction with gas price 2 should come first.
     assert_eq!(consensus.get_block(&mut mempool, 1), vec![txns[1].clone()]);
     let next_tnx = consensus.get_block(&mut mempool, 1);
@@ -286,7 +293,7 @@ fn test_update_invalid_transaction_in_mempool() {
 }
 
 #[test]
-fn test_update_invalid_transaction_in_mempool_crsn() {
+fn test_replace_transaction_with_lower_gas_rejected_crsn() {
     let (mut mempool, mut consensus) = setup_mempool();
     let txns = add_txns_to_mempool(
         &mut mempool,
@@ -296,12 +303,12 @@ fn test_update_invalid_transaction_in_mempool_crsn() {
         ],
     );
     let updated_txn = TestTransaction::make_signed_transaction_with_max_gas_amount(
-        &TestTransaction::new(0, 0, 5).crsn(0),
+        &TestTransaction::new(0, 0, 1).crsn(0),
         200,
     );
-    let _added_tnx = add_signed_txn(&mut mempool, updated_txn);
+    assert!(add_signed_txn(&mut mempool, updated_txn).is_err());
 
-    // Since both gas price and mas gas amount were updated, the ordering should not have changed.
+    // Since the gas price wasn't bumped, the transaction should not have been replaced.
     // The second transaction with gas price 2 should come first.
     assert_eq!(consensus.get_block(&mut mempool, 1), vec![txns[1].clone()]);
     let next_tnx = consensus.get_block(&mut mempool, 1);
@@ -672,3 +679,187 @@ fn test_get_transaction_by_hash_after_the_txn_is_updated() {
     let txn_by_new_hash = pool.get_by_hash(new_txn_hash);
     assert_eq!(txn_by_new_hash, Some(new_txn));
 }
+
+#[test]
+fn test_replace_by_fee() {
+    let mut config = NodeConfig::random();
+    config.mempool.replace_by_fee_min_gas_price_bump_pct = 10;
+    let mut pool = CoreMempool::new(&config);
+    let txns = add_txns_to_mempool(
+        &mut pool,
+        vec![
+            TestTransaction::new(0, 0, 100),
+            TestTransaction::new(1, 0, 50),
+        ],
+    );
+    let (timeline, _) = pool.read_timeline(0, 10);
+    assert_eq!(timeline, txns);
+
+    // The gas price bump is too small
+    let txn = TestTransaction::new(0, 0, 109).make_signed_transaction_with_max_gas_amount(200);
+    let status = pool.add_txn(
+        txn,
+        0,
+        AccountSequenceInfo::Sequential(0),
+        TimelineState::NotReady,
+    );
+    assert_eq!(status.code, MempoolStatusCode::InvalidUpdate);
+
+    // A high enough gas price replaces the txn, even if it's different
+    let replacement =
+        TestTransaction::new(0, 0, 110).make_signed_transaction_with_max_gas_amount(200);
+    add_signed_txn(&mut pool, replacement.clone()).unwrap();
+    assert_eq!(pool.get_by_hash(txns[0].clone().committed_hash()), None);
+    assert_eq!(
+        pool.get_by_hash(replacement.clone().committed_hash()),
+        Some(replacement.clone())
+    );
+
+    // The replacement is ordered by its own gas price, and is added to the end of the
+    // timeline so it's broadcast again
+    let (timeline, _) = pool.read_timeline(0, 10);
+    assert_eq!(timeline, vec![txns[1].clone(), replacement.clone()]);
+    let (timeline, _) = pool.read_timeline(2, 10);
+    assert_eq!(timeline, vec![replacement.clone()]);
+    assert_eq!(
+        pool.get_batch(10, HashSet::new()),
+        vec![replacement, txns[1].clone()]
+    );
+}
+
+#[test]
+fn test_get_gas_prices() {
+    let (mut mempool, _) = setup_mempool();
+    add_txns_to_mempool(
+        &mut mempool,
+        vec![
+            TestTransaction::new(0, 0, 3),
+            TestTransaction::new(1, 0, 7),
+            TestTransaction::new(2, 0, 5),
+            // Not ready, so it's not in the priority index.
+            TestTransaction::new(3, 1, 100),
+        ],
+    );
+
+    assert_eq!(mempool.get_gas_prices(10), vec![7, 5, 3]);
+    assert_eq!(mempool.get_gas_prices(2), vec![7, 5]);
+}
+
+#[test]
+fn test_get_account_transactions() {
+    let (mut mempool, _) = setup_mempool();
+    let txns = add_txns_to_mempool(
+        &mut mempool,
+        vec![
+            TestTransaction::new(0, 0, 3),
+            TestTransaction::new(1, 0, 7),
+            TestTransaction::new(0, 1, 5),
+            // Not ready, txn 2 is missing.
+            TestTransaction::new(0, 3, 100),
+        ],
+    );
+
+    let account_txns = mempool.get_account_transactions(&TestTransaction::get_address(0));
+    let view = |info: &PendingTransactionInfo| {
+        (
+            info.txn.sequence_number(),
+            info.parked,
+            info.timeline_id,
+            info.priority_index_position,
+        )
+    };
+    assert_eq!(
+        account_txns.iter().map(view).collect::<Vec<_>>(),
+        vec![
+            (0, false, Some(1), Some(2)),
+            (1, false, Some(3), Some(1)),
+            (3, true, None, None),
+        ]
+    );
+    assert_eq!(account_txns[2].txn, txns[3]);
+    assert!(account_txns
+        .iter()
+        .all(|info| info.insertion_time.is_some()));
+
+    assert!(mempool
+        .get_account_transactions(&TestTransaction::get_address(2))
+        .is_empty());
+}
+
+#[test]
+fn test_get_stats() {
+    let (mut mempool, _) = setup_mempool();
+    add_txns_to_mempool(
+        &mut mempool,
+        vec![
+            TestTransaction::new(0, 0, 1),
+            TestTransaction::new(1, 0, 1),
+            TestTransaction::new(1, 1, 1),
+            TestTransaction::new(1, 3, 1),
+            TestTransaction::new(2, 2, 1),
+            TestTransaction::new(2, 3, 1),
+        ],
+    );
+
+    let mut stats = mempool.get_stats();
+    assert_eq!(stats.top_accounts.len(), 3);
+    stats.retain_top_accounts(2);
+    assert_eq!(stats.total_txns, 6);
+    assert_eq!(stats.ready_txns, 3);
+    assert_eq!(stats.parked_txns, 3);
+    assert_eq!(stats.num_accounts, 3);
+    assert_eq!(
+        stats.top_accounts,
+        vec![
+            AccountMempoolStats {
+                address: TestTransaction::get_address(1),
+                ready_txns: 2,
+                parked_txns: 1,
+            },
+            AccountMempoolStats {
+                address: TestTransaction::get_address(2),
+                ready_txns: 0,
+                parked_txns: 2,
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_transaction_filter() {
+    let mut config = NodeConfig::random();
+    config.mempool.transaction_filter = TransactionFilterConfig {
+        allow: vec![TransactionFilterRule {
+            payload_type: Some(TransactionPayloadType::Script),
+            ..Default::default()
+        }],
+        deny: vec![TransactionFilterRule {
+            name: "blocked_sender".to_string(),
+            sender: Some(TestTransaction::get_address(1)),
+            ..Default::default()
+        }],
+    };
+    let mut pool = CoreMempool::new(&config);
+
+    // Allowed payload type from a sender that isn't denied
+    assert!(add_txn(&mut pool, TestTransaction::new(0, 0, 1)).is_ok());
+
+    // Denied sender
+    let txn = TestTransaction::new(1, 0, 1).make_signed_transaction();
+    let status = pool.add_txn(
+        txn,
+        1,
+        AccountSequenceInfo::Sequential(0),
+        TimelineState::NotReady,
+    );
+    assert_eq!(status.code, MempoolStatusCode::RejectedByFilter);
+    assert!(status.message.contains("blocked_sender"));
+
+    // Entry function rules never match scripts, so nothing is allowed
+    config.mempool.transaction_filter.allow = vec![TransactionFilterRule {
+        module_name: Some("coin".to_string()),
+        ..Default::default()
+    }];
+    let mut pool = CoreMempool::new(&config);
+    assert!(add_txn(&mut pool, TestTransaction::new(0, 0, 1)).is_err());
+}
@@ -11,10 +11,11 @@
 //! long as the latter is in its trusted peers set.
 use aptos_config::{
     config::{
-        DiscoveryMethod, NetworkConfig, Peer, PeerRole, PeerSet, RateLimitConfig, RoleType,
-        CONNECTION_BACKOFF_BASE, CONNECTIVITY_CHECK_INTERVAL_MS, MAX_CONCURRENT_NETWORK_REQS,
-        MAX_CONNECTION_DELAY_MS, MAX_FRAME_SIZE, MAX_FULLNODE_OUTBOUND_CONNECTIONS,
-        MAX_INBOUND_CONNECTIONS, NETWORK_CHANNEL_SIZE,
+        DiscoveryMethod, NetworkConfig, OutboundQueueConfig, Peer, PeerRateLimitConfig, PeerRole,
+        PeerSet, RateLimitConfig, RoleType, CONNECTION_BACKOFF_BASE,
+        CONNECTIVITY_CHECK_INTERVAL_MS, MAX_CONCURRENT_NETWORK_REQS, MAX_CONNECTION_DELAY_MS,
+        MAX_FRAME_SIZE, MAX_FULLNODE_OUTBOUND_CONNECTIONS, MAX_INBOUND_CONNECTIONS,
+        NETWORK_CHANNEL_SIZE,
     },
     network_id::NetworkContext,
 };
@@ -28,6 +29,7 @@ use network::{
     application::storage::PeerMetadataStorage,
     connectivity_manager::{builder::ConnectivityManagerBuilder, ConnectivityRequest},
     logging::NetworkSchema,
+    peer::outbound_queue_names,
     peer_manager::{
         builder::{AuthenticationMode, PeerManagerBuilder},
         ConnectionRequestSender,
@@ -36,6 +38,7 @@ use network::{
         health_checker::{self, builder::HealthCheckerBuilder},
         network::{AppConfig, NewNetworkEvents, NewNetworkSender},
     },
+    ProtocolId,
 };
 use network_discovery::DiscoveryChangeListener;
 use std::{
@@ -88,6 +91,8 @@ impl NetworkBuilder {
         inbound_connection_limit: usize,
         inbound_rate_limit_config: Option<RateLimitConfig>,
         outbound_rate_limit_config: Option<RateLimitConfig>,
+        peer_rate_limit_config: Option<PeerRateLimitConfig>,
+        outbound_queue_config: OutboundQueueConfig,
     ) -> Self {
         // A network cannot exist without a PeerManager
         // TODO:  construct this in create and pass it to new() as a parameter. The complication is manual construction of NetworkBuilder in various tests.
@@ -106,6 +111,8 @@ impl NetworkBuilder {
             inbound_connection_limit,
             inbound_rate_limit_config,
             outbound_rate_limit_config,
+            peer_rate_limit_config,
+            outbound_queue_config,
         );
 
         NetworkBuilder {
@@ -148,6 +155,8 @@ impl NetworkBuilder {
             MAX_INBOUND_CONNECTIONS,
             None,
             None,
+            None,
+            OutboundQueueConfig::default(),
         );
 
         builder.add_connectivity_manager(
@@ -185,6 +194,19 @@ impl NetworkBuilder {
 
         let network_context = NetworkContext::new(role, config.network_id, peer_id);
 
+        if let Some(peer_rate_limit_config) = &config.peer_rate_limit_config {
+            peer_rate_limit_config
+                .verify_protocols(&protocol_names())
+                .expect("Peer rate limits must be for known protocols");
+        }
+        config
+            .outbound_queue_config
+            .verify_queues(&outbound_queue_names())
+            .expect("Outbound queue settings must be for known queues");
+        config
+            .verify_discovery_methods()
+            .expect("Discovery methods must be well formed");
+
         let trusted_peers = Arc::new(RwLock::new(HashMap::new()));
 
         let mut network_builder = NetworkBuilder::new(
@@ -202,6 +224,8 @@ impl NetworkBuilder {
             config.max_inbound_connections,
             config.inbound_rate_limit_config,
             config.outbound_rate_limit_config,
+            config.peer_rate_limit_config.clone(),
+            config.outbound_queue_config.clone(),
         );
 
         network_builder.add_connection_monitoring(
@@ -394,6 +418,18 @@ impl NetworkBuilder {
                 *interval_duration,
                 self.time_service.clone(),
             ),
+            DiscoveryMethod::Dns(config) => DiscoveryChangeListener::dns(
+                self.network_context,
+                conn_mgr_reqs_tx,
+                config,
+                self.time_service.clone(),
+            ),
+            DiscoveryMethod::Http(config) => DiscoveryChangeListener::http(
+                self.network_context,
+                conn_mgr_reqs_tx,
+                config,
+                self.time_service.clone(),
+            ),
             DiscoveryMethod::None => return,
         };
 
@@ -457,6 +493,14 @@ impl NetworkBuilder {
     }
 }
 
+/// The names of all protocols, as used to configure them
+fn protocol_names() -> Vec<&'static str> {
+    ProtocolId::all()
+        .iter()
+        .map(|protocol_id| protocol_id.as_str())
+        .collect()
+}
+
 /// Retrieve and merge seeds so that they have all keys associated
 fn merge_seeds(config: &NetworkConfig) -> PeerSet {
     config.verify_seeds().expect("Seeds must be well formed");
@@ -0,0 +1,413 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::DiscoveryError;
+use aptos_config::config::{Peer, PeerRole, PeerSet, HANDSHAKE_VERSION};
+use aptos_crypto::{x25519, ValidCryptoMaterialStringExt};
+use aptos_logger::prelude::*;
+use aptos_time_service::{TimeService, TimeServiceTrait};
+use aptos_types::{
+    account_address::from_identity_public_key,
+    network_address::{DnsName, NetworkAddress, Protocol},
+};
+use async_trait::async_trait;
+use futures::{
+    stream::{BoxStream, StreamExt},
+    Stream,
+};
+use once_cell::sync::OnceCell;
+use std::{
+    collections::hash_map::Entry,
+    convert::TryFrom,
+    pin::Pin,
+    str::FromStr,
+    sync::Arc,
+    task::{Context, Poll},
+    time::Duration,
+};
+use trust_dns_resolver::{error::ResolveErrorKind, TokioAsyncResolver};
+
+/// The prefix of the name of the SRV records that point to seed peers
+const SRV_RECORD_PREFIX: &str = "_aptos._tcp";
+
+/// The maximum number of peers (and SRV targets) accepted from a single lookup, so that a
+/// domain can't flood the connectivity manager with peers
+const MAX_PEERS_PER_LOOKUP: usize = 100;
+
+/// Looks up the DNS records used for peer discovery. This is a trait so that
+/// tests don't need a DNS server.
+#[async_trait]
+pub(crate) trait DnsResolver: Send + Sync {
+    /// Returns the contents of all TXT records of the given name
+    async fn lookup_txt(&self, name: &str) -> Result<Vec<String>, DiscoveryError>;
+
+    /// Returns the target host and port of all SRV records of the given name
+    async fn lookup_srv(&self, name: &str) -> Result<Vec<(String, u16)>, DiscoveryError>;
+}
+
+/// A `DnsResolver` that uses the system's DNS configuration
+#[derive(Default)]
+pub(crate) struct SystemDnsResolver {
+    resolver: OnceCell<TokioAsyncResolver>,
+}
+
+impl SystemDnsResolver {
+    fn resolver(&self) -> Result<&TokioAsyncResolver, DiscoveryError> {
+        self.resolver.get_or_try_init(|| {
+            TokioAsyncResolver::tokio_from_system_conf()
+                .map_err(|error| DiscoveryError::Dns(error.to_string()))
+        })
+    }
+}
+
+#[async_trait]
+impl DnsResolver for SystemDnsResolver {
+    async fn lookup_txt(&self, name: &str) -> Result<Vec<String>, DiscoveryError> {
+        match self.resolver()?.txt_lookup(name).await {
+            Ok(lookup) => Ok(lookup
+                .iter()
+                .map(|txt| {
+                    // Long records are split into several strings
+                    txt.txt_data()
+                        .iter()
+                        .map(|data| String::from_utf8_lossy(data))
+                        .collect()
+                })
+                .collect()),
+            Err(error) => match error.kind() {
+                ResolveErrorKind::NoRecordsFound { .. } => Ok(vec![]),
+                _ => Err(DiscoveryError::Dns(error.to_string())),
+            },
+        }
+    }
+
+    async fn lookup_srv(&self, name: &str) -> Result<Vec<(String, u16)>, DiscoveryError> {
+        match self.resolver()?.srv_lookup(name).await {
+            Ok(lookup) => Ok(lookup
+                .iter()
+                .map(|srv| (srv.target().to_ascii(), srv.port()))
+                .collect()),
+            Err(error) => match error.kind() {
+                ResolveErrorKind::NoRecordsFound { .. } => Ok(vec![]),
+                _ => Err(DiscoveryError::Dns(error.to_string())),
+            },
+        }
+    }
+}
+
+/// Periodically resolves the seed peers advertised in the DNS records of a domain
+pub struct DnsStream {
+    stream: BoxStream<'static, Result<PeerSet, DiscoveryError>>,
+}
+
+impl DnsStream {
+    pub(crate) fn new(
+        domain_name: String,
+        interval_duration: Duration,
+        resolver: Arc<dyn DnsResolver>,
+        time_service: TimeService,
+    ) -> Self {
+        let stream = time_service
+            .interval(interval_duration)
+            .then(move |_| {
+                let domain_name = domain_name.clone();
+                let resolver = resolver.clone();
+                async move { resolve_peers(resolver.as_ref(), &domain_name).await }
+            })
+            .boxed();
+        DnsStream { stream }
+    }
+}
+
+impl Stream for DnsStream {
+    type Item = Result<PeerSet, DiscoveryError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.stream.poll_next_unpin(cx)
+    }
+}
+
+/// Resolves the seed peers advertised in the DNS records of the given domain.
+/// Invalid records (and hosts whose lookup fails) are skipped, so that one bad
+/// record doesn't hide all peers.
+pub(crate) async fn resolve_peers(
+    resolver: &dyn DnsResolver,
+    domain_name: &str,
+) -> Result<PeerSet, DiscoveryError> {
+    let mut addresses = vec![];
+
+    // Each TXT record of the domain holds the full address of a peer
+    for record in resolver.lookup_txt(domain_name).await? {
+        match NetworkAddress::from_str(record.trim()) {
+            Ok(address) => addresses.push(address),
+            Err(error) => warn!(
+                "Skipping invalid peer address in TXT record of {}: {:?}, error: {}",
+                domain_name, record, error
+            ),
+        }
+    }
+
+    // Each SRV record points to a peer, whose host has a TXT record with its noise key
+    let srv_name = format!("{}.{}", SRV_RECORD_PREFIX, domain_name);
+    let srv_targets = resolver.lookup_srv(&srv_name).await?;
+    if srv_targets.len() > MAX_PEERS_PER_LOOKUP {
+        warn!(
+            "Only resolving {} of the {} SRV targets of {}",
+            MAX_PEERS_PER_LOOKUP,
+            srv_targets.len(),
+            srv_name
+        );
+    }
+    for (host, port) in srv_targets.into_iter().take(MAX_PEERS_PER_LOOKUP) {
+        let host = host.trim_end_matches('.').to_string();
+        match resolve_noise_key(resolver, &host).await {
+            Ok(Some(pubkey)) => match srv_address(host.clone(), port, pubkey) {
+                Ok(address) => addresses.push(address),
+                Err(error) => warn!("Skipping invalid SRV target {}: {}", host, error),
+            },
+            Ok(None) => warn!("Skipping SRV target {} without a noise public key", host),
+            Err(error) => warn!("Skipping SRV target {}, TXT lookup failed: {}", host, error),
+        }
+    }
+
+    Ok(peers_from_addresses(addresses))
+}
+
+/// Returns the first noise public key in the TXT records of the given host
+async fn resolve_noise_key(
+    resolver: &dyn DnsResolver,
+    host: &str,
+) -> Result<Option<x25519::PublicKey>, DiscoveryError> {
+    Ok(resolver
+        .lookup_txt(host)
+        .await?
+        .iter()
+        .find_map(|record| x25519::PublicKey::from_encoded_string(record.trim()).ok()))
+}
+
+/// Builds the address of a peer pointed to by an SRV record
+fn srv_address(
+    host: String,
+    port: u16,
+    pubkey: x25519::PublicKey,
+) -> Result<NetworkAddress, DiscoveryError> {
+    let dns_name =
+        DnsName::try_from(host).map_err(|error| DiscoveryError::Parsing(error.to_string()))?;
+    let address =
+        NetworkAddress::from_protocols(vec![Protocol::Dns(dns_name), Protocol::Tcp(port)])
+            .map_err(|error| DiscoveryError::Parsing(error.to_string()))?;
+    Ok(address.append_prod_protos(pubkey, HANDSHAKE_VERSION))
+}
+
+/// Groups the addresses into peers. As on any public network, the peer ids are
+/// derived from the noise keys, so addresses without one are skipped. DNS records
+/// aren't authenticated, so the peers are untrusted: only the signed peer list of
+/// HTTP discovery can grant a role.
+fn peers_from_addresses(addresses: Vec<NetworkAddress>) -> PeerSet {
+    let mut peers = PeerSet::new();
+    for address in addresses {
+        let pubkey = match address.find_noise_proto() {
+            Some(pubkey) => pubkey,
+            None => {
+                warn!(
+                    "Skipping peer address without a noise public key: {}",
+                    address
+                );
+                continue;
+            }
+        };
+        let num_peers = peers.len();
+        match peers.entry(from_identity_public_key(pubkey)) {
+            Entry::Occupied(mut entry) => entry.get_mut().addresses.push(address),
+            Entry::Vacant(_) if num_peers >= MAX_PEERS_PER_LOOKUP => {
+                warn!(
+                    "Skipping peer address, already found {} peers: {}",
+                    MAX_PEERS_PER_LOOKUP, address
+                );
+            }
+            Entry::Vacant(entry) => {
+                entry.insert(Peer::from_addrs(PeerRole::Unknown, vec![address]));
+            }
+        }
+    }
+    peers
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aptos_crypto::Uniform;
+    use rand::{rngs::StdRng, SeedableRng};
+    use std::collections::{HashMap, HashSet};
+
+    /// A `DnsResolver` that serves fixed records
+    #[derive(Default)]
+    struct MockDnsResolver {
+        txt_records: HashMap<String, Vec<String>>,
+        srv_records: HashMap<String, Vec<(String, u16)>>,
+        failing_txt_lookups: HashSet<String>,
+    }
+
+    #[async_trait]
+    impl DnsResolver for MockDnsResolver {
+        async fn lookup_txt(&self, name: &str) -> Result<Vec<String>, DiscoveryError> {
+            if self.failing_txt_lookups.contains(name) {
+                return Err(DiscoveryError::Dns(format!("lookup of {} failed", name)));
+            }
+            Ok(self.txt_records.get(name).cloned().unwrap_or_default())
+        }
+
+        async fn lookup_srv(&self, name: &str) -> Result<Vec<(String, u16)>, DiscoveryError> {
+            Ok(self.srv_records.get(name).cloned().unwrap_or_default())
+        }
+    }
+
+    fn random_pubkey(rng: &mut StdRng) -> x25519::PublicKey {
+        x25519::PrivateKey::generate(rng).public_key()
+    }
+
+    #[tokio::test]
+    async fn test_resolve_txt_records() {
+        let mut rng = StdRng::from_seed([0u8; 32]);
+        let pubkey_1 = random_pubkey(&mut rng);
+        let pubkey_2 = random_pubkey(&mut rng);
+        let address_1 = NetworkAddress::from_str("/dns/seed1.example.com/tcp/6182")
+            .unwrap()
+            .append_prod_protos(pubkey_1, HANDSHAKE_VERSION);
+        let address_2 = NetworkAddress::from_str("/ip4/1.2.3.4/tcp/6182")
+            .unwrap()
+            .append_prod_protos(pubkey_2, HANDSHAKE_VERSION);
+        let address_3 = NetworkAddress::from_str("/ip4/1.2.3.5/tcp/6182")
+            .unwrap()
+            .append_prod_protos(pubkey_2, HANDSHAKE_VERSION);
+
+        let mut resolver = MockDnsResolver::default();
+        resolver.txt_records.insert(
+            "seeds.example.com".into(),
+            vec![
+                address_1.to_string(),
+                address_2.to_string(),
+                address_3.to_string(),
+                // Invalid records are skipped
+                "not an address".into(),
+                "/ip4/1.2.3.6/tcp/6182".into(),
+            ],
+        );
+
+        let peers = resolve_peers(&resolver, "seeds.example.com").await.unwrap();
+        assert_eq!(peers.len(), 2);
+        let peer_1 = peers.get(&from_identity_public_key(pubkey_1)).unwrap();
+        assert_eq!(peer_1.addresses, vec![address_1]);
+        assert_eq!(peer_1.role, PeerRole::Unknown);
+        let peer_2 = peers.get(&from_identity_public_key(pubkey_2)).unwrap();
+        assert_eq!(peer_2.addresses, vec![address_2, address_3]);
+        assert_eq!(peer_2.keys.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_resolve_srv_records() {
+        let mut rng = StdRng::from_seed([0u8; 32]);
+        let pubkey = random_pubkey(&mut rng);
+
+        let mut resolver = MockDnsResolver::default();
+        resolver.srv_records.insert(
+            "_aptos._tcp.seeds.example.com".into(),
+            vec![
+                ("node1.example.com.".into(), 6182),
+                // Hosts without a noise key are skipped
+                ("node2.example.com.".into(), 6182),
+            ],
+        );
+        resolver.txt_records.insert(
+            "node1.example.com".into(),
+            vec!["v=spf1 -all".into(), pubkey.to_encoded_string().unwrap()],
+        );
+
+        let peers = resolve_peers(&resolver, "seeds.example.com").await.unwrap();
+        assert_eq!(peers.len(), 1);
+        let peer = peers.get(&from_identity_public_key(pubkey)).unwrap();
+        assert_eq!(
+            peer.addresses,
+            vec![NetworkAddress::from_str("/dns/node1.example.com/tcp/6182")
+                .unwrap()
+                .append_prod_protos(pubkey, HANDSHAKE_VERSION)]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_resolve_srv_records_skips_failed_lookups() {
+        let mut rng = StdRng::from_seed([0u8; 32]);
+        let pubkey = random_pubkey(&mut rng);
+
+        let mut resolver = MockDnsResolver::default();
+        resolver.srv_records.insert(
+            "_aptos._tcp.seeds.example.com".into(),
+            vec![
+                ("node1.example.com.".into(), 6182),
+                ("node2.example.com.".into(), 6182),
+            ],
+        );
+        resolver
+            .failing_txt_lookups
+            .insert("node1.example.com".into());
+        resolver.txt_records.insert(
+            "node2.example.com".into(),
+            vec![pubkey.to_encoded_string().unwrap()],
+        );
+
+        let peers = resolve_peers(&resolver, "seeds.example.com").await.unwrap();
+        assert_eq!(peers.len(), 1);
+        assert!(peers.contains_key(&from_identity_public_key(pubkey)));
+    }
+
+    #[tokio::test]
+    async fn test_resolve_peers_limit() {
+        let mut rng = StdRng::from_seed([0u8; 32]);
+        let records = (0..MAX_PEERS_PER_LOOKUP + 10)
+            .map(|_| {
+                NetworkAddress::from_str("/ip4/1.2.3.4/tcp/6182")
+                    .unwrap()
+                    .append_prod_protos(random_pubkey(&mut rng), HANDSHAKE_VERSION)
+                    .to_string()
+            })
+            .collect();
+        let mut resolver = MockDnsResolver::default();
+        resolver
+            .txt_records
+            .insert("seeds.example.com".into(), records);
+
+        let peers = resolve_peers(&resolver, "seeds.example.com").await.unwrap();
+        assert_eq!(peers.len(), MAX_PEERS_PER_LOOKUP);
+        assert!(peers.values().all(|peer| peer.role == PeerRole::Unknown));
+    }
+
+    #[tokio::test]
+    async fn test_dns_stream() {
+        let mut rng = StdRng::from_seed([0u8; 32]);
+        let pubkey = random_pubkey(&mut rng);
+        let address = NetworkAddress::from_str("/ip4/1.2.3.4/tcp/6182")
+            .unwrap()
+            .append_prod_protos(pubkey, HANDSHAKE_VERSION);
+        let mut resolver = MockDnsResolver::default();
+        resolver
+            .txt_records
+            .insert("seeds.example.com".into(), vec![address.to_string()]);
+
+        let mut stream = DnsStream::new(
+            "seeds.example.com".into(),
+            Duration::from_millis(5),
+            Arc::new(resolver),
+            TimeService::real(),
+        );
+        for _ in 0..2 {
+            let peers = stream.next().await.unwrap().unwrap();
+            assert_eq!(
+                peers
+                    .get(&from_identity_public_key(pubkey))
+                    .unwrap()
+                    .addresses,
+                vec![address.clone()]
+            );
+        }
+    }
+}
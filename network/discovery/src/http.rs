@@ -0,0 +1,321 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::DiscoveryError;
+use aptos_config::config::PeerSet;
+use aptos_crypto::{
+    ed25519::{Ed25519PublicKey, Ed25519Signature},
+    Signature,
+};
+use aptos_time_service::{TimeService, TimeServiceTrait};
+use futures::{
+    stream::{BoxStream, StreamExt},
+    Stream,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+    time::Duration,
+};
+
+/// The timeout of a single fetch of the peer list
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// The maximum size of a served peer list, so that a server can't exhaust our memory
+const MAX_PEER_LIST_BYTES: usize = 4 * 1024 * 1024;
+
+/// A list of peers to publish for HTTP discovery
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct PeerList {
+    pub peers: PeerSet,
+    /// The list is rejected after this time, so that stale lists can't be replayed
+    pub expiration_timestamp_secs: u64,
+}
+
+/// A `PeerList` and its signature, in the JSON format served for HTTP discovery
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct SignedPeerList {
+    /// The JSON encoded `PeerList`. It's kept as a string, so that the signature
+    /// is verified against exactly the bytes that were signed.
+    pub peer_list: String,
+    /// The Ed25519 signature of the `peer_list` bytes
+    pub signature: Ed25519Signature,
+}
+
+impl SignedPeerList {
+    /// Verifies that the peer list is signed by the given key and hasn't
+    /// expired, and returns its peers.
+    pub fn verify(
+        &self,
+        public_key: &Ed25519PublicKey,
+        now_unix_secs: u64,
+    ) -> Result<PeerSet, DiscoveryError> {
+        self.signature
+            .verify_arbitrary_msg(self.peer_list.as_bytes(), public_key)
+            .map_err(|error| DiscoveryError::Verification(error.to_string()))?;
+
+        let peer_list: PeerList = serde_json::from_str(&self.peer_list)
+            .map_err(|error| DiscoveryError::Parsing(error.to_string()))?;
+        if peer_list.expiration_timestamp_secs <= now_unix_secs {
+            return Err(DiscoveryError::Verification(format!(
+                "Peer list expired at {}, now is {}",
+                peer_list.expiration_timestamp_secs, now_unix_secs
+            )));
+        }
+        Ok(peer_list.peers)
+    }
+}
+
+/// Periodically fetches and verifies a signed peer list over HTTP(S)
+pub struct HttpStream {
+    stream: BoxStream<'static, Result<PeerSet, DiscoveryError>>,
+}
+
+impl HttpStream {
+    pub(crate) fn new(
+        url: String,
+        public_key: Ed25519PublicKey,
+        interval_duration: Duration,
+        time_service: TimeService,
+    ) -> Self {
+        let client = reqwest::Client::new();
+        let stream = time_service
+            .clone()
+            .interval(interval_duration)
+            .then(move |_| {
+                let request = client.get(&url).timeout(REQUEST_TIMEOUT);
+                let public_key = public_key.clone();
+                let time_service = time_service.clone();
+                async move {
+                    let signed_peer_list = fetch_signed_peer_list(request).await?;
+                    signed_peer_list.verify(&public_key, time_service.now_unix_time().as_secs())
+                }
+            })
+            .boxed();
+        HttpStream { stream }
+    }
+}
+
+impl Stream for HttpStream {
+    type Item = Result<PeerSet, DiscoveryError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.stream.poll_next_unpin(cx)
+    }
+}
+
+async fn fetch_signed_peer_list(
+    request: reqwest::RequestBuilder,
+) -> Result<SignedPeerList, DiscoveryError> {
+    let mut response = request
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|error| DiscoveryError::Http(error.to_string()))?;
+    let too_large = || {
+        DiscoveryError::Http(format!(
+            "Peer list is larger than {} bytes",
+            MAX_PEER_LIST_BYTES
+        ))
+    };
+    if let Some(length) = response.content_length() {
+        if length > MAX_PEER_LIST_BYTES as u64 {
+            return Err(too_large());
+        }
+    }
+
+    // The length may not be known upfront, so it's also checked while reading
+    let mut body = Vec::new();
+    while let Some(chunk) = response
+        .chunk()
+        .await
+        .map_err(|error| DiscoveryError::Http(error.to_string()))?
+    {
+        if body.len() + chunk.len() > MAX_PEER_LIST_BYTES {
+            return Err(too_large());
+        }
+        body.extend_from_slice(&chunk);
+    }
+    serde_json::from_slice(&body).map_err(|error| DiscoveryError::Parsing(error.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DiscoveryChangeListener;
+    use aptos_config::{
+        config::{HttpDiscoveryConfig, Peer, PeerRole},
+        network_id::NetworkContext,
+    };
+    use aptos_crypto::{ed25519::Ed25519PrivateKey, SigningKey, Uniform};
+    use aptos_infallible::Mutex;
+    use aptos_types::{network_address::NetworkAddress, PeerId};
+    use network::connectivity_manager::{ConnectivityRequest, DiscoverySource};
+    use rand::{rngs::StdRng, SeedableRng};
+    use std::{net::SocketAddr, str::FromStr, sync::Arc};
+    use warp::{http::StatusCode, Filter};
+
+    const PEERS_PATH: &str = "peers.json";
+
+    fn create_peer_set() -> PeerSet {
+        let address = NetworkAddress::from_str("/ip4/1.2.3.4/tcp/6180/noise-ik/080e287879c918794170e258bfaddd75acac5b3e350419044655e4983a487120/handshake/0").unwrap();
+        let mut peers = PeerSet::new();
+        peers.insert(
+            PeerId::random(),
+            Peer::from_addrs(PeerRole::Upstream, vec![address]),
+        );
+        peers
+    }
+
+    fn sign_peer_list(
+        private_key: &Ed25519PrivateKey,
+        peers: &PeerSet,
+        expiration_timestamp_secs: u64,
+    ) -> SignedPeerList {
+        let peer_list = serde_json::to_string(&PeerList {
+            peers: peers.clone(),
+            expiration_timestamp_secs,
+        })
+        .unwrap();
+        let signature = private_key.sign_arbitrary_message(peer_list.as_bytes());
+        SignedPeerList {
+            peer_list,
+            signature,
+        }
+    }
+
+    /// Serves the current response body at `PEERS_PATH`, or a 404 if there is none
+    fn start_server(body: Arc<Mutex<Option<String>>>) -> SocketAddr {
+        let route = warp::path(PEERS_PATH).map(move || match body.lock().clone() {
+            Some(body) => warp::reply::with_status(body, StatusCode::OK),
+            None => warp::reply::with_status(String::new(), StatusCode::NOT_FOUND),
+        });
+        let (address, server) = warp::serve(route).bind_ephemeral(([127, 0, 0, 1], 0));
+        tokio::spawn(server);
+        address
+    }
+
+    fn create_stream(address: SocketAddr, public_key: Ed25519PublicKey) -> HttpStream {
+        HttpStream::new(
+            format!("http://{}/{}", address, PEERS_PATH),
+            public_key,
+            Duration::from_millis(5),
+            TimeService::real(),
+        )
+    }
+
+    fn now_unix_secs() -> u64 {
+        TimeService::real().now_unix_time().as_secs()
+    }
+
+    #[test]
+    fn test_verify_signed_peer_list() {
+        let mut rng = StdRng::from_seed([0u8; 32]);
+        let private_key = Ed25519PrivateKey::generate(&mut rng);
+        let public_key = private_key.public_key();
+        let peers = create_peer_set();
+
+        // A valid list is accepted
+        let signed_peer_list = sign_peer_list(&private_key, &peers, 100);
+        assert_eq!(signed_peer_list.verify(&public_key, 99).unwrap(), peers);
+
+        // But not once it has expired
+        assert!(matches!(
+            signed_peer_list.verify(&public_key, 100),
+            Err(DiscoveryError::Verification(_))
+        ));
+
+        // Nor if it's signed by another key
+        let other_public_key = Ed25519PrivateKey::generate(&mut rng).public_key();
+        assert!(matches!(
+            signed_peer_list.verify(&other_public_key, 99),
+            Err(DiscoveryError::Verification(_))
+        ));
+
+        // Nor if it has been tampered with
+        let mut tampered_peer_list = signed_peer_list;
+        tampered_peer_list.peer_list = tampered_peer_list.peer_list.replace("100", "200");
+        assert!(matches!(
+            tampered_peer_list.verify(&public_key, 99),
+            Err(DiscoveryError::Verification(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_http_stream() {
+        let mut rng = StdRng::from_seed([0u8; 32]);
+        let private_key = Ed25519PrivateKey::generate(&mut rng);
+        let peers = create_peer_set();
+        let body = Arc::new(Mutex::new(None));
+        let address = start_server(body.clone());
+        let mut stream = create_stream(address, private_key.public_key());
+
+        // Nothing is served yet
+        assert!(matches!(
+            stream.next().await.unwrap(),
+            Err(DiscoveryError::Http(_))
+        ));
+
+        // Serve a valid peer list
+        let signed_peer_list = sign_peer_list(&private_key, &peers, now_unix_secs() + 3600);
+        *body.lock() = Some(serde_json::to_string(&signed_peer_list).unwrap());
+        assert_eq!(stream.next().await.unwrap().unwrap(), peers);
+
+        // Serve an expired peer list
+        let signed_peer_list = sign_peer_list(&private_key, &peers, now_unix_secs() - 1);
+        *body.lock() = Some(serde_json::to_string(&signed_peer_list).unwrap());
+        assert!(matches!(
+            stream.next().await.unwrap(),
+            Err(DiscoveryError::Verification(_))
+        ));
+
+        // Serve garbage
+        *body.lock() = Some("not a peer list".into());
+        assert!(matches!(
+            stream.next().await.unwrap(),
+            Err(DiscoveryError::Parsing(_))
+        ));
+
+        // Serve a list that is too large
+        *body.lock() = Some(" ".repeat(MAX_PEER_LIST_BYTES + 1));
+        assert!(matches!(
+            stream.next().await.unwrap(),
+            Err(DiscoveryError::Http(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_http_listener() {
+        let mut rng = StdRng::from_seed([0u8; 32]);
+        let private_key = Ed25519PrivateKey::generate(&mut rng);
+        let peers = create_peer_set();
+        let signed_peer_list = sign_peer_list(&private_key, &peers, now_unix_secs() + 3600);
+        let body = Arc::new(Mutex::new(Some(
+            serde_json::to_string(&signed_peer_list).unwrap(),
+        )));
+        let address = start_server(body);
+
+        let (conn_mgr_reqs_tx, mut conn_mgr_reqs_rx) =
+            channel::new(1, &network::counters::PENDING_CONNECTIVITY_MANAGER_REQUESTS);
+        let listener = DiscoveryChangeListener::http(
+            NetworkContext::mock(),
+            conn_mgr_reqs_tx,
+            &HttpDiscoveryConfig {
+                url: format!("http://{}/{}", address, PEERS_PATH),
+                public_key: private_key.public_key(),
+                interval_secs: 1,
+            },
+            TimeService::real(),
+        );
+        tokio::spawn(Box::pin(listener).run());
+
+        match conn_mgr_reqs_rx.next().await {
+            Some(ConnectivityRequest::UpdateDiscoveredPeers(
+                DiscoverySource::Http,
+                actual_peers,
+            )) => assert_eq!(actual_peers, peers),
+            _ => panic!("No message sent by discovery"),
+        }
+    }
+}
@@ -1,8 +1,17 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{counters::DISCOVERY_COUNTS, file::FileStream, validator_set::ValidatorSetStream};
-use aptos_config::{config::PeerSet, network_id::NetworkContext};
+use crate::{
+    counters::DISCOVERY_COUNTS,
+    dns::{DnsStream, SystemDnsResolver},
+    file::FileStream,
+    http::HttpStream,
+    validator_set::ValidatorSetStream,
+};
+use aptos_config::{
+    config::{DnsDiscoveryConfig, HttpDiscoveryConfig, PeerSet},
+    network_id::NetworkContext,
+};
 use aptos_crypto::x25519;
 use aptos_logger::prelude::*;
 use aptos_time_service::TimeService;
@@ -16,19 +25,27 @@ use network::{
 use std::{
     path::Path,
     pin::Pin,
+    sync::Arc,
     task::{Context, Poll},
     time::Duration,
 };
 use tokio::runtime::Handle;
 
 mod counters;
+mod dns;
 mod file;
+mod http;
 mod validator_set;
 
+pub use http::{PeerList, SignedPeerList};
+
 #[derive(Debug)]
 pub enum DiscoveryError {
+    Dns(String),
+    Http(String),
     IO(std::io::Error),
     Parsing(String),
+    Verification(String),
 }
 
 /// A union type for all implementations of `DiscoveryChangeListenerTrait`
@@ -42,6 +59,8 @@ pub struct DiscoveryChangeListener {
 enum DiscoveryChangeStream {
     ValidatorSet(ValidatorSetStream),
     File(FileStream),
+    Dns(DnsStream),
+    Http(HttpStream),
 }
 
 impl Stream for DiscoveryChangeStream {
@@ -51,6 +70,8 @@ impl Stream for DiscoveryChangeStream {
         match self.get_mut() {
             Self::ValidatorSet(stream) => Pin::new(stream).poll_next(cx),
             Self::File(stream) => Pin::new(stream).poll_next(cx),
+            Self::Dns(stream) => Pin::new(stream).poll_next(cx),
+            Self::Http(stream) => Pin::new(stream).poll_next(cx),
         }
     }
 }
@@ -95,6 +116,46 @@ impl DiscoveryChangeListener {
         }
     }
 
+    pub fn dns(
+        network_context: NetworkContext,
+        update_channel: channel::Sender<ConnectivityRequest>,
+        config: &DnsDiscoveryConfig,
+        time_service: TimeService,
+    ) -> Self {
+        let source_stream = DiscoveryChangeStream::Dns(DnsStream::new(
+            config.domain_name.clone(),
+            Duration::from_secs(config.interval_secs),
+            Arc::new(SystemDnsResolver::default()),
+            time_service,
+        ));
+        DiscoveryChangeListener {
+            discovery_source: DiscoverySource::Dns,
+            network_context,
+            update_channel,
+            source_stream,
+        }
+    }
+
+    pub fn http(
+        network_context: NetworkContext,
+        update_channel: channel::Sender<ConnectivityRequest>,
+        config: &HttpDiscoveryConfig,
+        time_service: TimeService,
+    ) -> Self {
+        let source_stream = DiscoveryChangeStream::Http(HttpStream::new(
+            config.url.clone(),
+            config.public_key.clone(),
+            Duration::from_secs(config.interval_secs),
+            time_service,
+        ));
+        DiscoveryChangeListener {
+            discovery_source: DiscoverySource::Http,
+            network_context,
+            update_channel,
+            source_stream,
+        }
+    }
+
     pub fn start(self, executor: &Handle) {
         executor.spawn(Box::pin(self).run());
     }
@@ -3,6 +3,8 @@
 
 #![forbid(unsafe_code)]
 
+mod peer_monitor;
+
 use aptos_config::network_id::PeerNetworkId;
 use aptos_types::PeerId;
 use async_trait::async_trait;
@@ -25,6 +27,8 @@ use peer_monitoring_service_types::{
 use std::{sync::Arc, time::Duration};
 use thiserror::Error;
 
+pub use peer_monitor::PeerMonitor;
+
 #[derive(Debug, Error)]
 pub enum Error {
     #[error("Aptos network rpc error: {0}")]
@@ -32,6 +36,9 @@ pub enum Error {
 
     #[error("Error from remote monitoring service: {0}")]
     PeerMonitoringServiceError(#[from] PeerMonitoringServiceError),
+
+    #[error("Unexpected response from remote monitoring service: {0}")]
+    UnexpectedResponse(String),
 }
 
 /// The interface for sending peer monitoring service requests and querying
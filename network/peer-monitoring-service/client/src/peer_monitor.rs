@@ -0,0 +1,432 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{Error, PeerMonitoringServiceClient};
+use aptos_config::{config::PeerMonitoringServiceConfig, network_id::PeerNetworkId};
+use aptos_logger::prelude::*;
+use aptos_time_service::{TimeService, TimeServiceTrait};
+use futures::{future::join_all, StreamExt};
+use network::{
+    application::{
+        interface::NetworkInterface,
+        types::{PeerError, PeerMonitoringMetadata},
+    },
+    ProtocolId,
+};
+use peer_monitoring_service_types::{
+    sanitize_depth_from_validators, DepthFromValidatorsResponse, PeerMonitoringServiceRequest,
+    PeerMonitoringServiceResponse, PingRequest, PingResponse,
+};
+use std::{
+    collections::hash_map::Entry,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+/// The weight given to the newest ping latency when updating the average
+const LATENCY_SMOOTHING_FACTOR: f64 = 0.2;
+/// The frequency at which to log monitoring failures (secs)
+const MONITOR_LOG_FREQ_SECS: u64 = 60;
+
+/// A simple poller that periodically pings all connected peers and fetches
+/// their depth from the validator set. The results are stored in the peer
+/// metadata storage so that other components can select peers by them.
+pub struct PeerMonitor {
+    client: PeerMonitoringServiceClient,
+    config: PeerMonitoringServiceConfig,
+    ping_counter: Arc<AtomicU64>, // Used to match ping responses to requests
+    time_service: TimeService,
+}
+
+impl PeerMonitor {
+    pub fn new(
+        config: PeerMonitoringServiceConfig,
+        client: PeerMonitoringServiceClient,
+        time_service: TimeService,
+    ) -> Self {
+        Self {
+            client,
+            config,
+            ping_counter: Arc::new(AtomicU64::new(0)),
+            time_service,
+        }
+    }
+
+    /// Runs the monitor that continuously updates the peer monitoring metadata
+    pub async fn start(self) {
+        info!("Starting the peer monitor!");
+        let ticker = self
+            .time_service
+            .interval(Duration::from_millis(self.config.peer_monitor_interval_ms));
+        futures::pin_mut!(ticker);
+
+        loop {
+            // Wait for the next round before monitoring
+            ticker.next().await;
+
+            // Monitor all connected peers concurrently
+            let peers = self.get_connected_peers();
+            join_all(peers.into_iter().map(|peer| self.monitor_peer(peer))).await;
+        }
+    }
+
+    /// Returns all connected peers that support the peer monitoring service
+    fn get_connected_peers(&self) -> Vec<PeerNetworkId> {
+        let peer_metadata = self.client.peer_metadata_storage();
+        peer_metadata
+            .networks()
+            .flat_map(|network_id| {
+                peer_metadata
+                    .read_filtered(network_id, |(_, peer_info)| {
+                        peer_info.is_connected()
+                            && peer_info.supports_protocol(ProtocolId::PeerMonitoringServiceRpc)
+                    })
+                    .into_keys()
+            })
+            .collect()
+    }
+
+    /// Pings the given peer and fetches its depth from the validators, and
+    /// updates the peer's monitoring metadata with the results.
+    async fn monitor_peer(&self, peer: PeerNetworkId) {
+        let ping_latency = match self.ping_peer(peer).await {
+            Ok(ping_latency) => Some(ping_latency),
+            Err(error) => {
+                log_monitoring_failure(peer, "ping", error);
+                None
+            }
+        };
+        let depth_from_validators = match self.fetch_depth_from_validators(peer).await {
+            Ok(depth_from_validators) => depth_from_validators,
+            Err(error) => {
+                log_monitoring_failure(peer, "fetch the depth from validators of", error);
+                None
+            }
+        };
+
+        let result = self
+            .client
+            .peer_metadata_storage()
+            .write(peer, |entry| match entry {
+                Entry::Vacant(..) => Err(PeerError::NotFound),
+                Entry::Occupied(inner) => {
+                    // Don't trust the reported depth blindly, as it's used to select peers
+                    let peer_info = inner.get_mut();
+                    let sanitized_depth = sanitize_depth_from_validators(
+                        peer_info.active_connection.role,
+                        depth_from_validators,
+                    );
+                    if depth_from_validators.is_some() && sanitized_depth != depth_from_validators
+                    {
+                        sample!(
+                            SampleRate::Duration(Duration::from_secs(MONITOR_LOG_FREQ_SECS)),
+                            warn!(
+                                "Ignoring the implausible depth from validators {:?} of peer {:?} (role: {:?})",
+                                depth_from_validators, peer, peer_info.active_connection.role
+                            )
+                        );
+                    }
+                    update_monitoring_metadata(
+                        &mut peer_info.peer_monitoring_metadata,
+                        ping_latency,
+                        sanitized_depth,
+                    );
+                    Ok(())
+                }
+            });
+        if let Err(error) = result {
+            // The peer might have disconnected in the meantime
+            debug!(
+                "Failed to update the monitoring metadata of peer {:?}: {:?}",
+                peer, error
+            );
+        }
+    }
+
+    /// Sends a ping to the given peer and returns the round trip time
+    async fn ping_peer(&self, peer: PeerNetworkId) -> Result<Duration, Error> {
+        let ping_counter = self.ping_counter.fetch_add(1, Ordering::Relaxed);
+        let request = PeerMonitoringServiceRequest::Ping(PingRequest { ping_counter });
+
+        let start_time = self.time_service.now();
+        let response = self.send_request(peer, request).await?;
+        let ping_latency = self.time_service.now().duration_since(start_time);
+
+        match response {
+            PeerMonitoringServiceResponse::Ping(PingResponse {
+                ping_counter: response_counter,
+            }) if response_counter == ping_counter => Ok(ping_latency),
+            response => Err(Error::UnexpectedResponse(format!(
+                "Expected a ping response with counter {}, but got: {:?}",
+                ping_counter, response
+            ))),
+        }
+    }
+
+    /// Fetches the depth from the validators of the given peer
+    async fn fetch_depth_from_validators(&self, peer: PeerNetworkId) -> Result<Option<u64>, Error> {
+        let request = PeerMonitoringServiceRequest::GetDepthFromValidators;
+        match self.send_request(peer, request).await? {
+            PeerMonitoringServiceResponse::DepthFromValidators(DepthFromValidatorsResponse {
+                depth_from_validators,
+            }) => Ok(depth_from_validators),
+            response => Err(Error::UnexpectedResponse(format!(
+                "Expected a depth from validators response, but got: {:?}",
+                response
+            ))),
+        }
+    }
+
+    async fn send_request(
+        &self,
+        peer: PeerNetworkId,
+        request: PeerMonitoringServiceRequest,
+    ) -> Result<PeerMonitoringServiceResponse, Error> {
+        let timeout = Duration::from_millis(self.config.peer_request_timeout_ms);
+        self.client.send_request(peer, request, timeout).await
+    }
+}
+
+/// Updates the monitoring metadata with the latest results. The ping latency
+/// is kept as an exponential moving average, while the depth is replaced.
+fn update_monitoring_metadata(
+    metadata: &mut PeerMonitoringMetadata,
+    ping_latency: Option<Duration>,
+    depth_from_validators: Option<u64>,
+) {
+    if let Some(ping_latency) = ping_latency {
+        metadata.average_ping_latency = Some(match metadata.average_ping_latency {
+            Some(average_ping_latency) => {
+                average_ping_latency.mul_f64(1.0 - LATENCY_SMOOTHING_FACTOR)
+                    + ping_latency.mul_f64(LATENCY_SMOOTHING_FACTOR)
+            }
+            None => ping_latency,
+        });
+    }
+    metadata.depth_from_validators = depth_from_validators;
+}
+
+fn log_monitoring_failure(peer: PeerNetworkId, action: &str, error: Error) {
+    sample!(
+        SampleRate::Duration(Duration::from_secs(MONITOR_LOG_FREQ_SECS)),
+        warn!("Failed to {} peer {:?}: {:?}", action, peer, error)
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{PeerMonitoringServiceMultiSender, PeerMonitoringServiceNetworkSender};
+    use aptos_config::{config::PeerRole, network_id::NetworkId};
+    use aptos_time_service::MockTimeService;
+    use aptos_types::PeerId;
+    use channel::{aptos_channel, message_queues::QueueStyle};
+    use netcore::transport::ConnectionOrigin;
+    use network::{
+        application::storage::PeerMetadataStorage,
+        peer_manager::{ConnectionRequestSender, PeerManagerRequest, PeerManagerRequestSender},
+        protocols::network::NewNetworkSender,
+        transport::ConnectionMetadata,
+    };
+    use peer_monitoring_service_types::{PeerMonitoringServiceMessage, MAX_DEPTH_FROM_VALIDATORS};
+    use std::collections::HashMap;
+
+    #[tokio::test]
+    async fn test_peer_monitor() {
+        // Create the peer monitor for a fullnode, and start it
+        let (mut mock_network, peer_monitor) = MockNetwork::new();
+        let mock_time = mock_network.mock_time.clone();
+        tokio::spawn(peer_monitor.start());
+
+        // Add peers reporting different depths. Only validators can be at depth 0,
+        // and depths are bounded, so the other depths are ignored.
+        let reported_depths = vec![
+            (PeerRole::Validator, Some(0), Some(0)),
+            (PeerRole::Validator, None, Some(0)),
+            (PeerRole::ValidatorFullNode, Some(1), Some(1)),
+            (PeerRole::Unknown, Some(3), Some(3)),
+            (PeerRole::Unknown, None, None),
+            (PeerRole::Unknown, Some(0), None),
+            (
+                PeerRole::Unknown,
+                Some(MAX_DEPTH_FROM_VALIDATORS),
+                Some(MAX_DEPTH_FROM_VALIDATORS),
+            ),
+            (PeerRole::Unknown, Some(MAX_DEPTH_FROM_VALIDATORS + 1), None),
+            (PeerRole::Unknown, Some(u64::MAX), None),
+        ];
+        let mut expected_depths = HashMap::new();
+        for (role, reported_depth, expected_depth) in reported_depths {
+            let peer = mock_network.add_peer(role, reported_depth);
+            expected_depths.insert(peer, expected_depth);
+        }
+
+        // Advance time so the monitor pings all peers and fetches their depths
+        tokio::task::yield_now().await;
+        mock_time
+            .advance_async(Duration::from_millis(
+                PeerMonitoringServiceConfig::default().peer_monitor_interval_ms,
+            ))
+            .await;
+        for _ in 0..expected_depths.len() * 2 {
+            mock_network.handle_next_request().await;
+        }
+
+        // Verify the monitoring metadata of all peers is updated
+        for (peer, expected_depth) in expected_depths {
+            let peer_monitoring_metadata = mock_network.wait_for_monitoring_metadata(peer).await;
+            assert_eq!(
+                peer_monitoring_metadata.average_ping_latency,
+                Some(Duration::from_secs(0))
+            );
+            assert_eq!(
+                peer_monitoring_metadata.depth_from_validators,
+                expected_depth
+            );
+        }
+    }
+
+    #[test]
+    fn test_update_monitoring_metadata() {
+        let mut metadata = PeerMonitoringMetadata::default();
+
+        // The first latency is taken as is
+        update_monitoring_metadata(&mut metadata, Some(Duration::from_millis(100)), Some(2));
+        assert_eq!(
+            metadata.average_ping_latency,
+            Some(Duration::from_millis(100))
+        );
+        assert_eq!(metadata.depth_from_validators, Some(2));
+
+        // Later latencies are averaged
+        update_monitoring_metadata(&mut metadata, Some(Duration::from_millis(200)), Some(1));
+        assert_latency_eq(metadata.average_ping_latency, 120);
+        assert_eq!(metadata.depth_from_validators, Some(1));
+
+        // Failed pings keep the average, but the depth is always replaced
+        update_monitoring_metadata(&mut metadata, None, None);
+        assert_latency_eq(metadata.average_ping_latency, 120);
+        assert_eq!(metadata.depth_from_validators, None);
+    }
+
+    /// Verifies the average latency, allowing for floating point errors
+    fn assert_latency_eq(average_ping_latency: Option<Duration>, expected_millis: u64) {
+        let average_ping_latency = average_ping_latency.unwrap().as_micros();
+        let expected_latency = Duration::from_millis(expected_millis).as_micros();
+        assert!(
+            average_ping_latency.max(expected_latency) - average_ping_latency.min(expected_latency)
+                <= 1
+        );
+    }
+
+    /// A mock network that serves the requests sent by the peer monitor, on
+    /// behalf of the connected peers
+    struct MockNetwork {
+        mock_time: MockTimeService,
+        peer_manager_request_receiver:
+            aptos_channel::Receiver<(PeerId, ProtocolId), PeerManagerRequest>,
+        peer_metadata_storage: Arc<PeerMetadataStorage>,
+        reported_depths: HashMap<PeerId, Option<u64>>,
+    }
+
+    impl MockNetwork {
+        fn new() -> (Self, PeerMonitor) {
+            let queue_config = aptos_channel::Config::new(10).queue_style(QueueStyle::FIFO);
+            let (peer_manager_request_sender, peer_manager_request_receiver) = queue_config.build();
+            let (connection_request_sender, _connection_request_receiver) = queue_config.build();
+            let network_sender = PeerMonitoringServiceNetworkSender::new(
+                PeerManagerRequestSender::new(peer_manager_request_sender),
+                ConnectionRequestSender::new(connection_request_sender),
+            );
+            let mut network_senders = HashMap::new();
+            network_senders.insert(NetworkId::Public, network_sender);
+
+            let peer_metadata_storage = PeerMetadataStorage::new(&[NetworkId::Public]);
+            let client = PeerMonitoringServiceClient::new(
+                PeerMonitoringServiceMultiSender::new(network_senders),
+                peer_metadata_storage.clone(),
+            );
+            let time_service = TimeService::mock();
+            let peer_monitor = PeerMonitor::new(
+                PeerMonitoringServiceConfig::default(),
+                client,
+                time_service.clone(),
+            );
+
+            let mock_network = Self {
+                mock_time: time_service.into_mock(),
+                peer_manager_request_receiver,
+                peer_metadata_storage,
+                reported_depths: HashMap::new(),
+            };
+            (mock_network, peer_monitor)
+        }
+
+        /// Connects a new peer with the given role, which reports the given depth
+        fn add_peer(&mut self, role: PeerRole, reported_depth: Option<u64>) -> PeerNetworkId {
+            let peer_id = PeerId::random();
+            let mut connection_metadata = ConnectionMetadata::mock_with_role_and_origin(
+                peer_id,
+                role,
+                ConnectionOrigin::Outbound,
+            );
+            connection_metadata
+                .application_protocols
+                .insert(ProtocolId::PeerMonitoringServiceRpc);
+            self.peer_metadata_storage
+                .insert_connection(NetworkId::Public, connection_metadata);
+            self.reported_depths.insert(peer_id, reported_depth);
+            PeerNetworkId::new(NetworkId::Public, peer_id)
+        }
+
+        /// Responds to the next request sent by the peer monitor
+        async fn handle_next_request(&mut self) {
+            let (peer_id, rpc_request) = match self.peer_manager_request_receiver.next().await {
+                Some(PeerManagerRequest::SendRpc(peer_id, rpc_request)) => (peer_id, rpc_request),
+                request => panic!("Unexpected peer manager request: {:?}", request),
+            };
+            let protocol_id = rpc_request.protocol_id;
+            let response = match protocol_id
+                .from_bytes::<PeerMonitoringServiceMessage>(&rpc_request.data)
+                .unwrap()
+            {
+                PeerMonitoringServiceMessage::Request(PeerMonitoringServiceRequest::Ping(
+                    PingRequest { ping_counter },
+                )) => PeerMonitoringServiceResponse::Ping(PingResponse { ping_counter }),
+                PeerMonitoringServiceMessage::Request(
+                    PeerMonitoringServiceRequest::GetDepthFromValidators,
+                ) => PeerMonitoringServiceResponse::DepthFromValidators(
+                    DepthFromValidatorsResponse {
+                        depth_from_validators: self.reported_depths[&peer_id],
+                    },
+                ),
+                message => panic!("Unexpected message: {:?}", message),
+            };
+            let response_data = protocol_id
+                .to_bytes(&PeerMonitoringServiceMessage::Response(Ok(response)))
+                .unwrap();
+            rpc_request.res_tx.send(Ok(response_data.into())).unwrap();
+        }
+
+        /// Waits for the peer monitor to update the monitoring metadata of the peer
+        async fn wait_for_monitoring_metadata(
+            &self,
+            peer: PeerNetworkId,
+        ) -> PeerMonitoringMetadata {
+            loop {
+                let peer_monitoring_metadata = self
+                    .peer_metadata_storage
+                    .read(peer)
+                    .unwrap()
+                    .peer_monitoring_metadata;
+                if peer_monitoring_metadata.average_ping_latency.is_some() {
+                    return peer_monitoring_metadata;
+                }
+                tokio::task::yield_now().await;
+            }
+        }
+    }
+}
@@ -8,17 +8,28 @@ use crate::{
     metrics::{increment_counter, start_timer},
     network::PeerMonitoringServiceNetworkEvents,
 };
-use ::network::{application::storage::PeerMetadataStorage, ProtocolId};
-use aptos_config::config::PeerMonitoringServiceConfig;
+use ::network::{
+    application::{storage::PeerMetadataStorage, types::PeerInfo},
+    ProtocolId,
+};
+use aptos_config::{
+    config::{PeerMonitoringServiceConfig, PeerRole},
+    network_id::PeerNetworkId,
+};
 use aptos_logger::prelude::*;
 use bounded_executor::BoundedExecutor;
 use futures::stream::StreamExt;
 use peer_monitoring_service_types::{
-    ConnectedPeersResponse, PeerMonitoringServiceError, PeerMonitoringServiceRequest,
-    PeerMonitoringServiceResponse, Result, ServerProtocolVersionResponse,
+    sanitize_depth_from_validators, ConnectedPeersResponse, DepthFromValidatorsResponse,
+    KnownPeersResponse, PeerMonitoringServiceError, PeerMonitoringServiceRequest,
+    PeerMonitoringServiceResponse, PingRequest, PingResponse, Result,
+    ServerProtocolVersionResponse, ValidatorsAndVFNsResponse, MAX_DEPTH_FROM_VALIDATORS,
 };
 use serde::{Deserialize, Serialize};
-use std::{collections::HashMap, sync::Arc};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
 use thiserror::Error;
 use tokio::runtime::Handle;
 
@@ -144,7 +155,7 @@ impl Handler {
                 self.get_server_protocol_version()
             }
             PeerMonitoringServiceRequest::GetValidatorsAndVFNs => self.get_validators_and_vfns(),
-            PeerMonitoringServiceRequest::Ping => self.handle_ping(),
+            PeerMonitoringServiceRequest::Ping(request) => self.handle_ping(request),
         };
 
         // Process the response and handle any errors
@@ -181,28 +192,49 @@ impl Handler {
     }
 
     fn get_connected_peers(&self) -> Result<PeerMonitoringServiceResponse, Error> {
-        // Fetch all connected peers
-        let mut connected_peers = HashMap::new();
-        for network in self.peer_metadata.networks() {
-            for (peer_network_id, peer_info) in self.peer_metadata.read_all(network) {
-                if peer_info.is_connected() {
-                    connected_peers.insert(peer_network_id, peer_info);
-                }
-            }
-        }
-
-        // Return the connected peers
         Ok(PeerMonitoringServiceResponse::ConnectedPeers(
-            ConnectedPeersResponse { connected_peers },
+            ConnectedPeersResponse {
+                connected_peers: self.fetch_connected_peers(),
+            },
         ))
     }
 
     fn get_depth_from_validators(&self) -> Result<PeerMonitoringServiceResponse, Error> {
-        unimplemented!();
+        // Validators are at depth 0. Any other node is one hop further from
+        // the validators than the closest of its connected peers (if that's
+        // still a plausible depth).
+        let is_validator = self
+            .peer_metadata
+            .networks()
+            .any(|network_id| network_id.is_validator_network());
+        let depth_from_validators = if is_validator {
+            Some(0)
+        } else {
+            self.fetch_connected_peers()
+                .values()
+                .filter_map(peer_depth_from_validators)
+                .min()
+                .map(|depth| depth + 1)
+                .filter(|depth| *depth <= MAX_DEPTH_FROM_VALIDATORS)
+        };
+
+        Ok(PeerMonitoringServiceResponse::DepthFromValidators(
+            DepthFromValidatorsResponse {
+                depth_from_validators,
+            },
+        ))
     }
 
     fn get_known_peers(&self) -> Result<PeerMonitoringServiceResponse, Error> {
-        unimplemented!();
+        // Fetch all peers, whatever their connection state
+        let mut known_peers = HashSet::new();
+        for network in self.peer_metadata.networks() {
+            known_peers.extend(self.peer_metadata.keys(network));
+        }
+
+        Ok(PeerMonitoringServiceResponse::KnownPeers(
+            KnownPeersResponse { known_peers },
+        ))
     }
 
     fn get_server_protocol_version(&self) -> Result<PeerMonitoringServiceResponse, Error> {
@@ -214,12 +246,52 @@ impl Handler {
     }
 
     fn get_validators_and_vfns(&self) -> Result<PeerMonitoringServiceResponse, Error> {
-        unimplemented!();
+        // Sort the connected peers by the roles they have for us
+        let mut validators = HashSet::new();
+        let mut vfns = HashSet::new();
+        for (peer_network_id, peer_info) in self.fetch_connected_peers() {
+            match peer_info.active_connection.role {
+                PeerRole::Validator => {
+                    validators.insert(peer_network_id);
+                }
+                PeerRole::ValidatorFullNode => {
+                    vfns.insert(peer_network_id);
+                }
+                _ => {}
+            }
+        }
+
+        Ok(PeerMonitoringServiceResponse::ValidatorsAndVFNs(
+            ValidatorsAndVFNsResponse { validators, vfns },
+        ))
     }
 
-    fn handle_ping(&self) -> Result<PeerMonitoringServiceResponse, Error> {
-        unimplemented!();
+    fn handle_ping(&self, request: &PingRequest) -> Result<PeerMonitoringServiceResponse, Error> {
+        Ok(PeerMonitoringServiceResponse::Ping(PingResponse {
+            ping_counter: request.ping_counter,
+        }))
     }
+
+    /// Returns all connected peers across all networks
+    fn fetch_connected_peers(&self) -> HashMap<PeerNetworkId, PeerInfo> {
+        let mut connected_peers = HashMap::new();
+        for network in self.peer_metadata.networks() {
+            connected_peers.extend(
+                self.peer_metadata
+                    .read_filtered(network, |(_, peer_info)| peer_info.is_connected()),
+            );
+        }
+        connected_peers
+    }
+}
+
+/// Returns the depth of the given peer from the validators, if known. Peers
+/// connected to us as validators are always at depth 0.
+fn peer_depth_from_validators(peer_info: &PeerInfo) -> Option<u64> {
+    sanitize_depth_from_validators(
+        peer_info.active_connection.role,
+        peer_info.peer_monitoring_metadata.depth_from_validators,
+    )
 }
 
 /// Logs the response sent by the monitoring service for a request
@@ -29,11 +29,13 @@ use network::{
     transport::{ConnectionId, ConnectionMetadata},
 };
 use peer_monitoring_service_types::{
-    ConnectedPeersResponse, PeerMonitoringServiceError, PeerMonitoringServiceMessage,
-    PeerMonitoringServiceRequest, PeerMonitoringServiceResponse, ServerProtocolVersionResponse,
+    ConnectedPeersResponse, DepthFromValidatorsResponse, KnownPeersResponse,
+    PeerMonitoringServiceError, PeerMonitoringServiceMessage, PeerMonitoringServiceRequest,
+    PeerMonitoringServiceResponse, PingRequest, PingResponse, ServerProtocolVersionResponse,
+    ValidatorsAndVFNsResponse, MAX_DEPTH_FROM_VALIDATORS,
 };
 use std::{
-    collections::{hash_map::Entry, HashMap},
+    collections::{hash_map::Entry, HashMap, HashSet},
     str::FromStr,
     sync::Arc,
 };
@@ -123,6 +125,228 @@ async fn test_get_connected_peers() {
     assert_eq!(response, expected_response);
 }
 
+#[tokio::test]
+async fn test_ping() {
+    // Create the peer monitoring client and server
+    let (mut mock_client, service, _) = MockClient::new();
+    tokio::spawn(service.start());
+
+    // Process several pings and verify the counters are echoed back
+    for ping_counter in 0..3 {
+        let request = PeerMonitoringServiceRequest::Ping(PingRequest { ping_counter });
+        let response = mock_client.send_request(request).await.unwrap();
+        assert_eq!(
+            response,
+            PeerMonitoringServiceResponse::Ping(PingResponse { ping_counter })
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_get_known_peers() {
+    // Create the peer monitoring client and server
+    let (mut mock_client, service, peer_metadata_storage) = MockClient::new();
+    tokio::spawn(service.start());
+
+    // Add a connected and a disconnected peer
+    let connected_peer = add_connected_peer(
+        &peer_metadata_storage,
+        NetworkId::Validator,
+        PeerRole::Validator,
+    );
+    let disconnected_peer = add_connected_peer(
+        &peer_metadata_storage,
+        NetworkId::Validator,
+        PeerRole::Validator,
+    );
+    disconnect_peer(&peer_metadata_storage, disconnected_peer);
+
+    // Process a request to fetch the known peers
+    let request = PeerMonitoringServiceRequest::GetKnownPeers;
+    let response = mock_client.send_request(request).await.unwrap();
+
+    // Verify both peers are known
+    let known_peers: HashSet<_> = vec![connected_peer, disconnected_peer]
+        .into_iter()
+        .collect();
+    let expected_response =
+        PeerMonitoringServiceResponse::KnownPeers(KnownPeersResponse { known_peers });
+    assert_eq!(response, expected_response);
+}
+
+#[tokio::test]
+async fn test_get_validators_and_vfns() {
+    // Create the peer monitoring client and server
+    let (mut mock_client, service, peer_metadata_storage) =
+        MockClient::new_with_networks(&[NetworkId::Vfn, NetworkId::Public]);
+    tokio::spawn(service.start());
+
+    // Add peers with different roles
+    let validator = add_connected_peer(&peer_metadata_storage, NetworkId::Vfn, PeerRole::Validator);
+    let vfn = add_connected_peer(
+        &peer_metadata_storage,
+        NetworkId::Public,
+        PeerRole::ValidatorFullNode,
+    );
+    let disconnected_vfn = add_connected_peer(
+        &peer_metadata_storage,
+        NetworkId::Public,
+        PeerRole::ValidatorFullNode,
+    );
+    disconnect_peer(&peer_metadata_storage, disconnected_vfn);
+    add_connected_peer(&peer_metadata_storage, NetworkId::Public, PeerRole::Unknown);
+
+    // Process a request to fetch the validators and VFNs
+    let request = PeerMonitoringServiceRequest::GetValidatorsAndVFNs;
+    let response = mock_client.send_request(request).await.unwrap();
+
+    // Verify only the connected validators and VFNs are returned
+    let expected_response =
+        PeerMonitoringServiceResponse::ValidatorsAndVFNs(ValidatorsAndVFNsResponse {
+            validators: vec![validator].into_iter().collect(),
+            vfns: vec![vfn].into_iter().collect(),
+        });
+    assert_eq!(response, expected_response);
+}
+
+#[tokio::test]
+async fn test_get_depth_from_validators_max_depth() {
+    // Create the peer monitoring client and server for a fullnode
+    let (mut mock_client, service, peer_metadata_storage) =
+        MockClient::new_with_networks(&[NetworkId::Public]);
+    tokio::spawn(service.start());
+
+    // Verify a peer at the maximum depth doesn't make the node deeper than that
+    let peer = add_connected_peer(&peer_metadata_storage, NetworkId::Public, PeerRole::Unknown);
+    set_depth_from_validators(&peer_metadata_storage, peer, MAX_DEPTH_FROM_VALIDATORS);
+    let request = PeerMonitoringServiceRequest::GetDepthFromValidators;
+    let response = mock_client.send_request(request.clone()).await.unwrap();
+    assert_eq!(response, create_depth_from_validators_response(None));
+
+    // Verify the depth is known once a closer peer connects
+    let peer = add_connected_peer(&peer_metadata_storage, NetworkId::Public, PeerRole::Unknown);
+    set_depth_from_validators(&peer_metadata_storage, peer, MAX_DEPTH_FROM_VALIDATORS - 1);
+    let response = mock_client.send_request(request).await.unwrap();
+    assert_eq!(
+        response,
+        create_depth_from_validators_response(Some(MAX_DEPTH_FROM_VALIDATORS))
+    );
+}
+
+#[tokio::test]
+async fn test_get_depth_from_validators_validator() {
+    // Create the peer monitoring client and server for a validator
+    let (mut mock_client, service, _) = MockClient::new();
+    tokio::spawn(service.start());
+
+    // Verify the validator is at depth 0
+    let request = PeerMonitoringServiceRequest::GetDepthFromValidators;
+    let response = mock_client.send_request(request).await.unwrap();
+    assert_eq!(response, create_depth_from_validators_response(Some(0)));
+}
+
+#[tokio::test]
+async fn test_get_depth_from_validators_fullnode() {
+    // Create the peer monitoring client and server for a fullnode
+    let (mut mock_client, service, peer_metadata_storage) =
+        MockClient::new_with_networks(&[NetworkId::Vfn, NetworkId::Public]);
+    tokio::spawn(service.start());
+
+    // Verify the depth is unknown without any peers
+    let request = PeerMonitoringServiceRequest::GetDepthFromValidators;
+    let response = mock_client.send_request(request.clone()).await.unwrap();
+    assert_eq!(response, create_depth_from_validators_response(None));
+
+    // Add peers with known depths and verify the depth is one more than the closest
+    for depth in [3, 2, 4] {
+        let peer = add_connected_peer(&peer_metadata_storage, NetworkId::Public, PeerRole::Unknown);
+        set_depth_from_validators(&peer_metadata_storage, peer, depth);
+    }
+    let response = mock_client.send_request(request.clone()).await.unwrap();
+    assert_eq!(response, create_depth_from_validators_response(Some(3)));
+
+    // Add peers with implausible depths and verify they are ignored: only
+    // validators can be at depth 0, and depths are bounded.
+    for depth in [0, MAX_DEPTH_FROM_VALIDATORS + 1, u64::MAX] {
+        let peer = add_connected_peer(&peer_metadata_storage, NetworkId::Public, PeerRole::Unknown);
+        set_depth_from_validators(&peer_metadata_storage, peer, depth);
+    }
+    let response = mock_client.send_request(request.clone()).await.unwrap();
+    assert_eq!(response, create_depth_from_validators_response(Some(3)));
+
+    // Connect to a validator and verify the depth is 1
+    let validator = add_connected_peer(&peer_metadata_storage, NetworkId::Vfn, PeerRole::Validator);
+    let response = mock_client.send_request(request.clone()).await.unwrap();
+    assert_eq!(response, create_depth_from_validators_response(Some(1)));
+
+    // Disconnect from the validator and verify the depth goes back up
+    disconnect_peer(&peer_metadata_storage, validator);
+    let response = mock_client.send_request(request).await.unwrap();
+    assert_eq!(response, create_depth_from_validators_response(Some(3)));
+}
+
+/// Adds a new connected peer with the given role to the peer metadata storage
+fn add_connected_peer(
+    peer_metadata_storage: &PeerMetadataStorage,
+    network_id: NetworkId,
+    role: PeerRole,
+) -> PeerNetworkId {
+    let peer_id = PeerId::random();
+    let connection_metadata = ConnectionMetadata::new(
+        peer_id,
+        ConnectionId::default(),
+        NetworkAddress::from_str("/ip4/127.0.0.1/tcp/8081").unwrap(),
+        ConnectionOrigin::Inbound,
+        MessagingProtocolVersion::V1,
+        ProtocolIdSet::empty(),
+        role,
+    );
+    peer_metadata_storage.insert_connection(network_id, connection_metadata);
+    PeerNetworkId::new(network_id, peer_id)
+}
+
+/// Marks the given peer as disconnected in the peer metadata storage
+fn disconnect_peer(peer_metadata_storage: &PeerMetadataStorage, peer_network_id: PeerNetworkId) {
+    peer_metadata_storage
+        .write(peer_network_id, |entry| match entry {
+            Entry::Vacant(..) => Err(PeerError::NotFound),
+            Entry::Occupied(inner) => {
+                inner.get_mut().status = PeerState::Disconnected;
+                Ok(())
+            }
+        })
+        .unwrap();
+}
+
+/// Sets the depth from the validators of the given peer, as if it was monitored
+fn set_depth_from_validators(
+    peer_metadata_storage: &PeerMetadataStorage,
+    peer_network_id: PeerNetworkId,
+    depth_from_validators: u64,
+) {
+    peer_metadata_storage
+        .write(peer_network_id, |entry| match entry {
+            Entry::Vacant(..) => Err(PeerError::NotFound),
+            Entry::Occupied(inner) => {
+                inner
+                    .get_mut()
+                    .peer_monitoring_metadata
+                    .depth_from_validators = Some(depth_from_validators);
+                Ok(())
+            }
+        })
+        .unwrap();
+}
+
+/// Creates a depth from validators response with the given depth
+fn create_depth_from_validators_response(
+    depth_from_validators: Option<u64>,
+) -> PeerMonitoringServiceResponse {
+    PeerMonitoringServiceResponse::DepthFromValidators(DepthFromValidatorsResponse {
+        depth_from_validators,
+    })
+}
+
 /// A wrapper around the inbound network interface/channel for easily sending
 /// mock client requests to a [`PeerMonitoringServiceServer`].
 struct MockClient {
@@ -131,6 +355,12 @@ struct MockClient {
 
 impl MockClient {
     fn new() -> (Self, PeerMonitoringServiceServer, Arc<PeerMetadataStorage>) {
+        Self::new_with_networks(&[NetworkId::Validator])
+    }
+
+    fn new_with_networks(
+        network_ids: &[NetworkId],
+    ) -> (Self, PeerMonitoringServiceServer, Arc<PeerMetadataStorage>) {
         initialize_logger();
 
         // Create the peer monitoring service event stream
@@ -149,7 +379,7 @@ impl MockClient {
         );
 
         // Create the peer monitoring server
-        let peer_metadata_storage = PeerMetadataStorage::new(&[NetworkId::Validator]);
+        let peer_metadata_storage = PeerMetadataStorage::new(network_ids);
         let executor = tokio::runtime::Handle::current();
         let peer_monitoring_server = PeerMonitoringServiceServer::new(
             peer_monitoring_service_config,
@@ -3,14 +3,35 @@
 
 #![forbid(unsafe_code)]
 
-use aptos_config::network_id::PeerNetworkId;
+use aptos_config::{config::PeerRole, network_id::PeerNetworkId};
 use network::application::types::PeerInfo;
 use serde::{Deserialize, Serialize};
-use std::{collections::HashMap, convert::TryFrom};
+use std::{
+    collections::{HashMap, HashSet},
+    convert::TryFrom,
+};
 use thiserror::Error;
 
 pub type Result<T, E = PeerMonitoringServiceError> = ::std::result::Result<T, E>;
 
+/// The maximum depth from the validators that is considered plausible. Deeper
+/// (reported) depths are treated as unknown.
+pub const MAX_DEPTH_FROM_VALIDATORS: u64 = 50;
+
+/// Sanity checks the depth from the validators reported by a peer with the
+/// given role. Peers connected as validators are always at depth 0, and no
+/// other peer can be. Depths beyond `MAX_DEPTH_FROM_VALIDATORS` are ignored.
+pub fn sanitize_depth_from_validators(
+    peer_role: PeerRole,
+    depth_from_validators: Option<u64>,
+) -> Option<u64> {
+    if peer_role == PeerRole::Validator {
+        Some(0)
+    } else {
+        depth_from_validators.filter(|depth| (1..=MAX_DEPTH_FROM_VALIDATORS).contains(depth))
+    }
+}
+
 /// An error that can be returned to the client on a failure to
 /// process a request.
 #[derive(Clone, Debug, Deserialize, Eq, Error, PartialEq, Serialize)]
@@ -38,7 +59,7 @@ pub enum PeerMonitoringServiceRequest {
     GetKnownPeers,            // Returns all of the known peers in the network
     GetServerProtocolVersion, // Fetches the protocol version run by the server
     GetValidatorsAndVFNs,     // Returns the current validators and VFNs
-    Ping, // A simple message used by the client to ensure liveness and measure latency
+    Ping(PingRequest), // A simple message used by the client to ensure liveness and measure latency
 }
 
 impl PeerMonitoringServiceRequest {
@@ -50,11 +71,17 @@ impl PeerMonitoringServiceRequest {
             Self::GetKnownPeers => "get_known_peers",
             Self::GetServerProtocolVersion => "get_server_protocol_version",
             Self::GetValidatorsAndVFNs => "get_validators_and_vfns",
-            Self::Ping => "ping",
+            Self::Ping(_) => "ping",
         }
     }
 }
 
+/// A ping request, carrying a counter to be echoed back
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct PingRequest {
+    pub ping_counter: u64,
+}
+
 /// A peer monitoring service response
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
 #[allow(clippy::large_enum_variant)]
@@ -90,19 +117,19 @@ pub struct ConnectedPeersResponse {
 /// A response for the depth from validators request
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub struct DepthFromValidatorsResponse {
-    pub todo: bool,
+    pub depth_from_validators: Option<u64>, // None if no path to the validators is known
 }
 
 /// A response for the known peers request
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub struct KnownPeersResponse {
-    pub todo: bool,
+    pub known_peers: HashSet<PeerNetworkId>, // All peers known, whatever their connection state
 }
 
 /// A response for the ping request
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub struct PingResponse {
-    pub todo: bool,
+    pub ping_counter: u64, // The counter of the ping request
 }
 
 /// A response for the server protocol version request
@@ -114,7 +141,8 @@ pub struct ServerProtocolVersionResponse {
 /// A response for the current validators and VFNs
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub struct ValidatorsAndVFNsResponse {
-    pub todo: bool,
+    pub validators: HashSet<PeerNetworkId>, // The connected peers that are validators
+    pub vfns: HashSet<PeerNetworkId>,       // The connected peers that are VFNs
 }
 
 #[derive(Clone, Debug, Error)]
@@ -3,6 +3,7 @@
 
 use crate::{protocols::wire::handshake::v1::ProtocolId, transport::ConnectionMetadata};
 use serde::{Deserialize, Serialize};
+use std::time::Duration;
 
 /// Errors related to the peer layer in the `NetworkInterface`
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -15,6 +16,7 @@ pub enum PeerError {
 pub struct PeerInfo {
     pub status: PeerState,
     pub active_connection: ConnectionMetadata,
+    pub peer_monitoring_metadata: PeerMonitoringMetadata,
 }
 
 impl PeerInfo {
@@ -22,6 +24,7 @@ impl PeerInfo {
         PeerInfo {
             status: PeerState::Connected,
             active_connection: connection_metadata,
+            peer_monitoring_metadata: PeerMonitoringMetadata::default(),
         }
     }
 
@@ -36,6 +39,16 @@ impl PeerInfo {
     }
 }
 
+/// What the peer monitoring service found out about a peer. Unknown until the peer has been
+/// successfully monitored.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct PeerMonitoringMetadata {
+    /// A moving average of the round trip times of the pings sent to the peer
+    pub average_ping_latency: Option<Duration>,
+    /// The number of hops from the peer to the validator set (validators are at depth 0)
+    pub depth_from_validators: Option<u64>,
+}
+
 /// The current state of a `Peer` at any one time
 /// TODO: Allow nodes that are unhealthy to stay connected
 #[derive(Clone, Copy, Debug, Deserialize, Eq, Ord, PartialEq, PartialOrd, Serialize)]
@@ -475,6 +475,87 @@ pub static NETWORK_RATE_LIMIT_METRICS: Lazy<HistogramVec> = Lazy::new(|| {
     .unwrap()
 });
 
+pub static RATE_LIMITED_INBOUND_MESSAGES: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "aptos_network_rate_limited_inbound_messages",
+        "Number of inbound messages dropped by the peer rate limits",
+        &["role_type", "network_id", "peer_id", "protocol_id", "limit"]
+    )
+    .unwrap()
+});
+
+pub static RATE_LIMITED_INBOUND_BYTES: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "aptos_network_rate_limited_inbound_bytes",
+        "Number of inbound bytes dropped by the peer rate limits",
+        &["role_type", "network_id", "peer_id", "protocol_id", "limit"]
+    )
+    .unwrap()
+});
+
+/// Counts an inbound message dropped for exceeding the given peer rate limit
+pub fn rate_limited_inbound_traffic(
+    network_context: &NetworkContext,
+    protocol_id: ProtocolId,
+    limit: &str,
+    size: u64,
+) {
+    let network_id = network_context.network_id();
+    let peer_id = network_context.peer_id().short_str();
+    let values = [
+        network_context.role().as_str(),
+        network_id.as_str(),
+        peer_id.as_str(),
+        protocol_id.as_str(),
+        limit,
+    ];
+    RATE_LIMITED_INBOUND_MESSAGES
+        .with_label_values(&values)
+        .inc();
+    RATE_LIMITED_INBOUND_BYTES
+        .with_label_values(&values)
+        .inc_by(size);
+}
+
+pub static APTOS_NETWORK_OUTBOUND_QUEUEING_DELAY: Lazy<HistogramVec> = Lazy::new(|| {
+    register_histogram_vec!(
+        "aptos_network_outbound_queueing_delay_seconds",
+        "Time outbound messages wait in the queue of their protocol before being written, in seconds",
+        &["role_type", "network_id", "peer_id", "protocol_id"]
+    )
+    .unwrap()
+});
+
+pub fn outbound_queueing_delay(network_context: &NetworkContext, protocol: &str) -> Histogram {
+    APTOS_NETWORK_OUTBOUND_QUEUEING_DELAY.with_label_values(&[
+        network_context.role().as_str(),
+        network_context.network_id().as_str(),
+        network_context.peer_id().short_str().as_str(),
+        protocol,
+    ])
+}
+
+pub static APTOS_NETWORK_OUTBOUND_QUEUE_DROPPED_MESSAGES: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "aptos_network_outbound_queue_dropped_messages",
+        "Number of outbound messages dropped because the queue of their protocol was full",
+        &["role_type", "network_id", "peer_id", "protocol_id"]
+    )
+    .unwrap()
+});
+
+pub fn outbound_queue_dropped_messages(
+    network_context: &NetworkContext,
+    protocol: &str,
+) -> IntCounter {
+    APTOS_NETWORK_OUTBOUND_QUEUE_DROPPED_MESSAGES.with_label_values(&[
+        network_context.role().as_str(),
+        network_context.network_id().as_str(),
+        network_context.peer_id().short_str().as_str(),
+        protocol,
+    ])
+}
+
 pub static NETWORK_APPLICATION_INBOUND_METRIC: Lazy<HistogramVec> = Lazy::new(|| {
     register_histogram_vec!(
         "aptos_network_app_inbound_traffic",
@@ -32,7 +32,7 @@ use crate::{
     transport::{self, Connection, ConnectionMetadata},
     ProtocolId,
 };
-use aptos_config::network_id::NetworkContext;
+use aptos_config::{config::OutboundQueueConfig, network_id::NetworkContext};
 use aptos_logger::prelude::*;
 use aptos_rate_limiter::rate_limit::SharedBucket;
 use aptos_time_service::{TimeService, TimeServiceTrait};
@@ -48,18 +48,29 @@ use futures::{
 };
 use serde::Serialize;
 use short_hex_str::AsShortHexStr;
-use std::{fmt, panic, time::Duration};
+use std::{
+    fmt, panic,
+    time::{Duration, Instant},
+};
 use tokio::runtime::Handle;
 use tokio_util::compat::{
     FuturesAsyncReadCompatExt, TokioAsyncReadCompatExt, TokioAsyncWriteCompatExt,
 };
 
+mod outbound_queue;
+mod rate_limit;
 #[cfg(test)]
 mod test;
 
 #[cfg(any(test, feature = "fuzzing"))]
 pub mod fuzzing;
 
+pub use outbound_queue::{queue_names as outbound_queue_names, OutboundQueueSender};
+pub use rate_limit::{PeerRateLimiter, SharedPeerRateLimiter};
+
+/// The frequency at which to log the dropped inbound messages of a peer (secs)
+const RATE_LIMIT_LOG_FREQ_SECS: u64 = 60;
+
 /// Requests [`Peer`] receives from the [`PeerManager`](crate::peer_manager::PeerManager).
 #[derive(Debug)]
 pub enum PeerRequest {
@@ -137,12 +148,19 @@ pub struct Peer<TSocket> {
     inbound_rate_limiter: Option<SharedBucket>,
     /// Optional outbound rate limiter
     outbound_rate_limiter: Option<SharedBucket>,
+    /// Optional rate limiter of inbound messages, per protocol and across protocols
+    peer_rate_limiter: Option<SharedPeerRateLimiter>,
+    /// The last time a dropped inbound message of this peer was logged
+    last_rate_limit_log: Option<Instant>,
+    /// Bounds and weights of the outbound message queue of each protocol
+    outbound_queue_config: OutboundQueueConfig,
 }
 
 impl<TSocket> Peer<TSocket>
 where
     TSocket: AsyncRead + AsyncWrite + Send + 'static,
 {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         network_context: NetworkContext,
         executor: Handle,
@@ -157,6 +175,8 @@ where
         max_frame_size: usize,
         inbound_rate_limiter: Option<SharedBucket>,
         outbound_rate_limiter: Option<SharedBucket>,
+        peer_rate_limiter: Option<SharedPeerRateLimiter>,
+        outbound_queue_config: OutboundQueueConfig,
     ) -> Self {
         let Connection {
             metadata: connection_metadata,
@@ -189,6 +209,9 @@ where
             max_frame_size,
             inbound_rate_limiter,
             outbound_rate_limiter,
+            peer_rate_limiter,
+            last_rate_limit_log: None,
+            outbound_queue_config,
         }
     }
 
@@ -228,6 +251,7 @@ where
         //   2. `close_tx`: Handle to close the task and underlying connection.
         let (mut write_reqs_tx, writer_close_tx) = Self::start_writer_task(
             &self.executor,
+            &self.outbound_queue_config,
             self.time_service.clone(),
             self.connection_metadata.clone(),
             self.network_context,
@@ -298,37 +322,28 @@ where
     // Start a new task on the given executor which is responsible for writing outbound messages on
     // the wire. The function returns two channels which can be used to send intructions to the
     // task:
-    // 1. The first channel is used to send outbound NetworkMessages to the task
+    // 1. The first is the outbound queue onto which NetworkMessages are pushed for the task, see
+    //    `outbound_queue` for how they are prioritized
     // 2. The second channel is used to instruct the task to close the connection and terminate.
     // If outbound messages are queued when the task receives a close instruction, it discards
     // them and immediately closes the connection.
     fn start_writer_task(
         executor: &Handle,
+        outbound_queue_config: &OutboundQueueConfig,
         time_service: TimeService,
         connection_metadata: ConnectionMetadata,
         network_context: NetworkContext,
         mut writer: NetworkMessageSink<impl AsyncWrite + Unpin + Send + 'static>,
-    ) -> (
-        channel::Sender<(
-            NetworkMessage,
-            oneshot::Sender<Result<(), PeerManagerError>>,
-        )>,
-        oneshot::Sender<()>,
-    ) {
+    ) -> (OutboundQueueSender, oneshot::Sender<()>) {
         let remote_peer_id = connection_metadata.remote_peer_id;
-        let (write_reqs_tx, mut write_reqs_rx): (
-            channel::Sender<(
-                NetworkMessage,
-                oneshot::Sender<Result<(), PeerManagerError>>,
-            )>,
-            _,
-        ) = channel::new(1024, &counters::PENDING_WIRE_MESSAGES);
+        let (write_reqs_tx, mut write_reqs_rx) =
+            outbound_queue::new(outbound_queue_config, network_context, time_service.clone());
         let (close_tx, close_rx) = oneshot::channel();
         let writer_task = async move {
             let mut close_rx = close_rx.into_stream();
             loop {
                 futures::select! {
-                    (message, ack_ch) = write_reqs_rx.select_next_some() => {
+                    (message, ack_ch) = write_reqs_rx.next().fuse() => {
                         if let Err(err) = writer
                             .send(&message)
                             .map_ok(|_| ack_ch.send(Ok(())))
@@ -404,10 +419,7 @@ where
     async fn handle_inbound_message(
         &mut self,
         message: Result<NetworkMessage, ReadError>,
-        write_reqs_tx: &mut channel::Sender<(
-            NetworkMessage,
-            oneshot::Sender<Result<(), PeerManagerError>>,
-        )>,
+        write_reqs_tx: &mut OutboundQueueSender,
     ) -> Result<(), PeerManagerError> {
         trace!(
             NetworkSchema::new(&self.network_context)
@@ -430,7 +442,7 @@ where
                     let message = NetworkMessage::Error(error_code);
 
                     let (ack_tx, _) = oneshot::channel();
-                    write_reqs_tx.send((message, ack_tx)).await?;
+                    write_reqs_tx.push(None, message, ack_tx)?;
                     return Err(err.into());
                 }
                 ReadError::IoError(_) => {
@@ -442,7 +454,14 @@ where
         };
 
         match message {
-            NetworkMessage::DirectSendMsg(message) => self.handle_inbound_direct_send(message),
+            NetworkMessage::DirectSendMsg(message) => {
+                if self.within_rate_limits(message.protocol_id, message.raw_msg.len()) {
+                    self.handle_inbound_direct_send(message)
+                }
+            }
+            NetworkMessage::Error(ErrorCode::RateLimited(rate_limited)) => self
+                .outbound_rpcs
+                .handle_rate_limited_request(rate_limited.request_id),
             NetworkMessage::Error(error_msg) => {
                 warn!(
                     NetworkSchema::new(&self.network_context)
@@ -455,6 +474,14 @@ where
                 );
             }
             NetworkMessage::RpcRequest(request) => {
+                if !self.within_rate_limits(request.protocol_id, request.raw_request.len()) {
+                    // Let the peer fail the request, rather than wait for it to time out
+                    let error_code =
+                        ErrorCode::rate_limited(request.protocol_id, request.request_id);
+                    let (ack_tx, _) = oneshot::channel();
+                    write_reqs_tx.push(None, NetworkMessage::Error(error_code), ack_tx)?;
+                    return Ok(());
+                }
                 if let Err(err) = self
                     .inbound_rpcs
                     .handle_inbound_request(&mut self.peer_notifs_tx, request)
@@ -476,6 +503,49 @@ where
         Ok(())
     }
 
+    /// Returns true iff an inbound message of the given protocol and size is
+    /// within the rate limits of the remote peer. Otherwise, the message should
+    /// be dropped.
+    fn within_rate_limits(&mut self, protocol_id: ProtocolId, num_bytes: usize) -> bool {
+        let result = match self.peer_rate_limiter.as_ref() {
+            Some(peer_rate_limiter) => peer_rate_limiter.lock().try_acquire(protocol_id, num_bytes),
+            None => return true,
+        };
+        match result {
+            Ok(()) => true,
+            Err(exceeded_limit) => {
+                counters::rate_limited_inbound_traffic(
+                    &self.network_context,
+                    protocol_id,
+                    exceeded_limit.as_str(),
+                    num_bytes as u64,
+                );
+
+                // Log at most once per period for each peer, so a single noisy
+                // peer can't hide the others
+                let now = self.time_service.now();
+                let log_freq = Duration::from_secs(RATE_LIMIT_LOG_FREQ_SECS);
+                if self
+                    .last_rate_limit_log
+                    .map_or(true, |last_log| now.duration_since(last_log) >= log_freq)
+                {
+                    self.last_rate_limit_log = Some(now);
+                    warn!(
+                        NetworkSchema::new(&self.network_context)
+                            .connection_metadata(&self.connection_metadata),
+                        protocol_id = protocol_id,
+                        "{} Dropping inbound message for protocol {} from peer {}, as it exceeds the {} rate limit",
+                        self.network_context,
+                        protocol_id,
+                        self.remote_peer_id().short_str(),
+                        exceeded_limit.as_str(),
+                    );
+                }
+                false
+            }
+        }
+    }
+
     /// Handle an inbound DirectSendMsg from the remote peer. There's not much to
     /// do here other than bump some counters and forward the message up to the
     /// PeerManager.
@@ -516,10 +586,7 @@ where
     async fn handle_outbound_request(
         &mut self,
         request: PeerRequest,
-        write_reqs_tx: &mut channel::Sender<(
-            NetworkMessage,
-            oneshot::Sender<Result<(), PeerManagerError>>,
-        )>,
+        write_reqs_tx: &mut OutboundQueueSender,
     ) {
         trace!(
             "Peer {} PeerRequest::{:?}",
@@ -544,7 +611,7 @@ where
                 });
                 let (ack_tx, _ack_rx) = oneshot::channel();
 
-                match write_reqs_tx.send((message, ack_tx)).await {
+                match write_reqs_tx.push(Some(protocol_id), message, ack_tx) {
                     Ok(_) => {
                         counters::direct_send_messages(&self.network_context, SENT_LABEL).inc();
                         counters::direct_send_bytes(&self.network_context, SENT_LABEL)
@@ -598,6 +665,10 @@ where
     async fn do_shutdown(mut self, writer_close_tx: oneshot::Sender<()>, reason: DisconnectReason) {
         let remote_peer_id = self.remote_peer_id();
 
+        // Release the rate limiter first, so the PeerManager can garbage collect
+        // it when handling the disconnection.
+        self.peer_rate_limiter = None;
+
         // Send a PeerDisconnected event to PeerManager.
         if let Err(e) = self
             .connection_notifs_tx
@@ -0,0 +1,497 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+//! The queue of outbound [`NetworkMessage`]s of a single connection, shared by
+//! the [`Peer`](crate::peer::Peer) actor and its writer task.
+//!
+//! Each protocol has its own bounded queue, and the protocols are grouped into
+//! priority classes. Consensus messages are always written before mempool
+//! messages, which are always written before state sync messages, so that large
+//! state sync responses can't delay consensus. Within a class, the protocols
+//! share the connection by deficit round robin, in proportion to their weights.
+//!
+//! Pushing a message never waits. When the queue of its protocol is full, the
+//! message is dropped and the push fails: a direct send is lost, and an rpc
+//! fails without waiting for its timeout. This differs from the single channel the queue replaced, whose
+//! awaited send stalled the `Peer` actor until the writer caught up, and so
+//! pushed back on the applications. Dropped messages are counted by
+//! `aptos_network_outbound_queue_dropped_messages`.
+
+use crate::{
+    counters::{self, PENDING_WIRE_MESSAGES},
+    peer_manager::PeerManagerError,
+    protocols::wire::messaging::v1::NetworkMessage,
+    ProtocolId,
+};
+use aptos_config::{config::OutboundQueueConfig, network_id::NetworkContext};
+use aptos_infallible::Mutex;
+use aptos_time_service::{TimeService, TimeServiceTrait};
+use futures::channel::oneshot;
+use std::{collections::VecDeque, sync::Arc, time::Instant};
+use tokio::sync::Notify;
+
+/// The number of bytes a protocol of weight 1 may write per round
+const QUANTUM_BYTES: usize = 64 * 1024;
+
+/// The metrics label of messages without a protocol, i.e., error messages
+const CONTROL_LABEL: &str = "control";
+
+pub type AckSender = oneshot::Sender<Result<(), PeerManagerError>>;
+
+/// The priority classes of outbound messages, from highest to lowest
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum PriorityClass {
+    Consensus = 0,
+    Mempool,
+    StateSync,
+}
+
+const NUM_PRIORITY_CLASSES: usize = 3;
+
+impl PriorityClass {
+    /// Returns the class of the given protocol. Messages without a protocol
+    /// (i.e., error messages) and health checks are small, and keep the
+    /// connection alive, so they go along with consensus.
+    fn of(protocol_id: Option<ProtocolId>) -> Self {
+        use ProtocolId::*;
+        match protocol_id {
+            None
+            | Some(ConsensusRpcBcs)
+            | Some(ConsensusDirectSendBcs)
+            | Some(ConsensusDirectSendJson)
+            | Some(ConsensusRpcJson)
+            | Some(HealthCheckerRpc) => PriorityClass::Consensus,
+            Some(MempoolDirectSend)
+            | Some(MempoolRpc)
+            | Some(DiscoveryDirectSend)
+            | Some(PeerMonitoringServiceRpc) => PriorityClass::Mempool,
+            Some(StateSyncDirectSend) | Some(StorageServiceRpc) => PriorityClass::StateSync,
+        }
+    }
+}
+
+fn protocol_label(protocol_id: Option<ProtocolId>) -> &'static str {
+    protocol_id.map_or(CONTROL_LABEL, ProtocolId::as_str)
+}
+
+/// The protocols with a queue, where `None` is for messages without a protocol
+fn queued_protocols() -> impl Iterator<Item = Option<ProtocolId>> {
+    std::iter::once(None).chain(ProtocolId::all().iter().copied().map(Some))
+}
+
+/// The names of the queues, as used to configure them
+pub fn queue_names() -> Vec<&'static str> {
+    queued_protocols().map(protocol_label).collect()
+}
+
+/// Returns the number of payload bytes of the message
+fn payload_size(message: &NetworkMessage) -> usize {
+    match message {
+        NetworkMessage::Error(_) => 0,
+        NetworkMessage::RpcRequest(request) => request.raw_request.len(),
+        NetworkMessage::RpcResponse(response) => response.raw_response.len(),
+        NetworkMessage::DirectSendMsg(message) => message.raw_msg.len(),
+    }
+}
+
+struct QueuedMessage {
+    message: NetworkMessage,
+    ack_tx: AckSender,
+    protocol_id: Option<ProtocolId>,
+    size: usize,
+    enqueue_time: Instant,
+}
+
+struct ProtocolQueue {
+    protocol_id: Option<ProtocolId>,
+    messages: VecDeque<QueuedMessage>,
+    max_size: usize,
+    /// The number of bytes added to the deficit each round
+    quantum: usize,
+    /// The number of bytes the protocol may still write in this round
+    deficit: usize,
+}
+
+/// The protocol queues of a single priority class, served by deficit round robin
+#[derive(Default)]
+struct PriorityClassQueues {
+    queues: Vec<ProtocolQueue>,
+    /// The index of the queue being served in this round
+    current: usize,
+    num_messages: usize,
+}
+
+impl PriorityClassQueues {
+    fn pop(&mut self) -> Option<QueuedMessage> {
+        if self.num_messages == 0 {
+            return None;
+        }
+        loop {
+            let queue = &mut self.queues[self.current];
+            match queue.messages.front() {
+                Some(message) if message.size <= queue.deficit => {
+                    queue.deficit -= message.size;
+                    let message = queue.messages.pop_front();
+                    if queue.messages.is_empty() {
+                        // Idle queues don't save up their deficit
+                        queue.deficit = 0;
+                    }
+                    self.num_messages -= 1;
+                    return message;
+                }
+                Some(_) => (),
+                None => queue.deficit = 0,
+            }
+
+            // Move on to the next queue, which gets its quantum for this round
+            self.current = (self.current + 1) % self.queues.len();
+            let queue = &mut self.queues[self.current];
+            if !queue.messages.is_empty() {
+                queue.deficit = queue.deficit.saturating_add(queue.quantum);
+            }
+        }
+    }
+}
+
+struct OutboundQueues {
+    /// The queues of each priority class, from highest to lowest priority
+    classes: Vec<PriorityClassQueues>,
+    /// Whether the writer task has terminated
+    closed: bool,
+}
+
+impl OutboundQueues {
+    fn new(config: &OutboundQueueConfig) -> Self {
+        let mut classes: Vec<PriorityClassQueues> = (0..NUM_PRIORITY_CLASSES)
+            .map(|_| PriorityClassQueues::default())
+            .collect();
+        for protocol_id in queued_protocols() {
+            let label = protocol_label(protocol_id);
+            let max_size = config
+                .protocol_queue_sizes
+                .get(label)
+                .copied()
+                .unwrap_or(config.default_queue_size);
+            let weight = config
+                .protocol_weights
+                .get(label)
+                .copied()
+                .unwrap_or(1)
+                .max(1);
+            classes[PriorityClass::of(protocol_id) as usize]
+                .queues
+                .push(ProtocolQueue {
+                    protocol_id,
+                    messages: VecDeque::new(),
+                    max_size,
+                    quantum: weight.saturating_mul(QUANTUM_BYTES),
+                    deficit: 0,
+                });
+        }
+        Self {
+            classes,
+            closed: false,
+        }
+    }
+
+    fn push(&mut self, message: QueuedMessage) -> Result<(), QueuedMessage> {
+        let class = &mut self.classes[PriorityClass::of(message.protocol_id) as usize];
+        let queue = class
+            .queues
+            .iter_mut()
+            .find(|queue| queue.protocol_id == message.protocol_id)
+            .expect("Every protocol has a queue");
+        if self.closed || queue.messages.len() >= queue.max_size {
+            return Err(message);
+        }
+        queue.messages.push_back(message);
+        class.num_messages += 1;
+        Ok(())
+    }
+
+    fn pop(&mut self) -> Option<QueuedMessage> {
+        self.classes.iter_mut().find_map(PriorityClassQueues::pop)
+    }
+}
+
+struct Shared {
+    queues: Mutex<OutboundQueues>,
+    /// Wakes up the writer task when a message is pushed
+    notify: Notify,
+}
+
+/// Creates the outbound queue of a connection, returning the handle used to
+/// push messages and the receiver used by the writer task.
+pub fn new(
+    config: &OutboundQueueConfig,
+    network_context: NetworkContext,
+    time_service: TimeService,
+) -> (OutboundQueueSender, OutboundQueueReceiver) {
+    let shared = Arc::new(Shared {
+        queues: Mutex::new(OutboundQueues::new(config)),
+        notify: Notify::new(),
+    });
+    let sender = OutboundQueueSender {
+        shared: shared.clone(),
+        network_context,
+        time_service: time_service.clone(),
+    };
+    let receiver = OutboundQueueReceiver {
+        shared,
+        network_context,
+        time_service,
+    };
+    (sender, receiver)
+}
+
+#[derive(Clone)]
+pub struct OutboundQueueSender {
+    shared: Arc<Shared>,
+    network_context: NetworkContext,
+    time_service: TimeService,
+}
+
+impl OutboundQueueSender {
+    /// Queues the message for writing. Messages without a protocol are error
+    /// messages. The message is dropped if the queue of its protocol is full
+    /// or the connection is shutting down. This never waits for the writer, so
+    /// callers get no backpressure, only the error.
+    pub fn push(
+        &mut self,
+        protocol_id: Option<ProtocolId>,
+        message: NetworkMessage,
+        ack_tx: AckSender,
+    ) -> Result<(), PeerManagerError> {
+        let message = QueuedMessage {
+            size: payload_size(&message),
+            message,
+            ack_tx,
+            protocol_id,
+            enqueue_time: self.time_service.now(),
+        };
+        let mut queues = self.shared.queues.lock();
+        match queues.push(message) {
+            Ok(()) => {
+                drop(queues);
+                PENDING_WIRE_MESSAGES.inc();
+                self.shared.notify.notify_one();
+                Ok(())
+            }
+            Err(_) if queues.closed => Err(PeerManagerError::ShuttingDownPeer),
+            Err(_) => {
+                counters::outbound_queue_dropped_messages(
+                    &self.network_context,
+                    protocol_label(protocol_id),
+                )
+                .inc();
+                Err(PeerManagerError::OutboundQueueFull(protocol_label(
+                    protocol_id,
+                )))
+            }
+        }
+    }
+}
+
+pub struct OutboundQueueReceiver {
+    shared: Arc<Shared>,
+    network_context: NetworkContext,
+    time_service: TimeService,
+}
+
+impl OutboundQueueReceiver {
+    /// Waits for the next message to write, by priority and fair share
+    pub async fn next(&mut self) -> (NetworkMessage, AckSender) {
+        loop {
+            let maybe_message = self.shared.queues.lock().pop();
+            if let Some(message) = maybe_message {
+                PENDING_WIRE_MESSAGES.dec();
+                let queueing_delay = self.time_service.now().duration_since(message.enqueue_time);
+                counters::outbound_queueing_delay(
+                    &self.network_context,
+                    protocol_label(message.protocol_id),
+                )
+                .observe(queueing_delay.as_secs_f64());
+                return (message.message, message.ack_tx);
+            }
+            self.shared.notify.notified().await;
+        }
+    }
+}
+
+impl Drop for OutboundQueueReceiver {
+    /// Drops all pending messages, and any messages pushed later
+    fn drop(&mut self) {
+        let mut queues = self.shared.queues.lock();
+        queues.closed = true;
+        while queues.pop().is_some() {
+            PENDING_WIRE_MESSAGES.dec();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocols::wire::messaging::v1::{DirectSendMsg, ErrorCode};
+    use futures::FutureExt;
+    use std::collections::HashMap;
+
+    fn direct_send(protocol_id: ProtocolId, size: usize) -> NetworkMessage {
+        NetworkMessage::DirectSendMsg(DirectSendMsg {
+            protocol_id,
+            priority: 0,
+            raw_msg: vec![0; size],
+        })
+    }
+
+    fn new_queue(config: &OutboundQueueConfig) -> (OutboundQueueSender, OutboundQueueReceiver) {
+        new(config, NetworkContext::mock(), TimeService::mock())
+    }
+
+    fn push(sender: &mut OutboundQueueSender, protocol_id: ProtocolId, size: usize) {
+        let (ack_tx, _) = oneshot::channel();
+        sender
+            .push(Some(protocol_id), direct_send(protocol_id, size), ack_tx)
+            .unwrap();
+    }
+
+    /// Returns the protocols of the next messages, which must all be queued
+    fn pop_protocols(receiver: &mut OutboundQueueReceiver, count: usize) -> Vec<ProtocolId> {
+        (0..count)
+            .map(|_| match receiver.next().now_or_never().unwrap().0 {
+                NetworkMessage::DirectSendMsg(message) => message.protocol_id,
+                message => panic!("Unexpected message: {:?}", message),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_priority_classes() {
+        let (mut sender, mut receiver) = new_queue(&OutboundQueueConfig::default());
+
+        push(&mut sender, ProtocolId::StorageServiceRpc, 1_000_000);
+        push(&mut sender, ProtocolId::MempoolDirectSend, 100);
+        push(&mut sender, ProtocolId::StorageServiceRpc, 1_000_000);
+        push(&mut sender, ProtocolId::ConsensusRpcBcs, 100);
+
+        // Consensus is written first, then mempool, and state sync last
+        assert_eq!(
+            pop_protocols(&mut receiver, 4),
+            vec![
+                ProtocolId::ConsensusRpcBcs,
+                ProtocolId::MempoolDirectSend,
+                ProtocolId::StorageServiceRpc,
+                ProtocolId::StorageServiceRpc,
+            ]
+        );
+        assert!(receiver.next().now_or_never().is_none());
+    }
+
+    #[test]
+    fn test_weighted_fair_queuing() {
+        let config = OutboundQueueConfig {
+            protocol_weights: [(ProtocolId::StorageServiceRpc.as_str().to_string(), 3)]
+                .iter()
+                .cloned()
+                .collect::<HashMap<_, _>>(),
+            ..OutboundQueueConfig::default()
+        };
+        let (mut sender, mut receiver) = new_queue(&config);
+        for _ in 0..8 {
+            push(&mut sender, ProtocolId::StateSyncDirectSend, QUANTUM_BYTES);
+            push(&mut sender, ProtocolId::StorageServiceRpc, QUANTUM_BYTES);
+        }
+
+        // Storage service messages get three times the bandwidth, until
+        // there are no more of them
+        let protocols = pop_protocols(&mut receiver, 16);
+        let num_storage_service_messages = protocols[..8]
+            .iter()
+            .filter(|protocol_id| **protocol_id == ProtocolId::StorageServiceRpc)
+            .count();
+        assert_eq!(num_storage_service_messages, 6);
+        assert!(protocols[12..]
+            .iter()
+            .all(|protocol_id| *protocol_id == ProtocolId::StateSyncDirectSend));
+    }
+
+    #[test]
+    fn test_large_messages_are_not_starved() {
+        let (mut sender, mut receiver) = new_queue(&OutboundQueueConfig::default());
+        push(
+            &mut sender,
+            ProtocolId::StorageServiceRpc,
+            10 * QUANTUM_BYTES,
+        );
+        for _ in 0..20 {
+            push(&mut sender, ProtocolId::StateSyncDirectSend, QUANTUM_BYTES);
+        }
+
+        // The large message is written once it has saved up enough rounds
+        let protocols = pop_protocols(&mut receiver, 21);
+        let position = protocols
+            .iter()
+            .position(|protocol_id| *protocol_id == ProtocolId::StorageServiceRpc)
+            .unwrap();
+        assert!(position <= 10);
+    }
+
+    #[test]
+    fn test_queue_names() {
+        let queue_names = queue_names();
+        assert_eq!(queue_names.len(), ProtocolId::all().len() + 1);
+        assert!(queue_names.contains(&CONTROL_LABEL));
+        assert!(queue_names.contains(&ProtocolId::ConsensusRpcBcs.as_str()));
+    }
+
+    #[test]
+    fn test_queue_bounds() {
+        let config = OutboundQueueConfig {
+            default_queue_size: 2,
+            protocol_queue_sizes: [(ProtocolId::ConsensusRpcBcs.as_str().to_string(), 1)]
+                .iter()
+                .cloned()
+                .collect::<HashMap<_, _>>(),
+            ..OutboundQueueConfig::default()
+        };
+        let (mut sender, mut receiver) = new_queue(&config);
+
+        // Messages are dropped once the queue of their protocol is full
+        push(&mut sender, ProtocolId::ConsensusRpcBcs, 1);
+        let (ack_tx, _) = oneshot::channel();
+        assert!(matches!(
+            sender.push(
+                Some(ProtocolId::ConsensusRpcBcs),
+                direct_send(ProtocolId::ConsensusRpcBcs, 1),
+                ack_tx
+            ),
+            Err(PeerManagerError::OutboundQueueFull(_))
+        ));
+        push(&mut sender, ProtocolId::MempoolDirectSend, 1);
+        push(&mut sender, ProtocolId::MempoolDirectSend, 1);
+        let (ack_tx, _) = oneshot::channel();
+        assert!(sender
+            .push(
+                Some(ProtocolId::MempoolDirectSend),
+                direct_send(ProtocolId::MempoolDirectSend, 1),
+                ack_tx
+            )
+            .is_err());
+
+        // Popping a message makes room for another one
+        pop_protocols(&mut receiver, 1);
+        push(&mut sender, ProtocolId::ConsensusRpcBcs, 1);
+
+        // Nothing can be pushed once the writer has terminated
+        drop(receiver);
+        let (ack_tx, _) = oneshot::channel();
+        assert!(matches!(
+            sender.push(
+                None,
+                NetworkMessage::Error(ErrorCode::parsing_error(0, 0)),
+                ack_tx
+            ),
+            Err(PeerManagerError::ShuttingDownPeer)
+        ));
+    }
+}
@@ -0,0 +1,321 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+//! Rate limits on the inbound messages of a single peer, both across all
+//! protocols and per [`ProtocolId`], as configured by [`PeerRateLimitConfig`].
+//! The limiter of a peer is shared across its connections, so reconnecting
+//! doesn't reset its limits.
+
+use crate::ProtocolId;
+use aptos_config::config::{PeerRateLimitConfig, PeerRole, TrafficLimits};
+use aptos_infallible::Mutex;
+use aptos_rate_limiter::rate_limit::Bucket;
+use aptos_types::PeerId;
+use short_hex_str::AsShortHexStr;
+use std::{collections::HashMap, sync::Arc};
+
+pub type SharedPeerRateLimiter = Arc<Mutex<PeerRateLimiter>>;
+
+/// The limit exceeded by a dropped message
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ExceededLimit {
+    Peer,
+    Protocol,
+}
+
+impl ExceededLimit {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ExceededLimit::Peer => "peer",
+            ExceededLimit::Protocol => "protocol",
+        }
+    }
+}
+
+/// Token buckets for the bytes and messages of a single limit
+#[derive(Debug)]
+struct TrafficBuckets {
+    bytes: Bucket,
+    messages: Bucket,
+}
+
+impl TrafficBuckets {
+    fn new(label: &str, key: String, limits: &TrafficLimits, multiplier: usize) -> Self {
+        Self {
+            bytes: new_bucket(
+                format!("{}_bytes", label),
+                key.clone(),
+                limits.byte_rate.saturating_mul(multiplier),
+                limits.byte_burst.saturating_mul(multiplier),
+            ),
+            messages: new_bucket(
+                format!("{}_messages", label),
+                key,
+                limits.message_rate.saturating_mul(multiplier),
+                limits.message_burst.saturating_mul(multiplier),
+            ),
+        }
+    }
+
+    /// Takes the tokens for a message of the given size, if both buckets have enough
+    fn try_acquire(&mut self, num_bytes: usize) -> bool {
+        if self.bytes.acquire_all_tokens(num_bytes).is_err() {
+            return false;
+        }
+        if self.messages.acquire_all_tokens(1).is_err() {
+            self.bytes.return_tokens(num_bytes);
+            return false;
+        }
+        true
+    }
+
+    /// Gives back the tokens of a message that was dropped by another limit
+    fn release(&mut self, num_bytes: usize) {
+        self.bytes.return_tokens(num_bytes);
+        self.messages.return_tokens(1);
+    }
+
+    fn is_full(&mut self) -> bool {
+        self.bytes.is_full() && self.messages.is_full()
+    }
+}
+
+fn new_bucket(label: String, key: String, rate: usize, burst: usize) -> Bucket {
+    // A bucket can't be smaller than its fill rate
+    let size = burst.max(rate);
+    Bucket::new(label, String::new(), key, size, size, rate, None)
+}
+
+/// Rate limits the inbound messages of a single peer
+#[derive(Debug)]
+pub struct PeerRateLimiter {
+    remote_role: PeerRole,
+    peer_buckets: TrafficBuckets,
+    protocol_buckets: HashMap<ProtocolId, TrafficBuckets>,
+}
+
+impl PeerRateLimiter {
+    pub fn new(
+        config: &PeerRateLimitConfig,
+        remote_peer_id: PeerId,
+        remote_role: PeerRole,
+    ) -> Self {
+        let multiplier = if is_trusted(remote_role) {
+            config.trusted_peer_multiplier
+        } else {
+            1
+        };
+        let key = remote_peer_id.short_str().to_string();
+        let protocol_buckets = ProtocolId::all()
+            .iter()
+            .filter_map(|protocol_id| {
+                config
+                    .protocol_limits
+                    .get(protocol_id.as_str())
+                    .map(|limits| {
+                        let buckets = TrafficBuckets::new(
+                            protocol_id.as_str(),
+                            key.clone(),
+                            limits,
+                            multiplier,
+                        );
+                        (*protocol_id, buckets)
+                    })
+            })
+            .collect();
+        Self {
+            remote_role,
+            peer_buckets: TrafficBuckets::new("peer", key, &config.peer_limits, multiplier),
+            protocol_buckets,
+        }
+    }
+
+    /// The role the limits were chosen for
+    pub fn remote_role(&self) -> PeerRole {
+        self.remote_role
+    }
+
+    /// Returns true iff all limits have refilled, in which case the limiter
+    /// can be dropped and later recreated without relaxing any limit.
+    pub fn is_full(&mut self) -> bool {
+        self.peer_buckets.is_full()
+            && self
+                .protocol_buckets
+                .values_mut()
+                .all(TrafficBuckets::is_full)
+    }
+
+    /// Accounts for an inbound message of the given protocol and size. Returns
+    /// the exceeded limit if the message should be dropped, in which case
+    /// nothing is accounted.
+    pub fn try_acquire(
+        &mut self,
+        protocol_id: ProtocolId,
+        num_bytes: usize,
+    ) -> Result<(), ExceededLimit> {
+        let mut protocol_buckets = self.protocol_buckets.get_mut(&protocol_id);
+        if let Some(buckets) = protocol_buckets.as_mut() {
+            if !buckets.try_acquire(num_bytes) {
+                return Err(ExceededLimit::Protocol);
+            }
+        }
+        if !self.peer_buckets.try_acquire(num_bytes) {
+            if let Some(buckets) = protocol_buckets {
+                buckets.release(num_bytes);
+            }
+            return Err(ExceededLimit::Peer);
+        }
+        Ok(())
+    }
+}
+
+/// Returns true iff peers of the given role get the relaxed limits
+fn is_trusted(role: PeerRole) -> bool {
+    matches!(
+        role,
+        PeerRole::Validator
+            | PeerRole::PreferredUpstream
+            | PeerRole::Upstream
+            | PeerRole::ValidatorFullNode
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limits(byte_rate: usize, message_rate: usize) -> TrafficLimits {
+        TrafficLimits {
+            byte_rate,
+            byte_burst: byte_rate,
+            message_rate,
+            message_burst: message_rate,
+        }
+    }
+
+    fn create_config(
+        peer_limits: TrafficLimits,
+        protocol_limits: TrafficLimits,
+    ) -> PeerRateLimitConfig {
+        PeerRateLimitConfig {
+            peer_limits,
+            protocol_limits: [(
+                ProtocolId::StorageServiceRpc.as_str().to_string(),
+                protocol_limits,
+            )]
+            .iter()
+            .cloned()
+            .collect(),
+            trusted_peer_multiplier: 2,
+        }
+    }
+
+    #[test]
+    fn test_peer_limits() {
+        let config = create_config(limits(100, 3), limits(1000, 1000));
+        let mut rate_limiter = PeerRateLimiter::new(&config, PeerId::random(), PeerRole::Unknown);
+
+        // The byte limit applies across protocols
+        assert_eq!(
+            rate_limiter.try_acquire(ProtocolId::MempoolDirectSend, 60),
+            Ok(())
+        );
+        assert_eq!(
+            rate_limiter.try_acquire(ProtocolId::StorageServiceRpc, 60),
+            Err(ExceededLimit::Peer)
+        );
+        assert_eq!(
+            rate_limiter.try_acquire(ProtocolId::StorageServiceRpc, 40),
+            Ok(())
+        );
+
+        // And so does the message limit
+        assert_eq!(
+            rate_limiter.try_acquire(ProtocolId::MempoolDirectSend, 0),
+            Ok(())
+        );
+        assert_eq!(
+            rate_limiter.try_acquire(ProtocolId::MempoolDirectSend, 0),
+            Err(ExceededLimit::Peer)
+        );
+    }
+
+    #[test]
+    fn test_protocol_limits() {
+        let config = create_config(limits(1000, 1000), limits(100, 2));
+        let mut rate_limiter = PeerRateLimiter::new(&config, PeerId::random(), PeerRole::Unknown);
+
+        // Only the limited protocol is throttled
+        assert_eq!(
+            rate_limiter.try_acquire(ProtocolId::StorageServiceRpc, 80),
+            Ok(())
+        );
+        assert_eq!(
+            rate_limiter.try_acquire(ProtocolId::StorageServiceRpc, 80),
+            Err(ExceededLimit::Protocol)
+        );
+        assert_eq!(
+            rate_limiter.try_acquire(ProtocolId::MempoolDirectSend, 80),
+            Ok(())
+        );
+        assert_eq!(
+            rate_limiter.try_acquire(ProtocolId::StorageServiceRpc, 20),
+            Ok(())
+        );
+        assert_eq!(
+            rate_limiter.try_acquire(ProtocolId::StorageServiceRpc, 0),
+            Err(ExceededLimit::Protocol)
+        );
+    }
+
+    #[test]
+    fn test_dropped_messages_are_not_accounted() {
+        let config = create_config(limits(100, 1000), limits(1000, 1000));
+        let mut rate_limiter = PeerRateLimiter::new(&config, PeerId::random(), PeerRole::Unknown);
+
+        // A message dropped by the peer limit doesn't use up the protocol limit
+        assert_eq!(
+            rate_limiter.try_acquire(ProtocolId::MempoolDirectSend, 100),
+            Ok(())
+        );
+        assert_eq!(
+            rate_limiter.try_acquire(ProtocolId::StorageServiceRpc, 1000),
+            Err(ExceededLimit::Peer)
+        );
+        let protocol_buckets = rate_limiter
+            .protocol_buckets
+            .get_mut(&ProtocolId::StorageServiceRpc)
+            .unwrap();
+        assert!(protocol_buckets.try_acquire(1000));
+    }
+
+    #[test]
+    fn test_is_full() {
+        let config = create_config(limits(1000, 1000), limits(1000, 1000));
+        let mut rate_limiter = PeerRateLimiter::new(&config, PeerId::random(), PeerRole::Unknown);
+        assert!(rate_limiter.is_full());
+
+        // Using up any limit means the limiter can't be replaced by a new one
+        rate_limiter
+            .try_acquire(ProtocolId::StorageServiceRpc, 10)
+            .unwrap();
+        assert!(!rate_limiter.is_full());
+    }
+
+    #[test]
+    fn test_trusted_peer_limits() {
+        let config = create_config(limits(100, 1000), limits(1000, 1000));
+        for role in [PeerRole::Unknown, PeerRole::Upstream].iter() {
+            let mut rate_limiter = PeerRateLimiter::new(&config, PeerId::random(), *role);
+            let expected_result = if is_trusted(*role) {
+                Ok(())
+            } else {
+                Err(ExceededLimit::Peer)
+            };
+            assert_eq!(
+                rate_limiter.try_acquire(ProtocolId::MempoolDirectSend, 200),
+                expected_result
+            );
+        }
+    }
+}
@@ -6,7 +6,7 @@ use crate::{
         INBOUND_RPC_TIMEOUT_MS, MAX_CONCURRENT_INBOUND_RPCS, MAX_CONCURRENT_OUTBOUND_RPCS,
         MAX_FRAME_SIZE, NETWORK_CHANNEL_SIZE,
     },
-    peer::{DisconnectReason, Peer, PeerNotification, PeerRequest},
+    peer::{DisconnectReason, Peer, PeerNotification, PeerRateLimiter, PeerRequest},
     peer_manager::TransportNotification,
     protocols::{
         direct_send::Message,
@@ -14,7 +14,7 @@ use crate::{
         wire::{
             handshake::v1::{MessagingProtocolVersion, ProtocolIdSet},
             messaging::v1::{
-                DirectSendMsg, NetworkMessage, NetworkMessageSink, NetworkMessageStream,
+                DirectSendMsg, ErrorCode, NetworkMessage, NetworkMessageSink, NetworkMessageStream,
                 RpcRequest, RpcResponse,
             },
         },
@@ -22,7 +22,12 @@ use crate::{
     transport::{Connection, ConnectionId, ConnectionMetadata},
     ProtocolId,
 };
-use aptos_config::{config::PeerRole, network_id::NetworkContext};
+use aptos_config::{
+    config::{OutboundQueueConfig, PeerRateLimitConfig, PeerRole, TrafficLimits},
+    network_id::NetworkContext,
+};
+use aptos_infallible::Mutex;
+use aptos_rate_limiter::rate_limit::Bucket;
 use aptos_time_service::{MockTimeService, TimeService};
 use aptos_types::{network_address::NetworkAddress, PeerId};
 use bytes::Bytes;
@@ -36,7 +41,7 @@ use futures::{
 };
 use memsocket::MemorySocket;
 use netcore::transport::ConnectionOrigin;
-use std::{collections::HashSet, str::FromStr, time::Duration};
+use std::{collections::HashSet, str::FromStr, sync::Arc, time::Duration};
 use tokio::runtime::{Handle, Runtime};
 use tokio_util::compat::{
     FuturesAsyncReadCompatExt, TokioAsyncReadCompatExt, TokioAsyncWriteCompatExt,
@@ -90,6 +95,8 @@ fn build_test_peer(
         MAX_FRAME_SIZE,
         None,
         None,
+        None,
+        OutboundQueueConfig::default(),
     );
     let peer_handle = PeerHandle(peer_reqs_tx);
 
@@ -280,6 +287,185 @@ fn peer_recv_message() {
     rt.block_on(future::join3(peer.start(), server, client));
 }
 
+// Inbound messages exceeding the rate limits of the remote peer should be dropped.
+#[test]
+fn peer_recv_rate_limited_messages() {
+    ::aptos_logger::Logger::init_for_testing();
+    let rt = Runtime::new().unwrap();
+    let (mut peer, _peer_handle, connection, _connection_notifs_rx, peer_notifs_rx) =
+        build_test_peer(
+            rt.handle().clone(),
+            TimeService::mock(),
+            ConnectionOrigin::Inbound,
+        );
+    let config = PeerRateLimitConfig {
+        peer_limits: TrafficLimits {
+            message_rate: 10,
+            message_burst: 10,
+            ..TrafficLimits::default()
+        },
+        ..PeerRateLimitConfig::default()
+    };
+    peer.peer_rate_limiter = Some(Arc::new(Mutex::new(PeerRateLimiter::new(
+        &config,
+        peer.remote_peer_id(),
+        PeerRole::Unknown,
+    ))));
+
+    let send_msg = NetworkMessage::DirectSendMsg(DirectSendMsg {
+        protocol_id: PROTOCOL,
+        priority: 0,
+        raw_msg: Vec::from("hello world"),
+    });
+
+    let client = async move {
+        let mut connection = NetworkMessageSink::new(connection, MAX_FRAME_SIZE, None);
+        for _ in 0..30 {
+            connection.send(&send_msg).await.unwrap();
+        }
+        // Client then closes connection.
+        connection.close().await.unwrap();
+    };
+
+    // Only the messages within the limit are received, until the Peer shuts down
+    let server = async move {
+        let received = peer_notifs_rx.collect::<Vec<_>>().await;
+        assert_eq!(received.len(), 10);
+    };
+    rt.block_on(future::join3(peer.start(), server, client));
+}
+
+// Inbound rpc requests exceeding the rate limits of the remote peer should be
+// dropped, and the remote peer told about it.
+#[test]
+fn peer_recv_rate_limited_rpc() {
+    ::aptos_logger::Logger::init_for_testing();
+    let rt = Runtime::new().unwrap();
+    let (mut peer, _peer_handle, mut connection, _connection_notifs_rx, mut peer_notifs_rx) =
+        build_test_peer(
+            rt.handle().clone(),
+            TimeService::mock(),
+            ConnectionOrigin::Inbound,
+        );
+    let config = PeerRateLimitConfig {
+        peer_limits: TrafficLimits {
+            message_rate: 1,
+            message_burst: 1,
+            ..TrafficLimits::default()
+        },
+        ..PeerRateLimitConfig::default()
+    };
+    peer.peer_rate_limiter = Some(Arc::new(Mutex::new(PeerRateLimiter::new(
+        &config,
+        peer.remote_peer_id(),
+        PeerRole::Unknown,
+    ))));
+    let (mut client_sink, mut client_stream) = build_network_sink_stream(&mut connection);
+
+    let rpc_request = |request_id| {
+        NetworkMessage::RpcRequest(RpcRequest {
+            request_id,
+            protocol_id: PROTOCOL,
+            priority: 0,
+            raw_request: Vec::from("hello world"),
+        })
+    };
+
+    let client = async move {
+        // The first request is within the limits, but the second isn't
+        client_sink.send(&rpc_request(1)).await.unwrap();
+        client_sink.send(&rpc_request(2)).await.unwrap();
+        let received = client_stream.next().await.unwrap().unwrap();
+        assert_eq!(
+            received,
+            NetworkMessage::Error(ErrorCode::rate_limited(PROTOCOL, 2))
+        );
+        // Client then closes connection.
+        client_sink.close().await.unwrap();
+    };
+    let server = async move {
+        // Only the first request reaches the application
+        match peer_notifs_rx.next().await.unwrap() {
+            PeerNotification::RecvRpc(request) => {
+                assert_eq!(request.data, Bytes::from("hello world"))
+            }
+            notification => panic!("Unexpected PeerNotification: {:?}", notification),
+        }
+        assert!(peer_notifs_rx.next().await.is_none());
+    };
+    rt.block_on(future::join3(peer.start(), server, client));
+}
+
+// A consensus message shouldn't wait behind a full queue of state sync messages
+// on a slow connection.
+#[test]
+fn peer_send_consensus_message_under_state_sync_load() {
+    ::aptos_logger::Logger::init_for_testing();
+    let rt = Runtime::new().unwrap();
+    let (mut peer, mut peer_handle, mut connection, _connection_notifs_rx, _peer_notifs_rx) =
+        build_test_peer(
+            rt.handle().clone(),
+            TimeService::mock(),
+            ConnectionOrigin::Inbound,
+        );
+    peer.outbound_queue_config = OutboundQueueConfig {
+        protocol_queue_sizes: [(ProtocolId::StateSyncDirectSend.as_str().to_string(), 2)]
+            .iter()
+            .cloned()
+            .collect(),
+        ..OutboundQueueConfig::default()
+    };
+
+    // Throttle the connection, so that the writer stalls on the second state
+    // sync message and the others back up in the queue
+    let bytes_per_sec = 64 * 1024;
+    peer.outbound_rate_limiter = Some(Arc::new(Mutex::new(Bucket::new(
+        "test".to_string(),
+        "test".to_string(),
+        "test".to_string(),
+        bytes_per_sec,
+        bytes_per_sec,
+        bytes_per_sec,
+        None,
+    ))));
+    let (_server_sink, mut server_stream) = build_network_sink_stream(&mut connection);
+    let (done_tx, done_rx) = oneshot::channel();
+
+    let client = async move {
+        // More state sync messages than the queue holds, and then a vote
+        for _ in 0..5 {
+            peer_handle.send_direct_send(Message {
+                protocol_id: ProtocolId::StateSyncDirectSend,
+                mdata: Bytes::from(vec![0; bytes_per_sec / 2]),
+            });
+        }
+        peer_handle.send_direct_send(Message {
+            protocol_id: ProtocolId::ConsensusDirectSendBcs,
+            mdata: Bytes::from("vote"),
+        });
+        // Keep the peer alive until the vote is received
+        done_rx.await.unwrap();
+    };
+    let server = async move {
+        let mut protocols = Vec::new();
+        while !protocols.contains(&ProtocolId::ConsensusDirectSendBcs) {
+            match server_stream.next().await.unwrap().unwrap() {
+                NetworkMessage::DirectSendMsg(message) => protocols.push(message.protocol_id),
+                message => panic!("Unexpected message: {:?}", message),
+            }
+        }
+        // Only the state sync messages already taken by the writer go first,
+        // rather than every queued one
+        assert!(
+            protocols.len() <= 3,
+            "Consensus message was delayed: {:?}",
+            protocols
+        );
+        done_tx.send(()).unwrap();
+    };
+    rt.block_on(future::join3(peer.start(), server, client));
+}
+
 // Two connected Peer actors should be able to send/recv a DirectSend from each
 // other and then shutdown gracefully.
 #[test]
@@ -633,6 +819,42 @@ fn peer_send_rpc() {
     rt.block_on(future::join3(peer.start(), server, client));
 }
 
+// An outbound rpc request should fail right away when the remote peer drops it
+// for exceeding its rate limits.
+#[test]
+fn peer_send_rate_limited_rpc() {
+    ::aptos_logger::Logger::init_for_testing();
+    let rt = Runtime::new().unwrap();
+    let (peer, mut peer_handle, mut connection, _connection_notifs_rx, _peer_notifs_rx) =
+        build_test_peer(
+            rt.handle().clone(),
+            TimeService::mock(),
+            ConnectionOrigin::Inbound,
+        );
+    let (mut server_sink, mut server_stream) = build_network_sink_stream(&mut connection);
+    let timeout = Duration::from_millis(10_000);
+
+    let client = async move {
+        // The time service is mocked, so the request can only fail with the error
+        let result = peer_handle
+            .send_rpc_request(PROTOCOL, Bytes::from(&b"hello world"[..]), timeout)
+            .await;
+        assert!(matches!(result, Err(RpcError::RateLimited)));
+        // Client then closes connection.
+    };
+    let server = async move {
+        let received = server_stream.next().await.unwrap().unwrap();
+        let request_id = match received {
+            NetworkMessage::RpcRequest(request) => request.request_id,
+            _ => panic!("Expected RpcRequest; unexpected: {:?}", received),
+        };
+        let error = NetworkMessage::Error(ErrorCode::rate_limited(PROTOCOL, request_id));
+        server_sink.send(&error).await.unwrap();
+        assert!(matches!(server_stream.next().await, None));
+    };
+    rt.block_on(future::join3(peer.start(), server, client));
+}
+
 #[test]
 fn peer_send_rpc_concurrent() {
     ::aptos_logger::Logger::init_for_testing();
@@ -15,7 +15,9 @@ use crate::{
     ProtocolId,
 };
 use aptos_config::{
-    config::{PeerSet, RateLimitConfig, HANDSHAKE_VERSION},
+    config::{
+        OutboundQueueConfig, PeerRateLimitConfig, PeerSet, RateLimitConfig, HANDSHAKE_VERSION,
+    },
     network_id::NetworkContext,
 };
 use aptos_crypto::x25519;
@@ -81,6 +83,8 @@ struct PeerManagerContext {
     inbound_connection_limit: usize,
     inbound_rate_limit_config: Option<RateLimitConfig>,
     outbound_rate_limit_config: Option<RateLimitConfig>,
+    peer_rate_limit_config: Option<PeerRateLimitConfig>,
+    outbound_queue_config: OutboundQueueConfig,
 }
 
 impl PeerManagerContext {
@@ -105,6 +109,8 @@ impl PeerManagerContext {
         inbound_connection_limit: usize,
         inbound_rate_limit_config: Option<RateLimitConfig>,
         outbound_rate_limit_config: Option<RateLimitConfig>,
+        peer_rate_limit_config: Option<PeerRateLimitConfig>,
+        outbound_queue_config: OutboundQueueConfig,
     ) -> Self {
         Self {
             pm_reqs_tx,
@@ -123,6 +129,8 @@ impl PeerManagerContext {
             inbound_connection_limit,
             inbound_rate_limit_config,
             outbound_rate_limit_config,
+            peer_rate_limit_config,
+            outbound_queue_config,
         }
     }
 
@@ -182,6 +190,8 @@ impl PeerManagerBuilder {
         inbound_connection_limit: usize,
         inbound_rate_limit_config: Option<RateLimitConfig>,
         outbound_rate_limit_config: Option<RateLimitConfig>,
+        peer_rate_limit_config: Option<PeerRateLimitConfig>,
+        outbound_queue_config: OutboundQueueConfig,
     ) -> Self {
         // Setup channel to send requests to peer manager.
         let (pm_reqs_tx, pm_reqs_rx) = aptos_channel::new(
@@ -218,6 +228,8 @@ impl PeerManagerBuilder {
                 inbound_connection_limit,
                 inbound_rate_limit_config,
                 outbound_rate_limit_config,
+                peer_rate_limit_config,
+                outbound_queue_config,
             )),
             peer_manager: None,
             listen_address,
@@ -360,6 +372,8 @@ impl PeerManagerBuilder {
             pm_context.inbound_connection_limit,
             inbound_rate_limiters,
             outbound_rate_limiters,
+            pm_context.peer_rate_limit_config,
+            pm_context.outbound_queue_config,
         );
 
         // PeerManager constructor appends a public key to the listen_address.
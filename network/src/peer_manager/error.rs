@@ -22,6 +22,9 @@ pub enum PeerManagerError {
     #[error("Shutting down Peer")]
     ShuttingDownPeer,
 
+    #[error("Outbound queue is full for protocol {0}")]
+    OutboundQueueFull(&'static str),
+
     #[error("Not connected with Peer {0}")]
     NotConnected(PeerId),
 
@@ -14,7 +14,7 @@ use crate::{
     constants,
     counters::{self},
     logging::*,
-    peer::{Peer, PeerNotification, PeerRequest},
+    peer::{Peer, PeerNotification, PeerRateLimiter, PeerRequest, SharedPeerRateLimiter},
     transport::{
         Connection, ConnectionId, ConnectionMetadata, TSocket as TransportTSocket,
         TRANSPORT_TIMEOUT,
@@ -59,8 +59,8 @@ use crate::{
     peer_manager::transport::{TransportHandler, TransportRequest},
     protocols::network::SerializedRequest,
 };
-use aptos_config::config::{PeerRole, PeerSet};
-use aptos_infallible::RwLock;
+use aptos_config::config::{OutboundQueueConfig, PeerRateLimitConfig, PeerRole, PeerSet};
+use aptos_infallible::{Mutex, RwLock};
 pub use senders::*;
 pub use types::*;
 
@@ -126,6 +126,13 @@ where
     inbound_rate_limiters: IpAddrTokenBucketLimiter,
     /// Keyed storage of all outbound rate limiters
     outbound_rate_limiters: IpAddrTokenBucketLimiter,
+    /// Inbound rate limits of each peer, per protocol and across protocols
+    peer_rate_limit_config: Option<PeerRateLimitConfig>,
+    /// Inbound rate limiters of each peer. These outlive the connections of a
+    /// peer until their limits have refilled, so reconnecting doesn't reset them.
+    peer_rate_limiters: HashMap<PeerId, SharedPeerRateLimiter>,
+    /// Bounds and weights of the outbound message queues of each peer
+    outbound_queue_config: OutboundQueueConfig,
 }
 
 impl<TTransport, TSocket> PeerManager<TTransport, TSocket>
@@ -156,6 +163,8 @@ where
         inbound_connection_limit: usize,
         inbound_rate_limiters: IpAddrTokenBucketLimiter,
         outbound_rate_limiters: IpAddrTokenBucketLimiter,
+        peer_rate_limit_config: Option<PeerRateLimitConfig>,
+        outbound_queue_config: OutboundQueueConfig,
     ) -> Self {
         let (transport_notifs_tx, transport_notifs_rx) = channel::new(
             channel_size,
@@ -200,6 +209,9 @@ where
             inbound_connection_limit,
             inbound_rate_limiters,
             outbound_rate_limiters,
+            peer_rate_limit_config,
+            peer_rate_limiters: HashMap::new(),
+            outbound_queue_config,
         }
     }
 
@@ -425,6 +437,7 @@ where
                 self.inbound_rate_limiters.try_garbage_collect_key(&ip_addr);
                 self.outbound_rate_limiters
                     .try_garbage_collect_key(&ip_addr);
+                self.garbage_collect_peer_rate_limiters();
             }
         }
     }
@@ -657,6 +670,7 @@ where
             .unwrap_or(IpAddr::V4(Ipv4Addr::UNSPECIFIED));
         let inbound_rate_limiter = self.inbound_rate_limiters.bucket(ip_addr);
         let outbound_rate_limiter = self.outbound_rate_limiters.bucket(ip_addr);
+        let peer_rate_limiter = self.peer_rate_limiter(peer_id, conn_meta.role);
 
         // TODO: Add label for peer.
         let (peer_reqs_tx, peer_reqs_rx) = aptos_channel::new(
@@ -686,6 +700,8 @@ where
             self.max_frame_size,
             Some(inbound_rate_limiter),
             Some(outbound_rate_limiter),
+            peer_rate_limiter,
+            self.outbound_queue_config.clone(),
         );
         self.executor.spawn(peer.start());
 
@@ -704,6 +720,36 @@ where
         }
     }
 
+    /// Returns the inbound rate limiter of the given peer, if rate limits are
+    /// configured. The limiter of a previous connection is reused, unless the
+    /// role of the peer (and so its limits) changed.
+    fn peer_rate_limiter(
+        &mut self,
+        peer_id: PeerId,
+        role: PeerRole,
+    ) -> Option<SharedPeerRateLimiter> {
+        let config = self.peer_rate_limit_config.as_ref()?;
+        let peer_rate_limiter = self
+            .peer_rate_limiters
+            .entry(peer_id)
+            .and_modify(|peer_rate_limiter| {
+                if peer_rate_limiter.lock().remote_role() != role {
+                    *peer_rate_limiter =
+                        Arc::new(Mutex::new(PeerRateLimiter::new(config, peer_id, role)));
+                }
+            })
+            .or_insert_with(|| Arc::new(Mutex::new(PeerRateLimiter::new(config, peer_id, role))));
+        Some(peer_rate_limiter.clone())
+    }
+
+    /// Drops the rate limiters of disconnected peers once their limits have
+    /// refilled, as a new limiter would then behave the same
+    fn garbage_collect_peer_rate_limiters(&mut self) {
+        self.peer_rate_limiters.retain(|_, peer_rate_limiter| {
+            Arc::strong_count(peer_rate_limiter) > 1 || !peer_rate_limiter.lock().is_full()
+        });
+    }
+
     /// Sends a `ConnectionNotification` to all event handlers, warns on failures
     fn send_conn_notification(&mut self, peer_id: PeerId, notification: ConnectionNotification) {
         for handler in self.connection_event_handlers.iter_mut() {
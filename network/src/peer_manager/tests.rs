@@ -19,7 +19,9 @@ use crate::{
 };
 use anyhow::anyhow;
 use aptos_config::{
-    config::{PeerRole, MAX_INBOUND_CONNECTIONS},
+    config::{
+        OutboundQueueConfig, PeerRateLimitConfig, PeerRole, TrafficLimits, MAX_INBOUND_CONNECTIONS,
+    },
     network_id::NetworkContext,
 };
 use aptos_infallible::RwLock;
@@ -89,6 +91,23 @@ fn build_test_peer_manager(
     aptos_channel::Sender<PeerId, ConnectionRequest>,
     aptos_channel::Receiver<(PeerId, ProtocolId), PeerManagerNotification>,
     conn_notifs_channel::Receiver,
+) {
+    build_test_peer_manager_with_rate_limits(executor, peer_id, None)
+}
+
+fn build_test_peer_manager_with_rate_limits(
+    executor: Handle,
+    peer_id: PeerId,
+    peer_rate_limit_config: Option<PeerRateLimitConfig>,
+) -> (
+    PeerManager<
+        BoxedTransport<Connection<MemorySocket>, impl std::error::Error + Sync + Send + 'static>,
+        MemorySocket,
+    >,
+    aptos_channel::Sender<(PeerId, ProtocolId), PeerManagerRequest>,
+    aptos_channel::Sender<PeerId, ConnectionRequest>,
+    aptos_channel::Receiver<(PeerId, ProtocolId), PeerManagerNotification>,
+    conn_notifs_channel::Receiver,
 ) {
     let (peer_manager_request_tx, peer_manager_request_rx) =
         aptos_channel::new(QueueStyle::FIFO, 1, None);
@@ -114,6 +133,8 @@ fn build_test_peer_manager(
         MAX_INBOUND_CONNECTIONS,
         TokenBucketRateLimiter::open("inbound"),
         TokenBucketRateLimiter::open("outbound"),
+        peer_rate_limit_config,
+        OutboundQueueConfig::default(),
     );
 
     (
@@ -638,3 +659,86 @@ fn test_dial_disconnect() {
 
     runtime.block_on(test);
 }
+
+#[test]
+fn peer_manager_keeps_rate_limits_across_reconnects() {
+    ::aptos_logger::Logger::init_for_testing();
+    let runtime = ::tokio::runtime::Runtime::new().unwrap();
+
+    let ids = ordered_peer_ids(2);
+    let config = PeerRateLimitConfig {
+        peer_limits: TrafficLimits {
+            message_rate: 1,
+            message_burst: 1,
+            ..TrafficLimits::default()
+        },
+        ..PeerRateLimitConfig::default()
+    };
+    let (mut peer_manager, _request_tx, _connection_reqs_tx, _hello_rx, _conn_status_rx) =
+        build_test_peer_manager_with_rate_limits(runtime.handle().clone(), ids[0], Some(config));
+
+    let test = async move {
+        let (throttled_peer, idle_peer) = (ids[1], PeerId::random());
+
+        // Connect both peers, and use up the limits of one of them
+        let (mut outbound1, inbound1) = build_test_connection();
+        peer_manager.add_peer(create_connection(
+            inbound1,
+            throttled_peer,
+            NetworkAddress::mock(),
+            ConnectionOrigin::Inbound,
+            ConnectionId::from(0),
+        ));
+        let (mut outbound2, inbound2) = build_test_connection();
+        peer_manager.add_peer(create_connection(
+            inbound2,
+            idle_peer,
+            NetworkAddress::mock(),
+            ConnectionOrigin::Inbound,
+            ConnectionId::from(1),
+        ));
+        {
+            let mut rate_limiter = peer_manager.peer_rate_limiters[&throttled_peer].lock();
+            rate_limiter.try_acquire(ProtocolId::mock(), 0).unwrap();
+            rate_limiter.try_acquire(ProtocolId::mock(), 0).unwrap_err();
+        }
+
+        // Disconnect both peers. Only the limiter with used up limits is kept.
+        outbound1.close().await.unwrap();
+        assert_peer_disconnected_event(
+            throttled_peer,
+            ConnectionOrigin::Inbound,
+            DisconnectReason::ConnectionLost,
+            &mut peer_manager,
+        )
+        .await;
+        outbound2.close().await.unwrap();
+        assert_peer_disconnected_event(
+            idle_peer,
+            ConnectionOrigin::Inbound,
+            DisconnectReason::ConnectionLost,
+            &mut peer_manager,
+        )
+        .await;
+        assert!(peer_manager
+            .peer_rate_limiters
+            .contains_key(&throttled_peer));
+        assert!(!peer_manager.peer_rate_limiters.contains_key(&idle_peer));
+
+        // The reconnected peer is still throttled
+        let (_outbound3, inbound3) = build_test_connection();
+        peer_manager.add_peer(create_connection(
+            inbound3,
+            throttled_peer,
+            NetworkAddress::mock(),
+            ConnectionOrigin::Inbound,
+            ConnectionId::from(2),
+        ));
+        peer_manager.peer_rate_limiters[&throttled_peer]
+            .lock()
+            .try_acquire(ProtocolId::mock(), 0)
+            .unwrap_err();
+    };
+
+    runtime.block_on(test);
+}
@@ -41,6 +41,9 @@ pub enum RpcError {
 
     #[error("Rpc timed out")]
     TimedOut,
+
+    #[error("Rpc request was rate limited by the peer")]
+    RateLimited,
 }
 
 impl From<PeerManagerError> for RpcError {
@@ -50,8 +50,7 @@ use crate::{
         RESPONSE_LABEL, SENT_LABEL,
     },
     logging::NetworkSchema,
-    peer::PeerNotification,
-    peer_manager::PeerManagerError,
+    peer::{OutboundQueueSender, PeerNotification},
     protocols::{
         network::SerializedRequest,
         wire::messaging::v1::{NetworkMessage, Priority, RequestId, RpcRequest, RpcResponse},
@@ -70,7 +69,6 @@ use error::RpcError;
 use futures::{
     channel::oneshot,
     future::{BoxFuture, FusedFuture, Future, FutureExt},
-    sink::SinkExt,
     stream::{FuturesUnordered, StreamExt},
 };
 use serde::Serialize;
@@ -175,7 +173,10 @@ pub struct InboundRpcs {
     remote_peer_id: PeerId,
     /// The core async queue of pending inbound rpc tasks. The tasks are driven
     /// to completion by the `InboundRpcs::next_completed_response()` method.
-    inbound_rpc_tasks: FuturesUnordered<BoxFuture<'static, Result<RpcResponse, RpcError>>>,
+    /// Each response is tagged with the protocol of its request, which decides
+    /// its priority in the outbound write queue.
+    inbound_rpc_tasks:
+        FuturesUnordered<BoxFuture<'static, Result<(ProtocolId, RpcResponse), RpcError>>>,
     /// A blanket timeout on all inbound rpc requests. If the application handler
     /// doesn't respond to the request before this timeout, the request will be
     /// dropped.
@@ -258,11 +259,14 @@ impl InboundRpcs {
             .map(move |result| {
                 // Flatten the errors
                 let maybe_response = match result {
-                    Ok(Ok(Ok(response_bytes))) => Ok(RpcResponse {
-                        request_id,
-                        priority,
-                        raw_response: Vec::from(response_bytes.as_ref()),
-                    }),
+                    Ok(Ok(Ok(response_bytes))) => Ok((
+                        protocol_id,
+                        RpcResponse {
+                            request_id,
+                            priority,
+                            raw_response: Vec::from(response_bytes.as_ref()),
+                        },
+                    )),
                     Ok(Ok(Err(err))) => Err(err),
                     Ok(Err(oneshot::Canceled)) => Err(RpcError::UnexpectedResponseChannelCancel),
                     Err(timeout::Elapsed) => Err(RpcError::TimedOut),
@@ -288,7 +292,7 @@ impl InboundRpcs {
     /// `futures::select!`.
     pub fn next_completed_response(
         &mut self,
-    ) -> impl Future<Output = Result<RpcResponse, RpcError>> + FusedFuture + '_ {
+    ) -> impl Future<Output = Result<(ProtocolId, RpcResponse), RpcError>> + FusedFuture + '_ {
         self.inbound_rpc_tasks.select_next_some()
     }
 
@@ -297,14 +301,11 @@ impl InboundRpcs {
     /// the outbound write queue.
     pub async fn send_outbound_response(
         &mut self,
-        write_reqs_tx: &mut channel::Sender<(
-            NetworkMessage,
-            oneshot::Sender<Result<(), PeerManagerError>>,
-        )>,
-        maybe_response: Result<RpcResponse, RpcError>,
+        write_reqs_tx: &mut OutboundQueueSender,
+        maybe_response: Result<(ProtocolId, RpcResponse), RpcError>,
     ) -> Result<(), RpcError> {
         let network_context = &self.network_context;
-        let response = match maybe_response {
+        let (protocol_id, response) = match maybe_response {
             Ok(response) => response,
             Err(err) => {
                 counters::rpc_messages(network_context, RESPONSE_LABEL, FAILED_LABEL).inc();
@@ -323,7 +324,7 @@ impl InboundRpcs {
         );
         let message = NetworkMessage::RpcResponse(response);
         let (ack_tx, _) = oneshot::channel();
-        write_reqs_tx.send((message, ack_tx)).await?;
+        write_reqs_tx.push(Some(protocol_id), message, ack_tx)?;
 
         // Collect counters for sent response.
         counters::rpc_messages(network_context, RESPONSE_LABEL, SENT_LABEL).inc();
@@ -346,8 +347,8 @@ pub struct OutboundRpcs {
     /// request ids are local to each connection.
     request_id_gen: U32IdGenerator,
     /// A completion queue of pending outbound rpc tasks. Each task waits for
-    /// either a successful `RpcResponse` message, handed to it via the channel
-    /// in `pending_outbound_rpcs`, or waits for a timeout or cancellation
+    /// either an `RpcResponse` message or a rate limiting error, handed to it
+    /// via the channel in `pending_outbound_rpcs`, or waits for a timeout or cancellation
     /// notification. After completion, the task will yield its `RequestId` and
     /// other metadata (success/failure, success latency, response length) via
     /// the future from `next_completed_request`.
@@ -356,7 +357,8 @@ pub struct OutboundRpcs {
     /// Maps a `RequestId` into a handle to a task in the `outbound_rpc_tasks`
     /// completion queue. When a new `RpcResponse` message comes in, we will use
     /// this map to notify the corresponding task that its response has arrived.
-    pending_outbound_rpcs: HashMap<RequestId, (ProtocolId, oneshot::Sender<RpcResponse>)>,
+    pending_outbound_rpcs:
+        HashMap<RequestId, (ProtocolId, oneshot::Sender<Result<RpcResponse, RpcError>>)>,
     /// Only allow this many concurrent outbound rpcs at one time from this remote
     /// peer. New outbound requests exceeding this limit will be dropped.
     max_concurrent_outbound_rpcs: u32,
@@ -384,10 +386,7 @@ impl OutboundRpcs {
     pub async fn handle_outbound_request(
         &mut self,
         request: OutboundRpcRequest,
-        write_reqs_tx: &mut channel::Sender<(
-            NetworkMessage,
-            oneshot::Sender<Result<(), PeerManagerError>>,
-        )>,
+        write_reqs_tx: &mut OutboundQueueSender,
     ) -> Result<(), RpcError> {
         let network_context = &self.network_context;
         let peer_id = &self.remote_peer_id;
@@ -439,7 +438,7 @@ impl OutboundRpcs {
             raw_request: Vec::from(request_data.as_ref()),
         });
         let (ack_tx, _) = oneshot::channel();
-        write_reqs_tx.send((message, ack_tx)).await?;
+        write_reqs_tx.push(Some(protocol_id), message, ack_tx)?;
 
         // Collect counters for requests sent.
         counters::rpc_messages(network_context, REQUEST_LABEL, SENT_LABEL).inc();
@@ -447,7 +446,7 @@ impl OutboundRpcs {
         network_application_outbound_traffic(self.network_context, protocol_id, req_len);
 
         // Create channel over which response is delivered to outbound_rpc_task.
-        let (response_tx, response_rx) = oneshot::channel::<RpcResponse>();
+        let (response_tx, response_rx) = oneshot::channel::<Result<RpcResponse, RpcError>>();
 
         // Store send-side in the pending map so we can notify outbound_rpc_task
         // when the rpc response has arrived.
@@ -463,7 +462,8 @@ impl OutboundRpcs {
             .map(|result| {
                 // Flatten errors.
                 match result {
-                    Ok(Ok(response)) => Ok(Bytes::from(response.raw_response)),
+                    Ok(Ok(Ok(response))) => Ok(Bytes::from(response.raw_response)),
+                    Ok(Ok(Err(err))) => Err(err),
                     Ok(Err(oneshot::Canceled)) => Err(RpcError::UnexpectedResponseChannelCancel),
                     Err(timeout::Elapsed) => Err(RpcError::TimedOut),
                 }
@@ -593,7 +593,7 @@ impl OutboundRpcs {
                 protocol_id,
                 response.raw_response.len() as u64,
             );
-            response_tx.send(response).is_err()
+            response_tx.send(Ok(response)).is_err()
         } else {
             true
         };
@@ -618,4 +618,22 @@ impl OutboundRpcs {
             );
         }
     }
+
+    /// Handle an error from the remote peer telling us it dropped one of our
+    /// requests for exceeding its rate limits. If the request is still pending,
+    /// its task fails right away, instead of waiting for the timeout.
+    pub fn handle_rate_limited_request(&mut self, request_id: RequestId) {
+        if let Some((_, response_tx)) = self.pending_outbound_rpcs.remove(&request_id) {
+            let _ = response_tx.send(Err(RpcError::RateLimited));
+        } else {
+            info!(
+                NetworkSchema::new(&self.network_context).remote_peer(&self.remote_peer_id),
+                request_id = request_id,
+                "{} Received rate limiting error for expired request_id {} from {}. Discarding.",
+                self.network_context,
+                request_id,
+                self.remote_peer_id.short_str(),
+            );
+        }
+    }
 }
@@ -52,12 +52,21 @@ pub enum ErrorCode {
     ParsingError(ParsingErrorType),
     /// A message was received for a protocol that is not supported over this connection.
     NotSupported(NotSupportedType),
+    /// An rpc request was dropped as it exceeded the rate limits of the sender.
+    RateLimited(RateLimitedType),
 }
 
 impl ErrorCode {
     pub fn parsing_error(message: u8, protocol: u8) -> Self {
         ErrorCode::ParsingError(ParsingErrorType { message, protocol })
     }
+
+    pub fn rate_limited(protocol_id: ProtocolId, request_id: RequestId) -> Self {
+        ErrorCode::RateLimited(RateLimitedType {
+            protocol_id,
+            request_id,
+        })
+    }
 }
 
 /// Flags an invalid network message with as much header information as possible. This is a message
@@ -78,6 +87,15 @@ pub enum NotSupportedType {
     DirectSendMsg(ProtocolId),
 }
 
+/// Flags a dropped rpc request, so the sender can fail it instead of waiting
+/// for it to time out.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[cfg_attr(any(test, feature = "fuzzing"), derive(Arbitrary))]
+pub struct RateLimitedType {
+    pub protocol_id: ProtocolId,
+    pub request_id: RequestId,
+}
+
 /// Create alias RequestId for `u32`.
 pub type RequestId = u32;
 
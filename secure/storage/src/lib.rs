@@ -12,6 +12,7 @@ mod kv_storage;
 mod namespaced;
 mod on_disk;
 mod policy;
+mod remote_signer;
 mod storage;
 mod vault;
 
@@ -25,6 +26,7 @@ pub use crate::{
     namespaced::Namespaced,
     on_disk::OnDiskStorage,
     policy::{Capability, Identity, Permission, Policy},
+    remote_signer::RemoteSignerStorage,
     storage::Storage,
     vault::VaultStorage,
 };
@@ -1,18 +1,34 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{CryptoKVStorage, Error, GetResponse, KVStorage};
+use crate::{from_base64, to_base64, CryptoKVStorage, Error, GetResponse, KVStorage};
 use aptos_temppath::TempPath;
 use aptos_time_service::{TimeService, TimeServiceTrait};
-use serde::{de::DeserializeOwned, Serialize};
+use rand::{rngs::OsRng, Rng};
+use ring::{
+    aead::{self, Aad, LessSafeKey, Nonce, UnboundKey},
+    pbkdf2,
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use serde_json::Value;
 use std::{
     collections::HashMap,
     fs::{self, File},
     io::{Read, Write},
+    num::NonZeroU32,
     path::PathBuf,
 };
 
+/// The version of the sealed storage format, bumped on incompatible changes
+const SEALED_STORAGE_VERSION: u32 = 1;
+/// The field that tells sealed storage apart from plaintext storage
+const SEALED_STORAGE_VERSION_FIELD: &str = "sealed_storage_version";
+const PBKDF2_ITERATIONS: u32 = 100_000;
+/// The iteration count is read back from the file, so it's bounded: too few would weaken the key,
+/// and too many would stall the node on startup
+const MAX_PBKDF2_ITERATIONS: u32 = 10 * PBKDF2_ITERATIONS;
+const SALT_LENGTH: usize = 16;
+
 /// OnDiskStorage represents a key value store that is persisted to the local filesystem and is
 /// intended for single threads (or must be wrapped by a Arc<RwLock<>>). This provides no permission
 /// checks and simply offers a proof of concept to unblock building of applications without more
@@ -20,10 +36,15 @@ use std::{
 /// must make copies of all key material which violates the code base. It violates it because
 /// the anticipation is that data stores would securely handle key material. This should not be used
 /// in production.
+///
+/// The storage can optionally be sealed (i.e., encrypted at rest) with an AES-256-GCM key derived
+/// from a secret, such as a passphrase or the contents of a keyfile. This protects the data if the
+/// file leaks, but the data remains in the clear in memory.
 pub struct OnDiskStorage {
     file_path: PathBuf,
     temp_path: TempPath,
     time_service: TimeService,
+    sealing_key: Option<SealingKey>,
 }
 
 impl OnDiskStorage {
@@ -47,24 +68,88 @@ impl OnDiskStorage {
             file_path,
             temp_path: TempPath::new_with_temp_dir(file_dir),
             time_service,
+            sealing_key: None,
         }
     }
 
-    fn read(&self) -> Result<HashMap<String, Value>, Error> {
+    /// Opens a sealed storage, using a key derived from the given secret. If the file holds
+    /// plaintext data (e.g., from an unsealed storage), it is sealed in place.
+    pub fn new_sealed(file_path: PathBuf, secret: Vec<u8>) -> Result<Self, Error> {
+        let mut storage = Self::new(file_path);
+        let contents = storage.read_contents()?;
+        if contents.is_empty() {
+            storage.sealing_key = Some(SealingKey::new(secret)?);
+            return Ok(storage);
+        }
+
+        let value: Value = serde_json::from_str(&contents)?;
+        if value.get(SEALED_STORAGE_VERSION_FIELD).is_some() {
+            let sealed_data: SealedData = serde_json::from_value(value)?;
+            let sealing_key = SealingKey::derive(
+                secret,
+                sealed_data.salt.clone(),
+                sealed_data.pbkdf2_iterations,
+            )?;
+            // Fail early if the secret is wrong, rather than on the first read
+            sealing_key.unseal(sealed_data)?;
+            storage.sealing_key = Some(sealing_key);
+        } else {
+            let data = serde_json::from_value(value)?;
+            storage.sealing_key = Some(SealingKey::new(secret)?);
+            storage.write(&data)?;
+        }
+        Ok(storage)
+    }
+
+    /// Reseals the storage with a key derived from the new secret. This also seals a plaintext
+    /// storage. The write is atomic: if it fails, the storage remains sealed with the old key.
+    pub fn rotate_sealing_key(&mut self, secret: Vec<u8>) -> Result<(), Error> {
+        let data = self.read()?;
+        let sealing_key = self.sealing_key.replace(SealingKey::new(secret)?);
+        let result = self.write(&data);
+        if result.is_err() {
+            self.sealing_key = sealing_key;
+        }
+        result
+    }
+
+    fn read_contents(&self) -> Result<String, Error> {
         let mut file = File::open(&self.file_path)?;
         let mut contents = String::new();
         file.read_to_string(&mut contents)?;
+        Ok(contents)
+    }
+
+    fn read(&self) -> Result<HashMap<String, Value>, Error> {
+        let contents = self.read_contents()?;
         if contents.is_empty() {
             return Ok(HashMap::new());
         }
-        let data = serde_json::from_str(&contents)?;
+        if let Some(sealing_key) = &self.sealing_key {
+            let plaintext = sealing_key.unseal(serde_json::from_str(&contents)?)?;
+            return Ok(serde_json::from_slice(&plaintext)?);
+        }
+
+        let data: HashMap<String, Value> = serde_json::from_str(&contents)?;
+        if data.contains_key(SEALED_STORAGE_VERSION_FIELD) {
+            return Err(Error::InternalError(format!(
+                "Storage at {:?} is sealed, a passphrase or keyfile is required",
+                self.file_path
+            )));
+        }
         Ok(data)
     }
 
+    /// Writes to a temporary file in the same directory first, and then moves it over the
+    /// storage file, so that a crash never leaves a partially written storage behind.
     fn write(&self, data: &HashMap<String, Value>) -> Result<(), Error> {
-        let contents = serde_json::to_vec(data)?;
+        let mut contents = serde_json::to_vec(data)?;
+        if let Some(sealing_key) = &self.sealing_key {
+            contents = serde_json::to_vec(&sealing_key.seal(contents)?)?;
+        }
         let mut file = File::create(self.temp_path.path())?;
         file.write_all(&contents)?;
+        file.sync_all()?;
         fs::rename(&self.temp_path, &self.file_path)?;
         Ok(())
     }
@@ -99,3 +184,113 @@ impl KVStorage for OnDiskStorage {
 }
 
 impl CryptoKVStorage for OnDiskStorage {}
+
+/// The contents of a sealed storage file
+#[derive(Deserialize, Serialize)]
+struct SealedData {
+    sealed_storage_version: u32,
+    pbkdf2_iterations: u32,
+    #[serde(serialize_with = "to_base64", deserialize_with = "from_base64")]
+    salt: Vec<u8>,
+    #[serde(serialize_with = "to_base64", deserialize_with = "from_base64")]
+    nonce: Vec<u8>,
+    /// The serialized data, followed by the authentication tag
+    #[serde(serialize_with = "to_base64", deserialize_with = "from_base64")]
+    ciphertext: Vec<u8>,
+}
+
+/// An AES-256-GCM key derived from a secret using PBKDF2-HMAC-SHA256. The salt is authenticated
+/// along with the data, and a fresh random nonce is used for every write.
+struct SealingKey {
+    secret: Vec<u8>,
+    salt: Vec<u8>,
+    pbkdf2_iterations: u32,
+    key: LessSafeKey,
+}
+
+impl SealingKey {
+    /// Derives a key from the secret with a fresh random salt
+    fn new(secret: Vec<u8>) -> Result<Self, Error> {
+        let salt = OsRng.gen::<[u8; SALT_LENGTH]>().to_vec();
+        Self::derive(secret, salt, PBKDF2_ITERATIONS)
+    }
+
+    fn derive(secret: Vec<u8>, salt: Vec<u8>, pbkdf2_iterations: u32) -> Result<Self, Error> {
+        let iterations = NonZeroU32::new(pbkdf2_iterations)
+            .filter(|_| (PBKDF2_ITERATIONS..=MAX_PBKDF2_ITERATIONS).contains(&pbkdf2_iterations))
+            .ok_or_else(|| {
+                Error::SerializationError(format!(
+                    "Unsupported number of PBKDF2 iterations in sealed storage: {}",
+                    pbkdf2_iterations
+                ))
+            })?;
+        let mut key_bytes = [0u8; 32];
+        pbkdf2::derive(
+            pbkdf2::PBKDF2_HMAC_SHA256,
+            iterations,
+            &salt,
+            &secret,
+            &mut key_bytes,
+        );
+        let key = UnboundKey::new(&aead::AES_256_GCM, &key_bytes)
+            .map_err(|_| Error::InternalError("Unable to create the sealing key".into()))?;
+
+        Ok(Self {
+            secret,
+            salt,
+            pbkdf2_iterations,
+            key: LessSafeKey::new(key),
+        })
+    }
+
+    fn seal(&self, plaintext: Vec<u8>) -> Result<SealedData, Error> {
+        let nonce = OsRng.gen::<[u8; aead::NONCE_LEN]>();
+        let mut ciphertext = plaintext;
+        self.key
+            .seal_in_place_append_tag(
+                Nonce::assume_unique_for_key(nonce),
+                Aad::from(self.salt.as_slice()),
+                &mut ciphertext,
+            )
+            .map_err(|_| Error::InternalError("Unable to seal storage".into()))?;
+
+        Ok(SealedData {
+            sealed_storage_version: SEALED_STORAGE_VERSION,
+            pbkdf2_iterations: self.pbkdf2_iterations,
+            salt: self.salt.clone(),
+            nonce: nonce.to_vec(),
+            ciphertext,
+        })
+    }
+
+    fn unseal(&self, sealed_data: SealedData) -> Result<Vec<u8>, Error> {
+        if sealed_data.sealed_storage_version != SEALED_STORAGE_VERSION {
+            return Err(Error::SerializationError(format!(
+                "Unsupported sealed storage version: {}",
+                sealed_data.sealed_storage_version
+            )));
+        }
+        // The storage may have been resealed with a different salt by another instance
+        if sealed_data.salt != self.salt || sealed_data.pbkdf2_iterations != self.pbkdf2_iterations
+        {
+            return Self::derive(
+                self.secret.clone(),
+                sealed_data.salt.clone(),
+                sealed_data.pbkdf2_iterations,
+            )?
+            .unseal(sealed_data);
+        }
+
+        let nonce = Nonce::try_assume_unique_for_key(&sealed_data.nonce)
+            .map_err(|_| Error::SerializationError("Invalid sealed storage nonce".into()))?;
+        let mut plaintext = sealed_data.ciphertext;
+        // Either the secret is wrong, or the storage was tampered with
+        let plaintext_len = self
+            .key
+            .open_in_place(nonce, Aad::from(self.salt.as_slice()), &mut plaintext)
+            .map_err(|_| Error::PermissionDenied)?
+            .len();
+        plaintext.truncate(plaintext_len);
+        Ok(plaintext)
+    }
+}
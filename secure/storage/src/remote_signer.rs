@@ -0,0 +1,324 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    from_base64, namespaced::NAMESPACE_SEPARATOR, to_base64, CryptoStorage, Error, GetResponse,
+    KVStorage, OnDiskStorage, PublicKeyResponse,
+};
+use aptos_crypto::{
+    ed25519::{Ed25519PrivateKey, Ed25519PublicKey, Ed25519Signature},
+    hash::CryptoHash,
+    traits::signing_message,
+    Signature,
+};
+use aptos_global_constants::{
+    APTOS_ROOT_KEY, CONSENSUS_KEY, FULLNODE_NETWORK_KEY, OPERATOR_KEY, OWNER_KEY,
+    VALIDATOR_NETWORK_KEY,
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::{path::PathBuf, sync::Arc, time::Duration};
+
+/// Default request timeouts for remote signer operations. As with the vault client, ureq 1.5.4
+/// uses the connection timeout for all operations.
+const DEFAULT_CONNECTION_TIMEOUT_MS: u64 = 1_000;
+const DEFAULT_RESPONSE_TIMEOUT_MS: u64 = 1_000;
+
+/// Keys that are only ever held by the service and must not be read from or written to the
+/// key/value store.
+const PRIVATE_KEY_NAMES: &[&str] = &[
+    APTOS_ROOT_KEY,
+    CONSENSUS_KEY,
+    FULLNODE_NETWORK_KEY,
+    OPERATOR_KEY,
+    OWNER_KEY,
+    VALIDATOR_NETWORK_KEY,
+];
+
+/// RemoteSignerStorage delegates all private key operations to a remote signing service (e.g., a
+/// KMS or an HSM front end) over HTTPS with mutual TLS. Private keys never leave the service: keys
+/// can be created, rotated and used for signing, but exporting or importing them is denied. As the
+/// service only handles keys, the key/value data (e.g., safety data and waypoints, none of which
+/// is secret) is kept in an OnDiskStorage. That store is plaintext, so private keys (by type) and
+/// the well known key names (e.g., the consensus key) are refused on the key/value path.
+///
+/// The service is expected to expose the following JSON endpoints (see the request and response
+/// types below), and to answer with a 404 for unknown keys and a 403 for denied requests:
+/// * GET  /v1/health: returns a 200 if the service is available.
+/// * POST /v1/keys/create: creates the named key, if it doesn't exist yet.
+/// * POST /v1/keys/read: returns the current (and previous) public key of the named key.
+/// * POST /v1/keys/rotate: rotates the named key, retaining the previous version.
+/// * POST /v1/keys/sign: signs the message using the named key (and version, if specified).
+pub struct RemoteSignerStorage {
+    agent: ureq::Agent,
+    server: String,
+    tls_connector: Arc<native_tls::TlsConnector>,
+    connection_timeout_ms: u64,
+    response_timeout_ms: u64,
+    kv_storage: OnDiskStorage,
+}
+
+impl RemoteSignerStorage {
+    /// Creates a new remote signer storage. The CA certificate is used to authenticate the
+    /// service, and the client certificate and key (both PEM encoded) to authenticate to it.
+    /// The server must be an https URL, as requests would otherwise be sent in plaintext.
+    pub fn new(
+        server: String,
+        ca_certificate: Option<String>,
+        client_identity: (String, String),
+        kv_path: PathBuf,
+        connection_timeout_ms: Option<u64>,
+        response_timeout_ms: Option<u64>,
+    ) -> Self {
+        assert!(
+            server.starts_with("https://"),
+            "The remote signer must be reached over https: {}",
+            server
+        );
+        let mut tls_builder = native_tls::TlsConnector::builder();
+        tls_builder.min_protocol_version(Some(native_tls::Protocol::Tlsv12));
+        if let Some(certificate) = ca_certificate {
+            // First try the certificate as a PEM encoded cert, then as DER, and then panic.
+            let mut cert = native_tls::Certificate::from_pem(certificate.as_bytes());
+            if cert.is_err() {
+                cert = native_tls::Certificate::from_der(certificate.as_bytes());
+            }
+            tls_builder.add_root_certificate(cert.unwrap());
+        }
+        let (certificate, key) = client_identity;
+        let identity = native_tls::Identity::from_pkcs8(certificate.as_bytes(), key.as_bytes())
+            .expect("Unable to parse the client certificate and key");
+        tls_builder.identity(identity);
+        let tls_connector = Arc::new(tls_builder.build().unwrap());
+
+        Self {
+            agent: ureq::Agent::new().set("connection", "keep-alive").build(),
+            server,
+            tls_connector,
+            connection_timeout_ms: connection_timeout_ms.unwrap_or(DEFAULT_CONNECTION_TIMEOUT_MS),
+            response_timeout_ms: response_timeout_ms.unwrap_or(DEFAULT_RESPONSE_TIMEOUT_MS),
+            kv_storage: OnDiskStorage::new(kv_path),
+        }
+    }
+
+    fn upgrade_request(&self, mut request: ureq::Request) -> ureq::Request {
+        request.timeout_connect(self.connection_timeout_ms);
+        request.timeout(Duration::from_millis(self.response_timeout_ms));
+        request.set_tls_connector(self.tls_connector.clone());
+        request
+    }
+
+    fn post<T: Serialize, R: DeserializeOwned>(
+        &self,
+        endpoint: &str,
+        name: &str,
+        request: &T,
+    ) -> Result<R, Error> {
+        let http_request = self
+            .agent
+            .post(&format!("{}/v1/keys/{}", self.server, endpoint));
+        let resp = self
+            .upgrade_request(http_request)
+            .send_json(serde_json::to_value(request)?);
+        let body = process_response(resp, name)?;
+        Ok(serde_json::from_str(&body)?)
+    }
+
+    fn read_key(&self, name: &str) -> Result<KeyResponse, Error> {
+        self.post("read", name, &KeyRequest::new(name))
+    }
+
+    fn sign_message<T: CryptoHash + Serialize>(
+        &self,
+        name: &str,
+        version: Option<Ed25519PublicKey>,
+        message: &T,
+    ) -> Result<Ed25519Signature, Error> {
+        let request = SignRequest {
+            name: name.into(),
+            public_key: version.clone(),
+            message: signing_message(message),
+        };
+        let response: SignResponse = self.post("sign", name, &request)?;
+
+        // Don't trust the service blindly: the signature must be valid, and made with the
+        // requested key version
+        if version.map_or(false, |version| version != response.public_key) {
+            return Err(Error::InternalError(format!(
+                "Remote signer used the wrong version of key: {}",
+                name
+            )));
+        }
+        response
+            .signature
+            .verify(message, &response.public_key)
+            .map_err(|e| Error::InternalError(format!("Invalid remote signature: {}", e)))?;
+        Ok(response.signature)
+    }
+}
+
+/// Returns the body of the response, or the corresponding error if the request failed.
+fn process_response(resp: ureq::Response, name: &str) -> Result<String, Error> {
+    if resp.synthetic() {
+        return Err(Error::InternalError(format!(
+            "Unable to reach the remote signer: {}",
+            resp.into_string()?
+        )));
+    }
+    let status = resp.status();
+    // Always read the body, so that the stream can be re-used
+    let body = resp.into_string()?;
+    match status {
+        200 => Ok(body),
+        403 => Err(Error::PermissionDenied),
+        404 => Err(Error::KeyNotSet(name.into())),
+        _ => Err(Error::InternalError(format!(
+            "Remote signer error, status code: {}, body: {}",
+            status, body
+        ))),
+    }
+}
+
+/// Denies access to private keys on the key/value path, as they'd be stored in plaintext.
+fn check_kv_access<T>(key: &str) -> Result<(), Error> {
+    // Keys may be namespaced, i.e., namespace/name
+    let name = key.rsplit(NAMESPACE_SEPARATOR).next().unwrap_or(key);
+    if PRIVATE_KEY_NAMES.contains(&name) || std::any::type_name::<T>().ends_with("PrivateKey") {
+        return Err(Error::PermissionDenied);
+    }
+    Ok(())
+}
+
+impl KVStorage for RemoteSignerStorage {
+    fn available(&self) -> Result<(), Error> {
+        let request = self.agent.get(&format!("{}/v1/health", self.server));
+        process_response(self.upgrade_request(request).call(), "")?;
+        self.kv_storage.available()
+    }
+
+    fn get<T: DeserializeOwned>(&self, key: &str) -> Result<GetResponse<T>, Error> {
+        check_kv_access::<T>(key)?;
+        self.kv_storage.get(key)
+    }
+
+    fn set<T: Serialize>(&mut self, key: &str, value: T) -> Result<(), Error> {
+        check_kv_access::<T>(key)?;
+        self.kv_storage.set(key, value)
+    }
+
+    /// Note: this only resets the key/value data, the keys held by the service are untouched.
+    #[cfg(any(test, feature = "testing"))]
+    fn reset_and_clear(&mut self) -> Result<(), Error> {
+        self.kv_storage.reset_and_clear()
+    }
+}
+
+impl CryptoStorage for RemoteSignerStorage {
+    fn create_key(&mut self, name: &str) -> Result<Ed25519PublicKey, Error> {
+        let response: KeyResponse = self.post("create", name, &KeyRequest::new(name))?;
+        Ok(response.public_key)
+    }
+
+    fn export_private_key(&self, _name: &str) -> Result<Ed25519PrivateKey, Error> {
+        Err(Error::PermissionDenied)
+    }
+
+    fn import_private_key(&mut self, _name: &str, _key: Ed25519PrivateKey) -> Result<(), Error> {
+        Err(Error::PermissionDenied)
+    }
+
+    fn export_private_key_for_version(
+        &self,
+        _name: &str,
+        _version: Ed25519PublicKey,
+    ) -> Result<Ed25519PrivateKey, Error> {
+        Err(Error::PermissionDenied)
+    }
+
+    fn get_public_key(&self, name: &str) -> Result<PublicKeyResponse, Error> {
+        let response = self.read_key(name)?;
+        Ok(PublicKeyResponse {
+            last_update: response.last_update,
+            public_key: response.public_key,
+        })
+    }
+
+    fn get_public_key_previous_version(&self, name: &str) -> Result<Ed25519PublicKey, Error> {
+        self.read_key(name)?
+            .previous_public_key
+            .ok_or_else(|| Error::KeyVersionNotFound(name.into(), "previous version".into()))
+    }
+
+    fn rotate_key(&mut self, name: &str) -> Result<Ed25519PublicKey, Error> {
+        let response: KeyResponse = self.post("rotate", name, &KeyRequest::new(name))?;
+        Ok(response.public_key)
+    }
+
+    fn sign<T: CryptoHash + Serialize>(
+        &self,
+        name: &str,
+        message: &T,
+    ) -> Result<Ed25519Signature, Error> {
+        self.sign_message(name, None, message)
+    }
+
+    fn sign_using_version<T: CryptoHash + Serialize>(
+        &self,
+        name: &str,
+        version: Ed25519PublicKey,
+        message: &T,
+    ) -> Result<Ed25519Signature, Error> {
+        let key = match self.read_key(name) {
+            Ok(key) => key,
+            Err(Error::KeyNotSet(_)) => {
+                return Err(Error::KeyVersionNotFound(name.into(), version.to_string()))
+            }
+            Err(e) => return Err(e),
+        };
+        if key.public_key != version && key.previous_public_key.as_ref() != Some(&version) {
+            return Err(Error::KeyVersionNotFound(name.into(), version.to_string()));
+        }
+        self.sign_message(name, Some(version), message)
+    }
+}
+
+/// The request for the create, read and rotate endpoints.
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+pub struct KeyRequest {
+    pub name: String,
+}
+
+impl KeyRequest {
+    fn new(name: &str) -> Self {
+        Self { name: name.into() }
+    }
+}
+
+/// The response of the create, read and rotate endpoints.
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+pub struct KeyResponse {
+    /// The current version of the key
+    pub public_key: Ed25519PublicKey,
+    /// The version of the key before the last rotation, if any
+    pub previous_public_key: Option<Ed25519PublicKey>,
+    /// Time since Unix Epoch in seconds of the last creation or rotation
+    pub last_update: u64,
+}
+
+/// The request for the sign endpoint.
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+pub struct SignRequest {
+    pub name: String,
+    /// The version of the key to sign with, or the current one if not specified
+    pub public_key: Option<Ed25519PublicKey>,
+    /// The bytes to sign, as is (i.e., they are already prefixed with the hasher seed)
+    #[serde(serialize_with = "to_base64", deserialize_with = "from_base64")]
+    pub message: Vec<u8>,
+}
+
+/// The response of the sign endpoint.
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+pub struct SignResponse {
+    /// The version of the key used to sign
+    pub public_key: Ed25519PublicKey,
+    pub signature: Ed25519Signature,
+}
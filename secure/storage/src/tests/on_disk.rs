@@ -1,8 +1,12 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{tests::suite, OnDiskStorage, Storage};
+use crate::{tests::suite, Error, KVStorage, OnDiskStorage, Storage};
 use aptos_temppath::TempPath;
+use std::fs;
+
+const PASSPHRASE: &[u8] = b"correct horse battery staple";
+const ROTATED_PASSPHRASE: &[u8] = b"incorrect horse battery staple";
 
 #[test]
 fn on_disk() {
@@ -10,3 +14,90 @@ fn on_disk() {
     let mut storage = Storage::from(OnDiskStorage::new(path_buf));
     suite::execute_all_storage_tests(&mut storage);
 }
+
+#[test]
+fn on_disk_sealed() {
+    let path_buf = TempPath::new().path().to_path_buf();
+    let mut storage =
+        Storage::from(OnDiskStorage::new_sealed(path_buf, PASSPHRASE.to_vec()).unwrap());
+    suite::execute_all_storage_tests(&mut storage);
+}
+
+#[test]
+fn on_disk_sealed_at_rest() {
+    let temp_path = TempPath::new();
+    let path_buf = temp_path.path().to_path_buf();
+    let mut storage = OnDiskStorage::new_sealed(path_buf.clone(), PASSPHRASE.to_vec()).unwrap();
+    storage.set("secret", "hunter2").unwrap();
+
+    let contents = fs::read_to_string(&path_buf).unwrap();
+    assert!(!contents.contains("hunter2"));
+
+    // The data can only be read back with the right secret
+    let storage = OnDiskStorage::new_sealed(path_buf.clone(), PASSPHRASE.to_vec()).unwrap();
+    assert_eq!(storage.get::<String>("secret").unwrap().value, "hunter2");
+    assert_eq!(
+        OnDiskStorage::new_sealed(path_buf.clone(), ROTATED_PASSPHRASE.to_vec()).err(),
+        Some(Error::PermissionDenied)
+    );
+    assert!(matches!(
+        OnDiskStorage::new(path_buf).get::<String>("secret"),
+        Err(Error::InternalError(_))
+    ));
+}
+
+#[test]
+fn on_disk_seal_plaintext() {
+    let temp_path = TempPath::new();
+    let path_buf = temp_path.path().to_path_buf();
+    let mut storage = OnDiskStorage::new(path_buf.clone());
+    storage.set("secret", "hunter2").unwrap();
+
+    // Opening plaintext storage as sealed seals it in place
+    let storage = OnDiskStorage::new_sealed(path_buf.clone(), PASSPHRASE.to_vec()).unwrap();
+    assert_eq!(storage.get::<String>("secret").unwrap().value, "hunter2");
+    let contents = fs::read_to_string(&path_buf).unwrap();
+    assert!(!contents.contains("hunter2"));
+}
+
+#[test]
+fn on_disk_rotate_sealing_key() {
+    let temp_path = TempPath::new();
+    let path_buf = temp_path.path().to_path_buf();
+    let mut storage = OnDiskStorage::new_sealed(path_buf.clone(), PASSPHRASE.to_vec()).unwrap();
+    storage.set("secret", "hunter2").unwrap();
+
+    storage
+        .rotate_sealing_key(ROTATED_PASSPHRASE.to_vec())
+        .unwrap();
+    assert_eq!(storage.get::<String>("secret").unwrap().value, "hunter2");
+
+    // Only the new secret unseals the storage
+    assert_eq!(
+        OnDiskStorage::new_sealed(path_buf.clone(), PASSPHRASE.to_vec()).err(),
+        Some(Error::PermissionDenied)
+    );
+    let storage = OnDiskStorage::new_sealed(path_buf, ROTATED_PASSPHRASE.to_vec()).unwrap();
+    assert_eq!(storage.get::<String>("secret").unwrap().value, "hunter2");
+}
+
+#[test]
+fn on_disk_sealed_pbkdf2_iterations() {
+    let temp_path = TempPath::new();
+    let path_buf = temp_path.path().to_path_buf();
+    let mut storage = OnDiskStorage::new_sealed(path_buf.clone(), PASSPHRASE.to_vec()).unwrap();
+    storage.set("secret", "hunter2").unwrap();
+
+    // Iteration counts outside of the supported range are rejected before deriving the key
+    let contents: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&path_buf).unwrap()).unwrap();
+    for pbkdf2_iterations in [0, 1, u32::MAX] {
+        let mut contents = contents.clone();
+        contents["pbkdf2_iterations"] = pbkdf2_iterations.into();
+        fs::write(&path_buf, contents.to_string()).unwrap();
+        assert!(matches!(
+            OnDiskStorage::new_sealed(path_buf.clone(), PASSPHRASE.to_vec()),
+            Err(Error::SerializationError(_))
+        ));
+    }
+}
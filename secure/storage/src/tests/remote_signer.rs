@@ -0,0 +1,486 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    remote_signer::{KeyRequest, KeyResponse, SignRequest, SignResponse},
+    CryptoStorage, Error, KVStorage, RemoteSignerStorage,
+};
+use aptos_crypto::{
+    ed25519::{Ed25519PrivateKey, Ed25519PublicKey},
+    test_utils::TestAptosCrypto,
+    PrivateKey, Signature, SigningKey, Uniform,
+};
+use aptos_global_constants::CONSENSUS_KEY;
+use aptos_infallible::Mutex;
+use aptos_temppath::TempPath;
+use openssl::{
+    asn1::Asn1Time,
+    bn::BigNum,
+    ec::{EcGroup, EcKey},
+    hash::MessageDigest,
+    nid::Nid,
+    pkey::{PKey, Private},
+    ssl::{SslAcceptor, SslMethod, SslVerifyMode},
+    x509::{
+        extension::{BasicConstraints, KeyUsage, SubjectAlternativeName},
+        store::X509StoreBuilder,
+        X509NameBuilder, X509,
+    },
+};
+use std::{
+    collections::HashMap,
+    io::{BufRead, BufReader, Read, Write},
+    net::TcpListener,
+    sync::Arc,
+    thread,
+};
+
+const KEY: &str = "consensus_key";
+const UNKNOWN_KEY: &str = "unknown_key";
+
+/// The current and previous versions of each key held by the mock signer
+type MockKeys = Arc<Mutex<HashMap<String, (Ed25519PrivateKey, Option<Ed25519PrivateKey>)>>>;
+
+/// A minimal HTTP/1.1 over TLS implementation of the remote signer API, holding its keys in
+/// memory. Like the real service, it only accepts clients with a certificate issued by its CA.
+struct MockRemoteSigner {
+    address: String,
+    ca_certificate: X509,
+    ca_key: PKey<Private>,
+}
+
+impl MockRemoteSigner {
+    fn start() -> Self {
+        let ca_key = generate_tls_key();
+        let ca_certificate = issue_certificate("Mock CA", &ca_key, None);
+        let server_key = generate_tls_key();
+        let server_certificate =
+            issue_certificate("127.0.0.1", &server_key, Some((&ca_certificate, &ca_key)));
+
+        let mut acceptor = SslAcceptor::mozilla_intermediate_v5(SslMethod::tls()).unwrap();
+        acceptor.set_private_key(&server_key).unwrap();
+        acceptor.set_certificate(&server_certificate).unwrap();
+        let mut client_ca_store = X509StoreBuilder::new().unwrap();
+        client_ca_store.add_cert(ca_certificate.clone()).unwrap();
+        acceptor
+            .set_verify_cert_store(client_ca_store.build())
+            .unwrap();
+        acceptor.set_verify(SslVerifyMode::PEER | SslVerifyMode::FAIL_IF_NO_PEER_CERT);
+        let acceptor = Arc::new(acceptor.build());
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = format!("https://{}", listener.local_addr().unwrap());
+        let keys = MockKeys::default();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let acceptor = acceptor.clone();
+                let keys = keys.clone();
+                thread::spawn(move || {
+                    // Clients which fail the handshake are simply disconnected
+                    if let Ok(stream) = acceptor.accept(stream.unwrap()) {
+                        handle_connection(stream, keys)
+                    }
+                });
+            }
+        });
+        Self {
+            address,
+            ca_certificate,
+            ca_key,
+        }
+    }
+
+    /// Returns a client certificate and key issued by the mock's CA
+    fn client_identity(&self) -> (String, String) {
+        let key = generate_tls_key();
+        let certificate =
+            issue_certificate("client", &key, Some((&self.ca_certificate, &self.ca_key)));
+        to_identity(&certificate, &key)
+    }
+
+    fn ca_certificate(&self) -> String {
+        String::from_utf8(self.ca_certificate.to_pem().unwrap()).unwrap()
+    }
+
+    fn storage(&self) -> RemoteSignerStorage {
+        self.storage_with(Some(self.ca_certificate()), self.client_identity())
+    }
+
+    fn storage_with(
+        &self,
+        ca_certificate: Option<String>,
+        client_identity: (String, String),
+    ) -> RemoteSignerStorage {
+        let kv_path = TempPath::new().path().to_path_buf();
+        RemoteSignerStorage::new(
+            self.address.clone(),
+            ca_certificate,
+            client_identity,
+            kv_path,
+            None,
+            None,
+        )
+    }
+}
+
+fn generate_tls_key() -> PKey<Private> {
+    let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1).unwrap();
+    PKey::from_ec_key(EcKey::generate(&group).unwrap()).unwrap()
+}
+
+/// Issues a certificate for 127.0.0.1 signed by the issuer, or a self-signed CA certificate if
+/// there's no issuer.
+fn issue_certificate(
+    common_name: &str,
+    key: &PKey<Private>,
+    issuer: Option<(&X509, &PKey<Private>)>,
+) -> X509 {
+    let mut name = X509NameBuilder::new().unwrap();
+    name.append_entry_by_text("CN", common_name).unwrap();
+    let name = name.build();
+
+    let mut builder = X509::builder().unwrap();
+    builder.set_version(2).unwrap();
+    let serial_number = BigNum::from_u32(rand::random()).unwrap();
+    builder
+        .set_serial_number(&serial_number.to_asn1_integer().unwrap())
+        .unwrap();
+    builder.set_subject_name(&name).unwrap();
+    builder.set_pubkey(key).unwrap();
+    builder
+        .set_not_before(&Asn1Time::days_from_now(0).unwrap())
+        .unwrap();
+    builder
+        .set_not_after(&Asn1Time::days_from_now(1).unwrap())
+        .unwrap();
+    let signing_key = match issuer {
+        Some((issuer_certificate, issuer_key)) => {
+            builder
+                .set_issuer_name(issuer_certificate.subject_name())
+                .unwrap();
+            let subject_alt_name = SubjectAlternativeName::new()
+                .ip("127.0.0.1")
+                .build(&builder.x509v3_context(Some(issuer_certificate), None))
+                .unwrap();
+            builder.append_extension(subject_alt_name).unwrap();
+            issuer_key
+        }
+        None => {
+            builder.set_issuer_name(&name).unwrap();
+            builder
+                .append_extension(BasicConstraints::new().critical().ca().build().unwrap())
+                .unwrap();
+            builder
+                .append_extension(
+                    KeyUsage::new()
+                        .critical()
+                        .key_cert_sign()
+                        .crl_sign()
+                        .build()
+                        .unwrap(),
+                )
+                .unwrap();
+            key
+        }
+    };
+    builder.sign(signing_key, MessageDigest::sha256()).unwrap();
+    builder.build()
+}
+
+/// Returns the PEM encoded certificate and PKCS #8 key, as expected by `RemoteSignerStorage`
+fn to_identity(certificate: &X509, key: &PKey<Private>) -> (String, String) {
+    (
+        String::from_utf8(certificate.to_pem().unwrap()).unwrap(),
+        String::from_utf8(key.private_key_to_pem_pkcs8().unwrap()).unwrap(),
+    )
+}
+
+/// Serves requests on the connection until the client closes it
+fn handle_connection<S: Read + Write>(stream: S, keys: MockKeys) {
+    let mut reader = BufReader::new(stream);
+    loop {
+        let mut request_line = String::new();
+        if reader.read_line(&mut request_line).unwrap_or(0) == 0 {
+            return;
+        }
+        let mut content_length = 0;
+        loop {
+            let mut header = String::new();
+            reader.read_line(&mut header).unwrap();
+            let header = header.trim();
+            if header.is_empty() {
+                break;
+            }
+            if let Some((name, value)) = header.split_once(':') {
+                if name.eq_ignore_ascii_case("content-length") {
+                    content_length = value.trim().parse().unwrap();
+                }
+            }
+        }
+        let mut body = vec![0; content_length];
+        reader.read_exact(&mut body).unwrap();
+
+        let path = request_line.split_whitespace().nth(1).unwrap_or_default();
+        let (status, body) = handle_request(path, &body, &keys);
+        let response = format!(
+            "HTTP/1.1 {} Mock\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
+            status,
+            body.len(),
+            body
+        );
+        if reader.get_mut().write_all(response.as_bytes()).is_err() {
+            return;
+        }
+    }
+}
+
+fn handle_request(path: &str, body: &[u8], keys: &MockKeys) -> (u16, String) {
+    if path == "/v1/health" {
+        return (200, String::new());
+    }
+
+    let mut keys = keys.lock();
+    let key_response = |(current, previous): &(Ed25519PrivateKey, Option<Ed25519PrivateKey>)| {
+        serde_json::to_string(&KeyResponse {
+            public_key: current.public_key(),
+            previous_public_key: previous.as_ref().map(PrivateKey::public_key),
+            last_update: 0,
+        })
+        .unwrap()
+    };
+    match path {
+        "/v1/keys/create" => {
+            let request: KeyRequest = serde_json::from_slice(body).unwrap();
+            let key = keys
+                .entry(request.name)
+                .or_insert_with(|| (generate_key(), None));
+            (200, key_response(key))
+        }
+        "/v1/keys/read" => {
+            let request: KeyRequest = serde_json::from_slice(body).unwrap();
+            match keys.get(&request.name) {
+                Some(key) => (200, key_response(key)),
+                None => (404, String::new()),
+            }
+        }
+        "/v1/keys/rotate" => {
+            let request: KeyRequest = serde_json::from_slice(body).unwrap();
+            match keys.remove(&request.name) {
+                Some((current, _)) => {
+                    let key = (generate_key(), Some(current));
+                    let response = key_response(&key);
+                    keys.insert(request.name, key);
+                    (200, response)
+                }
+                None => (404, String::new()),
+            }
+        }
+        "/v1/keys/sign" => {
+            let request: SignRequest = serde_json::from_slice(body).unwrap();
+            let (current, previous) = match keys.get(&request.name) {
+                Some(key) => key,
+                None => return (404, String::new()),
+            };
+            let private_key = match request.public_key {
+                None => current,
+                Some(version) if version == current.public_key() => current,
+                Some(version) => match previous {
+                    Some(previous) if version == previous.public_key() => previous,
+                    _ => return (404, String::new()),
+                },
+            };
+            let response = SignResponse {
+                public_key: private_key.public_key(),
+                signature: private_key.sign_arbitrary_message(&request.message),
+            };
+            (200, serde_json::to_string(&response).unwrap())
+        }
+        _ => (403, String::new()),
+    }
+}
+
+fn generate_key() -> Ed25519PrivateKey {
+    Ed25519PrivateKey::generate(&mut rand::rngs::OsRng)
+}
+
+#[test]
+fn test_remote_signer_available() {
+    let signer = MockRemoteSigner::start();
+    signer.storage().available().unwrap();
+
+    // Nothing is listening on the discard port
+    let kv_path = TempPath::new().path().to_path_buf();
+    let storage = RemoteSignerStorage::new(
+        "https://127.0.0.1:9".into(),
+        Some(signer.ca_certificate()),
+        signer.client_identity(),
+        kv_path,
+        None,
+        None,
+    );
+    assert!(matches!(storage.available(), Err(Error::InternalError(_))));
+}
+
+#[test]
+fn test_remote_signer_mutual_tls() {
+    let signer = MockRemoteSigner::start();
+
+    // The service rejects a client certificate which wasn't issued by its CA
+    let untrusted_ca_key = generate_tls_key();
+    let untrusted_ca_certificate = issue_certificate("Untrusted CA", &untrusted_ca_key, None);
+    let client_key = generate_tls_key();
+    let client_certificate = issue_certificate(
+        "client",
+        &client_key,
+        Some((&untrusted_ca_certificate, &untrusted_ca_key)),
+    );
+    let mut storage = signer.storage_with(
+        Some(signer.ca_certificate()),
+        to_identity(&client_certificate, &client_key),
+    );
+    assert!(matches!(storage.available(), Err(Error::InternalError(_))));
+    assert!(matches!(
+        storage.create_key(KEY),
+        Err(Error::InternalError(_))
+    ));
+
+    // The client rejects the service if it doesn't trust its CA
+    let storage = signer.storage_with(None, signer.client_identity());
+    assert!(matches!(storage.available(), Err(Error::InternalError(_))));
+}
+
+#[test]
+#[should_panic(expected = "The remote signer must be reached over https")]
+fn test_remote_signer_requires_https() {
+    let signer = MockRemoteSigner::start();
+    let kv_path = TempPath::new().path().to_path_buf();
+    RemoteSignerStorage::new(
+        signer.address.replace("https://", "http://"),
+        Some(signer.ca_certificate()),
+        signer.client_identity(),
+        kv_path,
+        None,
+        None,
+    );
+}
+
+#[test]
+fn test_remote_signer_keys() {
+    let signer = MockRemoteSigner::start();
+    let mut storage = signer.storage();
+    let message = TestAptosCrypto("Hello, World".to_string());
+
+    assert_eq!(
+        storage.get_public_key(UNKNOWN_KEY).unwrap_err(),
+        Error::KeyNotSet(UNKNOWN_KEY.into())
+    );
+
+    let public_key = storage.create_key(KEY).unwrap();
+    assert_eq!(storage.get_public_key(KEY).unwrap().public_key, public_key);
+    assert_eq!(
+        storage.get_public_key_previous_version(KEY).unwrap_err(),
+        Error::KeyVersionNotFound(KEY.into(), "previous version".into())
+    );
+
+    let signature = storage.sign(KEY, &message).unwrap();
+    signature.verify(&message, &public_key).unwrap();
+
+    let rotated_public_key = storage.rotate_key(KEY).unwrap();
+    assert_ne!(public_key, rotated_public_key);
+    assert_eq!(
+        storage.get_public_key(KEY).unwrap().public_key,
+        rotated_public_key
+    );
+    assert_eq!(
+        storage.get_public_key_previous_version(KEY).unwrap(),
+        public_key
+    );
+
+    // Both the current and the previous version can sign, but no other
+    let signature = storage.sign(KEY, &message).unwrap();
+    signature.verify(&message, &rotated_public_key).unwrap();
+    let signature = storage
+        .sign_using_version(KEY, public_key.clone(), &message)
+        .unwrap();
+    signature.verify(&message, &public_key).unwrap();
+
+    let unknown_version = Ed25519PublicKey::from(&generate_key());
+    assert_eq!(
+        storage
+            .sign_using_version(KEY, unknown_version.clone(), &message)
+            .unwrap_err(),
+        Error::KeyVersionNotFound(KEY.into(), unknown_version.to_string())
+    );
+    assert_eq!(
+        storage
+            .sign_using_version(UNKNOWN_KEY, public_key.clone(), &message)
+            .unwrap_err(),
+        Error::KeyVersionNotFound(UNKNOWN_KEY.into(), public_key.to_string())
+    );
+}
+
+#[test]
+fn test_remote_signer_private_keys_stay_remote() {
+    let signer = MockRemoteSigner::start();
+    let mut storage = signer.storage();
+    let public_key = storage.create_key(KEY).unwrap();
+
+    assert_eq!(
+        storage.export_private_key(KEY).unwrap_err(),
+        Error::PermissionDenied
+    );
+    assert_eq!(
+        storage
+            .export_private_key_for_version(KEY, public_key)
+            .unwrap_err(),
+        Error::PermissionDenied
+    );
+    assert_eq!(
+        storage.import_private_key(KEY, generate_key()).unwrap_err(),
+        Error::PermissionDenied
+    );
+}
+
+#[test]
+fn test_remote_signer_key_value() {
+    let signer = MockRemoteSigner::start();
+    let mut storage = signer.storage();
+
+    storage.set("waypoint", 5u64).unwrap();
+    assert_eq!(storage.get::<u64>("waypoint").unwrap().value, 5);
+    assert_eq!(
+        storage.get::<u64>(UNKNOWN_KEY).unwrap_err(),
+        Error::KeyNotSet(UNKNOWN_KEY.into())
+    );
+}
+
+#[test]
+fn test_remote_signer_key_value_denies_private_keys() {
+    let signer = MockRemoteSigner::start();
+    let mut storage = signer.storage();
+
+    // Private keys are refused by type, whatever their name
+    assert_eq!(
+        storage.set("waypoint", generate_key()).unwrap_err(),
+        Error::PermissionDenied
+    );
+    assert_eq!(
+        storage.get::<Ed25519PrivateKey>("waypoint").unwrap_err(),
+        Error::PermissionDenied
+    );
+
+    // The well known key names are refused, even when namespaced
+    assert_eq!(
+        storage.set(CONSENSUS_KEY, 5u64).unwrap_err(),
+        Error::PermissionDenied
+    );
+    let namespaced_key = format!("namespace/{}", CONSENSUS_KEY);
+    assert_eq!(
+        storage.set(&namespaced_key, 5u64).unwrap_err(),
+        Error::PermissionDenied
+    );
+    assert_eq!(
+        storage.get::<u64>(&namespaced_key).unwrap_err(),
+        Error::PermissionDenied
+    );
+}
@@ -56,6 +56,7 @@ pub enum LogEvent {
     PeerIgnored,
     PeerNoLongerIgnored,
     PeerPollingError,
+    PeerSelected,
     PeerSelectionError,
     PriorityAndRegularPeers,
     ResponseError,
@@ -1,6 +1,7 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
+use crate::aptosnet::state::PeerSelectionWeight;
 use aptos_config::network_id::PeerNetworkId;
 use aptos_crypto::_once_cell::sync::Lazy;
 use aptos_metrics_core::{
@@ -54,6 +55,23 @@ pub static REQUEST_LATENCIES: Lazy<HistogramVec> = Lazy::new(|| {
     .unwrap()
 });
 
+/// Buckets for the selection weight factors, which are all in [0, 1]
+const PEER_SELECTION_FACTOR_BUCKETS: &[f64] =
+    &[0.01, 0.05, 0.1, 0.2, 0.3, 0.4, 0.5, 0.6, 0.7, 0.8, 0.9, 1.0];
+
+/// Histogram for tracking the selection weight factors of the peers selected
+/// to service requests. This isn't labelled by peer (the selected peers are
+/// already tracked by the sent requests), so the number of series is bounded.
+pub static PEER_SELECTION_FACTORS: Lazy<HistogramVec> = Lazy::new(|| {
+    register_histogram_vec!(
+        "aptos_data_client_peer_selection_factors",
+        "Histogram of the weight factors of the peers selected to service requests",
+        &["factor", "network"],
+        PEER_SELECTION_FACTOR_BUCKETS.to_vec()
+    )
+    .unwrap()
+});
+
 /// Gauge for tracking the number of in-flight polls
 pub static IN_FLIGHT_POLLS: Lazy<IntGaugeVec> = Lazy::new(|| {
     register_int_gauge_vec!(
@@ -144,6 +162,24 @@ pub fn set_gauge(counter: &Lazy<IntGaugeVec>, label: String, value: u64) {
     counter.with_label_values(&[&label]).set(value as i64);
 }
 
+/// Updates the selection metrics for a peer selected to service a request
+pub fn update_peer_selection_metrics(
+    peer_network_id: PeerNetworkId,
+    selection_weight: &PeerSelectionWeight,
+) {
+    let network = peer_network_id.network_id();
+    for (factor, value) in [
+        ("score", selection_weight.score_factor),
+        ("latency", selection_weight.latency_factor),
+        ("depth", selection_weight.depth_factor),
+        ("total", selection_weight.weight()),
+    ] {
+        PEER_SELECTION_FACTORS
+            .with_label_values(&[factor, network.as_str()])
+            .observe(value);
+    }
+}
+
 /// Starts the timer for the provided histogram and label values.
 pub fn start_request_timer(
     histogram: &Lazy<HistogramVec>,
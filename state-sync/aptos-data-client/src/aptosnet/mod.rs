@@ -5,8 +5,8 @@ use crate::{
     aptosnet::{
         logging::{LogEntry, LogEvent, LogSchema},
         metrics::{
-            increment_request_counter, set_gauge, start_request_timer, DataType, PRIORITIZED_PEER,
-            REGULAR_PEER,
+            increment_request_counter, set_gauge, start_request_timer,
+            update_peer_selection_metrics, DataType, PRIORITIZED_PEER, REGULAR_PEER,
         },
         state::{ErrorType, PeerStates},
     },
@@ -92,6 +92,8 @@ pub struct AptosNetDataClient {
     global_summary_cache: Arc<RwLock<GlobalDataSummary>>,
     /// Used for generating the next request/response id.
     response_id_generator: Arc<U64IdGenerator>,
+    /// The service used to measure response times.
+    time_service: TimeService,
 }
 
 impl AptosNetDataClient {
@@ -108,11 +110,13 @@ impl AptosNetDataClient {
             network_client: network_client.clone(),
             peer_states: Arc::new(RwLock::new(PeerStates::new(
                 base_config,
+                data_client_config,
                 storage_service_config,
                 network_client.get_peer_metadata_storage(),
             ))),
             global_summary_cache: Arc::new(RwLock::new(GlobalDataSummary::empty())),
             response_id_generator: Arc::new(U64IdGenerator::new()),
+            time_service: time_service.clone(),
         };
         let poller = DataSummaryPoller::new(
             client.clone(),
@@ -155,10 +159,8 @@ impl AptosNetDataClient {
             self.identify_serviceable(regular_peers, request)
         };
 
-        // Randomly select a peer to handle the request
-        serviceable_peers
-            .choose(&mut rand::thread_rng())
-            .copied()
+        // Select a peer to handle the request, favoring the best peers
+        self.select_weighted_peer(serviceable_peers, request)
             .ok_or_else(|| {
                 Error::DataIsUnavailable(
                     format!("No connected peers are advertising that they can serve this data! Request: {:?}",request),
@@ -166,6 +168,46 @@ impl AptosNetDataClient {
             })
     }
 
+    /// Selects one of the given peers with a probability proportional to its
+    /// selection weight. This spreads the requests across the peers, but
+    /// sends most of them to the peers with the best scores, lowest latencies
+    /// and shortest distances from the validators.
+    fn select_weighted_peer(
+        &self,
+        peers: Vec<PeerNetworkId>,
+        request: &StorageServiceRequest,
+    ) -> Option<PeerNetworkId> {
+        let peers_and_weights: Vec<_> = {
+            let peer_states = self.peer_states.read();
+            peers
+                .into_iter()
+                .map(|peer| (peer, peer_states.get_peer_selection_weight(&peer)))
+                .collect()
+        };
+
+        // If all weights are zero, fall back to a uniformly random selection
+        let mut rng = rand::thread_rng();
+        let (peer, selection_weight) = peers_and_weights
+            .choose_weighted(&mut rng, |(_, selection_weight)| selection_weight.weight())
+            .ok()
+            .or_else(|| peers_and_weights.choose(&mut rng))?;
+
+        debug!(
+            (LogSchema::new(LogEntry::StorageServiceRequest)
+                .event(LogEvent::PeerSelected)
+                .request_type(request.get_label())
+                .peer(peer)
+                .message(&format!(
+                    "Selected peer out of {:?} with weight: {:?}",
+                    peers_and_weights.len(),
+                    selection_weight
+                )))
+        );
+        update_peer_selection_metrics(*peer, selection_weight);
+
+        Some(*peer)
+    }
+
     /// Identifies the peers in the given set of prospective peers
     /// that can service the specified request.
     fn identify_serviceable(
@@ -362,11 +404,13 @@ impl AptosNetDataClient {
 
         increment_request_counter(&metrics::SENT_REQUESTS, request.get_label(), peer);
 
+        let start_time = self.time_service.now();
         let result = self
             .network_client
             .send_request(
                 peer,
                 request.clone(),
+                self.data_client_config.compression_algorithm,
                 Duration::from_millis(self.data_client_config.response_timeout_ms),
             )
             .await;
@@ -389,7 +433,11 @@ impl AptosNetDataClient {
                 // On the one hand, scoring dynamics are simpler when each request
                 // is successful or failed but not both; on the other hand, this
                 // feels simpler for the consumer.
-                self.peer_states.write().update_score_success(peer);
+                let response_time = self.time_service.now().duration_since(start_time);
+                let mut peer_states = self.peer_states.write();
+                peer_states.update_score_success(peer);
+                peer_states.record_response_time(peer, response_time);
+                drop(peer_states);
 
                 // Package up all of the context needed to fully report an error
                 // with this RPC.
@@ -412,12 +460,22 @@ impl AptosNetDataClient {
                 let client_error = match error {
                     storage_service_client::Error::RpcError(err) => match err {
                         RpcError::NotConnected(_) => Error::DataIsUnavailable(err.to_string()),
-                        RpcError::TimedOut => Error::TimeoutWaitingForResponse(err.to_string()),
+                        RpcError::TimedOut => {
+                            // Count the timeout as a (very slow) response time
+                            let response_time = self.time_service.now().duration_since(start_time);
+                            self.peer_states
+                                .write()
+                                .record_response_time(peer, response_time);
+                            Error::TimeoutWaitingForResponse(err.to_string())
+                        }
                         _ => Error::UnexpectedErrorEncountered(err.to_string()),
                     },
                     storage_service_client::Error::StorageServiceError(err) => {
                         Error::UnexpectedErrorEncountered(err.to_string())
                     }
+                    storage_service_client::Error::InvalidResponse(err) => {
+                        Error::InvalidResponse(err.to_string())
+                    }
                 };
 
                 error!(
@@ -6,7 +6,7 @@ use crate::{
     AdvertisedData, GlobalDataSummary, OptimalChunkSizes, ResponseError,
 };
 use aptos_config::{
-    config::{BaseConfig, StorageServiceConfig},
+    config::{AptosDataClientConfig, BaseConfig, PeerRole, StorageServiceConfig},
     network_id::{NetworkId, PeerNetworkId},
 };
 use aptos_logger::prelude::*;
@@ -14,9 +14,10 @@ use itertools::Itertools;
 use netcore::transport::ConnectionOrigin;
 use network::application::storage::PeerMetadataStorage;
 use std::{
-    cmp::min,
-    collections::{HashMap, HashSet},
+    cmp::{max, min},
+    collections::{HashMap, HashSet, VecDeque},
     sync::Arc,
+    time::Duration,
 };
 use storage_service_types::requests::StorageServiceRequest;
 use storage_service_types::responses::StorageServerSummary;
@@ -34,6 +35,14 @@ const MALICIOUS_MULTIPLIER: f64 = 0.8;
 /// Ignore a peer when their score dips below this threshold.
 const IGNORE_PEER_THRESHOLD: f64 = 25.0;
 
+/// The number of most recent response times to keep for each peer.
+const MAX_NUM_RESPONSE_TIMES: usize = 20;
+/// The latency (in milliseconds) at which a peer's latency factor is halved.
+/// Also used for peers whose latency we haven't measured yet.
+const REFERENCE_LATENCY_MS: f64 = 100.0;
+/// The distance from the validators assumed for peers that haven't reported it.
+const UNKNOWN_DEPTH_FROM_VALIDATORS: u64 = 3;
+
 pub(crate) enum ErrorType {
     /// A response or error that's not actively malicious but also doesn't help
     /// us make progress, e.g., timeouts, remote errors, invalid data, etc...
@@ -61,6 +70,8 @@ struct PeerState {
     storage_summary: Option<StorageServerSummary>,
     /// For now, a simplified port of the original state-sync v1 scoring system.
     score: f64,
+    /// The response times of the most recent requests sent to this peer.
+    response_times: VecDeque<Duration>,
 }
 
 impl Default for PeerState {
@@ -68,6 +79,7 @@ impl Default for PeerState {
         Self {
             storage_summary: None,
             score: STARTING_SCORE,
+            response_times: VecDeque::new(),
         }
     }
 }
@@ -100,6 +112,47 @@ impl PeerState {
         };
         self.score = f64::max(self.score * multiplier, MIN_SCORE);
     }
+
+    /// Records the response time of a request, evicting the oldest if required
+    fn record_response_time(&mut self, response_time: Duration) {
+        if self.response_times.len() >= MAX_NUM_RESPONSE_TIMES {
+            self.response_times.pop_front();
+        }
+        self.response_times.push_back(response_time);
+    }
+
+    /// Returns the given percentile (0 to 100) of the recent response times,
+    /// or `None` if we haven't received any responses yet.
+    fn response_time_percentile(&self, percentile: f64) -> Option<Duration> {
+        if self.response_times.is_empty() {
+            return None;
+        }
+        let mut response_times: Vec<_> = self.response_times.iter().copied().collect();
+        response_times.sort_unstable();
+        let rank = (percentile * response_times.len() as f64 / 100.0).ceil() as usize;
+        let index = min(rank.saturating_sub(1), response_times.len() - 1);
+        Some(response_times[index])
+    }
+}
+
+/// The factors that make up a peer's weight when selecting peers for requests.
+/// Each factor is in [0, 1], and peers are picked with a probability
+/// proportional to the product of their factors.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct PeerSelectionWeight {
+    /// The peer's score, relative to the max score.
+    pub score_factor: f64,
+    /// Decreases with the peer's ping latency and response times.
+    pub latency_factor: f64,
+    /// Decreases with the peer's distance from the validators.
+    pub depth_factor: f64,
+}
+
+impl PeerSelectionWeight {
+    /// Returns the weight of the peer for weighted selection
+    pub fn weight(&self) -> f64 {
+        self.score_factor * self.latency_factor * self.depth_factor
+    }
 }
 
 /// Contains all of the unbanned peers' most recent [`StorageServerSummary`] data
@@ -108,6 +161,7 @@ impl PeerState {
 #[derive(Debug)]
 pub(crate) struct PeerStates {
     base_config: BaseConfig,
+    data_client_config: AptosDataClientConfig,
     storage_service_config: StorageServiceConfig,
     peer_to_state: HashMap<PeerNetworkId, PeerState>,
     in_flight_priority_polls: HashSet<PeerNetworkId>, // The priority peers with in-flight polls
@@ -118,11 +172,13 @@ pub(crate) struct PeerStates {
 impl PeerStates {
     pub fn new(
         base_config: BaseConfig,
+        data_client_config: AptosDataClientConfig,
         storage_service_config: StorageServiceConfig,
         peer_metadata_storage: Arc<PeerMetadataStorage>,
     ) -> Self {
         Self {
             base_config,
+            data_client_config,
             storage_service_config,
             peer_to_state: HashMap::new(),
             in_flight_priority_polls: HashSet::new(),
@@ -193,6 +249,58 @@ impl PeerStates {
         }
     }
 
+    /// Records the time it took the peer to respond to a request
+    pub fn record_response_time(&mut self, peer: PeerNetworkId, response_time: Duration) {
+        self.peer_to_state
+            .entry(peer)
+            .or_default()
+            .record_response_time(response_time);
+    }
+
+    /// Returns the weight of the peer when selecting peers for requests. This
+    /// combines the peer's score, its measured latency and its distance from
+    /// the validators (as reported by the peer monitoring service).
+    pub fn get_peer_selection_weight(&self, peer: &PeerNetworkId) -> PeerSelectionWeight {
+        let peer_state = self.peer_to_state.get(peer);
+        let score = peer_state.map_or(STARTING_SCORE, |peer_state| peer_state.score);
+        let peer_info = self.peer_metadata_storage.read(*peer);
+
+        // Use the worse of the ping latency and the recent response times. The
+        // ping latency is known before we send any requests, but the response
+        // times also capture how quickly the peer serves data.
+        let ping_latency = peer_info
+            .as_ref()
+            .and_then(|peer_info| peer_info.peer_monitoring_metadata.average_ping_latency);
+        let response_time = peer_state.and_then(|peer_state| {
+            peer_state.response_time_percentile(self.data_client_config.response_time_percentile)
+        });
+        let latency_ms = match (ping_latency, response_time) {
+            (Some(ping_latency), Some(response_time)) => Some(max(ping_latency, response_time)),
+            (ping_latency, response_time) => ping_latency.or(response_time),
+        }
+        .map_or(REFERENCE_LATENCY_MS, |latency| {
+            latency.as_secs_f64() * 1000.0
+        });
+
+        // Validators are always at depth 0, even if they haven't reported it yet
+        let depth_from_validators = match peer_info {
+            Some(peer_info) if peer_info.active_connection.role == PeerRole::Validator => 0,
+            Some(peer_info) => peer_info
+                .peer_monitoring_metadata
+                .depth_from_validators
+                .unwrap_or(UNKNOWN_DEPTH_FROM_VALIDATORS),
+            None => UNKNOWN_DEPTH_FROM_VALIDATORS,
+        };
+
+        PeerSelectionWeight {
+            score_factor: score / MAX_SCORE,
+            latency_factor: (REFERENCE_LATENCY_MS / (REFERENCE_LATENCY_MS + latency_ms))
+                .powf(self.data_client_config.latency_selection_weight),
+            depth_factor: (1.0 / (1.0 + depth_from_validators as f64))
+                .powf(self.data_client_config.depth_selection_weight),
+        }
+    }
+
     /// Returns the number of in-flight priority polls
     pub fn num_in_flight_priority_polls(&self) -> u64 {
         self.in_flight_priority_polls.len() as u64
@@ -2,7 +2,10 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use super::{AptosDataClient, AptosNetDataClient, DataSummaryPoller, Error};
-use crate::aptosnet::{poll_peer, state::calculate_optimal_chunk_sizes};
+use crate::aptosnet::{
+    poll_peer,
+    state::{calculate_optimal_chunk_sizes, ErrorType, PeerSelectionWeight},
+};
 use aptos_config::{
     config::{AptosDataClientConfig, BaseConfig, RoleType, StorageServiceConfig},
     network_id::{NetworkId, PeerNetworkId},
@@ -21,7 +24,11 @@ use futures::StreamExt;
 use maplit::hashmap;
 use netcore::transport::ConnectionOrigin;
 use network::{
-    application::{interface::MultiNetworkSender, storage::PeerMetadataStorage, types::PeerState},
+    application::{
+        interface::MultiNetworkSender,
+        storage::PeerMetadataStorage,
+        types::{PeerMonitoringMetadata, PeerState},
+    },
     peer_manager::{ConnectionRequestSender, PeerManagerRequest, PeerManagerRequestSender},
     protocols::{network::NewNetworkSender, wire::handshake::v1::ProtocolId},
     transport::ConnectionMetadata,
@@ -177,6 +184,27 @@ impl MockNetwork {
             .unwrap();
     }
 
+    /// Updates the monitoring metadata of the given peer
+    fn update_peer_monitoring_metadata(
+        &mut self,
+        peer: PeerNetworkId,
+        average_ping_latency: Option<Duration>,
+        depth_from_validators: Option<u64>,
+    ) {
+        self.peer_infos
+            .write(peer, |entry| match entry {
+                Entry::Vacant(..) => panic!("Peer must exist!"),
+                Entry::Occupied(inner) => {
+                    inner.get_mut().peer_monitoring_metadata = PeerMonitoringMetadata {
+                        average_ping_latency,
+                        depth_from_validators,
+                    };
+                    Ok(())
+                }
+            })
+            .unwrap();
+    }
+
     /// Get the next request sent from the client.
     async fn next_request(&mut self) -> Option<NetworkRequest> {
         match self.peer_mgr_reqs_rx.next().await {
@@ -186,11 +214,14 @@ impl MockNetwork {
                 let res_tx = network_request.res_tx;
 
                 let message: StorageServiceMessage = bcs::from_bytes(data.as_ref()).unwrap();
-                let request = match message {
-                    StorageServiceMessage::Request(request) => request,
+                let (request, compression) = match message {
+                    StorageServiceMessage::Request(request) => (request, None),
+                    StorageServiceMessage::CompressedRequest(request, algorithm) => {
+                        (request, Some(algorithm))
+                    }
                     _ => panic!("unexpected: {:?}", message),
                 };
-                let response_sender = ResponseSender::new(res_tx);
+                let response_sender = ResponseSender::new(res_tx, compression);
 
                 Some((peer_id, protocol, request, response_sender))
             }
@@ -1083,6 +1114,132 @@ async fn bad_peer_is_eventually_added_back() {
         .contains(&CompleteDataRange::new(0, 200).unwrap()));
 }
 
+#[tokio::test]
+async fn peer_selection_weights() {
+    ::aptos_logger::Logger::init_for_testing();
+    let (mut mock_network, _, client, _) = MockNetwork::new(None, None, None);
+
+    // Add two peers that haven't been measured yet and verify they have the same weight
+    let fast_peer = mock_network.add_peer(false);
+    let slow_peer = mock_network.add_peer(false);
+    let fast_peer_weight = get_peer_selection_weight(&client, fast_peer);
+    assert_eq!(
+        fast_peer_weight,
+        get_peer_selection_weight(&client, slow_peer)
+    );
+    assert!(fast_peer_weight.weight() > 0.0);
+
+    // Record response times and verify the percentile is used as the latency
+    for response_time_ms in 1..=10 {
+        client
+            .peer_states
+            .write()
+            .record_response_time(fast_peer, Duration::from_millis(response_time_ms * 10));
+    }
+    let latency_factor = get_peer_selection_weight(&client, fast_peer).latency_factor;
+    assert!((latency_factor - 100.0 / (100.0 + 90.0)).abs() < 1e-9);
+
+    // Verify that the ping latency is used if it's worse than the response times
+    mock_network.update_peer_monitoring_metadata(
+        fast_peer,
+        Some(Duration::from_millis(300)),
+        Some(1),
+    );
+    let fast_peer_weight = get_peer_selection_weight(&client, fast_peer);
+    assert!((fast_peer_weight.latency_factor - 100.0 / (100.0 + 300.0)).abs() < 1e-9);
+    assert!((fast_peer_weight.depth_factor - 0.5).abs() < 1e-9);
+
+    // Make the other peer slower and further away, and verify it has a lower weight
+    mock_network.update_peer_monitoring_metadata(
+        slow_peer,
+        Some(Duration::from_millis(3_000)),
+        Some(4),
+    );
+    let slow_peer_weight = get_peer_selection_weight(&client, slow_peer);
+    assert!(slow_peer_weight.latency_factor < fast_peer_weight.latency_factor);
+    assert!(slow_peer_weight.depth_factor < fast_peer_weight.depth_factor);
+    assert!(slow_peer_weight.weight() < fast_peer_weight.weight());
+
+    // Verify that the scores are also taken into account
+    client
+        .peer_states
+        .write()
+        .update_score_error(fast_peer, ErrorType::Malicious);
+    let weight_after_error = get_peer_selection_weight(&client, fast_peer);
+    assert!(weight_after_error.score_factor < fast_peer_weight.score_factor);
+    assert!(weight_after_error.weight() < fast_peer_weight.weight());
+}
+
+#[tokio::test]
+async fn peer_selection_weights_disabled() {
+    ::aptos_logger::Logger::init_for_testing();
+
+    // Create a data client that ignores latency and depth
+    let data_client_config = AptosDataClientConfig {
+        depth_selection_weight: 0.0,
+        latency_selection_weight: 0.0,
+        ..Default::default()
+    };
+    let (mut mock_network, _, client, _) = MockNetwork::new(None, Some(data_client_config), None);
+
+    // Add a slow and distant peer and verify only its score matters
+    let peer = mock_network.add_peer(false);
+    mock_network.update_peer_monitoring_metadata(peer, Some(Duration::from_secs(10)), Some(10));
+    let peer_weight = get_peer_selection_weight(&client, peer);
+    assert_eq!(peer_weight.latency_factor, 1.0);
+    assert_eq!(peer_weight.depth_factor, 1.0);
+    assert_eq!(peer_weight.weight(), peer_weight.score_factor);
+}
+
+#[tokio::test]
+async fn weighted_peer_request_selection() {
+    ::aptos_logger::Logger::init_for_testing();
+    let (mut mock_network, _, client, _) = MockNetwork::new(None, None, None);
+
+    // Add a fast and nearby peer, and a slow and distant peer
+    let fast_peer = mock_network.add_peer(false);
+    mock_network.update_peer_monitoring_metadata(
+        fast_peer,
+        Some(Duration::from_millis(10)),
+        Some(1),
+    );
+    let slow_peer = mock_network.add_peer(false);
+    mock_network.update_peer_monitoring_metadata(
+        slow_peer,
+        Some(Duration::from_millis(1_000)),
+        Some(4),
+    );
+
+    // Select peers many times and verify both are used, but mostly the fast one
+    let storage_request = StorageServiceRequest::GetStorageServerSummary;
+    let mut num_fast_peer_selections = 0;
+    let num_selections = 1_000;
+    for _ in 0..num_selections {
+        let peer = client.choose_peer_for_request(&storage_request).unwrap();
+        if peer == fast_peer {
+            num_fast_peer_selections += 1;
+        } else {
+            assert_eq!(peer, slow_peer);
+        }
+    }
+    assert!(num_fast_peer_selections > num_selections * 9 / 10);
+    assert!(num_fast_peer_selections < num_selections);
+
+    // Add a priority peer and verify it's always selected, however slow
+    let priority_peer = mock_network.add_peer(true);
+    mock_network.update_peer_monitoring_metadata(
+        priority_peer,
+        Some(Duration::from_millis(5_000)),
+        None,
+    );
+    for _ in 0..10 {
+        assert_eq!(
+            client.choose_peer_for_request(&storage_request),
+            Ok(priority_peer)
+        );
+    }
+}
+
 #[tokio::test]
 async fn optimal_chunk_size_calculations() {
     // Create a test storage service config
@@ -1166,6 +1323,14 @@ fn fetch_peer_to_poll(
     result
 }
 
+/// Returns the selection weight of the given peer
+fn get_peer_selection_weight(
+    client: &AptosNetDataClient,
+    peer: PeerNetworkId,
+) -> PeerSelectionWeight {
+    client.peer_states.read().get_peer_selection_weight(&peer)
+}
+
 /// Fetches the number of in flight requests for peers depending on priority
 fn get_num_in_flight_polls(client: AptosNetDataClient, is_priority_peer: bool) -> u64 {
     if is_priority_peer {
@@ -38,6 +38,7 @@ mod tests;
 // will be retrieved using FIFO ordering.
 const EVENT_NOTIFICATION_CHANNEL_SIZE: usize = 100;
 const RECONFIG_NOTIFICATION_CHANNEL_SIZE: usize = 1;
+const COMMIT_NOTIFICATION_CHANNEL_SIZE: usize = 1;
 
 #[derive(Clone, Debug, Deserialize, Error, PartialEq, Serialize)]
 pub enum Error {
@@ -80,6 +81,9 @@ pub struct EventSubscriptionService {
     // Reconfig subscription registry
     reconfig_subscriptions: HashMap<SubscriptionId, ReconfigSubscription>,
 
+    // Commit subscription registry
+    commit_subscriptions: HashMap<SubscriptionId, CommitSubscription>,
+
     // Database to fetch on-chain configuration data
     storage: Arc<RwLock<DbReaderWriter>>,
 
@@ -96,6 +100,7 @@ impl EventSubscriptionService {
             event_key_subscriptions: HashMap::new(),
             subscription_id_to_event_subscription: HashMap::new(),
             reconfig_subscriptions: HashMap::new(),
+            commit_subscriptions: HashMap::new(),
             config_registry: config_registry.to_vec(),
             storage,
             subscription_id_generator: U64IdGenerator::new(),
@@ -185,6 +190,36 @@ impl EventSubscriptionService {
         })
     }
 
+    /// Returns a CommitNotificationListener that is sent the latest committed
+    /// version every time new transactions are committed. Notifications don't
+    /// carry any events, and only the latest one is kept if the subscriber
+    /// falls behind.
+    pub fn subscribe_to_commits(&mut self) -> Result<CommitNotificationListener, Error> {
+        let (notification_sender, notification_receiver) =
+            aptos_channel::new(QueueStyle::KLAST, COMMIT_NOTIFICATION_CHANNEL_SIZE, None);
+
+        // Create a new commit subscription
+        let subscription_id = self.get_new_subscription_id();
+        let commit_subscription = CommitSubscription {
+            notification_sender,
+        };
+
+        // Store the new subscription
+        if let Some(old_subscription) = self
+            .commit_subscriptions
+            .insert(subscription_id, commit_subscription)
+        {
+            panic!(
+                "Duplicate commit subscription found! This should not occur! ID: {}, subscription: {:?}",
+                subscription_id, old_subscription
+            );
+        }
+
+        Ok(CommitNotificationListener {
+            notification_receiver,
+        })
+    }
+
     fn get_new_subscription_id(&mut self) -> u64 {
         self.subscription_id_generator.next()
     }
@@ -242,6 +277,15 @@ impl EventSubscriptionService {
         Ok(reconfig_event_found)
     }
 
+    /// This notifies all the commit subscribers of the new version.
+    fn notify_commit_subscribers(&mut self, version: Version) -> Result<(), Error> {
+        for (_, commit_subscription) in self.commit_subscriptions.iter_mut() {
+            commit_subscription.notify_subscriber_of_commit(version)?;
+        }
+
+        Ok(())
+    }
+
     /// This notifies all the reconfiguration subscribers of the on-chain
     /// configurations at the specified version.
     fn notify_reconfiguration_subscribers(&mut self, version: Version) -> Result<(), Error> {
@@ -318,6 +362,9 @@ impl EventSubscriptionService {
 
 impl EventNotificationSender for EventSubscriptionService {
     fn notify_events(&mut self, version: Version, events: Vec<ContractEvent>) -> Result<(), Error> {
+        // Commit subscribers are notified even if nothing was emitted
+        self.notify_commit_subscribers(version)?;
+
         if events.is_empty() {
             return Ok(()); // No events!
         }
@@ -391,6 +438,21 @@ impl ReconfigSubscription {
     }
 }
 
+/// A single commit subscription, holding the channel to send the
+/// corresponding notifications.
+#[derive(Debug)]
+struct CommitSubscription {
+    pub notification_sender: channel::aptos_channel::Sender<(), CommitNotification>,
+}
+
+impl CommitSubscription {
+    fn notify_subscriber_of_commit(&mut self, version: Version) -> Result<(), Error> {
+        self.notification_sender
+            .push((), CommitNotification { version })
+            .map_err(|error| Error::UnexpectedErrorEncountered(format!("{:?}", error)))
+    }
+}
+
 /// A notification for events.
 #[derive(Debug)]
 pub struct EventNotification {
@@ -405,12 +467,21 @@ pub struct ReconfigNotification {
     pub on_chain_configs: OnChainConfigPayload,
 }
 
+/// A notification for new commits.
+#[derive(Debug)]
+pub struct CommitNotification {
+    pub version: Version,
+}
+
 /// A subscription listener for on-chain events.
 pub type EventNotificationListener = NotificationListener<EventNotification>;
 
 /// A subscription listener for reconfigurations.
 pub type ReconfigNotificationListener = NotificationListener<ReconfigNotification>;
 
+/// A subscription listener for new commits.
+pub type CommitNotificationListener = NotificationListener<CommitNotification>;
+
 /// The component responsible for listening to subscription notifications.
 #[derive(Debug)]
 pub struct NotificationListener<T> {
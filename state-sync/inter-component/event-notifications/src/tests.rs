@@ -4,8 +4,8 @@
 #![forbid(unsafe_code)]
 
 use crate::{
-    Error, EventNotificationListener, EventNotificationSender, EventSubscriptionService,
-    ReconfigNotificationListener,
+    CommitNotificationListener, Error, EventNotificationListener, EventNotificationSender,
+    EventSubscriptionService, ReconfigNotificationListener,
 };
 use aptos_infallible::RwLock;
 use aptos_types::{
@@ -354,6 +354,34 @@ fn test_event_subscribers() {
     verify_no_event_notifications(vec![&mut listener_1]);
 }
 
+#[test]
+fn test_commit_subscribers() {
+    // Create subscription service and mock database
+    let mut event_service = create_event_subscription_service();
+
+    // Subscribe to commits
+    let mut listener = event_service.subscribe_to_commits().unwrap();
+
+    // Notify the subscription service of new events and verify the commit is notified
+    notify_events(
+        &mut event_service,
+        10,
+        vec![create_test_event(create_random_event_key())],
+    );
+    verify_commit_notification_received(&mut listener, 10);
+
+    // Commits without any events are notified too
+    notify_events(&mut event_service, 20, vec![]);
+    verify_commit_notification_received(&mut listener, 20);
+
+    // Only the latest commit is kept if the subscriber falls behind
+    for version in 30..40 {
+        notify_events(&mut event_service, version, vec![]);
+    }
+    verify_commit_notification_received(&mut listener, 39);
+    assert!(listener.select_next_some().now_or_never().is_none());
+}
+
 #[test]
 fn test_no_events_no_subscribers() {
     // Create subscription service and mock database
@@ -530,6 +558,14 @@ fn notify_events(
     assert_ok!(event_service.notify_events(version, events));
 }
 
+fn verify_commit_notification_received(
+    listener: &mut CommitNotificationListener,
+    version: Version,
+) {
+    let notification = listener.select_next_some().now_or_never().unwrap();
+    assert_eq!(notification.version, version);
+}
+
 fn create_test_event(event_key: EventKey) -> ContractEvent {
     ContractEvent::new(event_key, 0, TypeTag::Bool, bcs::to_bytes(&0).unwrap())
 }
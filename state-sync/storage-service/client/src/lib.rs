@@ -3,6 +3,7 @@
 
 #![forbid(unsafe_code)]
 
+use aptos_compression::CompressionAlgorithm;
 use aptos_config::network_id::PeerNetworkId;
 use aptos_types::PeerId;
 use async_trait::async_trait;
@@ -20,7 +21,7 @@ use network::{
 };
 use std::{sync::Arc, time::Duration};
 use storage_service_types::requests::StorageServiceRequest;
-use storage_service_types::responses::StorageServiceResponse;
+use storage_service_types::responses::{self, StorageServiceResponse};
 use storage_service_types::{StorageServiceError, StorageServiceMessage};
 use thiserror::Error;
 
@@ -31,6 +32,9 @@ pub enum Error {
 
     #[error("Error from remote storage service: {0}")]
     StorageServiceError(#[from] StorageServiceError),
+
+    #[error("Invalid response from remote storage service: {0}")]
+    InvalidResponse(#[from] responses::Error),
 }
 
 // TODO(philiphayes): need to expose access to somewhere to store per-peer data?
@@ -54,20 +58,35 @@ impl StorageServiceClient {
         }
     }
 
+    /// Sends the request to the recipient. If a compression algorithm is
+    /// given, the recipient is asked to compress the response with it.
     pub async fn send_request(
         &self,
         recipient: PeerNetworkId,
         request: StorageServiceRequest,
+        compression: Option<CompressionAlgorithm>,
         timeout: Duration,
     ) -> Result<StorageServiceResponse, Error> {
+        let message = match compression {
+            Some(algorithm) => StorageServiceMessage::CompressedRequest(request, algorithm),
+            None => StorageServiceMessage::Request(request),
+        };
         let message = self
             .network_sender
-            .send_rpc(recipient, StorageServiceMessage::Request(request), timeout)
+            .send_rpc(recipient, message, timeout)
             .await?;
         match message {
             StorageServiceMessage::Response(Ok(response)) => Ok(response),
             StorageServiceMessage::Response(Err(err)) => Err(Error::StorageServiceError(err)),
-            StorageServiceMessage::Request(_) => Err(Error::RpcError(RpcError::InvalidRpcResponse)),
+            StorageServiceMessage::CompressedResponse(Ok(compressed_response)) => {
+                Ok(StorageServiceResponse::decompress(&compressed_response)?)
+            }
+            StorageServiceMessage::CompressedResponse(Err(err)) => {
+                Err(Error::StorageServiceError(err))
+            }
+            StorageServiceMessage::Request(_) | StorageServiceMessage::CompressedRequest(..) => {
+                Err(Error::RpcError(RpcError::InvalidRpcResponse))
+            }
         }
     }
 
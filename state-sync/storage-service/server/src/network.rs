@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::metrics;
+use aptos_compression::CompressionAlgorithm;
 use aptos_config::config::StorageServiceConfig;
 use aptos_types::PeerId;
 use bytes::Bytes;
@@ -22,7 +23,7 @@ use std::{
 };
 use storage_service_types::requests::StorageServiceRequest;
 use storage_service_types::responses::StorageServiceResponse;
-use storage_service_types::{Result, StorageServiceMessage};
+use storage_service_types::{Result, StorageServiceError, StorageServiceMessage};
 
 pub fn network_endpoint_config(storage_config: StorageServiceConfig) -> AppConfig {
     let max_network_channel_size = storage_config.max_network_channel_size as usize;
@@ -64,7 +65,18 @@ impl StorageServiceNetworkEvents {
                 protocol_id,
                 response_tx,
             ) => {
-                let response_tx = ResponseSender::new(response_tx);
+                let response_tx = ResponseSender::new(response_tx, None);
+                Some((peer_id, protocol_id, request, response_tx))
+            }
+            Event::RpcRequest(
+                peer_id,
+                StorageServiceMessage::CompressedRequest(request, algorithm),
+                protocol_id,
+                response_tx,
+            ) => {
+                // The client picks the algorithm, but we bound the CPU spent on it
+                let response_tx =
+                    ResponseSender::new(response_tx, Some(algorithm.limit_for_peers()));
                 Some((peer_id, protocol_id, request, response_tx))
             }
             // We don't use DirectSend and don't care about connection events.
@@ -85,15 +97,31 @@ impl Stream for StorageServiceNetworkEvents {
 /// Provides a more strongly typed interface around the raw RPC response channel.
 pub struct ResponseSender {
     response_tx: oneshot::Sender<Result<Bytes, RpcError>>,
+    compression: Option<CompressionAlgorithm>, // The algorithm requested by the client (if any)
 }
 
 impl ResponseSender {
-    pub fn new(response_tx: oneshot::Sender<Result<Bytes, RpcError>>) -> Self {
-        Self { response_tx }
+    pub fn new(
+        response_tx: oneshot::Sender<Result<Bytes, RpcError>>,
+        compression: Option<CompressionAlgorithm>,
+    ) -> Self {
+        Self {
+            response_tx,
+            compression,
+        }
     }
 
     pub fn send(self, response: Result<StorageServiceResponse>) {
-        let msg = StorageServiceMessage::Response(response);
+        let msg = match self.compression {
+            Some(algorithm) => {
+                StorageServiceMessage::CompressedResponse(response.and_then(|response| {
+                    response
+                        .compress(algorithm)
+                        .map_err(|error| StorageServiceError::InternalError(error.to_string()))
+                }))
+            }
+            None => StorageServiceMessage::Response(response),
+        };
         let result = bcs::to_bytes(&msg)
             .map(Bytes::from)
             .map_err(RpcError::BcsError);
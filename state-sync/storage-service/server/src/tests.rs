@@ -5,6 +5,7 @@
 
 use crate::{network::StorageServiceNetworkEvents, StorageReader, StorageServiceServer};
 use anyhow::{format_err, Result};
+use aptos_compression::CompressionAlgorithm;
 use aptos_config::config::StorageServiceConfig;
 use aptos_crypto::{ed25519::Ed25519PrivateKey, HashValue, PrivateKey, SigningKey, Uniform};
 use aptos_logger::Level;
@@ -837,6 +838,74 @@ async fn test_get_transactions_with_proof() {
     }
 }
 
+#[tokio::test]
+async fn test_get_transactions_with_proof_compressed() {
+    for algorithm in [
+        CompressionAlgorithm::Lz4 { acceleration: 1 },
+        CompressionAlgorithm::Zstd { level: 3 },
+    ] {
+        // Create test data
+        let start_version = 0;
+        let end_version = 99;
+        let proof_version = end_version;
+        let transaction_list_with_proof =
+            create_transaction_list_with_proof(start_version, end_version, proof_version, true);
+
+        // Create the mock db reader
+        let mut db_reader = create_mock_db_reader();
+        let transaction_list_with_proof_clone = transaction_list_with_proof.clone();
+        db_reader
+            .expect_get_transactions()
+            .times(1)
+            .with(
+                eq(start_version),
+                eq(end_version - start_version + 1),
+                eq(proof_version),
+                eq(true),
+            )
+            .return_once(move |_, _, _, _| Ok(transaction_list_with_proof_clone));
+
+        // Create the storage client and server
+        let (mut mock_client, service, _) = MockClient::new(Some(db_reader));
+        tokio::spawn(service.start());
+
+        // Process a request that asks for a compressed response
+        let request =
+            StorageServiceRequest::GetTransactionsWithProof(TransactionsWithProofRequest {
+                proof_version,
+                start_version,
+                end_version,
+                include_events: true,
+            });
+        let response = mock_client
+            .process_compressed_request(request, algorithm)
+            .await
+            .unwrap();
+
+        // Verify the response is correct
+        match response {
+            StorageServiceResponse::TransactionsWithProof(transactions_with_proof) => {
+                assert_eq!(transactions_with_proof, transaction_list_with_proof)
+            }
+            _ => panic!("Expected transactions with proof but got: {:?}", response),
+        };
+
+        // Verify errors are still returned for invalid compressed requests
+        let request =
+            StorageServiceRequest::GetTransactionsWithProof(TransactionsWithProofRequest {
+                proof_version,
+                start_version: end_version,
+                end_version: start_version,
+                include_events: true,
+            });
+        let response = mock_client
+            .process_compressed_request(request, algorithm)
+            .await
+            .unwrap_err();
+        assert_matches!(response, StorageServiceError::InvalidRequest(_));
+    }
+}
+
 #[tokio::test]
 async fn test_get_transactions_with_proof_invalid() {
     // Create the storage client and server
@@ -1060,18 +1129,39 @@ impl MockClient {
         self.wait_for_response(receiver).await
     }
 
+    /// Send the given storage request (asking for a compressed response)
+    /// and wait for a response
+    async fn process_compressed_request(
+        &mut self,
+        request: StorageServiceRequest,
+        algorithm: CompressionAlgorithm,
+    ) -> Result<StorageServiceResponse, StorageServiceError> {
+        let receiver = self
+            .send_message(StorageServiceMessage::CompressedRequest(request, algorithm))
+            .await;
+        self.wait_for_response(receiver).await
+    }
+
     /// Send the specified storage request and return the receiver on which to
     /// expect a result.
     async fn send_request(
         &mut self,
         request: StorageServiceRequest,
+    ) -> Receiver<Result<bytes::Bytes, network::protocols::network::RpcError>> {
+        self.send_message(StorageServiceMessage::Request(request))
+            .await
+    }
+
+    /// Send the specified storage message and return the receiver on which to
+    /// expect a result.
+    async fn send_message(
+        &mut self,
+        message: StorageServiceMessage,
     ) -> Receiver<Result<bytes::Bytes, network::protocols::network::RpcError>> {
         // Create the inbound rpc request
         let peer_id = PeerId::ZERO;
         let protocol_id = ProtocolId::StorageServiceRpc;
-        let data = protocol_id
-            .to_bytes(&StorageServiceMessage::Request(request))
-            .unwrap();
+        let data = protocol_id.to_bytes(&message).unwrap();
         let (res_tx, res_rx) = oneshot::channel();
         let inbound_rpc = InboundRpcRequest {
             protocol_id,
@@ -1101,6 +1191,9 @@ impl MockClient {
                 .unwrap();
             match response {
                 StorageServiceMessage::Response(response) => response,
+                StorageServiceMessage::CompressedResponse(response) => {
+                    response.map(|data| StorageServiceResponse::decompress(&data).unwrap())
+                }
                 _ => panic!("Unexpected response message: {:?}", response),
             }
         } else {
@@ -3,6 +3,7 @@
 
 #![forbid(unsafe_code)]
 
+use aptos_compression::{CompressedData, CompressionAlgorithm};
 use requests::StorageServiceRequest;
 use responses::StorageServiceResponse;
 use serde::{Deserialize, Serialize};
@@ -39,4 +40,11 @@ pub enum StorageServiceMessage {
     /// A response from the storage service. If there was an error while handling
     /// the request, the service will return an [`StorageServiceError`] error.
     Response(Result<StorageServiceResponse>),
+    /// A request to the storage service, asking for the response to be
+    /// compressed using the given algorithm.
+    CompressedRequest(StorageServiceRequest, CompressionAlgorithm),
+    /// A response to a compressed request, holding the BCS encoded
+    /// [`StorageServiceResponse`] compressed with the requested algorithm
+    /// (see [`StorageServiceResponse::decompress`]).
+    CompressedResponse(Result<CompressedData>),
 }
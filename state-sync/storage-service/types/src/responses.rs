@@ -3,7 +3,8 @@
 
 use crate::responses::Error::UnexpectedResponseError;
 use crate::{Epoch, StorageServiceRequest};
-use aptos_config::config::StorageServiceConfig;
+use aptos_compression::{CompressedData, CompressionAlgorithm};
+use aptos_config::config::{StorageServiceConfig, MAX_FRAME_SIZE};
 use aptos_types::epoch_change::EpochChangeProof;
 use aptos_types::ledger_info::LedgerInfoWithSignatures;
 use aptos_types::state_store::state_value::StateValueChunkWithProof;
@@ -18,6 +19,8 @@ use thiserror::Error;
 
 #[derive(Clone, Debug, Deserialize, Error, PartialEq, Serialize)]
 pub enum Error {
+    #[error("Compression error: {0}")]
+    CompressionError(String),
     #[error("Data range cannot be degenerate")]
     DegenerateRangeError,
     #[error("Unexpected response error: {0}")]
@@ -55,6 +58,23 @@ impl StorageServiceResponse {
             Self::TransactionsWithProof(_) => "transactions_with_proof",
         }
     }
+
+    /// Serializes and compresses the response using the given algorithm
+    pub fn compress(&self, algorithm: CompressionAlgorithm) -> Result<CompressedData, Error> {
+        let raw_data =
+            bcs::to_bytes(self).map_err(|error| Error::CompressionError(error.to_string()))?;
+        aptos_compression::compress(raw_data, algorithm)
+            .map_err(|error| Error::CompressionError(error.to_string()))
+    }
+
+    /// Decompresses and deserializes a response compressed with [`Self::compress`].
+    /// Responses can't decompress to more than the maximum network frame size,
+    /// as they would have been too large to send uncompressed.
+    pub fn decompress(compressed_data: &CompressedData) -> Result<Self, Error> {
+        let raw_data = aptos_compression::decompress(compressed_data, MAX_FRAME_SIZE)
+            .map_err(|error| Error::CompressionError(error.to_string()))?;
+        bcs::from_bytes(&raw_data).map_err(|error| Error::CompressionError(error.to_string()))
+    }
 }
 
 impl Display for StorageServiceResponse {
@@ -1,7 +1,7 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
-use std::{sync::Arc, time::Duration};
+use std::{collections::BTreeMap, sync::Arc, time::Duration};
 
 use proptest::prelude::*;
 
@@ -10,17 +10,19 @@ use crate::{
     pruner::{Pruner, PrunerIndex},
     test_helper,
     test_helper::{arb_blocks_to_commit, put_as_state_root, put_transaction_info},
-    AptosDB, ROCKSDB_PROPERTIES,
+    AptosDB, MIN_RUNTIME_PRUNE_WINDOW, ROCKSDB_PROPERTIES,
 };
-use aptos_config::config::StoragePrunerConfig;
+use aptos_config::config::{RocksdbConfigs, StoragePrunerConfig, TARGET_SNAPSHOT_SIZE};
 use aptos_crypto::{hash::CryptoHash, HashValue};
 use aptos_temppath::TempPath;
 use aptos_types::{
+    block_info::BlockInfo,
+    ledger_info::{LedgerInfo, LedgerInfoWithSignatures},
     proof::SparseMerkleLeafNode,
     state_store::{state_key::StateKey, state_value::StateValue},
     transaction::{ExecutionStatus, TransactionInfo},
 };
-use storage_interface::{DbReader, ExecutedTrees, Order};
+use storage_interface::{DbReader, DbWriter, ExecutedTrees, Order};
 use test_helper::{test_save_blocks_impl, test_sync_transactions_impl};
 
 proptest! {
@@ -116,6 +118,85 @@ fn test_error_if_version_is_pruned() {
     );
 }
 
+#[test]
+fn test_set_prune_window_minimum() {
+    let tmp_dir = TempPath::new();
+    let aptos_db = AptosDB::open(
+        &tmp_dir,
+        false, /* readonly */
+        StoragePrunerConfig {
+            state_store_prune_window: Some(1_000_000),
+            ledger_prune_window: Some(10_000_000),
+            ledger_pruning_batch_size: 1,
+            state_store_pruning_batch_size: 1,
+        },
+        RocksdbConfigs::default(),
+        false, /* enable_indexer */
+        TARGET_SNAPSHOT_SIZE,
+    )
+    .unwrap();
+
+    for prune_window in [0, MIN_RUNTIME_PRUNE_WINDOW - 1] {
+        assert!(aptos_db
+            .set_prune_window(PrunerIndex::LedgerPrunerIndex, prune_window)
+            .is_err());
+    }
+    aptos_db
+        .set_prune_window(PrunerIndex::LedgerPrunerIndex, MIN_RUNTIME_PRUNE_WINDOW)
+        .unwrap();
+    let progress = aptos_db.get_pruner_progress().unwrap();
+    assert_eq!(progress[0].prune_window, Some(1_000_000));
+    assert_eq!(progress[1].prune_window, Some(MIN_RUNTIME_PRUNE_WINDOW));
+}
+
+#[test]
+fn test_prune_to_version_keeps_minimum_window() {
+    let tmp_dir = TempPath::new();
+    let aptos_db = AptosDB::open(
+        &tmp_dir,
+        false, /* readonly */
+        StoragePrunerConfig {
+            state_store_prune_window: Some(1_000_000),
+            ledger_prune_window: Some(10_000_000),
+            ledger_pruning_batch_size: 1,
+            state_store_pruning_batch_size: 1,
+        },
+        RocksdbConfigs::default(),
+        false, /* enable_indexer */
+        TARGET_SNAPSHOT_SIZE,
+    )
+    .unwrap();
+
+    let latest_version = MIN_RUNTIME_PRUNE_WINDOW + 10;
+    aptos_db
+        .ledger_store
+        .set_latest_ledger_info(LedgerInfoWithSignatures::new(
+            LedgerInfo::new(
+                BlockInfo::new(
+                    0,
+                    0,
+                    HashValue::zero(),
+                    HashValue::zero(),
+                    latest_version,
+                    0,
+                    None,
+                ),
+                HashValue::zero(),
+            ),
+            BTreeMap::new(),
+        ));
+
+    // Targets inside the minimum window (or beyond the latest version) are rejected
+    for target_version in [11, latest_version, latest_version + 1] {
+        assert!(aptos_db
+            .prune_to_version(PrunerIndex::LedgerPrunerIndex, target_version)
+            .is_err());
+    }
+    aptos_db
+        .prune_to_version(PrunerIndex::LedgerPrunerIndex, 10)
+        .unwrap();
+}
+
 #[test]
 fn test_get_latest_executed_trees() {
     let tmp_dir = TempPath::new();
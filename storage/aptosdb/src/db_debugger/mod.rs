@@ -0,0 +1,443 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module provides [`DbDebugger`], which works on the DB files of a stopped node directly, to
+//! inspect them, verify their consistency and repair them.
+
+#[cfg(test)]
+mod test;
+
+use crate::{
+    db_options::{ledger_db_column_families, state_merkle_db_column_families},
+    epoch_by_version::EpochByVersionSchema,
+    event_store::EventStore,
+    jellyfish_merkle_node::JellyfishMerkleNodeSchema,
+    ledger_info::LedgerInfoSchema,
+    ledger_store::LedgerStore,
+    pruner::{utils, PrunerIndex},
+    stale_node_index::StaleNodeIndexSchema,
+    state_merkle_db::StateMerkleDb,
+    state_store::MAX_WRITE_SETS_AFTER_SNAPSHOT,
+    state_value::StateValueSchema,
+    transaction_accumulator::TransactionAccumulatorSchema,
+    transaction_store::TransactionStore,
+    AptosDB, LEDGER_DB_NAME, STATE_MERKLE_DB_NAME,
+};
+use anyhow::{ensure, format_err, Result};
+use aptos_config::config::RocksdbConfigs;
+use aptos_crypto::hash::{CryptoHash, EventAccumulatorHasher};
+use aptos_jellyfish_merkle::{node_type::NodeKey, StaleNodeIndex};
+use aptos_logger::prelude::*;
+use aptos_types::{
+    ledger_info::LedgerInfoWithSignatures,
+    proof::{accumulator::InMemoryAccumulator, position::Position},
+    transaction::{Transaction, TransactionInfo, Version},
+};
+use schemadb::{ColumnFamilyName, ReadOptions, SchemaBatch, DB, DEFAULT_COLUMN_FAMILY_NAME};
+use std::{path::Path, sync::Arc};
+
+/// Number of versions verified or truncated at a time.
+const CHUNK_SIZE: Version = 10_000;
+
+/// Size of a column family, as estimated by RocksDB.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ColumnFamilyStats {
+    pub db_name: &'static str,
+    pub cf_name: ColumnFamilyName,
+    pub estimated_num_keys: u64,
+    pub total_sst_files_size: u64,
+    pub estimated_live_data_size: u64,
+}
+
+/// What `DbDebugger::verify()` went through.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct VerificationSummary {
+    pub num_transactions: u64,
+    pub num_state_snapshots: u64,
+}
+
+/// Opens the DB of a stopped node without starting any of the machinery of `AptosDB` (the
+/// buffered state, the pruners, the indexer), so nothing is written unless asked to.
+pub struct DbDebugger {
+    ledger_db: Arc<DB>,
+    state_merkle_db: Arc<StateMerkleDb>,
+    ledger_store: LedgerStore,
+    transaction_store: TransactionStore,
+    event_store: EventStore,
+}
+
+impl DbDebugger {
+    pub fn open<P: AsRef<Path>>(db_root_path: P, readonly: bool) -> Result<Self> {
+        let db_root_path = db_root_path.as_ref();
+        // Opening read-write would otherwise create an empty DB at a mistyped path.
+        for db_name in [LEDGER_DB_NAME, STATE_MERKLE_DB_NAME] {
+            let path = db_root_path.join(db_name);
+            ensure!(path.is_dir(), "DB not found at {:?}.", path);
+        }
+
+        let (ledger_db, state_merkle_db) =
+            AptosDB::open_dbs(db_root_path, &RocksdbConfigs::default(), readonly)?;
+        let ledger_db = Arc::new(ledger_db);
+
+        Ok(Self {
+            ledger_store: LedgerStore::new(Arc::clone(&ledger_db)),
+            transaction_store: TransactionStore::new(Arc::clone(&ledger_db)),
+            event_store: EventStore::new(Arc::clone(&ledger_db)),
+            state_merkle_db: Arc::new(StateMerkleDb::new(Arc::new(state_merkle_db))),
+            ledger_db,
+        })
+    }
+
+    pub fn get_latest_ledger_info(&self) -> Option<LedgerInfoWithSignatures> {
+        self.ledger_store.get_latest_ledger_info_option()
+    }
+
+    /// The version of the latest transaction, which can be ahead of the latest ledger info.
+    pub fn get_latest_version(&self) -> Result<Option<Version>> {
+        Ok(self
+            .ledger_store
+            .get_latest_transaction_info_option()?
+            .map(|(version, _)| version))
+    }
+
+    pub fn get_latest_state_snapshot_version(&self) -> Result<Option<Version>> {
+        self.state_merkle_db
+            .get_state_snapshot_version_before(Version::max_value())
+    }
+
+    pub fn get_column_family_stats(&self) -> Result<Vec<ColumnFamilyStats>> {
+        let dbs: [(&'static str, &DB, Vec<ColumnFamilyName>); 2] = [
+            (LEDGER_DB_NAME, &self.ledger_db, ledger_db_column_families()),
+            (
+                STATE_MERKLE_DB_NAME,
+                &self.state_merkle_db,
+                state_merkle_db_column_families(),
+            ),
+        ];
+
+        let mut stats = Vec::new();
+        for (db_name, db, cf_names) in dbs {
+            for cf_name in cf_names {
+                if cf_name == DEFAULT_COLUMN_FAMILY_NAME {
+                    continue;
+                }
+                stats.push(ColumnFamilyStats {
+                    db_name,
+                    cf_name,
+                    estimated_num_keys: db.get_property(cf_name, "rocksdb.estimate-num-keys")?,
+                    total_sst_files_size: db
+                        .get_property(cf_name, "rocksdb.total-sst-files-size")?,
+                    estimated_live_data_size: db
+                        .get_property(cf_name, "rocksdb.estimate-live-data-size")?,
+                });
+            }
+        }
+        Ok(stats)
+    }
+
+    /// The min readable versions the pruners would start from, i.e. how far they have pruned.
+    pub fn get_pruner_min_readable_versions(&self) -> Result<Vec<(PrunerIndex, Version)>> {
+        Ok(vec![
+            (
+                PrunerIndex::StateStorePrunerIndex,
+                utils::get_state_store_min_readable_version(&self.state_merkle_db)?,
+            ),
+            (
+                PrunerIndex::LedgerPrunerIndex,
+                utils::get_ledger_min_readable_version(&self.ledger_db)?,
+            ),
+        ])
+    }
+
+    /// Verifies the transactions in `[first_version, last_version]`, together with their write
+    /// sets and events, against the transaction accumulator, and the state snapshots in the range
+    /// against the state checkpoint hashes in the transaction infos.
+    pub fn verify(
+        &self,
+        first_version: Version,
+        last_version: Version,
+    ) -> Result<VerificationSummary> {
+        ensure!(
+            first_version <= last_version,
+            "First version {} is after last version {}.",
+            first_version,
+            last_version,
+        );
+        let ledger_version = self
+            .get_latest_version()?
+            .ok_or_else(|| format_err!("DB is empty."))?;
+        ensure!(
+            last_version <= ledger_version,
+            "Last version {} is after the latest version {}.",
+            last_version,
+            ledger_version,
+        );
+
+        let root_hash = self.ledger_store.get_root_hash(ledger_version)?;
+        if let Some(li) = self.get_latest_ledger_info() {
+            let li = li.ledger_info();
+            // The ledger info can be ahead of the transactions only in a DB being restored.
+            if li.version() <= ledger_version {
+                let expected = li.transaction_accumulator_hash();
+                let actual = self.ledger_store.get_root_hash(li.version())?;
+                ensure!(
+                    actual == expected,
+                    "Accumulator root hash at version {} is {}, but the latest ledger info has {}.",
+                    li.version(),
+                    actual,
+                    expected,
+                );
+            }
+        }
+
+        let mut num_state_snapshots = 0;
+        let mut chunk_first_version = first_version;
+        while chunk_first_version <= last_version {
+            let num_txns = std::cmp::min(CHUNK_SIZE, last_version - chunk_first_version + 1);
+            info!(
+                first_version = chunk_first_version,
+                num_transactions = num_txns,
+                "Verifying chunk."
+            );
+
+            let txn_infos = self
+                .ledger_store
+                .get_transaction_info_iter(chunk_first_version, num_txns as usize)?
+                .collect::<Result<Vec<_>>>()?;
+            let txn_info_hashes: Vec<_> = txn_infos.iter().map(CryptoHash::hash).collect();
+            self.ledger_store
+                .get_transaction_range_proof(Some(chunk_first_version), num_txns, ledger_version)?
+                .verify(root_hash, Some(chunk_first_version), &txn_info_hashes)?;
+
+            let txns = self
+                .transaction_store
+                .get_transaction_iter(chunk_first_version, num_txns as usize)?
+                .collect::<Result<Vec<_>>>()?;
+            let write_sets = self
+                .transaction_store
+                .get_write_sets(chunk_first_version, chunk_first_version + num_txns)?;
+            let events = self
+                .event_store
+                .get_events_by_version_iter(chunk_first_version, num_txns as usize)?
+                .collect::<Result<Vec<_>>>()?;
+
+            for (idx, txn_info) in txn_infos.iter().enumerate() {
+                let version = chunk_first_version + idx as u64;
+                ensure!(
+                    txns[idx].hash() == txn_info.transaction_hash(),
+                    "Transaction hash mismatch at version {}.",
+                    version,
+                );
+                ensure!(
+                    CryptoHash::hash(&write_sets[idx]) == txn_info.state_change_hash(),
+                    "Write set hash mismatch at version {}.",
+                    version,
+                );
+                let event_hashes: Vec<_> = events[idx].iter().map(CryptoHash::hash).collect();
+                ensure!(
+                    InMemoryAccumulator::<EventAccumulatorHasher>::from_leaves(&event_hashes)
+                        .root_hash()
+                        == txn_info.event_root_hash(),
+                    "Event root hash mismatch at version {}.",
+                    version,
+                );
+                if self.verify_state_snapshot(version, txn_info)? {
+                    num_state_snapshots += 1;
+                }
+            }
+
+            chunk_first_version += num_txns;
+        }
+
+        Ok(VerificationSummary {
+            num_transactions: last_version - first_version + 1,
+            num_state_snapshots,
+        })
+    }
+
+    /// Returns whether there's a state snapshot at `version` to verify. Not every checkpoint gets
+    /// a snapshot, and the older ones might have been pruned.
+    fn verify_state_snapshot(&self, version: Version, txn_info: &TransactionInfo) -> Result<bool> {
+        let root_hash = match self.state_merkle_db.get_root_hash_option(version)? {
+            Some(root_hash) => root_hash,
+            None => return Ok(false),
+        };
+        let expected = txn_info.state_checkpoint_hash().ok_or_else(|| {
+            format_err!(
+                "State snapshot at version {}, which is not a state checkpoint.",
+                version
+            )
+        })?;
+        ensure!(
+            root_hash == expected,
+            "State snapshot root hash at version {} is {}, but the transaction info has {}.",
+            version,
+            root_hash,
+            expected,
+        );
+        Ok(true)
+    }
+
+    /// Deletes everything after `target_version`, so the DB looks like it did right after
+    /// `target_version` was committed. There needs to be a state snapshot not too long before
+    /// `target_version`, for the node to rebuild the latest state from on start.
+    ///
+    /// Each chunk is deleted atomically, from the latest one down, so if interrupted this can
+    /// simply be run again. Ledger infos after `target_version` are gone too, so the latest ledger
+    /// info might end up being far behind, until the node catches up again. The indexer DB is not
+    /// touched.
+    pub fn truncate(&self, target_version: Version) -> Result<()> {
+        let latest_version = self
+            .get_latest_version()?
+            .ok_or_else(|| format_err!("DB is empty."))?;
+        ensure!(
+            target_version <= latest_version,
+            "Target version {} is after the latest version {}.",
+            target_version,
+            latest_version,
+        );
+        let ledger_min_readable_version = utils::get_ledger_min_readable_version(&self.ledger_db)?;
+        ensure!(
+            target_version >= ledger_min_readable_version,
+            "Target version {} has been pruned, min readable version: {}.",
+            target_version,
+            ledger_min_readable_version,
+        );
+        let snapshot_version = self
+            .state_merkle_db
+            .get_state_snapshot_version_before(target_version + 1)?
+            .ok_or_else(|| format_err!("No state snapshot at or before {}.", target_version))?;
+        let state_min_readable_version =
+            utils::get_state_store_min_readable_version(&self.state_merkle_db)?;
+        ensure!(
+            snapshot_version >= state_min_readable_version,
+            "Latest state snapshot at or before {} is at {}, which has been pruned.",
+            target_version,
+            snapshot_version,
+        );
+        ensure!(
+            target_version - snapshot_version <= MAX_WRITE_SETS_AFTER_SNAPSHOT,
+            "Latest state snapshot at or before {} is at {}, too far behind to rebuild the state from.",
+            target_version,
+            snapshot_version,
+        );
+
+        self.truncate_state_merkle_db(target_version)?;
+
+        let mut end_version = latest_version + 1;
+        while end_version > target_version + 1 {
+            let begin_version =
+                std::cmp::max(end_version.saturating_sub(CHUNK_SIZE), target_version + 1);
+            info!(
+                begin_version = begin_version,
+                end_version = end_version,
+                "Truncating ledger."
+            );
+            self.truncate_ledger_db(begin_version, end_version)?;
+            end_version = begin_version;
+        }
+
+        info!(
+            target_version = target_version,
+            state_snapshot_version = snapshot_version,
+            "DB truncated."
+        );
+        Ok(())
+    }
+
+    fn truncate_state_merkle_db(&self, target_version: Version) -> Result<()> {
+        let batch = SchemaBatch::new();
+
+        let mut iter = self
+            .state_merkle_db
+            .iter::<JellyfishMerkleNodeSchema>(ReadOptions::default())?;
+        iter.seek_to_last();
+        if let Some((last_node_key, _)) = iter.next().transpose()? {
+            let first_node_key = NodeKey::new_empty_path(target_version + 1);
+            if last_node_key.version() > target_version {
+                batch.delete_range_inclusive::<JellyfishMerkleNodeSchema>(
+                    &first_node_key,
+                    &last_node_key,
+                )?;
+            }
+        }
+
+        let mut iter = self
+            .state_merkle_db
+            .iter::<StaleNodeIndexSchema>(ReadOptions::default())?;
+        iter.seek_to_last();
+        if let Some((last_index, _)) = iter.next().transpose()? {
+            if last_index.stale_since_version > target_version {
+                // Nodes that became stale after the target version are live again.
+                let first_index = StaleNodeIndex {
+                    stale_since_version: target_version + 1,
+                    node_key: NodeKey::new_empty_path(0),
+                };
+                batch.delete_range_inclusive::<StaleNodeIndexSchema>(&first_index, &last_index)?;
+            }
+        }
+
+        self.state_merkle_db.write_schemas(batch)
+    }
+
+    /// Deletes everything in the ledger DB in `[begin_version, end_version)`, where `end_version`
+    /// is right after the latest version.
+    fn truncate_ledger_db(&self, begin_version: Version, end_version: Version) -> Result<()> {
+        let mut batch = SchemaBatch::new();
+        let num_txns = (end_version - begin_version) as usize;
+
+        let txns = self
+            .transaction_store
+            .get_transaction_iter(begin_version, num_txns)?
+            .collect::<Result<Vec<Transaction>>>()?;
+        self.transaction_store
+            .prune_transaction_by_hash(&txns, &mut batch)?;
+        self.transaction_store
+            .prune_transaction_by_account(&txns, &mut batch)?;
+        self.transaction_store
+            .prune_transaction_schema(begin_version, end_version, &mut batch)?;
+        self.transaction_store.prune_transaction_info_schema(
+            begin_version,
+            end_version,
+            &mut batch,
+        )?;
+
+        let write_sets = self
+            .transaction_store
+            .get_write_sets(begin_version, end_version)?;
+        for (version, write_set) in (begin_version..end_version).zip(write_sets.iter()) {
+            for (state_key, _write_op) in write_set {
+                batch.delete::<StateValueSchema>(&(state_key.clone(), version))?;
+            }
+        }
+        self.transaction_store
+            .prune_write_set(begin_version, end_version, &mut batch)?;
+
+        self.event_store
+            .prune_events(begin_version, end_version, &mut batch)?;
+        self.ledger_store
+            .prune_ledger_counters(begin_version, end_version, &mut batch)?;
+        batch.delete_range::<EpochByVersionSchema>(&begin_version, &end_version)?;
+
+        // Frozen nodes of the accumulator with `n` leaves are exactly the ones before leaf `n` in
+        // post-order.
+        batch.delete_range::<TransactionAccumulatorSchema>(
+            &Position::from_leaf_index(begin_version),
+            &Position::from_leaf_index(end_version),
+        )?;
+
+        let mut iter = self
+            .ledger_db
+            .rev_iter::<LedgerInfoSchema>(ReadOptions::default())?;
+        iter.seek_to_last();
+        for res in iter {
+            let (epoch, li) = res?;
+            if li.ledger_info().version() < begin_version {
+                break;
+            }
+            batch.delete::<LedgerInfoSchema>(&epoch)?;
+        }
+
+        self.ledger_db.write_schemas(batch)
+    }
+}
@@ -0,0 +1,126 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use crate::{
+    test_helper::{arb_blocks_to_commit, update_in_memory_state},
+    AptosDB,
+};
+use aptos_temppath::TempPath;
+use aptos_types::{ledger_info::LedgerInfoWithSignatures, transaction::TransactionToCommit};
+use proptest::prelude::*;
+use storage_interface::{DbReader, DbWriter};
+
+/// Commits the blocks to a fresh DB at the path, and returns the latest version.
+fn commit_blocks(
+    tmp_dir: &TempPath,
+    input: &[(Vec<TransactionToCommit>, LedgerInfoWithSignatures)],
+) -> Version {
+    let db = AptosDB::new_for_test(tmp_dir);
+    let mut in_memory_state = db
+        .state_store
+        .buffered_state()
+        .lock()
+        .current_state()
+        .clone();
+    let mut cur_ver: Version = 0;
+    for (txns_to_commit, ledger_info_with_sigs) in input.iter() {
+        update_in_memory_state(&mut in_memory_state, txns_to_commit.as_slice());
+        db.save_transactions(
+            txns_to_commit,
+            cur_ver,
+            cur_ver.checked_sub(1),
+            Some(ledger_info_with_sigs),
+            true,
+            in_memory_state.clone(),
+        )
+        .unwrap();
+        cur_ver += txns_to_commit.len() as u64;
+    }
+    cur_ver - 1
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(10))]
+
+    #[test]
+    fn test_verify_and_truncate(input in arb_blocks_to_commit()) {
+        let tmp_dir = TempPath::new();
+        let latest_version = commit_blocks(&tmp_dir, &input);
+        let target_version = input[0].0.len() as u64 - 1;
+
+        {
+            let debugger = DbDebugger::open(&tmp_dir, true /* readonly */).unwrap();
+            prop_assert_eq!(debugger.get_latest_version().unwrap(), Some(latest_version));
+            prop_assert_eq!(
+                debugger.get_latest_ledger_info().unwrap(),
+                input.last().unwrap().1.clone()
+            );
+            let summary = debugger.verify(0, latest_version).unwrap();
+            prop_assert_eq!(summary.num_transactions, latest_version + 1);
+            prop_assert!(debugger.verify(0, latest_version + 1).is_err());
+        }
+
+        {
+            let debugger = DbDebugger::open(&tmp_dir, false /* readonly */).unwrap();
+            debugger.truncate(target_version).unwrap();
+            // Truncating again is a no-op.
+            debugger.truncate(target_version).unwrap();
+            prop_assert_eq!(debugger.get_latest_version().unwrap(), Some(target_version));
+            debugger.verify(0, target_version).unwrap();
+        }
+
+        let db = AptosDB::new_for_test(&tmp_dir);
+        prop_assert_eq!(
+            db.ledger_store.get_latest_transaction_info().unwrap().0,
+            target_version
+        );
+        // Ledger infos are keyed by epoch, so the one of the first block might have been
+        // overwritten by a later one, which is now gone.
+        if let Some(li) = db.ledger_store.get_latest_ledger_info_option() {
+            prop_assert!(li.ledger_info().version() <= target_version);
+        }
+        prop_assert!(db.get_transaction_by_version(target_version + 1, target_version, false).is_err());
+        prop_assert_eq!(
+            db.get_accumulator_root_hash(target_version).unwrap(),
+            input[0].1.ledger_info().transaction_accumulator_hash()
+        );
+    }
+
+    #[test]
+    fn test_truncate_to_middle_of_block(input in arb_blocks_to_commit()) {
+        // The state is rebuilt from the snapshot at the end of an earlier block.
+        prop_assume!(input.len() >= 2);
+        let last_block_len = input.last().unwrap().0.len() as u64;
+        prop_assume!(last_block_len >= 2);
+
+        let tmp_dir = TempPath::new();
+        let latest_version = commit_blocks(&tmp_dir, &input);
+        // A version in the last block, other than its last one
+        let target_version = latest_version + 1 - last_block_len + (last_block_len - 2) / 2;
+        let expected_root_hash = {
+            let db = AptosDB::new_for_test(&tmp_dir);
+            db.get_accumulator_root_hash(target_version).unwrap()
+        };
+
+        {
+            let debugger = DbDebugger::open(&tmp_dir, false /* readonly */).unwrap();
+            debugger.truncate(target_version).unwrap();
+            prop_assert_eq!(debugger.get_latest_version().unwrap(), Some(target_version));
+            let summary = debugger.verify(0, target_version).unwrap();
+            prop_assert_eq!(summary.num_transactions, target_version + 1);
+        }
+
+        let db = AptosDB::new_for_test(&tmp_dir);
+        prop_assert_eq!(
+            db.ledger_store.get_latest_transaction_info().unwrap().0,
+            target_version
+        );
+        // The ledger info of the last block is beyond the target, so it's gone.
+        if let Some(li) = db.ledger_store.get_latest_ledger_info_option() {
+            prop_assert!(li.ledger_info().version() < target_version);
+        }
+        prop_assert!(db.get_transaction_by_version(target_version + 1, target_version, false).is_err());
+        prop_assert_eq!(db.get_accumulator_root_hash(target_version).unwrap(), expected_root_hash);
+    }
+}
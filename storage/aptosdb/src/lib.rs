@@ -14,6 +14,7 @@
 pub mod test_helper;
 
 pub mod backup;
+pub mod db_debugger;
 pub mod errors;
 pub mod metrics;
 pub mod schema;
@@ -55,7 +56,7 @@ use crate::{
     system_store::SystemStore,
     transaction_store::TransactionStore,
 };
-use anyhow::{bail, ensure, Result};
+use anyhow::{bail, ensure, format_err, Result};
 use aptos_config::config::{
     RocksdbConfig, RocksdbConfigs, StoragePrunerConfig, NO_OP_STORAGE_PRUNER_CONFIG,
     TARGET_SNAPSHOT_SIZE,
@@ -106,7 +107,7 @@ use std::{
 };
 use storage_interface::state_view::DbStateView;
 use storage_interface::{
-    state_delta::StateDelta, DbReader, DbWriter, ExecutedTrees, Order, StartupInfo,
+    state_delta::StateDelta, DbReader, DbWriter, ExecutedTrees, Order, PrunerProgress, StartupInfo,
     StateSnapshotReceiver,
 };
 
@@ -115,6 +116,10 @@ pub const STATE_MERKLE_DB_NAME: &str = "state_merkle_db";
 
 const MAX_LIMIT: u64 = 5000;
 
+/// The smallest prune window which can be set at runtime. Recent versions are still needed by
+/// state sync and the API, so an operator can't wipe them out by mistake.
+pub const MIN_RUNTIME_PRUNE_WINDOW: Version = 100_000;
+
 // TODO: Either implement an iteration API to allow a very old client to loop through a long history
 // or guarantee that there is always a recent enough waypoint and client knows to boot from there.
 const MAX_NUM_EPOCH_ENDING_LEDGER_INFO: usize = 100;
@@ -335,17 +340,51 @@ impl AptosDB {
         let state_merkle_db_path = db_root_path.as_ref().join(STATE_MERKLE_DB_NAME);
         let instant = Instant::now();
 
-        let (ledger_db, state_merkle_db) = if readonly {
+        let (ledger_db, state_merkle_db) =
+            Self::open_dbs(db_root_path.as_ref(), &rocksdb_configs, readonly)?;
+
+        let mut myself = Self::new_with_dbs(
+            ledger_db,
+            state_merkle_db,
+            storage_pruner_config,
+            target_snapshot_size,
+            readonly,
+        );
+
+        if !readonly && enable_indexer {
+            myself.open_indexer(db_root_path, rocksdb_configs.index_db_config)?;
+        }
+
+        info!(
+            ledger_db_path = ledger_db_path,
+            state_merkle_db_path = state_merkle_db_path,
+            time_ms = %instant.elapsed().as_millis(),
+            "Opened AptosDB (LedgerDB + StateMerkleDB).",
+        );
+        Ok(myself)
+    }
+
+    /// Opens the raw ledger and state merkle RocksDB instances under `db_root_path`, without
+    /// building any of the stores on top of them.
+    pub(crate) fn open_dbs(
+        db_root_path: &Path,
+        rocksdb_configs: &RocksdbConfigs,
+        readonly: bool,
+    ) -> Result<(DB, DB)> {
+        let ledger_db_path = db_root_path.join(LEDGER_DB_NAME);
+        let state_merkle_db_path = db_root_path.join(STATE_MERKLE_DB_NAME);
+
+        Ok(if readonly {
             (
                 DB::open_cf_readonly(
                     &gen_rocksdb_options(&rocksdb_configs.ledger_db_config, true),
-                    ledger_db_path.clone(),
+                    ledger_db_path,
                     "ledger_db_ro",
                     ledger_db_column_families(),
                 )?,
                 DB::open_cf_readonly(
                     &gen_rocksdb_options(&rocksdb_configs.state_merkle_db_config, true),
-                    state_merkle_db_path.clone(),
+                    state_merkle_db_path,
                     "state_merkle_db_ro",
                     state_merkle_db_column_families(),
                 )?,
@@ -354,38 +393,18 @@ impl AptosDB {
             (
                 DB::open_cf(
                     &gen_rocksdb_options(&rocksdb_configs.ledger_db_config, false),
-                    ledger_db_path.clone(),
+                    ledger_db_path,
                     "ledger_db",
                     gen_ledger_cfds(),
                 )?,
                 DB::open_cf(
                     &gen_rocksdb_options(&rocksdb_configs.state_merkle_db_config, false),
-                    state_merkle_db_path.clone(),
+                    state_merkle_db_path,
                     "state_merkle_db",
                     gen_state_merkle_cfds(),
                 )?,
             )
-        };
-
-        let mut myself = Self::new_with_dbs(
-            ledger_db,
-            state_merkle_db,
-            storage_pruner_config,
-            target_snapshot_size,
-            readonly,
-        );
-
-        if !readonly && enable_indexer {
-            myself.open_indexer(db_root_path, rocksdb_configs.index_db_config)?;
-        }
-
-        info!(
-            ledger_db_path = ledger_db_path,
-            state_merkle_db_path = state_merkle_db_path,
-            time_ms = %instant.elapsed().as_millis(),
-            "Opened AptosDB (LedgerDB + StateMerkleDB).",
-        );
-        Ok(myself)
+        })
     }
 
     fn open_indexer(
@@ -1400,6 +1419,26 @@ impl DbReader for AptosDB {
         })
     }
 
+    fn get_pruner_progress(&self) -> Result<Vec<PrunerProgress>> {
+        gauged_api("get_pruner_progress", || {
+            Ok([
+                PrunerIndex::StateStorePrunerIndex,
+                PrunerIndex::LedgerPrunerIndex,
+            ]
+            .iter()
+            .map(|&pruner_index| match self.pruner.as_ref() {
+                Some(pruner) => pruner.get_pruner_progress(pruner_index),
+                None => PrunerProgress {
+                    pruner_index,
+                    prune_window: None,
+                    min_readable_version: None,
+                    target_version: None,
+                },
+            })
+            .collect())
+        })
+    }
+
     fn get_table_info(&self, handle: TableHandle) -> Result<TableInfo> {
         gauged_api("get_table_info", || {
             self.get_table_info_option(handle)?
@@ -1679,6 +1718,58 @@ impl DbWriter for AptosDB {
             Ok(())
         })
     }
+
+    /// Changes the prune window of a pruner enabled on startup. Enlarging it doesn't bring back
+    /// what's already pruned. The window can't be smaller than `MIN_RUNTIME_PRUNE_WINDOW`.
+    fn set_prune_window(&self, pruner_index: PrunerIndex, prune_window: Version) -> Result<()> {
+        gauged_api("set_prune_window", || {
+            ensure!(
+                prune_window >= MIN_RUNTIME_PRUNE_WINDOW,
+                "Prune window {} is below the minimum of {}.",
+                prune_window,
+                MIN_RUNTIME_PRUNE_WINDOW,
+            );
+            let pruner = self
+                .pruner
+                .as_ref()
+                .ok_or_else(|| format_err!("Pruning is disabled."))?;
+            pruner.set_pruner_window(pruner_index, prune_window)?;
+            info!(
+                pruner = pruner_index.name(),
+                prune_window = prune_window,
+                "Prune window changed."
+            );
+            Ok(())
+        })
+    }
+
+    /// Makes a pruner enabled on startup prune all versions before `target_version` once,
+    /// regardless of its prune window. As with `set_prune_window`, the most recent
+    /// `MIN_RUNTIME_PRUNE_WINDOW` versions can't be pruned.
+    fn prune_to_version(&self, pruner_index: PrunerIndex, target_version: Version) -> Result<()> {
+        gauged_api("prune_to_version", || {
+            let pruner = self
+                .pruner
+                .as_ref()
+                .ok_or_else(|| format_err!("Pruning is disabled."))?;
+            let latest_version = self.get_latest_version()?;
+            let max_target_version = latest_version.saturating_sub(MIN_RUNTIME_PRUNE_WINDOW);
+            ensure!(
+                target_version <= max_target_version,
+                "Can't prune to version {}, the latest {} versions (up to {}) must be kept.",
+                target_version,
+                MIN_RUNTIME_PRUNE_WINDOW,
+                latest_version,
+            );
+            pruner.prune_to_version(pruner_index, target_version)?;
+            info!(
+                pruner = pruner_index.name(),
+                target_version = target_version,
+                "Manual pruning triggered."
+            );
+            Ok(())
+        })
+    }
 }
 
 // Convert requested range and order to a range in ascending order.
@@ -109,15 +109,15 @@ impl LedgerPrunerWorker {
                 db_pruner::Command::Prune { target_db_version } => {
                     if let Some(ledger_pruner_target_version) = target_db_version {
                         if let Some(ledger_pruner) = &self.ledger_pruner {
-                            if ledger_pruner_target_version > ledger_pruner.lock().target_version()
-                            {
+                            let ledger_pruner = ledger_pruner.lock();
+                            // Pruning only goes forward, a lower target (e.g. after the prune
+                            // window is enlarged or a manual prune) is ignored.
+                            if ledger_pruner_target_version > ledger_pruner.target_version() {
+                                ledger_pruner.set_target_version(ledger_pruner_target_version);
                                 // Switch to non-blocking to allow some work to be done after the
                                 // channel has drained.
                                 self.blocking_recv = false;
                             }
-                            ledger_pruner
-                                .lock()
-                                .set_target_version(ledger_pruner_target_version);
                         }
                     }
                 }
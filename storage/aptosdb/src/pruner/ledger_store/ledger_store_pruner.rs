@@ -10,12 +10,12 @@ use crate::{
         transaction_store::{
             transaction_store_pruner::TransactionStorePruner, write_set_pruner::WriteSetPruner,
         },
+        utils,
     },
-    transaction::TransactionSchema,
     EventStore, LedgerStore, TransactionStore,
 };
 use aptos_types::transaction::{AtomicVersion, Version};
-use schemadb::{ReadOptions, SchemaBatch, DB};
+use schemadb::{SchemaBatch, DB};
 use std::sync::{atomic::Ordering, Arc};
 
 pub const LEDGER_PRUNER_NAME: &str = "ledger pruner";
@@ -72,10 +72,7 @@ impl DBPruner for LedgerPruner {
     }
 
     fn initialize_min_readable_version(&self) -> anyhow::Result<Version> {
-        let mut iter = self.db.iter::<TransactionSchema>(ReadOptions::default())?;
-        iter.seek_to_first();
-        let version = iter.next().transpose()?.map_or(0, |(version, _)| version);
-        Ok(version)
+        utils::get_ledger_min_readable_version(&self.db)
     }
 
     fn min_readable_version(&self) -> Version {
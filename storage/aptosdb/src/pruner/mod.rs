@@ -20,17 +20,21 @@ use aptos_config::config::StoragePrunerConfig;
 use aptos_infallible::Mutex;
 
 use crate::pruner::PrunerIndex::LedgerPrunerIndex;
+use anyhow::{ensure, Result};
 use aptos_types::transaction::Version;
 use ledger_pruner_worker::LedgerPrunerWorker;
 use schemadb::DB;
 use state_pruner_worker::StatePrunerWorker;
 use std::{
+    cmp::max,
     sync::{
         mpsc::{channel, Sender},
         Arc,
     },
     thread::JoinHandle,
 };
+pub use storage_interface::PrunerIndex;
+use storage_interface::PrunerProgress;
 
 /// The `Pruner` is meant to be part of a `AptosDB` instance and runs in the background to prune old
 /// data.
@@ -40,11 +44,11 @@ use std::{
 #[derive(Debug)]
 pub(crate) struct Pruner {
     /// DB version window, which dictates how many versions of state store
-    /// to keep.
-    state_store_prune_window: Option<Version>,
+    /// to keep. It can be changed at runtime, but a pruner disabled on construction stays so.
+    state_store_prune_window: Mutex<Option<Version>>,
     /// DB version window, which dictates how many version of other stores like transaction, ledger
     /// info, events etc to keep.
-    ledger_prune_window: Option<Version>,
+    ledger_prune_window: Mutex<Option<Version>>,
     /// The worker thread handle for state_pruner, created upon Pruner instance construction and
     /// joined upon its destruction. It only becomes `None` after joined in `drop()`.
     state_pruner_worker_thread: Option<JoinHandle<()>>,
@@ -65,18 +69,15 @@ pub(crate) struct Pruner {
     /// last version we sent to the pruners.
     last_version_sent_to_state_pruner: Arc<Mutex<Version>>,
     last_version_sent_to_ledger_pruner: Arc<Mutex<Version>>,
+    /// The highest target version sent to each of the pruners.
+    state_pruner_target_version: Mutex<Version>,
+    ledger_pruner_target_version: Mutex<Version>,
     /// Ideal batch size of the versions to be sent to the ledger pruner
     ledger_pruner_pruning_batch_size: usize,
     /// latest version
     latest_version: Arc<Mutex<Version>>,
 }
 
-pub enum PrunerIndex {
-    #[allow(dead_code)]
-    StateStorePrunerIndex,
-    LedgerPrunerIndex,
-}
-
 impl Pruner {
     /// Creates a worker thread that waits on a channel for pruning commands.
     pub fn new(
@@ -141,8 +142,8 @@ impl Pruner {
             .expect("Creating ledger pruner thread should succeed.");
 
         Self {
-            state_store_prune_window: storage_pruner_config.state_store_prune_window,
-            ledger_prune_window: storage_pruner_config.ledger_prune_window,
+            state_store_prune_window: Mutex::new(storage_pruner_config.state_store_prune_window),
+            ledger_prune_window: Mutex::new(storage_pruner_config.ledger_prune_window),
             state_pruner_worker_thread: Some(state_pruner_worker_thread),
             state_pruner_command_sender: Mutex::new(state_pruner_command_sender),
             ledger_pruner_worker_thread: Some(ledger_pruner_worker_thread),
@@ -151,17 +152,86 @@ impl Pruner {
             ledger_pruner_min_readable_version: ledger_pruner_min_readable_version_clone,
             last_version_sent_to_state_pruner: Arc::new(Mutex::new(0)),
             last_version_sent_to_ledger_pruner: Arc::new(Mutex::new(0)),
+            state_pruner_target_version: Mutex::new(0),
+            ledger_pruner_target_version: Mutex::new(0),
             ledger_pruner_pruning_batch_size: storage_pruner_config.ledger_pruning_batch_size,
             latest_version: Arc::new(Mutex::new(0)),
         }
     }
 
     pub fn get_state_store_pruner_window(&self) -> Option<Version> {
-        self.state_store_prune_window
+        *self.state_store_prune_window.lock()
     }
 
     pub fn get_ledger_pruner_window(&self) -> Option<Version> {
-        self.ledger_prune_window
+        *self.ledger_prune_window.lock()
+    }
+
+    fn prune_window_by_pruner_index(&self, pruner_index: PrunerIndex) -> &Mutex<Option<Version>> {
+        match pruner_index {
+            PrunerIndex::StateStorePrunerIndex => &self.state_store_prune_window,
+            PrunerIndex::LedgerPrunerIndex => &self.ledger_prune_window,
+        }
+    }
+
+    /// Changes the prune window of a pruner, applying it right away to the latest version seen.
+    /// Making the window larger doesn't bring back what is already pruned.
+    pub fn set_pruner_window(
+        &self,
+        pruner_index: PrunerIndex,
+        prune_window: Version,
+    ) -> Result<()> {
+        {
+            let mut window = self.prune_window_by_pruner_index(pruner_index).lock();
+            ensure!(
+                window.is_some(),
+                "The {} is disabled, it can only be enabled on startup.",
+                pruner_index.name(),
+            );
+            *window = Some(prune_window);
+        }
+        PRUNER_WINDOW
+            .with_label_values(&[pruner_index.name()])
+            .set(prune_window as i64);
+
+        let latest_version = *self.latest_version.lock();
+        match pruner_index {
+            PrunerIndex::StateStorePrunerIndex => self.wake_state_pruner(latest_version),
+            PrunerIndex::LedgerPrunerIndex => self.wake_ledger_pruner(latest_version),
+        }
+        Ok(())
+    }
+
+    /// Makes a pruner prune all versions before `target_version` regardless of its prune window.
+    /// The caller is responsible for not pruning beyond the latest committed version.
+    pub fn prune_to_version(
+        &self,
+        pruner_index: PrunerIndex,
+        target_version: Version,
+    ) -> Result<()> {
+        ensure!(
+            self.prune_window_by_pruner_index(pruner_index)
+                .lock()
+                .is_some(),
+            "The {} is disabled.",
+            pruner_index.name(),
+        );
+        self.send_prune_command(pruner_index, Some(target_version));
+        Ok(())
+    }
+
+    pub fn get_pruner_progress(&self, pruner_index: PrunerIndex) -> PrunerProgress {
+        let prune_window = *self.prune_window_by_pruner_index(pruner_index).lock();
+        let target_version = match pruner_index {
+            PrunerIndex::StateStorePrunerIndex => *self.state_pruner_target_version.lock(),
+            PrunerIndex::LedgerPrunerIndex => *self.ledger_pruner_target_version.lock(),
+        };
+        PrunerProgress {
+            pruner_index,
+            prune_window,
+            min_readable_version: self.get_min_readable_version_by_pruner_index(pruner_index),
+            target_version: prune_window.map(|_| target_version),
+        }
     }
 
     pub fn get_min_readable_version_by_pruner_index(
@@ -201,24 +271,37 @@ impl Pruner {
     }
 
     fn wake_state_pruner(&self, latest_version: Version) {
-        self.state_pruner_command_sender
-            .lock()
-            .send(db_pruner::Command::Prune {
-                target_db_version: self
-                    .state_store_prune_window
-                    .map(|x| latest_version.saturating_sub(x)),
-            })
-            .expect("Receiver should not destruct prematurely.");
+        let target_db_version = self
+            .get_state_store_pruner_window()
+            .map(|x| latest_version.saturating_sub(x));
+        self.send_prune_command(PrunerIndex::StateStorePrunerIndex, target_db_version);
     }
 
     fn wake_ledger_pruner(&self, latest_version: Version) {
-        self.ledger_pruner_command_sender
+        let target_db_version = self
+            .get_ledger_pruner_window()
+            .map(|x| latest_version.saturating_sub(x));
+        self.send_prune_command(PrunerIndex::LedgerPrunerIndex, target_db_version);
+    }
+
+    fn send_prune_command(&self, pruner_index: PrunerIndex, target_db_version: Option<Version>) {
+        let (command_sender, last_target_version) = match pruner_index {
+            PrunerIndex::StateStorePrunerIndex => (
+                &self.state_pruner_command_sender,
+                &self.state_pruner_target_version,
+            ),
+            PrunerIndex::LedgerPrunerIndex => (
+                &self.ledger_pruner_command_sender,
+                &self.ledger_pruner_target_version,
+            ),
+        };
+        if let Some(target_version) = target_db_version {
+            let mut last_target_version = last_target_version.lock();
+            *last_target_version = max(*last_target_version, target_version);
+        }
+        command_sender
             .lock()
-            .send(db_pruner::Command::Prune {
-                target_db_version: self
-                    .ledger_prune_window
-                    .map(|x| latest_version.saturating_sub(x)),
-            })
+            .send(db_pruner::Command::Prune { target_db_version })
             .expect("Receiver should not destruct prematurely.");
     }
 
@@ -234,11 +317,11 @@ impl Pruner {
         *self.latest_version.lock() = latest_version;
         self.wake_state_pruner(latest_version);
 
-        if self.state_store_prune_window.is_some()
-            && latest_version > self.state_store_prune_window.unwrap()
+        let state_store_prune_window = self.get_state_store_pruner_window();
+        if state_store_prune_window.is_some() && latest_version > state_store_prune_window.unwrap()
         {
             let min_readable_state_store_version =
-                latest_version - self.state_store_prune_window.unwrap_or(0);
+                latest_version - state_store_prune_window.unwrap_or(0);
 
             // Assuming no big pruning chunks will be issued by a test.
             const TIMEOUT: Duration = Duration::from_secs(10);
@@ -274,10 +357,9 @@ impl Pruner {
             *self.last_version_sent_to_ledger_pruner.as_ref().lock() = latest_version;
         }
 
-        if self.ledger_prune_window.is_some() && latest_version > self.ledger_prune_window.unwrap()
-        {
-            let min_readable_ledger_version =
-                latest_version - self.ledger_prune_window.unwrap_or(0);
+        let ledger_prune_window = self.get_ledger_pruner_window();
+        if ledger_prune_window.is_some() && latest_version > ledger_prune_window.unwrap() {
+            let min_readable_ledger_version = latest_version - ledger_prune_window.unwrap_or(0);
 
             // Assuming no big pruning chunks will be issued by a test.
             const TIMEOUT: Duration = Duration::from_secs(10);
@@ -296,6 +378,34 @@ impl Pruner {
         Ok(())
     }
 
+    /// (For tests only.) Waits for a pruner to make versions before `min_readable_version`
+    /// unreadable.
+    #[cfg(test)]
+    pub fn wait_for_pruner(
+        &self,
+        pruner_index: PrunerIndex,
+        min_readable_version: Version,
+    ) -> anyhow::Result<()> {
+        use std::{
+            thread::sleep,
+            time::{Duration, Instant},
+        };
+
+        const TIMEOUT: Duration = Duration::from_secs(10);
+        let end = Instant::now() + TIMEOUT;
+
+        while Instant::now() < end {
+            if self
+                .get_min_readable_version_by_pruner_index(pruner_index)
+                .map_or(false, |v| v >= min_readable_version)
+            {
+                return Ok(());
+            }
+            sleep(Duration::from_millis(1));
+        }
+        anyhow::bail!("Timeout waiting for pruner worker.");
+    }
+
     /// (For tests only.) Ensure a pruner is disabled.
     #[cfg(test)]
     pub fn ensure_disabled(&self, pruner_index: PrunerIndex) -> anyhow::Result<()> {
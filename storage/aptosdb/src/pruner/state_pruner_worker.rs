@@ -108,14 +108,15 @@ impl StatePrunerWorker {
                 db_pruner::Command::Prune { target_db_version } => {
                     if let Some(state_pruner_target_version) = target_db_version {
                         if let Some(state_pruner) = &self.state_pruner {
-                            if state_pruner_target_version > state_pruner.lock().target_version() {
+                            let state_pruner = state_pruner.lock();
+                            // Pruning only goes forward, a lower target (e.g. after the prune
+                            // window is enlarged or a manual prune) is ignored.
+                            if state_pruner_target_version > state_pruner.target_version() {
+                                state_pruner.set_target_version(state_pruner_target_version);
                                 // Switch to non-blocking to allow some work to be done after the
                                 // channel has drained.
                                 self.blocking_recv = false;
                             }
-                            state_pruner
-                                .lock()
-                                .set_target_version(state_pruner_target_version);
                         }
                     }
                 }
@@ -2,8 +2,11 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    jellyfish_merkle_node::JellyfishMerkleNodeSchema, metrics::PRUNER_LEAST_READABLE_VERSION,
-    pruner::db_pruner::DBPruner, stale_node_index::StaleNodeIndexSchema, OTHER_TIMERS_SECONDS,
+    jellyfish_merkle_node::JellyfishMerkleNodeSchema,
+    metrics::PRUNER_LEAST_READABLE_VERSION,
+    pruner::{db_pruner::DBPruner, utils},
+    stale_node_index::StaleNodeIndexSchema,
+    OTHER_TIMERS_SECONDS,
 };
 use anyhow::Result;
 use aptos_jellyfish_merkle::StaleNodeIndex;
@@ -56,16 +59,7 @@ impl DBPruner for StateStorePruner {
     }
 
     fn initialize_min_readable_version(&self) -> Result<Version> {
-        let mut iter = self
-            .db
-            .iter::<StaleNodeIndexSchema>(ReadOptions::default())?;
-        iter.seek_to_first();
-        Ok(iter.next().transpose()?.map_or(0, |(index, _)| {
-            index
-                .stale_since_version
-                .checked_sub(1)
-                .expect("Nothing is stale since version 0.")
-        }))
+        utils::get_state_store_min_readable_version(&self.db)
     }
 
     fn min_readable_version(&self) -> Version {
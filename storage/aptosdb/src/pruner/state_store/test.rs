@@ -7,7 +7,7 @@ use aptos_crypto::HashValue;
 use aptos_temppath::TempPath;
 use aptos_types::state_store::{state_key::StateKey, state_value::StateValue};
 use schemadb::ReadOptions;
-use storage_interface::{jmt_update_refs, jmt_updates, DbReader};
+use storage_interface::{jmt_update_refs, jmt_updates, DbReader, PrunerProgress};
 
 use crate::stale_node_index::StaleNodeIndexSchema;
 use crate::{change_set::ChangeSet, pruner::*, state_store::StateStore, AptosDB};
@@ -322,6 +322,97 @@ fn test_state_store_pruner_disabled() {
     }
 }
 
+#[test]
+fn test_state_store_pruner_reconfiguration() {
+    let key = StateKey::Raw(String::from("test_key1").into_bytes());
+
+    let prune_batch_size = 10;
+    let num_versions = 25;
+    let tmp_dir = TempPath::new();
+    let aptos_db = AptosDB::new_for_test(&tmp_dir);
+    let state_store = &aptos_db.state_store;
+    let pruner = Pruner::new(
+        Arc::clone(&aptos_db.ledger_db),
+        Arc::clone(&aptos_db.state_merkle_db),
+        StoragePrunerConfig {
+            state_store_prune_window: Some(20),
+            ledger_prune_window: None,
+            ledger_pruning_batch_size: prune_batch_size,
+            state_store_pruning_batch_size: prune_batch_size,
+        },
+    );
+
+    for i in 0..num_versions {
+        put_value_set(
+            &aptos_db.ledger_db,
+            state_store,
+            vec![(key.clone(), StateValue::from(vec![i as u8]))],
+            i as u64, /* version */
+        );
+    }
+    let verify_pruned_before = |min_readable_version: Version| {
+        for i in 0..num_versions {
+            if i < min_readable_version {
+                assert!(state_store
+                    .get_state_value_with_proof_by_version(&key, i)
+                    .is_err());
+            } else {
+                verify_state_in_store(
+                    state_store,
+                    key.clone(),
+                    Some(&StateValue::from(vec![i as u8])),
+                    i,
+                );
+            }
+        }
+    };
+
+    pruner
+        .wake_and_wait_state_pruner(num_versions - 1 /* latest_version */)
+        .unwrap();
+    verify_pruned_before(4);
+
+    // Shrinking the window prunes right away.
+    pruner
+        .set_pruner_window(PrunerIndex::StateStorePrunerIndex, 10)
+        .unwrap();
+    pruner
+        .wait_for_pruner(PrunerIndex::StateStorePrunerIndex, 14)
+        .unwrap();
+    verify_pruned_before(14);
+
+    // Pruning to a version ignores the window.
+    pruner
+        .prune_to_version(PrunerIndex::StateStorePrunerIndex, 20)
+        .unwrap();
+    pruner
+        .wait_for_pruner(PrunerIndex::StateStorePrunerIndex, 20)
+        .unwrap();
+    verify_pruned_before(20);
+
+    // Enlarging the window doesn't move the progress backwards.
+    pruner
+        .set_pruner_window(PrunerIndex::StateStorePrunerIndex, 20)
+        .unwrap();
+    assert_eq!(
+        pruner.get_pruner_progress(PrunerIndex::StateStorePrunerIndex),
+        PrunerProgress {
+            pruner_index: PrunerIndex::StateStorePrunerIndex,
+            prune_window: Some(20),
+            min_readable_version: Some(20),
+            target_version: Some(20),
+        }
+    );
+
+    // A pruner disabled on startup can't be configured.
+    assert!(pruner
+        .set_pruner_window(PrunerIndex::LedgerPrunerIndex, 10)
+        .is_err());
+    assert!(pruner
+        .prune_to_version(PrunerIndex::LedgerPrunerIndex, 10)
+        .is_err());
+}
+
 #[test]
 fn test_worker_quit_eagerly() {
     let key = StateKey::Raw(String::from("test_key1").into_bytes());
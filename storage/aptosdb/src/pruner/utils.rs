@@ -8,11 +8,15 @@ use crate::{
         db_pruner::DBPruner, ledger_store::ledger_store_pruner::LedgerPruner,
         state_store::StateStorePruner,
     },
+    stale_node_index::StaleNodeIndexSchema,
+    transaction::TransactionSchema,
     EventStore, LedgerStore, TransactionStore,
 };
+use anyhow::Result;
 use aptos_config::config::StoragePrunerConfig;
 use aptos_infallible::Mutex;
-use schemadb::DB;
+use aptos_types::transaction::Version;
+use schemadb::{ReadOptions, DB};
 use std::sync::Arc;
 
 /// Utility functions to instantiate pruners.
@@ -44,3 +48,23 @@ pub fn create_ledger_pruner(
         None
     }
 }
+
+/// Finds out the min readable version of the state merkle DB, i.e. the version before the oldest
+/// stale node not yet pruned.
+pub(crate) fn get_state_store_min_readable_version(state_merkle_db: &DB) -> Result<Version> {
+    let mut iter = state_merkle_db.iter::<StaleNodeIndexSchema>(ReadOptions::default())?;
+    iter.seek_to_first();
+    Ok(iter.next().transpose()?.map_or(0, |(index, _)| {
+        index
+            .stale_since_version
+            .checked_sub(1)
+            .expect("Nothing is stale since version 0.")
+    }))
+}
+
+/// Finds out the min readable version of the ledger DB, i.e. the oldest transaction not yet pruned.
+pub(crate) fn get_ledger_min_readable_version(ledger_db: &DB) -> Result<Version> {
+    let mut iter = ledger_db.iter::<TransactionSchema>(ReadOptions::default())?;
+    iter.seek_to_first();
+    Ok(iter.next().transpose()?.map_or(0, |(version, _)| version))
+}
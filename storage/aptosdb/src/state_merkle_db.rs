@@ -58,6 +58,10 @@ impl StateMerkleDb {
         JellyfishMerkleTree::new(self).get_root_hash(version)
     }
 
+    pub fn get_root_hash_option(&self, version: Version) -> Result<Option<HashValue>> {
+        JellyfishMerkleTree::new(self).get_root_hash_option(version)
+    }
+
     pub fn get_leaf_count(&self, version: Version) -> Result<usize> {
         JellyfishMerkleTree::new(self).get_leaf_count(version)
     }
@@ -50,9 +50,10 @@ type StateValueBatch = aptos_jellyfish_merkle::StateValueBatch<StateKey, StateVa
 
 pub const MAX_VALUES_TO_FETCH_FOR_KEY_PREFIX: usize = 10_000;
 // We assume TARGET_SNAPSHOT_INTERVAL_IN_VERSION > block size.
-const MAX_WRITE_SETS_AFTER_SNAPSHOT: LeafCount = buffered_state::TARGET_SNAPSHOT_INTERVAL_IN_VERSION
-    * ((buffered_state::ASYNC_COMMIT_CHANNEL_BUFFER_SIZE + 2) * 2 - 1)
-    * 2;
+pub(crate) const MAX_WRITE_SETS_AFTER_SNAPSHOT: LeafCount =
+    buffered_state::TARGET_SNAPSHOT_INTERVAL_IN_VERSION
+        * ((buffered_state::ASYNC_COMMIT_CHANNEL_BUFFER_SIZE + 2) * 2 - 1)
+        * 2;
 
 #[derive(Debug)]
 pub(crate) struct StateDb {
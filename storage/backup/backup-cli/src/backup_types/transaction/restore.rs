@@ -70,7 +70,7 @@ pub struct TransactionRestoreController {
 }
 
 #[allow(dead_code)]
-struct LoadedChunk {
+pub(crate) struct LoadedChunk {
     pub manifest: TransactionChunk,
     pub txns: Vec<Transaction>,
     pub txn_infos: Vec<TransactionInfo>,
@@ -80,7 +80,7 @@ struct LoadedChunk {
 }
 
 impl LoadedChunk {
-    async fn load(
+    pub(crate) async fn load(
         manifest: TransactionChunk,
         storage: &Arc<dyn BackupStorage>,
         epoch_history: Option<&Arc<EpochHistory>>,
@@ -14,7 +14,10 @@ use backup_cli::{
         state_snapshot::backup::{StateSnapshotBackupController, StateSnapshotBackupOpt},
         transaction::backup::{TransactionBackupController, TransactionBackupOpt},
     },
-    coordinators::backup::{BackupCoordinator, BackupCoordinatorOpt},
+    coordinators::{
+        backup::{BackupCoordinator, BackupCoordinatorOpt},
+        compaction::{CompactionCoordinator, RetentionOpt},
+    },
     metadata::{cache, cache::MetadataCacheOpt},
     storage::StorageOpt,
     utils::{
@@ -30,6 +33,11 @@ enum Command {
     OneShot(OneShotCommand),
     #[structopt(about = "Long running process backing up the chain continuously.")]
     Coordinator(CoordinatorCommand),
+    #[structopt(
+        about = "Compact all metadata files into one, removing backups outside of the retention \
+        window."
+    )]
+    CompactMetadata(CompactMetadataOpt),
 }
 
 #[derive(StructOpt)]
@@ -124,6 +132,27 @@ struct CoordinatorRunOpt {
     storage: StorageOpt,
 }
 
+#[derive(StructOpt)]
+struct CompactMetadataOpt {
+    #[structopt(flatten)]
+    metadata_cache: MetadataCacheOpt,
+
+    #[structopt(flatten)]
+    retention: RetentionOpt,
+
+    #[structopt(
+        long,
+        help = "Only log the backups that would be removed, without changing the storage."
+    )]
+    dry_run: bool,
+
+    #[structopt(flatten)]
+    concurrent_downloads: ConcurrentDownloadsOpt,
+
+    #[structopt(subcommand)]
+    storage: StorageOpt,
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
     main_impl().await.map_err(|e| {
@@ -208,6 +237,17 @@ async fn main_impl() -> Result<()> {
                 .await?;
             }
         },
+        Command::CompactMetadata(opt) => {
+            CompactionCoordinator::new(
+                opt.storage.init_storage().await?,
+                opt.metadata_cache,
+                opt.retention,
+                opt.dry_run,
+                opt.concurrent_downloads.get(),
+            )
+            .run()
+            .await?;
+        }
     }
     Ok(())
 }
@@ -7,6 +7,7 @@ use crate::{
         state_snapshot::backup::{StateSnapshotBackupController, StateSnapshotBackupOpt},
         transaction::backup::{TransactionBackupController, TransactionBackupOpt},
     },
+    coordinators::compaction::{CompactionCoordinator, RetentionOpt},
     metadata,
     metadata::cache::MetadataCacheOpt,
     metrics::backup::{
@@ -48,6 +49,14 @@ pub struct BackupCoordinatorOpt {
     pub transaction_batch_size: usize,
     #[structopt(flatten)]
     pub concurernt_downloads: ConcurrentDownloadsOpt,
+    #[structopt(
+        long,
+        help = "If set, metadata is compacted periodically, keeping this number of the latest \
+        state snapshots and the transactions since the oldest of them. Older backups are removed."
+    )]
+    pub num_state_snapshots_to_keep: Option<usize>,
+    #[structopt(long, default_value = "3600")]
+    pub compaction_interval_secs: u64,
 }
 
 impl BackupCoordinatorOpt {
@@ -63,6 +72,14 @@ impl BackupCoordinatorOpt {
              that's not yet in a transaction backup, resulting in replaying all transactions \
              at restore time."
         );
+        ensure!(
+            self.num_state_snapshots_to_keep != Some(0),
+            "At least one state snapshot must be kept."
+        );
+        ensure!(
+            self.compaction_interval_secs > 0,
+            "Compaction interval must be greater than 0."
+        );
         Ok(())
     }
 }
@@ -75,6 +92,8 @@ pub struct BackupCoordinator {
     state_snapshot_interval: usize,
     transaction_batch_size: usize,
     concurrent_downloads: usize,
+    retention_opt: Option<RetentionOpt>,
+    compaction_interval_secs: u64,
 }
 
 impl BackupCoordinator {
@@ -93,6 +112,12 @@ impl BackupCoordinator {
             state_snapshot_interval: opt.state_snapshot_interval,
             transaction_batch_size: opt.transaction_batch_size,
             concurrent_downloads: opt.concurernt_downloads.get(),
+            retention_opt: opt
+                .num_state_snapshots_to_keep
+                .map(|num_state_snapshots_to_keep| RetentionOpt {
+                    num_state_snapshots_to_keep,
+                }),
+            compaction_interval_secs: opt.compaction_interval_secs,
         }
     }
     pub async fn run(&self) -> Result<()> {
@@ -141,6 +166,14 @@ impl BackupCoordinator {
                 Self::backup_transactions,
             )
             .boxed_local();
+        let compact_metadata = match &self.retention_opt {
+            Some(retention_opt) => {
+                IntervalStream::new(interval(Duration::from_secs(self.compaction_interval_secs)))
+                    .then(move |_| self.try_compact_metadata(retention_opt.clone()))
+                    .boxed_local()
+            }
+            None => stream::pending().boxed_local(),
+        };
 
         info!("Backup coordinator started.");
         let mut all_work = stream::select_all(vec![
@@ -148,6 +181,7 @@ impl BackupCoordinator {
             backup_epoch_endings,
             backup_state_snapshots,
             backup_transactions,
+            compact_metadata,
         ]);
 
         loop {
@@ -180,6 +214,21 @@ impl BackupCoordinator {
         };
     }
 
+    async fn try_compact_metadata(&self, retention_opt: RetentionOpt) {
+        if let Err(e) = CompactionCoordinator::new(
+            Arc::clone(&self.storage),
+            self.metadata_cache_opt.clone(),
+            retention_opt,
+            false, /* dry_run */
+            self.concurrent_downloads,
+        )
+        .run()
+        .await
+        {
+            warn!("Compacting metadata failed: {}. Will retry later.", e);
+        }
+    }
+
     async fn backup_epoch_endings(
         &self,
         mut last_epoch_ending_epoch_in_backup: Option<u64>,
@@ -0,0 +1,229 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    backup_types::{
+        epoch_ending::manifest::EpochEndingBackup, state_snapshot::manifest::StateSnapshotBackup,
+        transaction::manifest::TransactionBackup,
+    },
+    metadata,
+    metadata::{
+        cache::{LoadMetadataLines, MetadataCacheOpt},
+        Metadata,
+    },
+    metrics::compaction::{
+        COMPACTION_COORDINATOR_FAIL_TS, COMPACTION_COORDINATOR_START_TS,
+        COMPACTION_COORDINATOR_SUCC_TS, COMPACTION_EXPIRED_BACKUPS,
+    },
+    storage::{BackupStorage, FileHandle, ShellSafeName},
+    utils::{storage_ext::BackupStorageExt, unix_timestamp_sec},
+};
+use anyhow::{ensure, Result};
+use aptos_logger::prelude::*;
+use rand::random;
+use std::{
+    collections::{BTreeSet, HashSet},
+    convert::TryFrom,
+    sync::Arc,
+};
+use structopt::StructOpt;
+
+#[derive(Clone, StructOpt)]
+pub struct RetentionOpt {
+    #[structopt(
+        long,
+        help = "Number of the latest state snapshots to keep, together with the transactions \
+        since the oldest of them. Older state snapshots and transactions are removed."
+    )]
+    pub num_state_snapshots_to_keep: usize,
+}
+
+/// Combines all metadata files into one, dropping the backups outside of the retention window,
+/// whose files are then removed from the storage.
+pub struct CompactionCoordinator {
+    storage: Arc<dyn BackupStorage>,
+    metadata_cache_opt: MetadataCacheOpt,
+    retention_opt: RetentionOpt,
+    dry_run: bool,
+    concurrent_downloads: usize,
+}
+
+impl CompactionCoordinator {
+    pub fn new(
+        storage: Arc<dyn BackupStorage>,
+        metadata_cache_opt: MetadataCacheOpt,
+        retention_opt: RetentionOpt,
+        dry_run: bool,
+        concurrent_downloads: usize,
+    ) -> Self {
+        Self {
+            storage,
+            metadata_cache_opt,
+            retention_opt,
+            dry_run,
+            concurrent_downloads,
+        }
+    }
+
+    pub async fn run(self) -> Result<()> {
+        info!("Compaction coordinator started.");
+        COMPACTION_COORDINATOR_START_TS.set(unix_timestamp_sec());
+
+        let ret = self.run_impl().await;
+
+        if let Err(e) = &ret {
+            error!(
+                error = ?e,
+                "Compaction coordinator failed."
+            );
+            COMPACTION_COORDINATOR_FAIL_TS.set(unix_timestamp_sec());
+        } else {
+            info!("Compaction coordinator exiting with success.");
+            COMPACTION_COORDINATOR_SUCC_TS.set(unix_timestamp_sec());
+        }
+
+        ret
+    }
+
+    async fn run_impl(self) -> Result<()> {
+        // Listed before the metadata is loaded, so that a file saved in the meantime (by a backup
+        // coordinator, for example) is never removed without its content being compacted.
+        let metadata_files = self.storage.list_metadata_files().await?;
+        let metadata_view = metadata::cache::sync_and_load(
+            &self.metadata_cache_opt,
+            Arc::clone(&self.storage),
+            self.concurrent_downloads,
+        )
+        .await?;
+        let plan = metadata_view.plan_retention(self.retention_opt.num_state_snapshots_to_keep)?;
+        COMPACTION_EXPIRED_BACKUPS.set(plan.expired.len() as i64);
+        info!(
+            num_metadata_files = metadata_files.len(),
+            num_retained_backups = plan.retained.len(),
+            num_expired_backups = plan.expired.len(),
+            oldest_retained_version = ?plan.oldest_retained_version,
+            "Compaction planned."
+        );
+
+        // Find out all the files to remove before touching anything, so a broken manifest fails
+        // the compaction early.
+        let mut expired_files = Vec::new();
+        for meta in &plan.expired {
+            expired_files.extend(self.backup_files(meta).await?);
+        }
+
+        if self.dry_run {
+            for meta in &plan.expired {
+                info!(backup = ?meta, "Dry run, would remove backup.");
+            }
+            info!(
+                num_files_to_remove = expired_files.len(),
+                "Dry run, would compact {} metadata files into one.",
+                metadata_files.len(),
+            );
+            return Ok(());
+        }
+        if metadata_files.len() <= 1 && plan.expired.is_empty() {
+            info!("Nothing to compact.");
+            return Ok(());
+        }
+
+        self.save_compacted_metadata(&metadata_files, &plan.retained)
+            .await?;
+        for file_handle in &metadata_files {
+            self.storage.delete_file(file_handle).await?;
+        }
+        // Only after the metadata referring to them is gone, so an interrupted compaction never
+        // leaves behind a backup with missing files.
+        for file_handle in &expired_files {
+            self.storage.delete_file(file_handle).await?;
+        }
+        info!(
+            num_files_removed = expired_files.len(),
+            "Removed {} backups outside of the retention window.",
+            plan.expired.len(),
+        );
+
+        Ok(())
+    }
+
+    /// Saves all the metadata in a single new file, and verifies it can be read back before the
+    /// files it replaces are removed.
+    async fn save_compacted_metadata(
+        &self,
+        replaced_files: &[FileHandle],
+        metadata: &[Metadata],
+    ) -> Result<()> {
+        let name = ShellSafeName::try_from(format!(
+            "compacted_{}.{:04x}.meta",
+            unix_timestamp_sec(),
+            random::<u16>()
+        ))?;
+        let lines = metadata
+            .iter()
+            .map(Metadata::to_text_line)
+            .collect::<Result<Vec<_>>>()?;
+        self.storage.save_metadata_lines(&name, &lines).await?;
+
+        let replaced_files: HashSet<_> = replaced_files.iter().collect();
+        let mut saved = BTreeSet::new();
+        for file_handle in self.storage.list_metadata_files().await? {
+            if !replaced_files.contains(&file_handle) {
+                saved.extend(
+                    self.storage
+                        .open_for_read(&file_handle)
+                        .await?
+                        .load_metadata_lines()
+                        .await?,
+                );
+            }
+        }
+        let num_missing = metadata.iter().filter(|m| !saved.contains(m)).count();
+        ensure!(
+            num_missing == 0,
+            "{} metadata entries missing after saving the compacted metadata file {}.",
+            num_missing,
+            name.as_ref(),
+        );
+
+        Ok(())
+    }
+
+    /// All files of a backup, the manifest being the last.
+    async fn backup_files(&self, metadata: &Metadata) -> Result<Vec<FileHandle>> {
+        let manifest_handle = metadata.manifest();
+        let mut files: Vec<FileHandle> = match metadata {
+            Metadata::EpochEndingBackup(_) => {
+                let manifest: EpochEndingBackup =
+                    self.storage.load_json_file(manifest_handle).await?;
+                manifest
+                    .chunks
+                    .into_iter()
+                    .map(|chunk| chunk.ledger_infos)
+                    .collect()
+            }
+            Metadata::StateSnapshotBackup(_) => {
+                let manifest: StateSnapshotBackup =
+                    self.storage.load_json_file(manifest_handle).await?;
+                manifest
+                    .chunks
+                    .into_iter()
+                    .flat_map(|chunk| vec![chunk.blobs, chunk.proof])
+                    .chain(std::iter::once(manifest.proof))
+                    .collect()
+            }
+            Metadata::TransactionBackup(_) => {
+                let manifest: TransactionBackup =
+                    self.storage.load_json_file(manifest_handle).await?;
+                manifest
+                    .chunks
+                    .into_iter()
+                    .flat_map(|chunk| vec![chunk.transactions, chunk.proof])
+                    .collect()
+            }
+        };
+        files.push(manifest_handle.to_string());
+
+        Ok(files)
+    }
+}
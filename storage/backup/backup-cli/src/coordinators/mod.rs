@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 pub mod backup;
+pub mod compaction;
 pub mod replay_verify;
 pub mod restore;
 pub mod verify;
@@ -3,22 +3,31 @@
 
 use crate::{
     backup_types::{
-        epoch_ending::restore::EpochHistoryRestoreController,
+        epoch_ending::{manifest::EpochEndingBackup, restore::EpochHistoryRestoreController},
         state_snapshot::restore::{StateSnapshotRestoreController, StateSnapshotRestoreOpt},
-        transaction::restore::TransactionRestoreBatchController,
+        transaction::{
+            manifest::TransactionBackup,
+            restore::{LoadedChunk, TransactionRestoreBatchController},
+        },
     },
     metadata,
-    metadata::{cache::MetadataCacheOpt, TransactionBackupMeta},
+    metadata::{cache::MetadataCacheOpt, view::MetadataView, TransactionBackupMeta},
     metrics::restore::{
         COORDINATOR_FAIL_TS, COORDINATOR_START_TS, COORDINATOR_SUCC_TS, COORDINATOR_TARGET_VERSION,
     },
     storage::BackupStorage,
-    utils::{unix_timestamp_sec, GlobalRestoreOptions, RestoreRunMode},
+    utils::{
+        read_record_bytes::ReadRecordBytes, storage_ext::BackupStorageExt, unix_timestamp_sec,
+        GlobalRestoreOptions, RestoreRunMode,
+    },
 };
-use anyhow::{bail, Result};
+use anyhow::{anyhow, bail, ensure, Result};
 use aptos_logger::prelude::*;
-use aptos_types::transaction::Version;
-use std::sync::Arc;
+use aptos_types::{
+    account_config::NewBlockEvent, block_metadata::new_block_event_key,
+    contract_event::ContractEvent, ledger_info::LedgerInfoWithSignatures, transaction::Version,
+};
+use std::{convert::TryFrom, sync::Arc};
 use structopt::StructOpt;
 
 #[derive(StructOpt)]
@@ -39,6 +48,13 @@ pub struct RestoreCoordinatorOpt {
     pub ledger_history_start_version: Version,
     #[structopt(long, help = "Skip restoring epoch ending info, used for debugging.")]
     pub skip_epoch_endings: bool,
+    #[structopt(
+        long,
+        help = "Restore to the last version committed before this timestamp, in microseconds \
+        since the unix epoch, instead of to --target-version. The version is resolved from the \
+        block timestamps in the backups."
+    )]
+    pub target_timestamp: Option<u64>,
 }
 
 pub struct RestoreCoordinator {
@@ -48,6 +64,7 @@ pub struct RestoreCoordinator {
     replay_all: bool,
     ledger_history_start_version: Version,
     skip_epoch_endings: bool,
+    target_timestamp: Option<u64>,
 }
 
 impl RestoreCoordinator {
@@ -63,6 +80,7 @@ impl RestoreCoordinator {
             replay_all: opt.replay_all,
             ledger_history_start_version: opt.ledger_history_start_version,
             skip_epoch_endings: opt.skip_epoch_endings,
+            target_timestamp: opt.target_timestamp,
         }
     }
 
@@ -86,13 +104,26 @@ impl RestoreCoordinator {
         ret
     }
 
-    async fn run_impl(self) -> Result<()> {
+    async fn run_impl(mut self) -> Result<()> {
         let metadata_view = metadata::cache::sync_and_load(
             &self.metadata_cache_opt,
             Arc::clone(&self.storage),
             self.global_opt.concurrent_downloads,
         )
         .await?;
+        if let Some(timestamp) = self.target_timestamp {
+            ensure!(
+                self.target_version() == Version::max_value(),
+                "Target version and target timestamp can't be both set."
+            );
+            let version =
+                get_last_version_before_timestamp(&self.storage, &metadata_view, timestamp).await?;
+            info!(
+                "Restoring to version {}, the last one before timestamp {}.",
+                version, timestamp,
+            );
+            self.global_opt.target_version = version;
+        }
 
         let mut transactions =
             metadata_view.select_transaction_backups(0, self.target_version())?;
@@ -107,6 +138,15 @@ impl RestoreCoordinator {
             Some(b) => b.version + 1,
             None => 0,
         };
+        if let Some(first) = transactions.first() {
+            ensure!(
+                first.first_version <= replay_transactions_from_version,
+                "Transactions to replay from version {} are missing, the oldest transaction in \
+                 backup is at version {}, others could have been removed by the retention policy.",
+                replay_transactions_from_version,
+                first.first_version,
+            );
+        }
         COORDINATOR_TARGET_VERSION.set(actual_target_version as i64);
         info!("Planned to restore to version {}.", actual_target_version);
 
@@ -219,3 +259,141 @@ impl RestoreCoordinator {
         }
     }
 }
+
+/// Resolves the version of the last transaction committed before `timestamp` from the backups,
+/// the same way `DbReader::get_last_version_before_timestamp()` does against a DB: it's the
+/// version right before the first block proposed at or after the timestamp.
+async fn get_last_version_before_timestamp(
+    storage: &Arc<dyn BackupStorage>,
+    metadata_view: &MetadataView,
+    timestamp: u64,
+) -> Result<Version> {
+    // The epoch ending LedgerInfos carry the timestamps of the last blocks in the epochs, which
+    // narrows the search down to the transactions in a single epoch.
+    let mut first_version = 0;
+    let mut last_version = Version::max_value();
+    'epochs: for backup in metadata_view.select_epoch_ending_backups(Version::max_value())? {
+        let manifest: EpochEndingBackup = storage.load_json_file(&backup.manifest).await?;
+        for chunk in manifest.chunks {
+            let mut file = storage.open_for_read(&chunk.ledger_infos).await?;
+            while let Some(record_bytes) = file.read_record_bytes().await? {
+                let li: LedgerInfoWithSignatures = bcs::from_bytes(&record_bytes)?;
+                if li.ledger_info().timestamp_usecs() < timestamp {
+                    first_version = li.ledger_info().version() + 1;
+                } else {
+                    last_version = li.ledger_info().version();
+                    break 'epochs;
+                }
+            }
+        }
+    }
+
+    let transaction_backups =
+        metadata_view.select_transaction_backups(first_version, last_version)?;
+    if let Some(backup) = transaction_backups.first() {
+        ensure!(
+            backup.first_version <= first_version,
+            "Transactions from version {} are missing, can't resolve timestamp {}.",
+            first_version,
+            timestamp,
+        );
+    }
+    for backup in transaction_backups {
+        let manifest: TransactionBackup = storage.load_json_file(&backup.manifest).await?;
+        for chunk in manifest.chunks {
+            if chunk.last_version < first_version || chunk.first_version > last_version {
+                continue;
+            }
+            let chunk_first_version = chunk.first_version;
+            let chunk = LoadedChunk::load(chunk, storage, None /* epoch_history */).await?;
+            if let Some(version) = last_version_before_block_at(
+                (chunk_first_version..).zip(chunk.event_vecs.iter()),
+                timestamp,
+            )? {
+                return Ok(version);
+            }
+        }
+    }
+
+    bail!(
+        "No new block found beyond timestamp {}, so can't determine the last version before it.",
+        timestamp,
+    )
+}
+
+/// Looks for the first new block event at or after `timestamp`, returning the version before it.
+fn last_version_before_block_at<'a>(
+    event_vecs: impl Iterator<Item = (Version, &'a Vec<ContractEvent>)>,
+    timestamp: u64,
+) -> Result<Option<Version>> {
+    let new_block_event_key = new_block_event_key();
+    for (version, events) in event_vecs {
+        for event in events.iter().filter(|e| *e.key() == new_block_event_key) {
+            let new_block_event = NewBlockEvent::try_from(event)?;
+            if new_block_event.proposed_time() >= timestamp {
+                ensure!(
+                    event.sequence_number() > 0,
+                    "First block started at or after timestamp {}.",
+                    timestamp,
+                );
+                return version
+                    .checked_sub(1)
+                    .map(Some)
+                    .ok_or_else(|| anyhow!("A block with non-zero seq num started at version 0."));
+            }
+        }
+    }
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::coordinators::restore::last_version_before_block_at;
+    use aptos_types::{
+        account_address::AccountAddress, account_config::NewBlockEvent,
+        block_metadata::new_block_event_key, contract_event::ContractEvent, event::EventKey,
+    };
+    use move_deps::move_core_types::{language_storage::TypeTag, move_resource::MoveStructType};
+
+    fn new_block_event(key: EventKey, seq_num: u64, timestamp: u64) -> ContractEvent {
+        let event = NewBlockEvent::new(
+            0, /* epoch */
+            seq_num,
+            seq_num,
+            vec![],
+            AccountAddress::random(),
+            vec![],
+            timestamp,
+        );
+        ContractEvent::new(
+            key,
+            seq_num,
+            TypeTag::Struct(NewBlockEvent::struct_tag()),
+            bcs::to_bytes(&event).unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_last_version_before_block_at() {
+        let key = new_block_event_key();
+        let other_key = EventKey::new(0, AccountAddress::random());
+        let event_vecs = vec![
+            vec![new_block_event(key, 0, 0)],
+            vec![],
+            vec![new_block_event(other_key, 0, 500)],
+            vec![new_block_event(key, 1, 100)],
+            vec![],
+            vec![new_block_event(key, 2, 200)],
+            vec![],
+        ];
+        let find =
+            |timestamp| last_version_before_block_at((0..).zip(event_vecs.iter()), timestamp);
+
+        assert!(find(0).is_err());
+        assert_eq!(find(1).unwrap(), Some(2));
+        assert_eq!(find(100).unwrap(), Some(2));
+        assert_eq!(find(101).unwrap(), Some(4));
+        assert_eq!(find(200).unwrap(), Some(4));
+        assert_eq!(find(201).unwrap(), None);
+    }
+}
@@ -33,7 +33,7 @@ static TEMP_METADATA_CACHE_DIR: Lazy<TempPath> = Lazy::new(|| {
     dir
 });
 
-#[derive(StructOpt)]
+#[derive(Clone, StructOpt)]
 pub struct MetadataCacheOpt {
     #[structopt(
         long = "metadata-cache-dir",
@@ -183,7 +183,7 @@ impl FileHandleHash for FileHandle {
 }
 
 #[async_trait]
-trait LoadMetadataLines {
+pub(crate) trait LoadMetadataLines {
     async fn load_metadata_lines(&mut self) -> Result<Vec<Metadata>>;
 }
 
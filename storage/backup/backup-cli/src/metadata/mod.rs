@@ -4,13 +4,13 @@
 pub mod cache;
 pub mod view;
 
-use crate::storage::{FileHandle, ShellSafeName, TextLine};
+use crate::storage::{FileHandle, FileHandleRef, ShellSafeName, TextLine};
 use anyhow::Result;
 use aptos_types::transaction::Version;
 use serde::{Deserialize, Serialize};
 use std::convert::TryInto;
 
-#[derive(Deserialize, Serialize)]
+#[derive(Clone, Debug, Deserialize, Serialize, Eq, PartialEq, Ord, PartialOrd)]
 #[allow(clippy::enum_variant_names)] // to introduce: BackupperId, etc
 pub(crate) enum Metadata {
     EpochEndingBackup(EpochEndingBackupMeta),
@@ -65,12 +65,20 @@ impl Metadata {
         .unwrap()
     }
 
+    pub fn manifest(&self) -> &FileHandleRef {
+        match self {
+            Self::EpochEndingBackup(e) => &e.manifest,
+            Self::StateSnapshotBackup(s) => &s.manifest,
+            Self::TransactionBackup(t) => &t.manifest,
+        }
+    }
+
     pub fn to_text_line(&self) -> Result<TextLine> {
         TextLine::new(&serde_json::to_string(self)?)
     }
 }
 
-#[derive(Clone, Deserialize, Serialize, Eq, PartialEq, Ord, PartialOrd)]
+#[derive(Clone, Debug, Deserialize, Serialize, Eq, PartialEq, Ord, PartialOrd)]
 pub struct EpochEndingBackupMeta {
     pub first_epoch: u64,
     pub last_epoch: u64,
@@ -79,13 +87,13 @@ pub struct EpochEndingBackupMeta {
     pub manifest: FileHandle,
 }
 
-#[derive(Clone, Deserialize, Serialize, Eq, PartialEq, Ord, PartialOrd)]
+#[derive(Clone, Debug, Deserialize, Serialize, Eq, PartialEq, Ord, PartialOrd)]
 pub struct StateSnapshotBackupMeta {
     pub version: Version,
     pub manifest: FileHandle,
 }
 
-#[derive(Clone, Deserialize, Serialize, Eq, PartialEq, Ord, PartialOrd)]
+#[derive(Clone, Debug, Deserialize, Serialize, Eq, PartialEq, Ord, PartialOrd)]
 pub struct TransactionBackupMeta {
     pub first_version: Version,
     pub last_version: Version,
@@ -53,8 +53,15 @@ impl MetadataView {
         target_version: Version,
     ) -> Result<Vec<TransactionBackupMeta>> {
         // This can be more flexible, but for now we assume and check backups are continuous in
-        // range (which is always true when we backup from a single backup coordinator)
-        let mut next_ver = 0;
+        // range (which is always true when we backup from a single backup coordinator). They
+        // don't necessarily start from version 0 though, since the oldest ones can be expired by
+        // the retention policy, see `plan_retention()`.
+        let mut next_ver = self
+            .transaction_backups
+            .iter()
+            .map(|t| t.first_version)
+            .min()
+            .unwrap_or(0);
         let mut res = Vec::new();
         for backup in self.transaction_backups.iter().sorted() {
             if backup.first_version > target_version {
@@ -103,6 +110,99 @@ impl MetadataView {
 
         Ok(res)
     }
+
+    /// Splits all backups into the ones to keep and the ones to remove, under a retention policy
+    /// of keeping the latest `num_state_snapshots` state snapshots, as well as the transactions
+    /// since the oldest of them, so that any version since that snapshot can still be restored.
+    /// Epoch ending backups are always kept, since they are needed to verify everything else.
+    /// Fails if what's kept turns out not to be restorable.
+    pub(crate) fn plan_retention(&self, num_state_snapshots: usize) -> Result<RetentionPlan> {
+        ensure!(
+            num_state_snapshots > 0,
+            "At least one state snapshot must be kept."
+        );
+        self.select_epoch_ending_backups(Version::max_value())?;
+
+        let oldest_retained_version = match self
+            .state_snapshot_backups
+            .iter()
+            .map(|s| s.version)
+            .sorted()
+            .rev()
+            .dedup()
+            .nth(num_state_snapshots - 1)
+        {
+            Some(version) => version,
+            None => {
+                // Not that many snapshots yet, nothing to remove.
+                return Ok(RetentionPlan {
+                    oldest_retained_version: None,
+                    retained: self.all_metadata(),
+                    expired: Vec::new(),
+                });
+            }
+        };
+
+        let mut retained = Vec::new();
+        let mut expired = Vec::new();
+        for e in &self.epoch_ending_backups {
+            retained.push(Metadata::EpochEndingBackup(e.clone()));
+        }
+        for s in &self.state_snapshot_backups {
+            let meta = Metadata::StateSnapshotBackup(s.clone());
+            if s.version < oldest_retained_version {
+                expired.push(meta);
+            } else {
+                retained.push(meta);
+            }
+        }
+        let retained_transaction_backups =
+            self.select_transaction_backups(oldest_retained_version, Version::max_value())?;
+        if let Some(first) = retained_transaction_backups.first() {
+            ensure!(
+                first.first_version <= oldest_retained_version + 1,
+                "Transactions since the oldest retained state snapshot at version {} are \
+                 missing, expecting version {}, got {}.",
+                oldest_retained_version,
+                oldest_retained_version + 1,
+                first.first_version,
+            );
+        }
+        for t in &self.transaction_backups {
+            let meta = Metadata::TransactionBackup(t.clone());
+            if t.last_version < oldest_retained_version {
+                expired.push(meta);
+            } else {
+                retained.push(meta);
+            }
+        }
+
+        Ok(RetentionPlan {
+            oldest_retained_version: Some(oldest_retained_version),
+            retained,
+            expired,
+        })
+    }
+
+    pub(crate) fn all_metadata(&self) -> Vec<Metadata> {
+        self.epoch_ending_backups
+            .iter()
+            .cloned()
+            .map(Metadata::EpochEndingBackup)
+            .chain(
+                self.state_snapshot_backups
+                    .iter()
+                    .cloned()
+                    .map(Metadata::StateSnapshotBackup),
+            )
+            .chain(
+                self.transaction_backups
+                    .iter()
+                    .cloned()
+                    .map(Metadata::TransactionBackup),
+            )
+            .collect()
+    }
 }
 
 impl From<Vec<Metadata>> for MetadataView {
@@ -119,6 +219,15 @@ impl From<Vec<Metadata>> for MetadataView {
             }
         }
 
+        // The same metadata can be saved in multiple files, for example when a compaction
+        // didn't finish removing the files it had combined.
+        epoch_ending_backups.sort();
+        epoch_ending_backups.dedup();
+        state_snapshot_backups.sort();
+        state_snapshot_backups.dedup();
+        transaction_backups.sort();
+        transaction_backups.dedup();
+
         Self {
             epoch_ending_backups,
             state_snapshot_backups,
@@ -127,6 +236,14 @@ impl From<Vec<Metadata>> for MetadataView {
     }
 }
 
+/// Result of `MetadataView::plan_retention()`.
+pub(crate) struct RetentionPlan {
+    /// Version of the oldest state snapshot kept, `None` if nothing is to be removed.
+    pub oldest_retained_version: Option<Version>,
+    pub retained: Vec<Metadata>,
+    pub expired: Vec<Metadata>,
+}
+
 pub struct BackupStorageState {
     pub latest_epoch_ending_epoch: Option<u64>,
     pub latest_state_snapshot_version: Option<Version>,
@@ -175,3 +292,118 @@ impl FromStr for BackupStorageState {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::metadata::{view::MetadataView, Metadata};
+    use aptos_types::transaction::Version;
+
+    fn epoch_ending(first_epoch: u64, last_epoch: u64) -> Metadata {
+        Metadata::new_epoch_ending_backup(
+            first_epoch,
+            last_epoch,
+            first_epoch * 100,
+            last_epoch * 100 + 99,
+            format!("epoch_ending_{}/manifest", first_epoch),
+        )
+    }
+
+    fn state_snapshot(version: Version) -> Metadata {
+        Metadata::new_state_snapshot_backup(version, format!("state_snapshot_{}/manifest", version))
+    }
+
+    fn transaction(first_version: Version, last_version: Version) -> Metadata {
+        Metadata::new_transaction_backup(
+            first_version,
+            last_version,
+            format!("transaction_{}/manifest", first_version),
+        )
+    }
+
+    fn view() -> MetadataView {
+        vec![
+            epoch_ending(0, 0),
+            epoch_ending(1, 3),
+            state_snapshot(0),
+            state_snapshot(200),
+            state_snapshot(400),
+            transaction(0, 0),
+            transaction(1, 100),
+            transaction(101, 200),
+            transaction(201, 300),
+            transaction(301, 400),
+            transaction(401, 500),
+            // duplicated in multiple metadata files
+            transaction(401, 500),
+        ]
+        .into()
+    }
+
+    #[test]
+    fn test_plan_retention() {
+        let view = view();
+
+        let plan = view.plan_retention(2).unwrap();
+        assert_eq!(plan.oldest_retained_version, Some(200));
+        assert_eq!(
+            plan.expired,
+            vec![state_snapshot(0), transaction(0, 0), transaction(1, 100)]
+        );
+        assert_eq!(
+            plan.retained,
+            vec![
+                epoch_ending(0, 0),
+                epoch_ending(1, 3),
+                state_snapshot(200),
+                state_snapshot(400),
+                transaction(101, 200),
+                transaction(201, 300),
+                transaction(301, 400),
+                transaction(401, 500),
+            ]
+        );
+
+        // What's left can be compacted again, resulting in the same.
+        let compacted: MetadataView = plan.retained.clone().into();
+        let replan = compacted.plan_retention(2).unwrap();
+        assert_eq!(replan.retained, plan.retained);
+        assert!(replan.expired.is_empty());
+        assert_eq!(
+            compacted.select_transaction_backups(201, 400).unwrap(),
+            view.select_transaction_backups(201, 400).unwrap(),
+        );
+
+        // Not enough snapshots to expire anything.
+        let plan = view.plan_retention(4).unwrap();
+        assert_eq!(plan.oldest_retained_version, None);
+        assert_eq!(plan.retained, view.all_metadata());
+        assert!(plan.expired.is_empty());
+
+        assert!(view.plan_retention(0).is_err());
+    }
+
+    #[test]
+    fn test_plan_retention_not_restorable() {
+        // Transactions right after the oldest retained snapshot are missing.
+        let view: MetadataView = vec![
+            epoch_ending(0, 0),
+            state_snapshot(100),
+            state_snapshot(200),
+            transaction(201, 300),
+        ]
+        .into();
+        assert!(view.plan_retention(1).is_ok());
+        assert!(view.plan_retention(2).is_err());
+
+        // Transactions aren't continuous.
+        let view: MetadataView = vec![
+            epoch_ending(0, 0),
+            state_snapshot(0),
+            state_snapshot(200),
+            transaction(0, 100),
+            transaction(151, 300),
+        ]
+        .into();
+        assert!(view.plan_retention(1).is_err());
+    }
+}
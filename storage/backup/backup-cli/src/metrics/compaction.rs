@@ -0,0 +1,37 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use aptos_secure_push_metrics::{register_int_gauge, IntGauge};
+use once_cell::sync::Lazy;
+
+pub static COMPACTION_COORDINATOR_START_TS: Lazy<IntGauge> = Lazy::new(|| {
+    register_int_gauge!(
+        "aptos_db_backup_compaction_coordinator_start_timestamp_s",
+        "Timestamp when the compaction coordinator starts."
+    )
+    .unwrap()
+});
+
+pub static COMPACTION_COORDINATOR_SUCC_TS: Lazy<IntGauge> = Lazy::new(|| {
+    register_int_gauge!(
+        "aptos_db_backup_compaction_coordinator_succeed_timestamp_s",
+        "Timestamp when the compaction coordinator succeeds."
+    )
+    .unwrap()
+});
+
+pub static COMPACTION_COORDINATOR_FAIL_TS: Lazy<IntGauge> = Lazy::new(|| {
+    register_int_gauge!(
+        "aptos_db_backup_compaction_coordinator_fail_timestamp_s",
+        "Timestamp when the compaction coordinator fails."
+    )
+    .unwrap()
+});
+
+pub static COMPACTION_EXPIRED_BACKUPS: Lazy<IntGauge> = Lazy::new(|| {
+    register_int_gauge!(
+        "aptos_db_backup_compaction_expired_backups",
+        "Number of backups outside of the retention window found by the last compaction."
+    )
+    .unwrap()
+});
@@ -2,6 +2,8 @@
 // SPDX-License-Identifier: Apache-2.0
 
 pub mod backup;
+pub mod compaction;
 pub mod metadata;
 pub mod restore;
+pub mod storage;
 pub mod verify;
@@ -0,0 +1,14 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use aptos_secure_push_metrics::{register_int_counter_vec, IntCounterVec};
+use once_cell::sync::Lazy;
+
+pub static S3_REQUESTS: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "aptos_backup_storage_s3_requests",
+        "Number of requests made to the S3 backup storage, by operation and result.",
+        &["operation", "result"]
+    )
+    .unwrap()
+});
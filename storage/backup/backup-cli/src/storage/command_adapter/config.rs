@@ -62,11 +62,17 @@ pub struct Commands {
     /// Command line to save a line of metadata
     /// input env vars:
     ///     $FILE_NAME
-    /// stdin will be fed with a line of text with a trailing newline.
+    /// stdin will be fed with a line of text with a trailing newline, or multiple such lines when
+    /// metadata files are being compacted.
     pub save_metadata_line: String,
     /// Command line to list all existing metadata file handles.
     /// expected stdout to stream out lines of file handles.
     pub list_metadata_files: String,
+    /// Command line to delete a file, needed only to compact metadata and remove backups outside
+    /// of the retention window.
+    /// input env vars:
+    ///     $FILE_HANDLE
+    pub delete_file: Option<String>,
 }
 
 #[derive(Clone, Default, Deserialize)]
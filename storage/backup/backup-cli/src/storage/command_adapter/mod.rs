@@ -18,7 +18,7 @@ use crate::{
     },
     utils::error_notes::ErrorNotes,
 };
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use async_trait::async_trait;
 use std::path::PathBuf;
 use structopt::StructOpt;
@@ -143,4 +143,36 @@ impl BackupStorage for CommandAdapter {
             .err_notes((file!(), line!(), &buf))?;
         Ok(buf.lines().map(str::to_string).collect())
     }
+
+    async fn save_metadata_lines(&self, name: &ShellSafeName, lines: &[TextLine]) -> Result<()> {
+        let mut child = self
+            .cmd(
+                &self.config.commands.save_metadata_line,
+                vec![EnvVar::file_name(name.to_string())],
+            )
+            .spawn()?;
+
+        for line in lines {
+            child
+                .stdin()
+                .write_all(line.as_ref().as_bytes())
+                .await
+                .err_notes(name)?;
+        }
+        child.join().await?;
+        Ok(())
+    }
+
+    async fn delete_file(&self, file_handle: &FileHandleRef) -> Result<()> {
+        let cmd = self
+            .config
+            .commands
+            .delete_file
+            .as_ref()
+            .ok_or_else(|| anyhow!("The delete_file command is not configured."))?;
+        self.cmd(cmd, vec![EnvVar::file_handle(file_handle.to_string())])
+            .spawn()?
+            .join()
+            .await
+    }
 }
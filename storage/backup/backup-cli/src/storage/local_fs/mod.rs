@@ -15,7 +15,7 @@ use async_trait::async_trait;
 use std::path::{Path, PathBuf};
 use structopt::StructOpt;
 use tokio::{
-    fs::{create_dir, create_dir_all, read_dir, OpenOptions},
+    fs::{create_dir, create_dir_all, read_dir, remove_dir, remove_file, OpenOptions},
     io::{AsyncRead, AsyncWrite, AsyncWriteExt},
 };
 
@@ -122,4 +122,37 @@ impl BackupStorage for LocalFs {
         }
         Ok(res)
     }
+
+    async fn save_metadata_lines(&self, name: &ShellSafeName, lines: &[TextLine]) -> Result<()> {
+        let dir = self.metadata_dir();
+        create_dir_all(&dir).await.err_notes(name)?; // in case not yet created
+
+        let path = dir.join(name.as_ref());
+        let mut file = OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&path)
+            .await
+            .err_notes(&path)?;
+        for line in lines {
+            file.write_all(line.as_ref().as_bytes())
+                .await
+                .err_notes(&path)?;
+        }
+
+        Ok(())
+    }
+
+    async fn delete_file(&self, file_handle: &FileHandleRef) -> Result<()> {
+        let path = self.dir.join(file_handle);
+        remove_file(&path).await.err_notes(&path)?;
+
+        // Remove the backup folder too once it's empty, which fails harmlessly while it's not.
+        if let Some(parent) = path.parent() {
+            if parent != self.dir && parent != self.metadata_dir() {
+                let _ = remove_dir(parent).await;
+            }
+        }
+        Ok(())
+    }
 }
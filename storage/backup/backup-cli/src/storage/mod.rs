@@ -3,6 +3,7 @@
 
 pub mod command_adapter;
 pub mod local_fs;
+pub mod s3;
 
 #[cfg(test)]
 mod test_util;
@@ -12,6 +13,7 @@ mod tests;
 use crate::storage::{
     command_adapter::{CommandAdapter, CommandAdapterOpt},
     local_fs::{LocalFs, LocalFsOpt},
+    s3::{S3Opt, S3},
 };
 use anyhow::{ensure, Result};
 use async_trait::async_trait;
@@ -167,6 +169,13 @@ pub trait BackupStorage: Send + Sync {
     ///   2. But the cache does expect the content stays the same for a file handle, so when
     /// reorganising metadata files, give them new unique names.
     async fn list_metadata_files(&self) -> Result<Vec<FileHandle>>;
+    /// Asks to save multiple metadata entries in a single metadata file, used when combining
+    /// existing metadata files. Same as `save_metadata_line()` otherwise.
+    async fn save_metadata_lines(&self, name: &ShellSafeName, lines: &[TextLine]) -> Result<()>;
+    /// Deletes a file, either one referred to in a manifest or a metadata file. Only used to
+    /// remove backups outside of the retention window, so a storage which can't delete is
+    /// expected to return an error instead.
+    async fn delete_file(&self, file_handle: &FileHandleRef) -> Result<()>;
 }
 
 #[derive(StructOpt)]
@@ -175,6 +184,8 @@ pub enum StorageOpt {
     LocalFs(LocalFsOpt),
     #[structopt(about = "Select the CommandAdapter backup store.")]
     CommandAdapter(CommandAdapterOpt),
+    #[structopt(about = "Select the S3 (or S3 compatible) backup store.")]
+    S3(S3Opt),
 }
 
 impl StorageOpt {
@@ -182,6 +193,7 @@ impl StorageOpt {
         Ok(match self {
             StorageOpt::LocalFs(opt) => Arc::new(LocalFs::new_with_opt(opt)),
             StorageOpt::CommandAdapter(opt) => Arc::new(CommandAdapter::new_with_opt(opt).await?),
+            StorageOpt::S3(opt) => Arc::new(S3::new_with_opt(opt)?),
         })
     }
 }
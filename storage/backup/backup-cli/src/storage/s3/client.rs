@@ -0,0 +1,443 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{metrics::storage::S3_REQUESTS, utils::error_notes::ErrorNotes};
+use anyhow::{anyhow, ensure, Result};
+use aptos_logger::prelude::*;
+use bytes::Bytes;
+use regex::Regex;
+use reqwest::{header::AUTHORIZATION, Method, Response, StatusCode, Url};
+use ring::{digest, hmac};
+use std::time::Duration;
+
+const INITIAL_BACKOFF_MS: u64 = 100;
+const MAX_BACKOFF_MS: u64 = 10_000;
+
+pub(super) struct S3Credentials {
+    pub access_key_id: String,
+    pub secret_access_key: String,
+    pub session_token: Option<String>,
+}
+
+/// A minimal client for the S3 REST API, covering what the backup storage needs. Requests are
+/// signed with AWS Signature Version 4, and use path-style addressing (i.e. the bucket is the
+/// first path segment), which is what S3-compatible stores generally support.
+pub(super) struct S3Client {
+    client: reqwest::Client,
+    /// "http" or "https"
+    scheme: String,
+    /// Host and (non-default) port of the endpoint, which is signed as the Host header.
+    host: String,
+    bucket: String,
+    region: String,
+    credentials: S3Credentials,
+    /// How many times to retry a request failed with a network error, a 5xx or a 429.
+    max_retries: usize,
+}
+
+impl S3Client {
+    pub fn new(
+        endpoint: &str,
+        bucket: String,
+        region: String,
+        credentials: S3Credentials,
+        max_retries: usize,
+    ) -> Result<Self> {
+        let url = Url::parse(endpoint).err_notes(endpoint)?;
+        ensure!(
+            url.path() == "/" && url.query().is_none(),
+            "S3 endpoint can't have a path or query: {}",
+            endpoint,
+        );
+        let host = url
+            .host_str()
+            .ok_or_else(|| anyhow!("S3 endpoint has no host: {}", endpoint))?;
+        let host = match url.port() {
+            Some(port) => format!("{}:{}", host, port),
+            None => host.to_string(),
+        };
+
+        Ok(Self {
+            client: reqwest::Client::builder().no_proxy().build()?,
+            scheme: url.scheme().to_string(),
+            host,
+            bucket,
+            region,
+            credentials,
+            max_retries,
+        })
+    }
+
+    pub async fn put_object(&self, key: &str, body: Bytes) -> Result<()> {
+        self.send("put_object", Method::PUT, key, &[], body).await?;
+        Ok(())
+    }
+
+    pub async fn delete_object(&self, key: &str) -> Result<()> {
+        self.send("delete_object", Method::DELETE, key, &[], Bytes::new())
+            .await?;
+        Ok(())
+    }
+
+    pub async fn get_object(&self, key: &str) -> Result<Response> {
+        self.send("get_object", Method::GET, key, &[], Bytes::new())
+            .await
+    }
+
+    /// Lists the keys of all objects under `prefix`, following continuation tokens.
+    pub async fn list_objects(&self, prefix: &str) -> Result<Vec<String>> {
+        let mut keys = Vec::new();
+        let mut continuation_token = None;
+        loop {
+            let mut query = vec![
+                ("list-type", "2".to_string()),
+                ("prefix", prefix.to_string()),
+            ];
+            if let Some(token) = continuation_token.take() {
+                query.push(("continuation-token", token));
+            }
+            let body = self
+                .send("list_objects", Method::GET, "", &query, Bytes::new())
+                .await?
+                .text()
+                .await?;
+            keys.extend(xml_elements(&body, "Key"));
+            if xml_elements(&body, "IsTruncated")
+                .first()
+                .map(String::as_str)
+                != Some("true")
+            {
+                break;
+            }
+            continuation_token = Some(
+                xml_elements(&body, "NextContinuationToken")
+                    .pop()
+                    .ok_or_else(|| anyhow!("Truncated listing without continuation token."))?,
+            );
+        }
+        Ok(keys)
+    }
+
+    /// Returns the upload ID identifying the upload in succeeding requests.
+    pub async fn create_multipart_upload(&self, key: &str) -> Result<String> {
+        let body = self
+            .send(
+                "create_multipart_upload",
+                Method::POST,
+                key,
+                &[("uploads", String::new())],
+                Bytes::new(),
+            )
+            .await?
+            .text()
+            .await?;
+        xml_elements(&body, "UploadId")
+            .pop()
+            .ok_or_else(|| anyhow!("No UploadId in response: {}", body))
+    }
+
+    /// Returns the ETag of the part, which is needed to complete the upload. Part numbers start
+    /// from 1.
+    pub async fn upload_part(
+        &self,
+        key: &str,
+        upload_id: &str,
+        part_number: usize,
+        body: Bytes,
+    ) -> Result<String> {
+        let response = self
+            .send(
+                "upload_part",
+                Method::PUT,
+                key,
+                &[
+                    ("partNumber", part_number.to_string()),
+                    ("uploadId", upload_id.to_string()),
+                ],
+                body,
+            )
+            .await?;
+        let etag = response
+            .headers()
+            .get(reqwest::header::ETAG)
+            .ok_or_else(|| anyhow!("No ETag for part {} of {}.", part_number, key))?;
+        Ok(etag.to_str()?.to_string())
+    }
+
+    /// `etags` are those of parts 1 to N, in order.
+    pub async fn complete_multipart_upload(
+        &self,
+        key: &str,
+        upload_id: &str,
+        etags: &[String],
+    ) -> Result<()> {
+        let parts: String = etags
+            .iter()
+            .enumerate()
+            .map(|(idx, etag)| {
+                format!(
+                    "<Part><PartNumber>{}</PartNumber><ETag>{}</ETag></Part>",
+                    idx + 1,
+                    xml_escape(etag),
+                )
+            })
+            .collect();
+        let body = self
+            .send(
+                "complete_multipart_upload",
+                Method::POST,
+                key,
+                &[("uploadId", upload_id.to_string())],
+                Bytes::from(format!(
+                    "<CompleteMultipartUpload>{}</CompleteMultipartUpload>",
+                    parts
+                )),
+            )
+            .await?
+            .text()
+            .await?;
+        // S3 can still fail the upload after it started responding with a 200.
+        ensure!(
+            !body.contains("<Error>"),
+            "Failed completing multipart upload of {}: {}",
+            key,
+            body,
+        );
+        Ok(())
+    }
+
+    pub async fn abort_multipart_upload(&self, key: &str, upload_id: &str) -> Result<()> {
+        self.send(
+            "abort_multipart_upload",
+            Method::DELETE,
+            key,
+            &[("uploadId", upload_id.to_string())],
+            Bytes::new(),
+        )
+        .await?;
+        Ok(())
+    }
+
+    /// Sends the request, retrying with exponential backoff on network errors, 5xx and 429s.
+    async fn send(
+        &self,
+        operation: &'static str,
+        method: Method,
+        key: &str,
+        query: &[(&str, String)],
+        body: Bytes,
+    ) -> Result<Response> {
+        let mut attempt = 0;
+        loop {
+            let error = match self
+                .send_once(method.clone(), key, query, body.clone())
+                .await
+            {
+                Ok(response) if response.status().is_success() => {
+                    S3_REQUESTS.with_label_values(&[operation, "success"]).inc();
+                    return Ok(response);
+                }
+                Ok(response) => {
+                    let status = response.status();
+                    let error = anyhow!(
+                        "S3 {} of {:?} failed with status {}: {}",
+                        operation,
+                        key,
+                        status,
+                        response.text().await.unwrap_or_default(),
+                    );
+                    if !status.is_server_error() && status != StatusCode::TOO_MANY_REQUESTS {
+                        S3_REQUESTS.with_label_values(&[operation, "error"]).inc();
+                        return Err(error);
+                    }
+                    error
+                }
+                Err(e) => e,
+            };
+
+            if attempt >= self.max_retries {
+                S3_REQUESTS.with_label_values(&[operation, "error"]).inc();
+                return Err(error);
+            }
+            S3_REQUESTS.with_label_values(&[operation, "retry"]).inc();
+            let backoff_ms = INITIAL_BACKOFF_MS
+                .saturating_mul(1 << attempt.min(16))
+                .min(MAX_BACKOFF_MS);
+            warn!(
+                error = %error,
+                attempt = attempt,
+                "S3 request failed, retrying in {}ms.",
+                backoff_ms,
+            );
+            tokio::time::sleep(Duration::from_millis(backoff_ms)).await;
+            attempt += 1;
+        }
+    }
+
+    async fn send_once(
+        &self,
+        method: Method,
+        key: &str,
+        query: &[(&str, String)],
+        body: Bytes,
+    ) -> Result<Response> {
+        let canonical_uri = if key.is_empty() {
+            format!("/{}", uri_encode(&self.bucket, true))
+        } else {
+            format!(
+                "/{}/{}",
+                uri_encode(&self.bucket, true),
+                uri_encode(key, false)
+            )
+        };
+        let mut query: Vec<_> = query
+            .iter()
+            .map(|(name, value)| (uri_encode(name, true), uri_encode(value, true)))
+            .collect();
+        query.sort();
+        let canonical_query = query
+            .iter()
+            .map(|(name, value)| format!("{}={}", name, value))
+            .collect::<Vec<_>>()
+            .join("&");
+
+        let now = chrono::Utc::now();
+        let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+        let date = now.format("%Y%m%d").to_string();
+        let payload_hash = hex::encode(digest::digest(&digest::SHA256, &body));
+
+        // Sorted by name, as required by the signature.
+        let mut headers = vec![
+            ("host", self.host.clone()),
+            ("x-amz-content-sha256", payload_hash.clone()),
+            ("x-amz-date", amz_date.clone()),
+        ];
+        if let Some(session_token) = &self.credentials.session_token {
+            headers.push(("x-amz-security-token", session_token.clone()));
+        }
+        let canonical_headers: String = headers
+            .iter()
+            .map(|(name, value)| format!("{}:{}\n", name, value.trim()))
+            .collect();
+        let signed_headers = headers
+            .iter()
+            .map(|(name, _)| *name)
+            .collect::<Vec<_>>()
+            .join(";");
+        let canonical_request = format!(
+            "{}\n{}\n{}\n{}\n{}\n{}",
+            method, canonical_uri, canonical_query, canonical_headers, signed_headers, payload_hash,
+        );
+
+        let scope = format!("{}/{}/s3/aws4_request", date, self.region);
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+            amz_date,
+            scope,
+            hex::encode(digest::digest(
+                &digest::SHA256,
+                canonical_request.as_bytes()
+            )),
+        );
+        let signing_key = [date.as_str(), self.region.as_str(), "s3", "aws4_request"]
+            .iter()
+            .fold(
+                format!("AWS4{}", self.credentials.secret_access_key).into_bytes(),
+                |key, data| hmac_sha256(&key, data.as_bytes()),
+            );
+        let authorization = format!(
+            "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+            self.credentials.access_key_id,
+            scope,
+            signed_headers,
+            hex::encode(hmac_sha256(&signing_key, string_to_sign.as_bytes())),
+        );
+
+        let mut url = format!("{}://{}{}", self.scheme, self.host, canonical_uri);
+        if !canonical_query.is_empty() {
+            url.push('?');
+            url.push_str(&canonical_query);
+        }
+        let mut request = self
+            .client
+            .request(method, &url)
+            .header("x-amz-content-sha256", payload_hash)
+            .header("x-amz-date", amz_date)
+            .header(AUTHORIZATION, authorization);
+        if let Some(session_token) = &self.credentials.session_token {
+            request = request.header("x-amz-security-token", session_token);
+        }
+        Ok(request.body(body).send().await.err_notes(&url)?)
+    }
+}
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> Vec<u8> {
+    hmac::sign(&hmac::Key::new(hmac::HMAC_SHA256, key), data)
+        .as_ref()
+        .to_vec()
+}
+
+/// Percent-encodes everything but the unreserved characters, as required by the signature.
+fn uri_encode(input: &str, encode_slash: bool) -> String {
+    input
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                (b as char).to_string()
+            }
+            b'/' if !encode_slash => "/".to_string(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+/// Extracts the text of all the elements with the given tag. The S3 responses we deal with are
+/// simple enough not to warrant a full-blown XML parser.
+fn xml_elements(xml: &str, tag: &str) -> Vec<String> {
+    let re = Regex::new(&format!("(?s)<{0}>(.*?)</{0}>", tag)).unwrap();
+    re.captures_iter(xml)
+        .map(|captures| xml_unescape(&captures[1]))
+        .collect()
+}
+
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+fn xml_unescape(text: &str) -> String {
+    text.replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_uri_encode() {
+        assert_eq!(
+            uri_encode("backup/file-1.chunk_~", false),
+            "backup/file-1.chunk_~"
+        );
+        assert_eq!(uri_encode("backup/file 1", true), "backup%2Ffile%201");
+        assert_eq!(uri_encode("a+b=c", false), "a%2Bb%3Dc");
+    }
+
+    #[test]
+    fn test_xml_elements() {
+        let xml = r#"<ListBucketResult><IsTruncated>false</IsTruncated>
+            <Contents><Key>metadata/a</Key></Contents>
+            <Contents><Key>metadata/b&amp;c</Key></Contents></ListBucketResult>"#;
+        assert_eq!(xml_elements(xml, "Key"), vec!["metadata/a", "metadata/b&c"]);
+        assert_eq!(xml_elements(xml, "IsTruncated"), vec!["false"]);
+        assert!(xml_elements(xml, "NextContinuationToken").is_empty());
+        assert_eq!(xml_unescape(&xml_escape("\"etag\"&<>'")), "\"etag\"&<>'");
+    }
+}
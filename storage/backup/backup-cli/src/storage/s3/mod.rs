@@ -0,0 +1,336 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+mod client;
+
+#[cfg(test)]
+mod tests;
+
+use crate::storage::{
+    s3::client::{S3Client, S3Credentials},
+    BackupHandle, BackupHandleRef, BackupStorage, FileHandle, FileHandleRef, ShellSafeName,
+    TextLine,
+};
+use anyhow::{ensure, Result};
+use aptos_logger::prelude::*;
+use async_trait::async_trait;
+use bytes::Bytes;
+use futures::{ready, Future, TryStreamExt};
+use std::{
+    io,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+use structopt::StructOpt;
+use tokio::{
+    io::{AsyncRead, AsyncReadExt, AsyncWrite, DuplexStream},
+    task::JoinHandle,
+};
+use tokio_util::compat::FuturesAsyncReadCompatExt;
+
+/// S3 requires all parts of a multipart upload but the last one to be at least 5 MiB.
+const MIN_PART_SIZE_MB: usize = 5;
+/// Size of the pipe between the writer returned by `create_for_write()` and the upload task.
+const PIPE_SIZE: usize = 64 * 1024;
+
+#[derive(StructOpt)]
+pub struct S3Opt {
+    #[structopt(
+        long = "endpoint",
+        default_value = "https://s3.amazonaws.com",
+        help = "Endpoint of the S3 compatible object store, e.g. https://s3.us-west-2.amazonaws.com \
+        or http://localhost:9000."
+    )]
+    pub endpoint: String,
+    #[structopt(long = "bucket", help = "Bucket to hold backups.")]
+    pub bucket: String,
+    #[structopt(
+        long = "prefix",
+        default_value = "",
+        help = "Everything is stored under this key prefix (i.e. folder) in the bucket."
+    )]
+    pub prefix: String,
+    #[structopt(
+        long = "region",
+        default_value = "us-east-1",
+        env = "AWS_REGION",
+        help = "Region of the bucket."
+    )]
+    pub region: String,
+    #[structopt(
+        long = "access-key-id",
+        env = "AWS_ACCESS_KEY_ID",
+        hide_env_values = true
+    )]
+    pub access_key_id: String,
+    #[structopt(
+        long = "secret-access-key",
+        env = "AWS_SECRET_ACCESS_KEY",
+        hide_env_values = true
+    )]
+    pub secret_access_key: String,
+    #[structopt(
+        long = "session-token",
+        env = "AWS_SESSION_TOKEN",
+        hide_env_values = true
+    )]
+    pub session_token: Option<String>,
+    #[structopt(
+        long = "part-size-mb",
+        default_value = "16",
+        help = "Files are uploaded in parts of this size, buffered in memory. Min: 5."
+    )]
+    pub part_size_mb: usize,
+    #[structopt(
+        long = "max-retries",
+        default_value = "5",
+        help = "Times to retry a request failed with a network or server error."
+    )]
+    pub max_retries: usize,
+}
+
+/// A storage backend that talks to an S3 compatible object store directly. Files are streamed up
+/// in parts with multipart uploads, so they don't need to fit in memory.
+pub struct S3 {
+    client: Arc<S3Client>,
+    /// Key prefix everything is stored under, without the trailing slash.
+    prefix: String,
+    part_size: usize,
+}
+
+impl S3 {
+    const METADATA_DIR: &'static str = "metadata";
+
+    pub fn new_with_opt(opt: S3Opt) -> Result<Self> {
+        ensure!(
+            opt.part_size_mb >= MIN_PART_SIZE_MB,
+            "Part size must be at least {} MiB.",
+            MIN_PART_SIZE_MB,
+        );
+        let client = S3Client::new(
+            &opt.endpoint,
+            opt.bucket,
+            opt.region,
+            S3Credentials {
+                access_key_id: opt.access_key_id,
+                secret_access_key: opt.secret_access_key,
+                session_token: opt.session_token,
+            },
+            opt.max_retries,
+        )?;
+
+        Ok(Self::new(
+            client,
+            opt.prefix,
+            opt.part_size_mb * 1024 * 1024,
+        ))
+    }
+
+    fn new(client: S3Client, prefix: String, part_size: usize) -> Self {
+        Self {
+            client: Arc::new(client),
+            prefix: prefix.trim_matches('/').to_string(),
+            part_size,
+        }
+    }
+
+    fn key(&self, file_handle: &FileHandleRef) -> String {
+        if self.prefix.is_empty() {
+            file_handle.to_string()
+        } else {
+            format!("{}/{}", self.prefix, file_handle)
+        }
+    }
+
+    fn file_handle(&self, key: &str) -> FileHandle {
+        if self.prefix.is_empty() {
+            key.to_string()
+        } else {
+            let dir = format!("{}/", self.prefix);
+            key.strip_prefix(&dir).unwrap_or(key).to_string()
+        }
+    }
+}
+
+#[async_trait]
+impl BackupStorage for S3 {
+    async fn create_backup(&self, name: &ShellSafeName) -> Result<BackupHandle> {
+        // There are no folders to create in an object store.
+        Ok(name.to_string())
+    }
+
+    async fn create_for_write(
+        &self,
+        backup_handle: &BackupHandleRef,
+        name: &ShellSafeName,
+    ) -> Result<(FileHandle, Box<dyn AsyncWrite + Send + Unpin>)> {
+        let file_handle = format!("{}/{}", backup_handle, name.as_ref());
+        let writer = S3Writer::new(self.client.clone(), self.key(&file_handle), self.part_size);
+        Ok((file_handle, Box::new(writer)))
+    }
+
+    async fn open_for_read(
+        &self,
+        file_handle: &FileHandleRef,
+    ) -> Result<Box<dyn AsyncRead + Send + Unpin>> {
+        let response = self.client.get_object(&self.key(file_handle)).await?;
+        Ok(Box::new(
+            response
+                .bytes_stream()
+                .map_err(|e| futures::io::Error::new(futures::io::ErrorKind::Other, e))
+                .into_async_read()
+                .compat(),
+        ))
+    }
+
+    async fn save_metadata_line(&self, name: &ShellSafeName, content: &TextLine) -> Result<()> {
+        let file_handle = format!("{}/{}", Self::METADATA_DIR, name.as_ref());
+        self.client
+            .put_object(
+                &self.key(&file_handle),
+                Bytes::copy_from_slice(content.as_ref().as_bytes()),
+            )
+            .await
+    }
+
+    async fn list_metadata_files(&self) -> Result<Vec<FileHandle>> {
+        let dir = format!("{}/", Self::METADATA_DIR);
+        let keys = self.client.list_objects(&self.key(&dir)).await?;
+        Ok(keys.iter().map(|key| self.file_handle(key)).collect())
+    }
+
+    async fn save_metadata_lines(&self, name: &ShellSafeName, lines: &[TextLine]) -> Result<()> {
+        let file_handle = format!("{}/{}", Self::METADATA_DIR, name.as_ref());
+        let content: String = lines.iter().map(AsRef::<str>::as_ref).collect();
+        self.client
+            .put_object(&self.key(&file_handle), Bytes::from(content))
+            .await
+    }
+
+    async fn delete_file(&self, file_handle: &FileHandleRef) -> Result<()> {
+        self.client.delete_object(&self.key(file_handle)).await
+    }
+}
+
+/// The writer returned by `create_for_write()`. Data written is piped to a task which uploads it
+/// in parts, and shutting the writer down waits for the upload to finish.
+struct S3Writer {
+    pipe: DuplexStream,
+    upload: Option<JoinHandle<Result<()>>>,
+}
+
+impl S3Writer {
+    fn new(client: Arc<S3Client>, key: String, part_size: usize) -> Self {
+        let (pipe, pipe_reader) = tokio::io::duplex(PIPE_SIZE);
+        let upload = tokio::spawn(async move {
+            let res = upload_file(&client, &key, part_size, pipe_reader).await;
+            if let Err(e) = &res {
+                error!(error = %e, key = %key, "Failed uploading to S3.");
+            }
+            res
+        });
+        Self {
+            pipe,
+            upload: Some(upload),
+        }
+    }
+}
+
+impl AsyncWrite for S3Writer {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.pipe).poll_write(cx, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.pipe).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        ready!(Pin::new(&mut self.pipe).poll_shutdown(cx))?;
+        let upload = match self.upload.as_mut() {
+            Some(upload) => upload,
+            None => return Poll::Ready(Ok(())),
+        };
+        let res = ready!(Pin::new(upload).poll(cx));
+        self.upload = None;
+        Poll::Ready(match res {
+            Ok(Ok(())) => Ok(()),
+            Ok(Err(e)) => Err(io::Error::new(io::ErrorKind::Other, e)),
+            Err(e) => Err(io::Error::new(io::ErrorKind::Other, e)),
+        })
+    }
+}
+
+impl Drop for S3Writer {
+    fn drop(&mut self) {
+        // Dropped before shutdown, so the data is incomplete: cancel the upload before the pipe
+        // gets closed, so the partial file is never completed. A multipart upload in progress
+        // is left behind, to be cleaned up by the bucket's lifecycle rules.
+        if let Some(upload) = self.upload.take() {
+            upload.abort();
+        }
+    }
+}
+
+/// Uploads everything read from `reader` to `key`. Small files are uploaded in a single request,
+/// and larger ones with a multipart upload.
+async fn upload_file(
+    client: &S3Client,
+    key: &str,
+    part_size: usize,
+    mut reader: DuplexStream,
+) -> Result<()> {
+    let part = read_part(&mut reader, part_size).await?;
+    if part.len() < part_size {
+        return client.put_object(key, part).await;
+    }
+
+    let upload_id = client.create_multipart_upload(key).await?;
+    let res = upload_parts(client, key, &upload_id, part_size, part, reader).await;
+    if res.is_err() {
+        if let Err(e) = client.abort_multipart_upload(key, &upload_id).await {
+            warn!(error = %e, key = %key, "Failed aborting multipart upload.");
+        }
+    }
+    res
+}
+
+async fn upload_parts(
+    client: &S3Client,
+    key: &str,
+    upload_id: &str,
+    part_size: usize,
+    first_part: Bytes,
+    mut reader: DuplexStream,
+) -> Result<()> {
+    let mut etags = Vec::new();
+    let mut part = first_part;
+    // Only the last part can be short, and it can't be empty unless it's the only one.
+    while !part.is_empty() {
+        let is_last = part.len() < part_size;
+        etags.push(
+            client
+                .upload_part(key, upload_id, etags.len() + 1, part)
+                .await?,
+        );
+        if is_last {
+            break;
+        }
+        part = read_part(&mut reader, part_size).await?;
+    }
+    client
+        .complete_multipart_upload(key, upload_id, &etags)
+        .await
+}
+
+/// Reads up to `part_size` bytes, only returning less on EOF.
+async fn read_part(reader: &mut DuplexStream, part_size: usize) -> Result<Bytes> {
+    let mut buf = Vec::with_capacity(part_size);
+    reader.take(part_size as u64).read_to_end(&mut buf).await?;
+    Ok(Bytes::from(buf))
+}
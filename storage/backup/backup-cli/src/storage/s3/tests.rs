@@ -0,0 +1,298 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use crate::storage::test_util::{
+    arb_backups, arb_metadata_files, test_compact_metadata_files_impl,
+    test_save_and_list_metadata_files_impl, test_write_and_read_impl,
+};
+use aptos_infallible::Mutex;
+use proptest::prelude::*;
+use std::{
+    collections::{BTreeMap, HashMap},
+    net::SocketAddr,
+    str::FromStr,
+};
+use tokio::{io::AsyncWriteExt, runtime::Runtime};
+use warp::{
+    http::{Method, Response, StatusCode},
+    path::FullPath,
+    Filter,
+};
+
+const BUCKET: &str = "backups";
+const PREFIX: &str = "aptos/e1";
+/// Small enough for most test files to be uploaded in multiple parts.
+const PART_SIZE: usize = 100;
+/// Small enough for listings to span multiple pages.
+const MAX_KEYS: usize = 3;
+
+/// An in-memory stand-in for an S3 compatible object store, supporting just enough of the API
+/// for the S3 backup storage. Requests aren't authenticated.
+#[derive(Default)]
+struct MockS3State {
+    objects: BTreeMap<String, Bytes>,
+    uploads: HashMap<String, (String, BTreeMap<usize, Bytes>)>,
+    num_multipart_uploads: usize,
+    /// Number of upcoming requests to fail with a 503.
+    failures_to_inject: usize,
+    /// Whether to refuse uploading parts of multipart uploads.
+    reject_parts: bool,
+}
+
+type MockS3 = Arc<Mutex<MockS3State>>;
+
+fn handle_request(
+    state: &MockS3,
+    method: Method,
+    path: FullPath,
+    query: HashMap<String, String>,
+    body: Bytes,
+) -> Response<Vec<u8>> {
+    let mut state = state.lock();
+    if state.failures_to_inject > 0 {
+        state.failures_to_inject -= 1;
+        return response(StatusCode::SERVICE_UNAVAILABLE, "<Error>SlowDown</Error>");
+    }
+
+    let (bucket, key) = path
+        .as_str()
+        .trim_start_matches('/')
+        .split_once('/')
+        .unwrap_or((path.as_str().trim_start_matches('/'), ""));
+    if bucket != BUCKET {
+        return response(StatusCode::NOT_FOUND, "<Error>NoSuchBucket</Error>");
+    }
+    let key = key.to_string();
+
+    match method {
+        Method::GET if key.is_empty() => {
+            let prefix = query.get("prefix").cloned().unwrap_or_default();
+            let start_after = query.get("continuation-token").cloned().unwrap_or_default();
+            let keys: Vec<_> = state
+                .objects
+                .keys()
+                .filter(|k| k.starts_with(&prefix) && **k > start_after)
+                .take(MAX_KEYS + 1)
+                .cloned()
+                .collect();
+            let is_truncated = keys.len() > MAX_KEYS;
+            let mut xml = format!(
+                "<ListBucketResult><IsTruncated>{}</IsTruncated>",
+                is_truncated
+            );
+            for key in keys.iter().take(MAX_KEYS) {
+                xml.push_str(&format!("<Contents><Key>{}</Key></Contents>", key));
+            }
+            if is_truncated {
+                xml.push_str(&format!(
+                    "<NextContinuationToken>{}</NextContinuationToken>",
+                    keys[MAX_KEYS - 1]
+                ));
+            }
+            xml.push_str("</ListBucketResult>");
+            response(StatusCode::OK, xml)
+        }
+        Method::GET => match state.objects.get(&key) {
+            Some(object) => response(StatusCode::OK, object.to_vec()),
+            None => response(StatusCode::NOT_FOUND, "<Error>NoSuchKey</Error>"),
+        },
+        Method::PUT => match (query.get("uploadId"), query.get("partNumber")) {
+            (Some(_), Some(_)) if state.reject_parts => {
+                response(StatusCode::FORBIDDEN, "<Error>AccessDenied</Error>")
+            }
+            (Some(upload_id), Some(part_number)) => match state.uploads.get_mut(upload_id) {
+                Some((_, parts)) => {
+                    let part_number = usize::from_str(part_number).unwrap();
+                    parts.insert(part_number, body);
+                    let mut reply = response(StatusCode::OK, String::new());
+                    reply
+                        .headers_mut()
+                        .insert("ETag", format!("\"etag-{}\"", part_number).parse().unwrap());
+                    reply
+                }
+                None => response(StatusCode::NOT_FOUND, "<Error>NoSuchUpload</Error>"),
+            },
+            _ => {
+                state.objects.insert(key, body);
+                response(StatusCode::OK, String::new())
+            }
+        },
+        Method::POST if query.contains_key("uploads") => {
+            state.num_multipart_uploads += 1;
+            let upload_id = format!("upload-{}", state.num_multipart_uploads);
+            state
+                .uploads
+                .insert(upload_id.clone(), (key, BTreeMap::new()));
+            response(
+                StatusCode::OK,
+                format!(
+                    "<InitiateMultipartUploadResult><UploadId>{}</UploadId></InitiateMultipartUploadResult>",
+                    upload_id
+                ),
+            )
+        }
+        Method::POST => match query
+            .get("uploadId")
+            .and_then(|id| state.uploads.remove(id))
+        {
+            Some((upload_key, parts)) => {
+                assert_eq!(upload_key, key);
+                // All parts but the last one have the full size
+                let num_parts = parts.len();
+                for (idx, (part_number, part)) in parts.iter().enumerate() {
+                    assert_eq!(*part_number, idx + 1);
+                    assert!(!part.is_empty());
+                    assert!(idx + 1 == num_parts || part.len() == PART_SIZE);
+                }
+                let object: Vec<u8> = parts.values().flat_map(|part| part.to_vec()).collect();
+                state.objects.insert(key, Bytes::from(object));
+                response(
+                    StatusCode::OK,
+                    "<CompleteMultipartUploadResult></CompleteMultipartUploadResult>",
+                )
+            }
+            None => response(StatusCode::NOT_FOUND, "<Error>NoSuchUpload</Error>"),
+        },
+        Method::DELETE => {
+            if let Some(upload_id) = query.get("uploadId") {
+                state.uploads.remove(upload_id);
+            } else {
+                state.objects.remove(&key);
+            }
+            response(StatusCode::NO_CONTENT, String::new())
+        }
+        _ => response(StatusCode::METHOD_NOT_ALLOWED, String::new()),
+    }
+}
+
+fn response(status: StatusCode, body: impl Into<Vec<u8>>) -> Response<Vec<u8>> {
+    let mut response = Response::new(body.into());
+    *response.status_mut() = status;
+    response
+}
+
+fn start_mock_s3(state: MockS3) -> SocketAddr {
+    let routes = warp::method()
+        .and(warp::path::full())
+        .and(warp::query::<HashMap<String, String>>())
+        .and(warp::body::bytes())
+        .map(move |method, path, query, body| handle_request(&state, method, path, query, body));
+    let (address, server) = warp::serve(routes).bind_ephemeral(([127, 0, 0, 1], 0));
+    tokio::spawn(server);
+    address
+}
+
+fn get_store(address: SocketAddr, max_retries: usize) -> S3 {
+    let client = S3Client::new(
+        &format!("http://{}", address),
+        BUCKET.to_string(),
+        "us-east-1".to_string(),
+        S3Credentials {
+            access_key_id: "access_key_id".to_string(),
+            secret_access_key: "secret_access_key".to_string(),
+            session_token: None,
+        },
+        max_retries,
+    )
+    .unwrap();
+    S3::new(client, PREFIX.to_string(), PART_SIZE)
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(10))]
+
+    #[test]
+    fn test_write_and_read(
+        backups in arb_backups()
+    ) {
+        let rt = Runtime::new().unwrap();
+        rt.block_on(async {
+            let store = get_store(start_mock_s3(MockS3::default()), 0);
+            test_write_and_read_impl(Box::new(store), backups).await
+        });
+    }
+
+    #[test]
+    fn test_save_list_metadata_files(
+        input in arb_metadata_files(),
+    ) {
+        let rt = Runtime::new().unwrap();
+        rt.block_on(async {
+            let store = get_store(start_mock_s3(MockS3::default()), 0);
+            test_save_and_list_metadata_files_impl(Box::new(store), input).await
+        });
+    }
+
+    #[test]
+    fn test_compact_metadata_files(
+        input in arb_metadata_files(),
+    ) {
+        let rt = Runtime::new().unwrap();
+        rt.block_on(async {
+            let store = get_store(start_mock_s3(MockS3::default()), 0);
+            test_compact_metadata_files_impl(Box::new(store), input).await
+        });
+    }
+}
+
+async fn write_file(store: &S3, name: &str, content: &[u8]) -> std::io::Result<()> {
+    let (_, mut file) = store
+        .create_for_write("backup", &ShellSafeName::from_str(name).unwrap())
+        .await
+        .unwrap();
+    file.write_all(content).await?;
+    file.shutdown().await
+}
+
+#[tokio::test]
+async fn test_multipart_upload() {
+    let state = MockS3::default();
+    let store = get_store(start_mock_s3(state.clone()), 0);
+
+    // Short files are uploaded in one go, longer ones in parts.
+    for (name, size) in [
+        ("empty", 0),
+        ("short", PART_SIZE - 1),
+        ("one_part", PART_SIZE),
+        ("two_parts", PART_SIZE * 2),
+        ("long", PART_SIZE * 5 + 1),
+    ] {
+        let content: Vec<u8> = (0..size).map(|i| i as u8).collect();
+        write_file(&store, name, &content).await.unwrap();
+
+        let key = format!("{}/backup/{}", PREFIX, name);
+        assert_eq!(state.lock().objects.get(&key).unwrap().to_vec(), content);
+    }
+    assert_eq!(state.lock().num_multipart_uploads, 3);
+    assert!(state.lock().uploads.is_empty());
+}
+
+#[tokio::test]
+async fn test_retries() {
+    let state = MockS3::default();
+    let address = start_mock_s3(state.clone());
+    let name = ShellSafeName::from_str("metadata_line").unwrap();
+    let content = TextLine::new("content").unwrap();
+
+    // Server errors are retried
+    state.lock().failures_to_inject = 2;
+    let store = get_store(address, 2);
+    store.save_metadata_line(&name, &content).await.unwrap();
+    assert_eq!(
+        store.list_metadata_files().await.unwrap(),
+        vec!["metadata/metadata_line".to_string()]
+    );
+
+    // But only so many times
+    state.lock().failures_to_inject = 3;
+    assert!(store.save_metadata_line(&name, &content).await.is_err());
+
+    // And a failed multipart upload is aborted
+    state.lock().reject_parts = true;
+    assert!(write_file(&store, "rejected", &[0; PART_SIZE * 2])
+        .await
+        .is_err());
+    assert_eq!(state.lock().num_multipart_uploads, 1);
+    assert!(state.lock().uploads.is_empty());
+}
@@ -0,0 +1,115 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::{format_err, Result};
+use aptos_logger::{Level, Logger};
+use aptos_types::transaction::Version;
+use aptosdb::db_debugger::DbDebugger;
+use std::path::PathBuf;
+use structopt::StructOpt;
+
+#[derive(StructOpt)]
+#[structopt(
+    name = "db-tool",
+    about = "Inspect, verify and repair the DB of a stopped node."
+)]
+struct Opt {
+    #[structopt(long = "db-dir", parse(from_os_str), help = "Root dir of the DB.")]
+    db_dir: PathBuf,
+
+    #[structopt(subcommand)]
+    cmd: Command,
+}
+
+#[derive(StructOpt)]
+enum Command {
+    #[structopt(
+        about = "Prints the latest ledger info, sizes of the column families and pruner progress."
+    )]
+    Info,
+    #[structopt(
+        about = "Verifies the transactions in a version range against the transaction accumulator, \
+        and the state snapshots in it against the transaction infos."
+    )]
+    Verify {
+        #[structopt(long, default_value = "0")]
+        first_version: Version,
+        #[structopt(long, help = "Inclusive. Defaults to the latest version.")]
+        last_version: Option<Version>,
+    },
+    #[structopt(
+        about = "Deletes everything after the target version, which needs to have a state \
+        snapshot not too long before it. Back up the DB first if in doubt."
+    )]
+    Truncate {
+        #[structopt(long)]
+        target_version: Version,
+    },
+}
+
+fn main() -> Result<()> {
+    Logger::new().level(Level::Info).read_env().init();
+    let opt = Opt::from_args();
+
+    match opt.cmd {
+        Command::Info => {
+            let debugger = DbDebugger::open(&opt.db_dir, true /* readonly */)?;
+            match debugger.get_latest_ledger_info() {
+                Some(li) => println!("Latest ledger info: {}", li),
+                None => println!("Latest ledger info: None"),
+            }
+            println!("Latest version: {:?}", debugger.get_latest_version()?);
+            println!(
+                "Latest state snapshot version: {:?}",
+                debugger.get_latest_state_snapshot_version()?
+            );
+            for (pruner_index, min_readable_version) in
+                debugger.get_pruner_min_readable_versions()?
+            {
+                println!(
+                    "{} min readable version: {}",
+                    pruner_index.name(),
+                    min_readable_version
+                );
+            }
+            println!(
+                "{:<16} {:<24} {:>16} {:>20} {:>20}",
+                "DB", "Column family", "Keys (est.)", "SST files (bytes)", "Live data (bytes)"
+            );
+            for stats in debugger.get_column_family_stats()? {
+                println!(
+                    "{:<16} {:<24} {:>16} {:>20} {:>20}",
+                    stats.db_name,
+                    stats.cf_name,
+                    stats.estimated_num_keys,
+                    stats.total_sst_files_size,
+                    stats.estimated_live_data_size,
+                );
+            }
+        }
+        Command::Verify {
+            first_version,
+            last_version,
+        } => {
+            let debugger = DbDebugger::open(&opt.db_dir, true /* readonly */)?;
+            let last_version = match last_version {
+                Some(version) => version,
+                None => debugger
+                    .get_latest_version()?
+                    .ok_or_else(|| format_err!("DB is empty."))?,
+            };
+            let summary = debugger.verify(first_version, last_version)?;
+            println!(
+                "Verified {} transactions and {} state snapshots in [{}, {}].",
+                summary.num_transactions, summary.num_state_snapshots, first_version, last_version,
+            );
+        }
+        Command::Truncate { target_version } => {
+            let debugger = DbDebugger::open(&opt.db_dir, false /* readonly */)?;
+            debugger.truncate(target_version)?;
+            println!("Truncated DB to version {}.", target_version);
+        }
+    }
+
+    Ok(())
+}
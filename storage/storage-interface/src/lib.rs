@@ -33,7 +33,7 @@ use aptos_types::{
     write_set::WriteSet,
 };
 use serde::{Deserialize, Serialize};
-use std::{collections::HashMap, sync::Arc};
+use std::{collections::HashMap, str::FromStr, sync::Arc};
 use thiserror::Error;
 
 pub mod async_proof_fetcher;
@@ -118,6 +118,48 @@ pub trait StateSnapshotReceiver<K, V>: Send {
     fn finish_box(self: Box<Self>) -> Result<()>;
 }
 
+/// Identifies one of the DB pruners.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum PrunerIndex {
+    StateStorePrunerIndex,
+    LedgerPrunerIndex,
+}
+
+impl PrunerIndex {
+    pub fn name(&self) -> &'static str {
+        match self {
+            PrunerIndex::StateStorePrunerIndex => "state_pruner",
+            PrunerIndex::LedgerPrunerIndex => "ledger_pruner",
+        }
+    }
+}
+
+impl FromStr for PrunerIndex {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "state_pruner" => Ok(PrunerIndex::StateStorePrunerIndex),
+            "ledger_pruner" => Ok(PrunerIndex::LedgerPrunerIndex),
+            _ => Err(format_err!("Unknown pruner: {}", s)),
+        }
+    }
+}
+
+/// The progress of a DB pruner. All fields but `pruner_index` are `None` if the pruner is
+/// disabled.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct PrunerProgress {
+    pub pruner_index: PrunerIndex,
+    /// The number of latest versions the pruner keeps.
+    pub prune_window: Option<Version>,
+    /// Versions before this one are no longer readable.
+    pub min_readable_version: Option<Version>,
+    /// The version the pruner has been asked to prune up to. Pruning is done once
+    /// `min_readable_version` reaches it.
+    pub target_version: Option<Version>,
+}
+
 #[derive(Debug, Deserialize, Error, PartialEq, Serialize)]
 pub enum Error {
     #[error("Service error: {:?}", error)]
@@ -526,6 +568,11 @@ pub trait DbReader: Send + Sync {
         unimplemented!()
     }
 
+    /// Get the current window and progress of each of the DB pruners.
+    fn get_pruner_progress(&self) -> Result<Vec<PrunerProgress>> {
+        unimplemented!()
+    }
+
     /// Get table info from the internal indexer.
     fn get_table_info(&self, handle: TableHandle) -> Result<TableInfo> {
         unimplemented!()
@@ -667,6 +714,23 @@ pub trait DbWriter: Send + Sync {
     fn delete_genesis(&self) -> Result<()> {
         unimplemented!()
     }
+
+    /// Changes the prune window of an enabled pruner.
+    /// See [`AptosDB::set_prune_window`].
+    ///
+    /// [`AptosDB::set_prune_window`]: ../aptosdb/struct.AptosDB.html#method.set_prune_window
+    fn set_prune_window(&self, pruner_index: PrunerIndex, prune_window: Version) -> Result<()> {
+        unimplemented!()
+    }
+
+    /// Makes an enabled pruner prune everything before `target_version` once, regardless of its
+    /// prune window.
+    /// See [`AptosDB::prune_to_version`].
+    ///
+    /// [`AptosDB::prune_to_version`]: ../aptosdb/struct.AptosDB.html#method.prune_to_version
+    fn prune_to_version(&self, pruner_index: PrunerIndex, target_version: Version) -> Result<()> {
+        unimplemented!()
+    }
 }
 
 #[derive(Clone)]
@@ -4,7 +4,7 @@
 use aptos_indexer::{
     database::{new_db_pool, PgDbPool, PgPoolConnection},
     default_processor::DefaultTransactionProcessor,
-    indexer::tailer::Tailer,
+    indexer::{fetcher::TransactionFetcherOptions, tailer::Tailer},
     models::transactions::TransactionModel,
     token_processor::TokenTransactionProcessor,
 };
@@ -22,6 +22,9 @@ impl Test for Indexer {
 
 pub fn wipe_database(conn: &PgPoolConnection) {
     for table in [
+        "coin_activities",
+        "coin_balances",
+        "coin_infos",
         "metadatas",
         "tokens",
         "token_activities",
@@ -61,7 +64,11 @@ pub fn setup_indexer(ctx: &mut AptosContext) -> anyhow::Result<(PgDbPool, Tailer
     let conn_pool = new_db_pool(database_url.as_str())?;
     wipe_database(&conn_pool.get()?);
 
-    let mut tailer = Tailer::new(ctx.url(), conn_pool.clone())?;
+    let mut tailer = Tailer::new(
+        ctx.url(),
+        conn_pool.clone(),
+        TransactionFetcherOptions::default(),
+    )?;
     tailer.run_migrations();
 
     let pg_transaction_processor = DefaultTransactionProcessor::new(conn_pool.clone());
@@ -99,7 +106,13 @@ impl AptosTest for Indexer {
                 .into_inner()
                 .version;
 
-            tailer.process_next_batch((version + 1) as u8).await;
+            tailer.set_fetcher_version(0).await;
+            let mut next_version = 0;
+            while next_version <= version {
+                let txns = tailer.get_next_batch().await;
+                next_version += txns.len() as u64;
+                tailer.process_transactions(txns).await.unwrap();
+            }
 
             // Get them into the array and sort by type in order to prevent ordering from breaking tests
             let mut transactions = vec![];
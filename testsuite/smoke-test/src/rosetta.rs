@@ -45,6 +45,9 @@ pub async fn setup_test(
         tls_cert_path: None,
         tls_key_path: None,
         content_length_limit: None,
+        view_gas_limit: None,
+        max_concurrent_streams: None,
+        mempool_inspection_enabled: false,
     };
 
     // Start the server